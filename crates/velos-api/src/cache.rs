@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use velos_core::LogEntry;
+
+/// How long `/api/logs/:name` answers from memory before asking the daemon again.
+pub const LOG_CACHE_TTL: Duration = Duration::from_secs(1);
+
+/// Most (name, lines) requests kept at once.
+pub const LOG_CACHE_CAPACITY: usize = 64;
+
+type Clock = Arc<dyn Fn() -> Instant + Send + Sync>;
+
+/// (process name, line count) -> when it was fetched, and what came back.
+type Entries = HashMap<(String, u32), (Instant, Vec<LogEntry>)>;
+
+/// Short-lived cache of daemon log reads, keyed by the requested process
+/// name and line count, so dashboards polling the same view share one read.
+pub struct LogCache {
+    ttl: Duration,
+    capacity: usize,
+    clock: Clock,
+    entries: Mutex<Entries>,
+}
+
+impl LogCache {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self::with_clock(ttl, capacity, Arc::new(Instant::now))
+    }
+
+    fn with_clock(ttl: Duration, capacity: usize, clock: Clock) -> Self {
+        Self {
+            ttl,
+            capacity: capacity.max(1),
+            clock,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Entries cached for this request, if fetched less than the TTL ago.
+    pub fn get(&self, name: &str, lines: u32) -> Option<Vec<LogEntry>> {
+        let now = (self.clock)();
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let (fetched, cached) = entries.get(&(name.to_string(), lines))?;
+        (now.duration_since(*fetched) < self.ttl).then(|| cached.clone())
+    }
+
+    /// Store a fresh read. Expired entries go first when the cache is full,
+    /// then the oldest one.
+    pub fn insert(&self, name: &str, lines: u32, logs: Vec<LogEntry>) {
+        let now = (self.clock)();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let key = (name.to_string(), lines);
+        if !entries.contains_key(&key) && entries.len() >= self.capacity {
+            entries.retain(|_, (fetched, _)| now.duration_since(*fetched) < self.ttl);
+            if entries.len() >= self.capacity {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, (fetched, _))| *fetched)
+                    .map(|(k, _)| k.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(key, (now, logs));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(message: &str) -> LogEntry {
+        LogEntry {
+            timestamp_ms: 0,
+            level: 1,
+            stream: 0,
+            message: message.into(),
+            seq: 0,
+        }
+    }
+
+    #[test]
+    fn test_log_cache_ttl_and_capacity() {
+        let start = Instant::now();
+        let now = Arc::new(Mutex::new(start));
+        let clock_now = now.clone();
+        let cache = LogCache::with_clock(
+            Duration::from_secs(1),
+            2,
+            Arc::new(move || *clock_now.lock().unwrap()),
+        );
+        let advance = |ms: u64| *now.lock().unwrap() += Duration::from_millis(ms);
+        let messages = |logs: Option<Vec<LogEntry>>| {
+            logs.map(|l| l.into_iter().map(|e| e.message).collect::<Vec<_>>())
+        };
+
+        assert!(cache.get("api", 100).is_none());
+        cache.insert("api", 100, vec![entry("first")]);
+
+        // Within the TTL the cached read comes back; other line counts miss
+        advance(900);
+        assert_eq!(messages(cache.get("api", 100)), Some(vec!["first".into()]));
+        assert!(cache.get("api", 50).is_none());
+
+        // After it, the caller has to fetch again
+        advance(100);
+        assert!(cache.get("api", 100).is_none());
+        cache.insert("api", 100, vec![entry("second")]);
+        assert_eq!(messages(cache.get("api", 100)), Some(vec!["second".into()]));
+
+        // Full: the oldest entry makes room
+        advance(10);
+        cache.insert("web", 100, vec![entry("web")]);
+        advance(10);
+        cache.insert("db", 100, vec![entry("db")]);
+        assert!(cache.get("api", 100).is_none());
+        assert!(cache.get("web", 100).is_some() && cache.get("db", 100).is_some());
+    }
+}
@@ -1,7 +1,10 @@
+mod cache;
 mod middleware;
 mod routes;
 mod websocket;
 
+use std::sync::Arc;
+
 use axum::middleware as axum_mw;
 use axum::{Extension, Router};
 use tower_http::cors::{Any, CorsLayer};
@@ -18,6 +21,10 @@ pub async fn start_server(port: u16, api_token: Option<String>) -> Result<(), Ve
         .merge(websocket::router())
         .layer(axum_mw::from_fn(middleware::auth_middleware))
         .layer(Extension(middleware::ApiToken(api_token)))
+        .layer(Extension(Arc::new(cache::LogCache::new(
+            cache::LOG_CACHE_TTL,
+            cache::LOG_CACHE_CAPACITY,
+        ))))
         .layer(cors);
 
     let addr = format!("0.0.0.0:{port}");
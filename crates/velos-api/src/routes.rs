@@ -1,12 +1,16 @@
+use std::sync::Arc;
+
 use axum::extract::{Path, Query};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::routing::{delete, get, post};
-use axum::{Json, Router};
+use axum::{Extension, Json, Router};
 use serde::Deserialize;
-use velos_client::VelosClient;
+use velos_client::{resolve, VelosClient};
 use velos_core::protocol::StartPayload;
 
+use crate::cache::LogCache;
+
 pub fn router() -> Router {
     Router::new()
         .route("/api/processes", get(list_processes))
@@ -27,38 +31,34 @@ async fn connect() -> Result<VelosClient, (StatusCode, Json<serde_json::Value>)>
 }
 
 fn daemon_err(e: velos_core::VelosError) -> (StatusCode, Json<serde_json::Value>) {
+    use velos_core::VelosError;
+
     let msg = e.to_string();
-    if msg.contains("not found") {
-        (
-            StatusCode::NOT_FOUND,
-            Json(serde_json::json!({"error": msg})),
-        )
-    } else {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({"error": msg})),
-        )
-    }
+    let status = match e {
+        VelosError::ScriptNotFound(_) => StatusCode::BAD_REQUEST,
+        VelosError::NameInUse(_) => StatusCode::CONFLICT,
+        _ if msg.contains("not found") => StatusCode::NOT_FOUND,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(serde_json::json!({"error": msg})))
 }
 
 async fn resolve_name(
     client: &mut VelosClient,
     name: &str,
 ) -> Result<u32, (StatusCode, Json<serde_json::Value>)> {
-    if let Ok(id) = name.parse::<u32>() {
-        return Ok(id);
-    }
-    let procs = client.list().await.map_err(daemon_err)?;
-    procs
-        .iter()
-        .find(|p| p.name == name)
-        .map(|p| p.id)
-        .ok_or_else(|| {
-            (
-                StatusCode::NOT_FOUND,
-                Json(serde_json::json!({"error": format!("process not found: {name}")})),
-            )
-        })
+    resolve::resolve_one(client, name).await.map_err(daemon_err)
+}
+
+/// Every process `name` refers to: a cluster's instances, a glob's matches
+/// or an `@group`'s members.
+async fn resolve_names(
+    client: &mut VelosClient,
+    name: &str,
+) -> Result<Vec<u32>, (StatusCode, Json<serde_json::Value>)> {
+    resolve::resolve_many(client, name)
+        .await
+        .map_err(daemon_err)
 }
 
 // GET /api/processes
@@ -167,6 +167,17 @@ async fn start_process(
         shutdown_with_message: body.shutdown_with_message,
         instances: body.instances.unwrap_or(1),
         env_vars: String::new(),
+        instance_name_template: String::new(),
+        out_log: String::new(),
+        err_log: String::new(),
+        merge_logs: false,
+        args: Vec::new(),
+        group: String::new(),
+        log_rate_limit: 0,
+        success_exit_codes: vec![0],
+        stop_on_success: false,
+        max_cpu_restart: 0.0,
+        cpu_restart_window_secs: 60,
     };
 
     let result = client.start(payload).await.map_err(daemon_err)?;
@@ -182,9 +193,10 @@ async fn delete_process(
     Path(name): Path<String>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
     let mut client = connect().await?;
-    let id = resolve_name(&mut client, &name).await?;
-    client.stop(id).await.map_err(daemon_err)?;
-    client.delete(id).await.map_err(daemon_err)?;
+    for id in resolve_names(&mut client, &name).await? {
+        client.stop(id).await.map_err(daemon_err)?;
+        client.delete(id).await.map_err(daemon_err)?;
+    }
     Ok(Json(serde_json::json!({"status": "deleted", "name": name})))
 }
 
@@ -193,8 +205,9 @@ async fn restart_process(
     Path(name): Path<String>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
     let mut client = connect().await?;
-    let id = resolve_name(&mut client, &name).await?;
-    client.restart(id).await.map_err(daemon_err)?;
+    for id in resolve_names(&mut client, &name).await? {
+        client.restart(id).await.map_err(daemon_err)?;
+    }
     Ok(Json(
         serde_json::json!({"status": "restarted", "name": name}),
     ))
@@ -216,10 +229,18 @@ fn default_log_lines() -> u32 {
 async fn get_logs(
     Path(name): Path<String>,
     Query(query): Query<LogsQuery>,
+    Extension(cache): Extension<Arc<LogCache>>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let mut client = connect().await?;
-    let id = resolve_name(&mut client, &name).await?;
-    let entries = client.logs(id, query.lines).await.map_err(daemon_err)?;
+    let entries = match cache.get(&name, query.lines) {
+        Some(entries) => entries,
+        None => {
+            let mut client = connect().await?;
+            let id = resolve_name(&mut client, &name).await?;
+            let entries = client.logs(id, query.lines).await.map_err(daemon_err)?;
+            cache.insert(&name, query.lines, entries.clone());
+            entries
+        }
+    };
 
     let filtered = if let Some(ref level) = query.level {
         let level_num = match level.as_str() {
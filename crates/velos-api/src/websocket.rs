@@ -43,7 +43,8 @@ async fn handle_socket(mut socket: WebSocket, tx: Arc<broadcast::Sender<String>>
             incoming = socket.recv() => {
                 match incoming {
                     Some(Ok(Message::Ping(data))) => {
-                        if socket.send(Message::Pong(data)).await.is_err() {
+                        let sent = socket.send(Message::Pong(data)).await;
+                        if sent.is_err() {
                             break;
                         }
                     }
@@ -0,0 +1,196 @@
+use std::io::IsTerminal;
+
+use velos_config::{color_code, COLOR_NAMES};
+use velos_core::VelosError;
+use velos_log_engine::LogLevel;
+
+/// Value of the global `--color` flag.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorWhen {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Whether output may be colored. Resolved once in `main` from `--color`,
+/// `NO_COLOR` and whether stdout is a terminal, then handed to every
+/// rendering path so they all agree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColorChoice {
+    enabled: bool,
+}
+
+impl ColorChoice {
+    pub fn from_env(when: ColorWhen) -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        Self::resolve(when, std::io::stdout().is_terminal(), no_color)
+    }
+
+    /// `always`/`never` win outright; `auto` colors only a terminal and
+    /// honors `NO_COLOR` (https://no-color.org).
+    pub fn resolve(when: ColorWhen, is_tty: bool, no_color: bool) -> Self {
+        let enabled = match when {
+            ColorWhen::Always => true,
+            ColorWhen::Never => false,
+            ColorWhen::Auto => is_tty && !no_color,
+        };
+        Self { enabled }
+    }
+
+    pub fn enabled(self) -> bool {
+        self.enabled
+    }
+
+    /// Wrap `text` in the ANSI SGR `code` (e.g. "31" for red) when enabled.
+    pub fn paint(self, code: &str, text: &str) -> String {
+        if self.enabled {
+            format!("\x1b[{code}m{text}\x1b[0m")
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// Color for each log level in `velos logs`. Defaults to errors red,
+/// warnings yellow, debug dimmed and info plain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LevelColors {
+    /// Indexed by `LogLevel as usize`.
+    codes: [&'static str; 5],
+}
+
+impl Default for LevelColors {
+    fn default() -> Self {
+        let mut codes = [""; 5];
+        codes[LogLevel::Debug as usize] = "2";
+        codes[LogLevel::Warn as usize] = "33";
+        codes[LogLevel::Error as usize] = "31";
+        codes[LogLevel::Fatal as usize] = "31";
+        Self { codes }
+    }
+}
+
+impl LevelColors {
+    /// Apply `level -> color name` overrides on top of the current colors.
+    pub fn with_overrides<'a>(
+        mut self,
+        pairs: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<Self, VelosError> {
+        for (level, color) in pairs {
+            let parsed = LogLevel::parse(level).ok_or_else(|| {
+                VelosError::ProtocolError(format!(
+                    "unknown level '{level}' in color map (use debug, info, warn, error or fatal)"
+                ))
+            })?;
+            self.codes[parsed as usize] = color_code(color).ok_or_else(|| {
+                let names: Vec<&str> = COLOR_NAMES.iter().map(|(n, _)| *n).collect();
+                VelosError::ProtocolError(format!(
+                    "unknown color '{color}' for {level} (one of: {})",
+                    names.join(", ")
+                ))
+            })?;
+        }
+        Ok(self)
+    }
+
+    /// Color a rendered log line by its level.
+    pub fn paint(&self, color: ColorChoice, level: LogLevel, line: &str) -> String {
+        match self.codes[level as usize] {
+            "" => line.to_string(),
+            code => color.paint(code, line),
+        }
+    }
+}
+
+/// Split a `--color-map` value such as `error=magenta,warn=cyan`.
+pub fn parse_color_map(spec: &str) -> Result<Vec<(&str, &str)>, VelosError> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            pair.split_once('=')
+                .map(|(level, color)| (level.trim(), color.trim()))
+                .ok_or_else(|| {
+                    VelosError::ProtocolError(format!(
+                        "invalid --color-map entry '{pair}': expected LEVEL=COLOR"
+                    ))
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_resolution_matrix() {
+        let on = |when, tty, no_color| ColorChoice::resolve(when, tty, no_color).enabled();
+
+        assert!(on(ColorWhen::Auto, true, false));
+        assert!(!on(ColorWhen::Auto, false, false));
+        assert!(!on(ColorWhen::Auto, true, true));
+
+        for tty in [true, false] {
+            for no_color in [true, false] {
+                assert!(on(ColorWhen::Always, tty, no_color));
+                assert!(!on(ColorWhen::Never, tty, no_color));
+            }
+        }
+    }
+
+    #[test]
+    fn test_paint() {
+        let on = ColorChoice::resolve(ColorWhen::Always, false, false);
+        let off = ColorChoice::resolve(ColorWhen::Never, true, false);
+        assert_eq!(on.paint("31", "err"), "\x1b[31merr\x1b[0m");
+        assert_eq!(off.paint("31", "err"), "err");
+    }
+
+    #[test]
+    fn test_level_color_map() {
+        let on = ColorChoice::resolve(ColorWhen::Always, false, false);
+        let defaults = LevelColors::default();
+        assert_eq!(
+            defaults.paint(on, LogLevel::Error, "boom"),
+            "\x1b[31mboom\x1b[0m"
+        );
+
+        let pairs = parse_color_map("error=magenta, warn = Bright-Cyan,info=none").unwrap();
+        assert_eq!(
+            pairs,
+            [
+                ("error", "magenta"),
+                ("warn", "Bright-Cyan"),
+                ("info", "none")
+            ]
+        );
+        let custom = defaults.with_overrides(pairs).unwrap();
+        assert_eq!(
+            custom.paint(on, LogLevel::Error, "boom"),
+            "\x1b[35mboom\x1b[0m"
+        );
+        assert_eq!(
+            custom.paint(on, LogLevel::Warn, "slow"),
+            "\x1b[96mslow\x1b[0m"
+        );
+        // Untouched levels keep their defaults
+        assert_eq!(
+            custom.paint(on, LogLevel::Fatal, "dead"),
+            "\x1b[31mdead\x1b[0m"
+        );
+        assert_eq!(custom.paint(on, LogLevel::Info, "ok"), "ok");
+        let off = ColorChoice::resolve(ColorWhen::Never, true, false);
+        assert_eq!(custom.paint(off, LogLevel::Error, "boom"), "boom");
+
+        assert!(parse_color_map("error").is_err());
+        let err = LevelColors::default()
+            .with_overrides([("error", "purple")])
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown color 'purple'"));
+        assert!(LevelColors::default()
+            .with_overrides([("loud", "red")])
+            .is_err());
+    }
+}
@@ -1,15 +1,120 @@
 use clap::CommandFactory;
 use clap_complete::{generate, Shell};
 use std::io;
+use std::path::{Path, PathBuf};
 use velos_core::VelosError;
 
-pub fn run(shell: String) -> Result<(), VelosError> {
-    let shell: Shell = shell.parse().map_err(|_| {
-        VelosError::ProtocolError(format!(
-            "Unknown shell: {shell}. Supported: bash, zsh, fish, elvish, powershell"
-        ))
-    })?;
+pub fn run(shell: Option<String>, install: bool) -> Result<(), VelosError> {
+    let shell: Shell = match shell {
+        Some(s) => s.parse().map_err(|_| {
+            VelosError::ProtocolError(format!(
+                "Unknown shell: {s}. Supported: bash, zsh, fish, elvish, powershell"
+            ))
+        })?,
+        None => std::env::var("SHELL")
+            .ok()
+            .and_then(|s| shell_from_env(&s))
+            .ok_or_else(|| {
+                VelosError::ProtocolError(
+                    "cannot detect shell from $SHELL; pass one explicitly (bash, zsh, fish, ...)"
+                        .into(),
+                )
+            })?,
+    };
     let mut cmd = crate::Cli::command();
-    generate(shell, &mut cmd, "velos", &mut io::stdout());
+
+    if !install {
+        generate(shell, &mut cmd, "velos", &mut io::stdout());
+        return Ok(());
+    }
+
+    let home = dirs::home_dir()
+        .ok_or_else(|| VelosError::ProtocolError("cannot determine home directory".into()))?;
+    let (path, rc_hint) = install_path(shell, &home)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::File::create(&path)?;
+    generate(shell, &mut cmd, "velos", &mut file);
+
+    println!(
+        "[velos] Installed {shell} completions to {}",
+        path.display()
+    );
+    if let Some((rc, line)) = rc_hint {
+        println!("[velos] Add this to {rc} if it isn't there already:");
+        println!("  {line}");
+    }
     Ok(())
 }
+
+/// Shell named by a `$SHELL` value like "/usr/bin/zsh".
+fn shell_from_env(shell_var: &str) -> Option<Shell> {
+    Path::new(shell_var).file_name()?.to_str()?.parse().ok()
+}
+
+/// rc file and the line to add to it.
+type RcHint = (&'static str, &'static str);
+
+/// Conventional per-user completion file for `shell`, plus the rc file and
+/// line needed to load it (None when the shell picks it up on its own).
+fn install_path(shell: Shell, home: &Path) -> Result<(PathBuf, Option<RcHint>), VelosError> {
+    match shell {
+        Shell::Bash => Ok((
+            home.join(".bash_completion.d").join("velos"),
+            Some(("~/.bashrc", "source ~/.bash_completion.d/velos")),
+        )),
+        Shell::Zsh => Ok((
+            home.join(".zsh").join("completions").join("_velos"),
+            Some((
+                "~/.zshrc",
+                "fpath=(~/.zsh/completions $fpath); autoload -U compinit && compinit",
+            )),
+        )),
+        Shell::Fish => Ok((
+            home.join(".config")
+                .join("fish")
+                .join("completions")
+                .join("velos.fish"),
+            None,
+        )),
+        other => Err(VelosError::ProtocolError(format!(
+            "--install is not supported for {other}; redirect `velos completions {other}` instead"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_install_path_per_shell() {
+        let home = Path::new("/home/u");
+
+        let (path, rc) = install_path(Shell::Bash, home).unwrap();
+        assert_eq!(path, Path::new("/home/u/.bash_completion.d/velos"));
+        assert_eq!(rc.unwrap().0, "~/.bashrc");
+
+        let (path, rc) = install_path(Shell::Zsh, home).unwrap();
+        assert_eq!(path, Path::new("/home/u/.zsh/completions/_velos"));
+        assert!(rc.unwrap().1.contains("fpath"));
+
+        let (path, rc) = install_path(Shell::Fish, home).unwrap();
+        assert_eq!(
+            path,
+            Path::new("/home/u/.config/fish/completions/velos.fish")
+        );
+        assert!(rc.is_none());
+
+        assert!(install_path(Shell::PowerShell, home).is_err());
+    }
+
+    #[test]
+    fn test_shell_from_env() {
+        assert_eq!(shell_from_env("/bin/bash"), Some(Shell::Bash));
+        assert_eq!(shell_from_env("/usr/local/bin/fish"), Some(Shell::Fish));
+        assert_eq!(shell_from_env("zsh"), Some(Shell::Zsh));
+        assert_eq!(shell_from_env("/bin/tcsh"), None);
+    }
+}
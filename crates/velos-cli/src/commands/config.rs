@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use velos_core::redact;
 use velos_core::VelosError;
 
 /// Global daemon config at ~/.velos/config.toml
@@ -153,11 +154,11 @@ pub async fn run_set(key: String, value: String) -> Result<(), VelosError> {
     }
 
     save_global_config(&config)?;
-    println!("Set {key} = {}", mask_secret(&key, &value));
+    println!("Set {key} = {}", mask_secret(&key, &value, false));
     Ok(())
 }
 
-pub async fn run_get(key: Option<String>) -> Result<(), VelosError> {
+pub async fn run_get(key: Option<String>, show_secrets: bool) -> Result<(), VelosError> {
     let config = load_global_config()?;
 
     match key.as_deref() {
@@ -166,7 +167,11 @@ pub async fn run_get(key: Option<String>) -> Result<(), VelosError> {
         Some("ai.model") => println!("{}", ai_field(&config, |a| &a.model)),
         Some("ai.api_key") => println!(
             "{}",
-            mask_secret("ai.api_key", &ai_field(&config, |a| &a.api_key))
+            mask_secret(
+                "ai.api_key",
+                &ai_field(&config, |a| &a.api_key),
+                show_secrets
+            )
         ),
         Some("ai.base_url") => println!("{}", ai_field(&config, |a| &a.base_url)),
         Some("ai.max_iterations") => {
@@ -203,7 +208,7 @@ pub async fn run_get(key: Option<String>) -> Result<(), VelosError> {
                 .and_then(|n| n.telegram.as_ref())
                 .map(|t| t.bot_token.as_str())
                 .unwrap_or("");
-            println!("{}", mask_secret("telegram.bot_token", val));
+            println!("{}", mask_secret("telegram.bot_token", val, show_secrets));
         }
         Some("telegram.chat_id") => {
             let val = config
@@ -227,7 +232,10 @@ pub async fn run_get(key: Option<String>) -> Result<(), VelosError> {
                 println!("[ai]");
                 println!("  provider       = {}", ai.provider);
                 println!("  model          = {}", ai.model);
-                println!("  api_key        = {}", mask_secret("api_key", &ai.api_key));
+                println!(
+                    "  api_key        = {}",
+                    mask_secret("api_key", &ai.api_key, show_secrets)
+                );
                 if !ai.base_url.is_empty() {
                     println!("  base_url       = {}", ai.base_url);
                 }
@@ -245,7 +253,10 @@ pub async fn run_get(key: Option<String>) -> Result<(), VelosError> {
                 }
                 if let Some(tg) = &notif.telegram {
                     println!("[notifications.telegram]");
-                    println!("  bot_token = {}", mask_secret("bot_token", &tg.bot_token));
+                    println!(
+                        "  bot_token = {}",
+                        mask_secret("bot_token", &tg.bot_token, show_secrets)
+                    );
                     println!("  chat_id   = {}", tg.chat_id);
                     println!();
                 }
@@ -258,6 +269,43 @@ pub async fn run_get(key: Option<String>) -> Result<(), VelosError> {
     Ok(())
 }
 
+/// `velos config fmt <path>`: print a velos.toml in canonical form, or
+/// rewrite it in place with `--write`. A rewrite that would lose keys (see
+/// [`velos_config::format_losses`]) is refused.
+pub async fn run_fmt(path: String, write: bool, show_secrets: bool) -> Result<(), VelosError> {
+    let path = std::path::Path::new(&path);
+    let config_err = |e: velos_config::ConfigError| {
+        VelosError::ProtocolError(format!("{}: {e}", path.display()))
+    };
+    let original = std::fs::read_to_string(path)?;
+    let mut config = velos_config::parse(&original).map_err(config_err)?;
+    let formatted = velos_config::format(&config).map_err(config_err)?;
+    let losses = velos_config::format_losses(&original).map_err(config_err)?;
+
+    if !losses.is_empty() && write {
+        return Err(VelosError::ProtocolError(format!(
+            "not rewriting {}, formatting would change:\n  {}",
+            path.display(),
+            losses.join("\n  ")
+        )));
+    }
+    for loss in &losses {
+        eprintln!("[velos] Warning: {loss}");
+    }
+    if !write && show_secrets {
+        print!("{formatted}");
+    } else if !write {
+        redact_app_env(&mut config);
+        print!("{}", velos_config::format(&config).map_err(config_err)?);
+    } else if formatted == original {
+        println!("[velos] {} is already formatted", path.display());
+    } else {
+        std::fs::write(path, &formatted)?;
+        println!("[velos] Formatted {}", path.display());
+    }
+    Ok(())
+}
+
 fn ai_field(config: &GlobalConfig, f: impl Fn(&AiConfigToml) -> &str) -> String {
     config
         .ai
@@ -266,14 +314,25 @@ fn ai_field(config: &GlobalConfig, f: impl Fn(&AiConfigToml) -> &str) -> String
         .unwrap_or_default()
 }
 
-/// Mask sensitive values (api_key, bot_token) for display.
-fn mask_secret(key: &str, value: &str) -> String {
-    if (!key.contains("key") && !key.contains("token")) || value.len() < 16 {
+/// Mask secret `env` and `env_<profile>` values of every app, for printing.
+fn redact_app_env(config: &mut velos_config::VelosConfig) {
+    for app in config.apps.values_mut() {
+        app.env = redact::redact_env(&app.env);
+        for env in app.env_profiles.values_mut() {
+            *env = redact::redact_env(env);
+        }
+    }
+}
+
+/// Mask secret-looking values (see `velos_core::redact`) unless asked not to.
+fn mask_secret(key: &str, value: &str, show_secrets: bool) -> String {
+    if show_secrets
+        || value.is_empty()
+        || !redact::is_secret_key(key, redact::DEFAULT_SECRET_PATTERNS)
+    {
         return value.to_string();
     }
-    let start = &value[..8];
-    let end = &value[value.len() - 4..];
-    format!("{start}...{end}")
+    redact::REDACTED.to_string()
 }
 
 fn parse_bool(s: &str) -> Result<bool, VelosError> {
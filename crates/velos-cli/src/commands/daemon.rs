@@ -9,6 +9,9 @@ pub fn run(socket_path: Option<String>, state_dir: Option<String>) -> Result<(),
         )))
     })?;
 
+    // Report our version in ping replies so clients can spot a skew
+    velos_ffi::set_version(env!("CARGO_PKG_VERSION"));
+
     // Pass our binary path to Zig so it can fork+exec for crash notifications
     if let Ok(exe) = std::env::current_exe() {
         velos_ffi::set_notify_binary(&exe.to_string_lossy());
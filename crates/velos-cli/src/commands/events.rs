@@ -0,0 +1,193 @@
+use velos_core::protocol::DaemonEvent;
+use velos_core::VelosError;
+use velos_log_engine::format::format_timestamp_short;
+
+/// How often `--follow` asks the daemon for new events.
+const FOLLOW_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// `velos events [name]`: the daemon's own record of starts, stops, crashes
+/// and restart decisions, oldest first across every matching process.
+/// `--follow` keeps polling the event stream and prints events as they arrive.
+pub async fn run(
+    name: Option<String>,
+    lines: usize,
+    json: bool,
+    follow: bool,
+) -> Result<(), VelosError> {
+    let mut client = super::connect().await?;
+    let ids = match name {
+        Some(ref name) => Some(super::resolve_ids(&mut client, name).await?),
+        None => None,
+    };
+    let wanted = |e: &DaemonEvent| ids.as_ref().is_none_or(|ids| ids.contains(&e.process_id));
+
+    let mut cursor = Cursor::default();
+    let mut events = cursor.advance(client.events_since(cursor.since()).await?);
+    events.retain(|e| wanted(e));
+    let skip = events.len().saturating_sub(lines);
+
+    if events.is_empty() && !json && !follow {
+        println!("[velos] No events recorded");
+        return Ok(());
+    }
+    for e in &events[skip..] {
+        println!("{}", render(e, json));
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    loop {
+        tokio::time::sleep(FOLLOW_POLL_INTERVAL).await;
+        let fresh = cursor.advance(client.events_since(cursor.since()).await?);
+        for e in fresh.iter().filter(|e| wanted(e)) {
+            println!("{}", render(e, json));
+        }
+    }
+}
+
+/// `--follow` position in the event stream. The daemon only returns events
+/// strictly newer than the timestamp asked for, so the cursor asks for its
+/// own millisecond again and skips the events it already returned for it;
+/// events landing in the same millisecond after a poll aren't lost.
+#[derive(Default)]
+struct Cursor {
+    timestamp_ms: u64,
+    /// (process id, kind, exit code) of each event seen at `timestamp_ms`.
+    seen: Vec<(u32, u8, i32)>,
+}
+
+impl Cursor {
+    fn since(&self) -> u64 {
+        self.timestamp_ms.saturating_sub(1)
+    }
+
+    /// The events of `batch` (oldest first) not returned before.
+    fn advance(&mut self, batch: Vec<DaemonEvent>) -> Vec<DaemonEvent> {
+        let key = |e: &DaemonEvent| (e.process_id, e.event.kind, e.event.exit_code);
+        let mut already = std::mem::take(&mut self.seen);
+        let newest = batch
+            .last()
+            .map_or(self.timestamp_ms, |e| e.event.timestamp_ms);
+        let mut fresh = Vec::new();
+        for e in batch {
+            if e.event.timestamp_ms == newest {
+                self.seen.push(key(&e));
+            }
+            if e.event.timestamp_ms < self.timestamp_ms {
+                continue;
+            }
+            if e.event.timestamp_ms == self.timestamp_ms {
+                if let Some(i) = already.iter().position(|k| *k == key(&e)) {
+                    already.swap_remove(i);
+                    continue;
+                }
+            }
+            fresh.push(e);
+        }
+        if newest == self.timestamp_ms {
+            // Nothing newer arrived: keep what was seen at this millisecond
+            self.seen.append(&mut already);
+        }
+        self.timestamp_ms = newest;
+        fresh
+    }
+}
+
+/// One event per line: `[10:05:03] api (0) oom: restarting due to
+/// max_memory_restart`, or a compact JSON object with `--json`.
+fn render(e: &DaemonEvent, json: bool) -> String {
+    if json {
+        return serde_json::json!({
+            "timestamp_ms": e.event.timestamp_ms,
+            "id": e.process_id,
+            "name": e.name,
+            "kind": e.event.kind_str(),
+            "exit_code": e.event.exit_code,
+            "reason": e.event.describe(),
+        })
+        .to_string();
+    }
+    format!(
+        "[{}] {} ({}) {}: {}",
+        format_timestamp_short(e.event.timestamp_ms),
+        e.name,
+        e.process_id,
+        e.event.kind_str(),
+        e.event.describe()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use velos_core::protocol::LifecycleEvent;
+
+    fn event(kind: u8, exit_code: i32) -> DaemonEvent {
+        DaemonEvent {
+            process_id: 2,
+            name: "api".into(),
+            event: LifecycleEvent {
+                timestamp_ms: 36_303_000,
+                kind,
+                exit_code,
+            },
+        }
+    }
+
+    #[test]
+    fn test_render_each_event_kind() {
+        let cases = [
+            (0, 0, "restart: restarting after a clean exit"),
+            (1, 1, "crash: crashed (exit code 1)"),
+            (1, -9, "crash: crashed (signal 9)"),
+            (2, 0, "oom: restarting due to max_memory_restart"),
+            (3, 0, "cron: restarting due to cron_restart"),
+            (4, 0, "watch: restarting due to a watched file change"),
+            (5, 0, "manual: restart requested"),
+            (6, 0, "start: started"),
+            (7, 0, "stop: stop requested"),
+            (8, 0, "cpu: restarting due to max_cpu_restart"),
+            (42, 0, "unknown: unknown event 42"),
+        ];
+        for (kind, exit_code, expected) in cases {
+            assert_eq!(
+                render(&event(kind, exit_code), false),
+                format!("[10:05:03] api (2) {expected}")
+            );
+        }
+
+        let line = render(&event(1, 137), true);
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["id"], 2);
+        assert_eq!(value["kind"], "crash");
+        assert_eq!(value["reason"], "crashed (exit code 137)");
+        assert!(!line.contains('\n'));
+    }
+
+    #[test]
+    fn test_cursor_keeps_same_millisecond_events() {
+        let at = |timestamp_ms: u64, kind: u8| DaemonEvent {
+            event: LifecycleEvent {
+                timestamp_ms,
+                kind,
+                exit_code: 0,
+            },
+            ..event(0, 0)
+        };
+        let mut cursor = Cursor::default();
+        assert_eq!(cursor.advance(vec![at(100, 6), at(200, 7)]).len(), 2);
+        assert_eq!(cursor.since(), 199);
+
+        // The daemon returns the stop again, plus a start from the same ms
+        let fresh = cursor.advance(vec![at(200, 7), at(200, 6)]);
+        assert_eq!(fresh.len(), 1);
+        assert_eq!(fresh[0].event.kind, 6);
+
+        // Both are known now; an empty poll changes nothing
+        assert!(cursor.advance(vec![at(200, 7), at(200, 6)]).is_empty());
+        assert!(cursor.advance(Vec::new()).is_empty());
+        assert!(cursor.advance(vec![at(200, 7), at(200, 6)]).is_empty());
+        assert_eq!(cursor.advance(vec![at(200, 6), at(300, 2)]).len(), 1);
+    }
+}
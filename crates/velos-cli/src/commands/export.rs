@@ -0,0 +1,35 @@
+use velos_core::VelosError;
+
+/// `velos export --pm2`: write the apps in a velos.toml as a PM2 ecosystem
+/// file. A `.js` output gets the `module.exports = ` wrapper; `-` prints
+/// plain JSON.
+pub async fn run_pm2(config: String, output: String, force: bool) -> Result<(), VelosError> {
+    let loaded = velos_config::load(std::path::Path::new(&config))
+        .map_err(|e| VelosError::ProtocolError(format!("{config}: {e}")))?;
+    let export = velos_config::pm2::export(&loaded);
+    let json = serde_json::to_string_pretty(&export.ecosystem).unwrap_or_default();
+
+    for warning in &export.warnings {
+        eprintln!("[velos] Warning: {warning}");
+    }
+    if output == "-" {
+        println!("{json}");
+        return Ok(());
+    }
+    if std::path::Path::new(&output).exists() && !force {
+        return Err(VelosError::ProtocolError(format!(
+            "{output} already exists (use --force to overwrite, or --output -)"
+        )));
+    }
+    let text = if output.ends_with(".js") {
+        format!("module.exports = {json};\n")
+    } else {
+        format!("{json}\n")
+    };
+    std::fs::write(&output, text)?;
+    println!(
+        "[velos] Exported {} app(s) from {config} to {output}",
+        loaded.apps.len()
+    );
+    Ok(())
+}
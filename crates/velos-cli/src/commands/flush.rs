@@ -1,26 +1,19 @@
+use velos_client::disk::LogPaths;
 use velos_core::VelosError;
 
 pub async fn run(name_or_id: Option<String>, json: bool) -> Result<(), VelosError> {
-    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
-    let log_dir = std::path::PathBuf::from(&home).join(".velos").join("logs");
+    let log_dir = velos_client::disk::default_log_dir();
 
     if let Some(ref target) = name_or_id {
-        // Flush logs for a specific process — resolve name to find log files
+        // Flush logs for a specific process — its info holds the log paths
         let mut client = super::connect().await?;
         let id = super::resolve_id(&mut client, target).await?;
-        let procs = client.list().await?;
-        if let Some(p) = procs.iter().find(|p| p.id == id) {
-            let out_log = log_dir.join(format!("{}-out.log", p.name));
-            let err_log = log_dir.join(format!("{}-err.log", p.name));
-            let _ = std::fs::write(&out_log, b"");
-            let _ = std::fs::write(&err_log, b"");
-            if json {
-                println!("{}", serde_json::json!({ "flushed": p.name }));
-            } else {
-                println!("[velos] Flushed logs for '{}'", p.name);
-            }
+        let detail = client.info(id).await?;
+        LogPaths::new(&log_dir, &detail.name, &detail.out_log, &detail.err_log).truncate();
+        if json {
+            println!("{}", serde_json::json!({ "flushed": detail.name }));
         } else {
-            return Err(VelosError::ProcessNotFound(target.clone()));
+            println!("[velos] Flushed logs for '{}'", detail.name);
         }
     } else {
         // Flush all logs
@@ -33,6 +26,14 @@ pub async fn run(name_or_id: Option<String>, json: bool) -> Result<(), VelosErro
                 }
             }
         }
+        // Logs written outside the log dir are only known to the daemon
+        if let Ok(mut client) = velos_client::VelosClient::connect().await {
+            for p in client.list().await.unwrap_or_default() {
+                if let Ok(d) = client.info(p.id).await {
+                    LogPaths::new(&log_dir, &d.name, &d.out_log, &d.err_log).truncate();
+                }
+            }
+        }
         if json {
             println!("{}", serde_json::json!({ "flushed": "all" }));
         } else {
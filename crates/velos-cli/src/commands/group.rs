@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+
+use velos_core::protocol::ProcessInfo;
+use velos_core::VelosError;
+
+pub async fn run_list(json: bool) -> Result<(), VelosError> {
+    let mut client = super::connect().await?;
+    let procs = client.list().await?;
+    let counts = group_counts(&procs);
+
+    if json {
+        let groups: Vec<_> = counts
+            .iter()
+            .map(|(name, count)| serde_json::json!({ "group": name, "members": count }))
+            .collect();
+        println!("{}", serde_json::to_string(&groups).unwrap_or_default());
+    } else if counts.is_empty() {
+        println!("[velos] No groups (start processes with --group <name>)");
+    } else {
+        for (name, count) in &counts {
+            println!("  @{name:<20} {count} process(es)");
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn run_restart(group: String, json: bool) -> Result<(), VelosError> {
+    let mut client = super::connect().await?;
+    let ids = super::resolve_ids(&mut client, &format!("@{group}")).await?;
+
+    for id in &ids {
+        client.restart(*id).await?;
+    }
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({ "group": group, "restarted": ids })
+        );
+    } else {
+        println!(
+            "[velos] Restarted {} process(es) in group '{group}'",
+            ids.len()
+        );
+    }
+
+    Ok(())
+}
+
+pub async fn run_stop(group: String, json: bool) -> Result<(), VelosError> {
+    let mut client = super::connect().await?;
+    let ids = super::resolve_ids(&mut client, &format!("@{group}")).await?;
+
+    for id in &ids {
+        client.stop(*id).await?;
+    }
+
+    if json {
+        println!("{}", serde_json::json!({ "group": group, "stopped": ids }));
+    } else {
+        println!(
+            "[velos] Stopped {} process(es) in group '{group}'",
+            ids.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Member count per group, sorted by group name. Ungrouped processes are
+/// left out.
+fn group_counts(procs: &[ProcessInfo]) -> BTreeMap<&str, usize> {
+    let mut counts = BTreeMap::new();
+    for group in procs.iter().filter_map(|p| p.group.as_deref()) {
+        *counts.entry(group).or_default() += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use velos_client::resolve::group_members as members;
+
+    fn proc(id: u32, name: &str, group: Option<&str>) -> ProcessInfo {
+        ProcessInfo {
+            id,
+            name: name.into(),
+            pid: 1000 + id,
+            status: 1,
+            memory_bytes: 0,
+            uptime_ms: 0,
+            restart_count: 0,
+            cpu_percent: 0.0,
+            group: group.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn test_group_members_from_tagged_list() {
+        let procs = vec![
+            proc(0, "api:0", Some("backend")),
+            proc(1, "api:1", Some("backend")),
+            proc(2, "web", Some("frontend")),
+            proc(3, "cron", None),
+            proc(4, "worker", Some("backend")),
+        ];
+
+        assert_eq!(members(&procs, "backend"), vec![0, 1, 4]);
+        assert_eq!(members(&procs, "frontend"), vec![2]);
+        assert!(members(&procs, "Backend").is_empty());
+        assert!(members(&procs, "").is_empty());
+
+        let counts = group_counts(&procs);
+        assert_eq!(
+            counts.into_iter().collect::<Vec<_>>(),
+            [("backend", 3), ("frontend", 1)]
+        );
+    }
+}
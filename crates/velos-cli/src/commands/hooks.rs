@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use velos_core::VelosError;
+
+/// One `pre_start` / `pre_stop` / `post_stop` command from velos.toml.
+#[derive(Debug, Clone)]
+pub struct Hook {
+    pub stage: &'static str,
+    pub command: String,
+    pub cwd: PathBuf,
+    pub env: HashMap<String, String>,
+    pub timeout: Duration,
+}
+
+/// The hooks an app declares. All `None` for apps started without a config.
+#[derive(Debug, Clone, Default)]
+pub struct AppHooks {
+    pub pre_start: Option<Hook>,
+    pub pre_stop: Option<Hook>,
+    pub post_stop: Option<Hook>,
+}
+
+impl AppHooks {
+    /// Hooks run in the app's cwd with its config env on top of velos's own.
+    /// Fails if the app has hooks and its `env_file` can't be read.
+    pub fn from_app(app: &velos_config::AppConfig, config_dir: &Path) -> Result<Self, VelosError> {
+        if app.pre_start.is_none() && app.pre_stop.is_none() && app.post_stop.is_none() {
+            return Ok(Self::default());
+        }
+        let env = velos_config::merged_env(app, None)
+            .map_err(|e| VelosError::ProtocolError(format!("config error: {e}")))?;
+        let hook = |stage: &'static str, command: &Option<String>| {
+            command.as_ref().map(|command| Hook {
+                stage,
+                command: command.clone(),
+                cwd: PathBuf::from(super::start::app_cwd(app, config_dir)),
+                env: env.clone(),
+                timeout: Duration::from_millis(app.hook_timeout),
+            })
+        };
+        Ok(Self {
+            pre_start: hook("pre_start", &app.pre_start),
+            pre_stop: hook("pre_stop", &app.pre_stop),
+            post_stop: hook("post_stop", &app.post_stop),
+        })
+    }
+
+    /// Hooks for the app named `name` in ./velos.toml. Only exact app names
+    /// match: IDs, `@group` and globs run no hooks.
+    pub fn for_name(name: &str) -> Self {
+        let path = Path::new("velos.toml");
+        // A missing or broken velos.toml just means there are no hooks
+        let Ok(config) = velos_config::load(path) else {
+            return Self::default();
+        };
+        let config_dir = std::env::current_dir().unwrap_or_else(|_| ".".into());
+        config
+            .apps
+            .iter()
+            .find(|(key, app)| app.name.as_deref().unwrap_or(key) == name)
+            .map(|(_, app)| {
+                Self::from_app(app, &config_dir).unwrap_or_else(|e| {
+                    eprintln!("[velos] Warning: not running hooks for '{name}': {e}");
+                    Self::default()
+                })
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Hook {
+    /// Run the command through `sh -c`, echoing what it printed to stderr so
+    /// `--json` output stays clean. Fails on a non-zero exit, or if it runs
+    /// past the timeout (it is killed then).
+    pub async fn run(&self) -> Result<(), VelosError> {
+        let child = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .current_dir(&self.cwd)
+            .envs(&self.env)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                VelosError::SpawnFailed(format!("{} hook '{}': {e}", self.stage, self.command))
+            })?;
+
+        let output = match tokio::time::timeout(self.timeout, child.wait_with_output()).await {
+            Ok(output) => output.map_err(|e| {
+                VelosError::ProtocolError(format!("{} hook '{}': {e}", self.stage, self.command))
+            })?,
+            Err(_) => {
+                return Err(VelosError::ProtocolError(format!(
+                    "{} hook '{}' timed out after {}ms",
+                    self.stage,
+                    self.command,
+                    self.timeout.as_millis()
+                )))
+            }
+        };
+
+        for stream in [&output.stdout, &output.stderr] {
+            for line in String::from_utf8_lossy(stream).lines() {
+                eprintln!("[velos] {}: {line}", self.stage);
+            }
+        }
+        if !output.status.success() {
+            let status = match output.status.code() {
+                Some(code) => format!("exit code {code}"),
+                None => "killed by a signal".to_string(),
+            };
+            return Err(VelosError::ProtocolError(format!(
+                "{} hook '{}' failed ({status})",
+                self.stage, self.command
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Run `pre`, then `action`, then `post`. A failing `pre` aborts before
+/// `action` starts; `post` runs only once `action` has succeeded.
+pub async fn around<T>(
+    pre: Option<&Hook>,
+    action: impl Future<Output = Result<T, VelosError>>,
+    post: Option<&Hook>,
+) -> Result<T, VelosError> {
+    if let Some(hook) = pre {
+        hook.run().await?;
+    }
+    let result = action.await?;
+    if let Some(hook) = post {
+        hook.run().await?;
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hook(stage: &'static str, command: &str, cwd: &Path) -> Hook {
+        Hook {
+            stage,
+            command: command.into(),
+            cwd: cwd.to_path_buf(),
+            env: HashMap::from([("VELOS_HOOK_TEST".into(), "yes".into())]),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("velos_hooks_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_hooks_run_in_order() {
+        let dir = temp_dir("order");
+        let pre = hook("pre_start", "echo pre-$VELOS_HOOK_TEST >> trace", &dir);
+        let post = hook("post_stop", "echo post >> trace", &dir);
+        let trace = dir.join("trace");
+
+        let result = around(
+            Some(&pre),
+            async {
+                let mut seen = std::fs::read_to_string(&trace).unwrap();
+                seen.push_str("action\n");
+                std::fs::write(&trace, seen).unwrap();
+                Ok(7)
+            },
+            Some(&post),
+        )
+        .await;
+        assert_eq!(result.unwrap(), 7);
+        assert_eq!(
+            std::fs::read_to_string(&trace).unwrap(),
+            "pre-yes\naction\npost\n"
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_failing_pre_hook_aborts() {
+        let dir = temp_dir("abort");
+        let pre = hook("pre_start", "echo migrating; false", &dir);
+        let post = hook("post_stop", "echo post >> trace", &dir);
+        let ran = std::cell::Cell::new(false);
+
+        let err = around(
+            Some(&pre),
+            async {
+                ran.set(true);
+                Ok(())
+            },
+            Some(&post),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("pre_start hook"), "{err}");
+        assert!(err.to_string().contains("exit code 1"), "{err}");
+        assert!(!ran.get());
+        assert!(!dir.join("trace").exists());
+
+        let mut slow = hook("pre_stop", "sleep 5", &dir);
+        slow.timeout = Duration::from_millis(100);
+        let err = slow.run().await.unwrap_err();
+        assert!(err.to_string().contains("timed out after 100ms"), "{err}");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,33 @@
+use std::path::Path;
+
+use velos_core::VelosError;
+
+/// `velos import --pm2 <ecosystem file>`: convert a PM2 ecosystem file into
+/// a velos.toml. An existing output file is only replaced with `force`;
+/// `-` prints the TOML instead.
+pub async fn run_pm2(source: String, output: String, force: bool) -> Result<(), VelosError> {
+    let config_err =
+        |e: velos_config::ConfigError| VelosError::ProtocolError(format!("{source}: {e}"));
+    let text = std::fs::read_to_string(&source)?;
+    let import = velos_config::pm2::import(&text).map_err(config_err)?;
+    let toml = velos_config::format(&import.config).map_err(config_err)?;
+
+    for warning in &import.warnings {
+        eprintln!("[velos] Warning: {warning}");
+    }
+    if output == "-" {
+        print!("{toml}");
+        return Ok(());
+    }
+    if Path::new(&output).exists() && !force {
+        return Err(VelosError::ProtocolError(format!(
+            "{output} already exists (use --force to overwrite, or --output -)"
+        )));
+    }
+    std::fs::write(&output, toml)?;
+    println!(
+        "[velos] Imported {} app(s) from {source} into {output}",
+        import.config.apps.len()
+    );
+    Ok(())
+}
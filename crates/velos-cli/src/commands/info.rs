@@ -1,29 +1,43 @@
-use velos_core::VelosError;
+use std::collections::BTreeMap;
 
-pub async fn run(name_or_id: String, json: bool, ai: bool) -> Result<(), VelosError> {
+use serde_json::Value;
+use velos_core::protocol::{ExitReason, LifecycleEvent, ProcessDetail};
+use velos_core::{redact, VelosError};
+
+use super::JSON_SCHEMA_VERSION;
+
+pub async fn run(
+    name_or_id: String,
+    json: bool,
+    ai: bool,
+    history: bool,
+    diff_config: Option<String>,
+    show_secrets: bool,
+) -> Result<(), VelosError> {
     let mut client = super::connect().await?;
     let id = super::resolve_id(&mut client, &name_or_id).await?;
     let detail = client.info(id).await?;
 
+    if let Some(path) = diff_config {
+        return print_config_diff(&detail, &path, json);
+    }
+    let events = if history {
+        Some(client.history(id).await?)
+    } else {
+        None
+    };
+
     if ai {
-        let compact = serde_json::json!({
-            "n": detail.name,
-            "s": detail.status_str(),
-            "p": detail.pid,
-            "m": detail.memory_bytes,
-            "u": detail.uptime_ms,
-            "r": detail.restart_count,
-            "script": detail.script,
-            "cwd": detail.cwd,
-        });
+        let compact = info_ai(&detail, events.as_deref());
         println!("{}", serde_json::to_string(&compact).unwrap_or_default());
         return Ok(());
     }
 
     if json {
+        let value = info_json(&detail, events.as_deref(), show_secrets);
         println!(
             "{}",
-            serde_json::to_string_pretty(&detail).unwrap_or_default()
+            serde_json::to_string_pretty(&value).unwrap_or_default()
         );
         return Ok(());
     }
@@ -40,6 +54,9 @@ pub async fn run(name_or_id: String, json: bool, ai: bool) -> Result<(), VelosEr
     if detail.consecutive_crashes > 0 {
         println!("  Crashes:        {}", detail.consecutive_crashes);
     }
+    if detail.last_exit_reason != ExitReason::None {
+        println!("  Last exit:      {}", detail.last_exit_reason.as_str());
+    }
     if !detail.interpreter.is_empty() {
         println!("  Interpreter:    {}", detail.interpreter);
     }
@@ -65,6 +82,12 @@ pub async fn run(name_or_id: String, json: bool, ai: bool) -> Result<(), VelosEr
             format_bytes(detail.max_memory_restart)
         );
     }
+    if detail.max_cpu_restart > 0.0 {
+        println!(
+            "  Max CPU:        {}% over {}s",
+            detail.max_cpu_restart, detail.cpu_restart_window_secs
+        );
+    }
     if detail.watch {
         println!("  Watch mode:     enabled");
     }
@@ -77,10 +100,314 @@ pub async fn run(name_or_id: String, json: bool, ai: bool) -> Result<(), VelosEr
     if detail.shutdown_with_message {
         println!("  Shutdown msg:   true");
     }
+    if detail.log_rate_limit > 0 {
+        println!(
+            "  Log rate limit: {} lines/s ({} suppressed)",
+            detail.log_rate_limit, detail.suppressed_lines
+        );
+    }
+
+    let env = display_env(&detail, show_secrets);
+    if !env.is_empty() {
+        println!();
+        println!("  Env:");
+        for (key, value) in &env {
+            println!("    {key}={value}");
+        }
+    }
+
+    if let Some(events) = events {
+        println!();
+        println!("  History:");
+        if events.is_empty() {
+            println!("    (no restarts or crashes recorded)");
+        }
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        for e in events.iter().rev() {
+            let ago = format_uptime(now.saturating_sub(e.timestamp_ms));
+            match e.kind {
+                1 if e.exit_code < 0 => {
+                    println!("    {:>8} ago  crash    (signal {})", ago, -e.exit_code)
+                }
+                1 => println!("    {:>8} ago  crash    (exit code {})", ago, e.exit_code),
+                _ => println!("    {:>8} ago  {}", ago, e.kind_str()),
+            }
+        }
+    }
 
     Ok(())
 }
 
+/// `--ai` form: abbreviated keys, see the legend in commands/mod.rs.
+fn info_ai(detail: &ProcessDetail, events: Option<&[LifecycleEvent]>) -> Value {
+    let mut compact = serde_json::json!({
+        "schema_version": JSON_SCHEMA_VERSION,
+        "n": detail.name,
+        "s": detail.status_str(),
+        "p": detail.pid,
+        "m": detail.memory_bytes,
+        "u": detail.uptime_ms,
+        "r": detail.restart_count,
+        "le": detail.last_exit_reason.as_str(),
+        "script": detail.script,
+        "cwd": detail.cwd,
+    });
+    if let Some(events) = events {
+        compact["e"] = events
+            .iter()
+            .map(
+                |e| serde_json::json!({ "t": e.timestamp_ms, "k": e.kind_str(), "x": e.exit_code }),
+            )
+            .collect();
+    }
+    compact
+}
+
+/// The process environment in key order, secret values masked as `****`
+/// unless `show_secrets`.
+fn display_env(detail: &ProcessDetail, show_secrets: bool) -> BTreeMap<String, String> {
+    if show_secrets {
+        detail.env.clone().into_iter().collect()
+    } else {
+        redact::redact_env(&detail.env).into_iter().collect()
+    }
+}
+
+fn info_json(
+    detail: &ProcessDetail,
+    events: Option<&[LifecycleEvent]>,
+    show_secrets: bool,
+) -> Value {
+    let mut value = serde_json::to_value(detail).unwrap_or_default();
+    value["schema_version"] = JSON_SCHEMA_VERSION.into();
+    value["env"] = serde_json::json!(display_env(detail, show_secrets));
+    if let Some(events) = events {
+        value["history"] = events
+            .iter()
+            .map(|e| {
+                serde_json::json!({
+                    "timestamp_ms": e.timestamp_ms,
+                    "kind": e.kind_str(),
+                    "exit_code": e.exit_code,
+                })
+            })
+            .collect();
+    }
+    value
+}
+
+/// `--diff-config`: show where the running process has drifted from the
+/// app declared in `path`.
+fn print_config_diff(detail: &ProcessDetail, path: &str, json: bool) -> Result<(), VelosError> {
+    let config = velos_config::load(std::path::Path::new(path))
+        .map_err(|e| VelosError::ProtocolError(format!("config error: {e}")))?;
+    let (key, app) = find_app(&config, &detail.name).ok_or_else(|| {
+        VelosError::ProtocolError(format!("no app in {path} matches '{}'", detail.name))
+    })?;
+    let drift = config_drift(&Settings::from_app(app), &Settings::from_detail(detail));
+
+    if json {
+        let fields: Vec<_> = drift
+            .iter()
+            .map(|d| {
+                serde_json::json!({ "field": d.field, "declared": d.declared, "running": d.running })
+            })
+            .collect();
+        println!(
+            "{}",
+            serde_json::json!({
+                "name": detail.name,
+                "config": path,
+                "app": key,
+                "in_sync": drift.is_empty(),
+                "drift": fields,
+            })
+        );
+    } else if drift.is_empty() {
+        println!("[velos] '{}' matches [apps.{key}] in {path}", detail.name);
+    } else {
+        println!(
+            "[velos] '{}' differs from [apps.{key}] in {path}:",
+            detail.name
+        );
+        println!("  {:<22} {:<16} RUNNING", "FIELD", "DECLARED");
+        for d in &drift {
+            println!("  {:<22} {:<16} {}", d.field, d.declared, d.running);
+        }
+    }
+    Ok(())
+}
+
+/// The app a process was started from: same name, or the base name of a
+/// cluster instance ("api:2" → `[apps.api]`).
+fn find_app<'a>(
+    config: &'a velos_config::VelosConfig,
+    process_name: &str,
+) -> Option<(&'a str, &'a velos_config::AppConfig)> {
+    let app_name = |key: &'a String, app: &'a velos_config::AppConfig| {
+        app.name.as_deref().unwrap_or(key.as_str())
+    };
+    config
+        .apps
+        .iter()
+        .find(|(key, app)| app_name(key, app) == process_name)
+        .or_else(|| {
+            config.apps.iter().find(|(key, app)| {
+                velos_core::process::INSTANCE_NAME_TEMPLATES
+                    .iter()
+                    .any(|t| {
+                        velos_core::process::match_instance(t, app_name(key, app), process_name)
+                            .is_some()
+                    })
+            })
+        })
+        .map(|(key, app)| (key.as_str(), app))
+}
+
+/// Settings both the daemon reports and velos.toml declares, in one shape
+/// so the two sides compare field by field.
+#[derive(Debug, PartialEq)]
+struct Settings {
+    script: String,
+    interpreter: String,
+    autorestart: bool,
+    max_restarts: i32,
+    min_uptime_ms: u64,
+    restart_delay_ms: u64,
+    exp_backoff: bool,
+    kill_timeout_ms: u64,
+    max_memory_restart: u64,
+    max_cpu_restart: f32,
+    cpu_restart_window_secs: u32,
+    watch: bool,
+    cron_restart: String,
+    log_rate_limit: u32,
+}
+
+impl Settings {
+    fn from_detail(d: &ProcessDetail) -> Self {
+        Self {
+            script: d.script.clone(),
+            interpreter: d.interpreter.clone(),
+            autorestart: d.autorestart,
+            max_restarts: d.max_restarts,
+            min_uptime_ms: d.min_uptime_ms,
+            restart_delay_ms: d.restart_delay_ms.into(),
+            exp_backoff: d.exp_backoff,
+            kill_timeout_ms: d.kill_timeout_ms.into(),
+            max_memory_restart: d.max_memory_restart,
+            max_cpu_restart: d.max_cpu_restart,
+            cpu_restart_window_secs: d.cpu_restart_window_secs,
+            watch: d.watch,
+            cron_restart: d.cron_restart.clone(),
+            log_rate_limit: d.log_rate_limit,
+        }
+    }
+
+    /// Mirrors what `velos start --config` sends for the app.
+    fn from_app(app: &velos_config::AppConfig) -> Self {
+        Self {
+            script: app.script.clone(),
+            interpreter: app.interpreter.clone().unwrap_or_default(),
+            autorestart: app.autorestart,
+            max_restarts: app.max_restarts,
+            min_uptime_ms: app.min_uptime,
+            restart_delay_ms: app.restart_delay,
+            exp_backoff: app.exp_backoff_restart_delay,
+            kill_timeout_ms: app.kill_timeout,
+            max_memory_restart: app
+                .max_memory_restart
+                .as_deref()
+                .and_then(|m| velos_config::parse_memory_string(m).ok())
+                .unwrap_or(0),
+            max_cpu_restart: app.max_cpu_restart.unwrap_or(0.0),
+            cpu_restart_window_secs: app.cpu_restart_window_secs,
+            watch: app.watch,
+            cron_restart: app.cron_restart.clone().unwrap_or_default(),
+            log_rate_limit: app.log_rate_limit.unwrap_or(0),
+        }
+    }
+
+    /// Field name and display value, in a fixed order.
+    fn describe(&self) -> Vec<(&'static str, String)> {
+        let or = |s: &str, empty: &str| {
+            if s.is_empty() {
+                empty.to_string()
+            } else {
+                s.to_string()
+            }
+        };
+        vec![
+            ("script", self.script.clone()),
+            ("interpreter", or(&self.interpreter, "auto")),
+            ("autorestart", self.autorestart.to_string()),
+            (
+                "max_restarts",
+                if self.max_restarts < 0 {
+                    "unlimited".into()
+                } else {
+                    self.max_restarts.to_string()
+                },
+            ),
+            ("min_uptime_ms", self.min_uptime_ms.to_string()),
+            ("restart_delay_ms", self.restart_delay_ms.to_string()),
+            ("exp_backoff", self.exp_backoff.to_string()),
+            ("kill_timeout_ms", self.kill_timeout_ms.to_string()),
+            (
+                "max_memory_restart",
+                match self.max_memory_restart {
+                    0 => "none".into(),
+                    bytes => format_bytes(bytes),
+                },
+            ),
+            (
+                "max_cpu_restart",
+                if self.max_cpu_restart > 0.0 {
+                    format!(
+                        "{}% over {}s",
+                        self.max_cpu_restart, self.cpu_restart_window_secs
+                    )
+                } else {
+                    "none".into()
+                },
+            ),
+            ("watch", self.watch.to_string()),
+            ("cron_restart", or(&self.cron_restart, "none")),
+            (
+                "log_rate_limit",
+                match self.log_rate_limit {
+                    0 => "unlimited".into(),
+                    n => format!("{n}/s"),
+                },
+            ),
+        ]
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Drift {
+    field: &'static str,
+    declared: String,
+    running: String,
+}
+
+fn config_drift(declared: &Settings, running: &Settings) -> Vec<Drift> {
+    declared
+        .describe()
+        .into_iter()
+        .zip(running.describe())
+        .filter(|((_, d), (_, r))| d != r)
+        .map(|((field, declared), (_, running))| Drift {
+            field,
+            declared,
+            running,
+        })
+        .collect()
+}
+
 fn format_bytes(bytes: u64) -> String {
     if bytes < 1024 {
         format!("{bytes} B")
@@ -93,7 +420,7 @@ fn format_bytes(bytes: u64) -> String {
     }
 }
 
-fn format_uptime(ms: u64) -> String {
+pub(crate) fn format_uptime(ms: u64) -> String {
     let secs = ms / 1000;
     if secs < 60 {
         format!("{secs}s")
@@ -105,3 +432,98 @@ fn format_uptime(ms: u64) -> String {
         format!("{}d {}h", secs / 86400, (secs % 86400) / 3600)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_drift_max_restarts() {
+        let config = velos_config::parse(
+            r#"
+[apps.api]
+script = "server.js"
+max_restarts = 10
+"#,
+        )
+        .unwrap();
+        let detail = ProcessDetail {
+            name: "api:1".into(),
+            script: "server.js".into(),
+            autorestart: true,
+            max_restarts: 15,
+            min_uptime_ms: 1000,
+            kill_timeout_ms: 5000,
+            ..Default::default()
+        };
+
+        let (key, app) = find_app(&config, &detail.name).unwrap();
+        assert_eq!(key, "api");
+        let drift = config_drift(&Settings::from_app(app), &Settings::from_detail(&detail));
+        assert_eq!(
+            drift,
+            vec![Drift {
+                field: "max_restarts",
+                declared: "10".into(),
+                running: "15".into(),
+            }]
+        );
+
+        let in_sync = ProcessDetail {
+            max_restarts: 10,
+            ..detail
+        };
+        assert!(
+            config_drift(&Settings::from_app(app), &Settings::from_detail(&in_sync)).is_empty()
+        );
+        assert!(find_app(&config, "worker").is_none());
+    }
+
+    #[test]
+    fn test_info_json_has_schema_version() {
+        let detail = ProcessDetail {
+            name: "api".into(),
+            ..Default::default()
+        };
+        for value in [info_json(&detail, None, false), info_ai(&detail, Some(&[]))] {
+            assert_eq!(value["schema_version"], 5);
+        }
+        let value = info_json(&detail, None, false);
+        assert_eq!(value["name"], "api");
+        assert_eq!(value["merge_logs"], false);
+        assert_eq!(value["cpu_restart_window_secs"], 0);
+        assert_eq!(value["out_log"], "");
+        assert_eq!(value["env"], serde_json::json!({}));
+        assert_eq!(JSON_SCHEMA_VERSION, 5);
+    }
+
+    #[test]
+    fn test_info_json_redacts_env() {
+        let detail = ProcessDetail {
+            name: "api".into(),
+            env: [
+                ("PORT", "3000"),
+                ("GITHUB_TOKEN", "ghp_x"),
+                ("SESSION_SECRET", "s3cret"),
+                ("PASSWORD", "hunter2"),
+            ]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+            ..Default::default()
+        };
+        let value = info_json(&detail, None, false);
+        assert_eq!(
+            value["env"],
+            serde_json::json!({
+                "GITHUB_TOKEN": "****",
+                "PASSWORD": "****",
+                "PORT": "3000",
+                "SESSION_SECRET": "****",
+            })
+        );
+        let shown = info_json(&detail, None, true);
+        assert_eq!(shown["env"]["GITHUB_TOKEN"], "ghp_x");
+        assert_eq!(shown["env"]["PASSWORD"], "hunter2");
+    }
+}
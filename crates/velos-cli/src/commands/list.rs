@@ -1,3 +1,4 @@
+use serde_json::Value;
 use tabled::{
     builder::Builder,
     settings::{
@@ -6,36 +7,32 @@ use tabled::{
         Alignment, Color, Modify,
     },
 };
+use velos_core::protocol::ProcessInfo;
 use velos_core::VelosError;
 
-pub async fn run(json: bool, ai: bool) -> Result<(), VelosError> {
+use super::JSON_SCHEMA_VERSION;
+use crate::color::ColorChoice;
+
+pub async fn run(json: bool, ai: bool, color: ColorChoice) -> Result<(), VelosError> {
     let mut client = super::connect().await?;
-    let procs = client.list().await?;
+    let list = client.list_lenient().await?;
+    if let Some(ref why) = list.truncated {
+        eprintln!("[velos] Warning: incomplete reply from the daemon, {why}");
+    }
+    let procs = list.procs;
 
     if ai {
-        let compact: Vec<_> = procs
-            .iter()
-            .map(|p| {
-                serde_json::json!({
-                    "n": p.name,
-                    "i": p.id,
-                    "s": p.status_str(),
-                    "c": p.cpu_percent,
-                    "m": p.memory_bytes,
-                    "u": p.uptime_ms,
-                    "r": p.restart_count,
-                    "p": p.pid,
-                })
-            })
-            .collect();
-        println!("{}", serde_json::to_string(&compact).unwrap_or_default());
+        println!(
+            "{}",
+            serde_json::to_string(&list_ai(&procs)).unwrap_or_default()
+        );
         return Ok(());
     }
 
     if json {
         println!(
             "{}",
-            serde_json::to_string_pretty(&procs).unwrap_or_default()
+            serde_json::to_string_pretty(&list_json(&procs)).unwrap_or_default()
         );
         return Ok(());
     }
@@ -89,8 +86,12 @@ pub async fn run(json: bool, ai: bool) -> Result<(), VelosError> {
     let mut table = builder.build();
     table
         .with(Style::rounded())
-        .with(Modify::new(Rows::first()).with(Color::new("\x1b[1;37m", "\x1b[0m")))
         .with(Modify::new(Columns::single(0)).with(Alignment::right()));
+    if !color.enabled() {
+        println!("{table}");
+        return Ok(());
+    }
+    table.with(Modify::new(Rows::first()).with(Color::new("\x1b[1;37m", "\x1b[0m")));
 
     // Color status column (index 4) per-row
     let table_str = table.to_string();
@@ -125,7 +126,31 @@ pub async fn run(json: bool, ai: bool) -> Result<(), VelosError> {
     Ok(())
 }
 
-fn format_bytes(bytes: u64) -> String {
+/// `--ai` form: abbreviated keys, see the legend in commands/mod.rs.
+fn list_ai(procs: &[ProcessInfo]) -> Value {
+    let compact: Vec<_> = procs
+        .iter()
+        .map(|p| {
+            serde_json::json!({
+                "n": p.name,
+                "i": p.id,
+                "s": p.status_str(),
+                "c": p.cpu_percent,
+                "m": p.memory_bytes,
+                "u": p.uptime_ms,
+                "r": p.restart_count,
+                "p": p.pid,
+            })
+        })
+        .collect();
+    serde_json::json!({ "schema_version": JSON_SCHEMA_VERSION, "processes": compact })
+}
+
+fn list_json(procs: &[ProcessInfo]) -> Value {
+    serde_json::json!({ "schema_version": JSON_SCHEMA_VERSION, "processes": procs })
+}
+
+pub(crate) fn format_bytes(bytes: u64) -> String {
     if bytes < 1024 {
         format!("{bytes}b")
     } else if bytes < 1024 * 1024 {
@@ -137,7 +162,7 @@ fn format_bytes(bytes: u64) -> String {
     }
 }
 
-fn format_uptime(ms: u64) -> String {
+pub(crate) fn format_uptime(ms: u64) -> String {
     let secs = ms / 1000;
     if secs == 0 {
         "0s".to_string()
@@ -151,3 +176,30 @@ fn format_uptime(ms: u64) -> String {
         format!("{}D", secs / 86400)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_json_has_schema_version() {
+        let procs = vec![ProcessInfo {
+            id: 0,
+            name: "api".into(),
+            pid: 42,
+            status: 1,
+            memory_bytes: 0,
+            uptime_ms: 0,
+            restart_count: 0,
+            cpu_percent: 0.0,
+            group: None,
+        }];
+        let json = list_json(&procs);
+        assert_eq!(json["schema_version"], JSON_SCHEMA_VERSION);
+        assert_eq!(json["processes"][0]["name"], "api");
+        assert!(json["processes"][0]["group"].is_null());
+        let ai = list_ai(&procs);
+        assert_eq!(ai["schema_version"], 5);
+        assert_eq!(ai["processes"][0]["n"], "api");
+    }
+}
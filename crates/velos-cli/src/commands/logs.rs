@@ -1,79 +1,425 @@
-use velos_core::VelosError;
+use std::collections::VecDeque;
+use std::io::IsTerminal;
+
+use velos_client::disk::LogPaths;
+use velos_client::VelosClient;
+use velos_core::protocol::{LifecycleEvent, ProcessDetail, VERSION_2};
+use velos_core::{LogEntry, VelosError};
 use velos_log_engine::classifier::Classifier;
 use velos_log_engine::dedup::DedupEngine;
-use velos_log_engine::pattern::PatternDetector;
-use velos_log_engine::summary;
-use velos_log_engine::{format, LogLevel};
+use velos_log_engine::extract::{Extracted, FieldExtractor};
+use velos_log_engine::history::PatternHistory;
+use velos_log_engine::pattern::{self, DetectedPattern, PatternDetector, Trend};
+use velos_log_engine::{aggregate, anomaly, filter, format, LogLevel};
+use velos_log_engine::{sink, summary};
+
+use crate::color::{self, ColorChoice, LevelColors};
 
 pub struct LogsArgs {
     pub name: String,
     pub lines: u32,
     pub json: bool,
+    pub flatten_parsed: bool,
     pub ai: bool,
     pub grep: Option<String>,
     pub level: Option<String>,
+    pub min_level: Option<String>,
+    pub errors_only: bool,
+    pub warnings: bool,
+    pub exclude_level: Option<String>,
     pub since: Option<String>,
     pub until: Option<String>,
+    pub since_restart: bool,
     pub dedupe: bool,
+    pub aggregate: Option<String>,
+    pub regex_extract: Option<String>,
+    pub keep_unmatched: bool,
+    pub stats: bool,
+    pub timeline: bool,
+    pub bucket: String,
+    pub compare_window: Option<String>,
     pub summary: bool,
+    pub summary_format: Option<SummaryFormat>,
+    pub trend: Option<String>,
+    pub limit_bytes: Option<String>,
+    pub max_line_length: Option<u32>,
+    pub truncate: bool,
+    pub sample: Option<String>,
+    pub head: Option<u32>,
+    pub explain_anomalies: bool,
+    pub ndjson: bool,
+    pub output_dir: Option<String>,
+    pub follow: bool,
+    pub pause_on_match: bool,
+    pub interval: u64,
+    pub merge_clusters: bool,
+    pub since_seq: Option<u64>,
+    pub fields: Option<String>,
+    pub preset: Option<String>,
+    pub clear_after: bool,
+    pub include_internal: bool,
+    pub until_now: bool,
+    pub default_window: Option<String>,
+    pub format: LogFormat,
+    pub color: ColorChoice,
+    pub level_colors: LevelColors,
+    /// `Some(true)` for --merge, `Some(false)` for --no-merge, `None` to
+    /// follow the process's `merge_logs`.
+    pub merge: Option<bool>,
+    /// `[logs]` from ./velos.toml, read once by [`logs_config`].
+    pub config: Option<velos_config::LogEngineConfig>,
+}
+
+/// Layout of text lines (`--format`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogFormat {
+    /// `[INFO|out|10:05:03] msg`, colored by level
+    Leveled,
+    /// `[out|10:05:03] msg`, never colored: for grep and other pipes
+    Plain,
+}
+
+impl LogFormat {
+    pub fn from_env(explicit: Option<Self>) -> Self {
+        Self::resolve(explicit, std::io::stdout().is_terminal())
+    }
+
+    /// An explicit `--format` wins; otherwise a terminal gets the leveled
+    /// view and a pipe the plain one.
+    fn resolve(explicit: Option<Self>, is_tty: bool) -> Self {
+        explicit.unwrap_or(if is_tty { Self::Leveled } else { Self::Plain })
+    }
+
+    /// Color a rendered line by level, unless the format is plain.
+    fn paint(
+        self,
+        colors: &LevelColors,
+        color: ColorChoice,
+        level: LogLevel,
+        line: &str,
+    ) -> String {
+        match self {
+            Self::Leveled => colors.paint(color, level, line),
+            Self::Plain => line.to_string(),
+        }
+    }
+}
+
+/// Rendering of `--summary` (`--summary-format`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum SummaryFormat {
+    /// Terminal text
+    Text,
+    /// Markdown with a patterns table, for issues and chat
+    Md,
+    /// One line of compact JSON with abbreviated keys
+    Ai,
+    /// The full summary as JSON (same as --json)
+    Json,
 }
 
+impl SummaryFormat {
+    /// An explicit `--summary-format` wins; `--json` and `--ai` mean JSON.
+    fn resolve(explicit: Option<Self>, json: bool) -> Self {
+        explicit.unwrap_or(if json { Self::Json } else { Self::Text })
+    }
+}
+
+/// How many lines to fetch when `--head` asks for the start of the buffer.
+const HEAD_FETCH_LINES: u32 = 10_000;
+
+/// Sample lines shown per anomaly with `--explain-anomalies`.
+const EXPLAIN_LINES: usize = 5;
+
+/// How often `--follow` polls the daemon for new lines.
+const FOLLOW_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+/// Lines fetched per `--follow` poll; bursts larger than this between polls are cut.
+const FOLLOW_FETCH_LINES: u32 = 1000;
+/// Lines `--pause-on-match` holds while paused; later ones are dropped.
+const PAUSE_HELD_LINES: usize = 10_000;
+
 pub async fn run(args: LogsArgs) -> Result<(), VelosError> {
-    let mut client = super::connect().await?;
-    let id = super::resolve_id(&mut client, &args.name).await?;
-
-    let entries = client.logs(id, args.lines).await?;
-
-    // Classify
-    let classifier = Classifier::with_defaults();
-    let mut processed = classifier.classify_batch(&entries);
-
-    // Filter by level
-    if let Some(ref levels) = args.level {
-        let allowed: Vec<LogLevel> = levels
-            .split(',')
-            .filter_map(|l| match l.trim().to_lowercase().as_str() {
-                "debug" => Some(LogLevel::Debug),
-                "info" => Some(LogLevel::Info),
-                "warn" | "warning" => Some(LogLevel::Warn),
-                "error" | "err" => Some(LogLevel::Error),
-                "fatal" => Some(LogLevel::Fatal),
-                _ => None,
-            })
-            .collect();
-        processed.retain(|e| allowed.contains(&e.level));
+    let clear = args
+        .clear_after
+        .then(|| clear_process_logs(args.name.clone()));
+    clear_on_success(show(args), clear).await
+}
+
+/// Await `show`, then `clear` only if the lines were fetched and printed.
+/// A future does nothing until awaited, so a failed read never flushes.
+async fn clear_on_success(
+    show: impl std::future::Future<Output = Result<(), VelosError>>,
+    clear: Option<impl std::future::Future<Output = Result<(), VelosError>>>,
+) -> Result<(), VelosError> {
+    show.await?;
+    if let Some(clear) = clear {
+        clear.await?;
     }
+    Ok(())
+}
+
+/// `--clear-after`: empty the process's log files, as `velos flush` does.
+/// With the daemon down the logs were read from disk by name, so `target`
+/// is already the process name.
+async fn clear_process_logs(target: String) -> Result<(), VelosError> {
+    let log_dir = velos_client::disk::default_log_dir();
+    let (name, paths) = match VelosClient::connect().await {
+        Ok(mut client) => {
+            let id = super::resolve_id(&mut client, &target).await?;
+            let d = client.info(id).await?;
+            let paths = LogPaths::new(&log_dir, &d.name, &d.out_log, &d.err_log);
+            (d.name, paths)
+        }
+        Err(VelosError::DaemonNotRunning(_)) => {
+            let paths = LogPaths::saved(&log_dir, &target);
+            (target, paths)
+        }
+        Err(e) => return Err(e),
+    };
+    paths.truncate();
+    eprintln!("[velos] Cleared logs for '{name}'");
+    Ok(())
+}
+
+async fn show(args: LogsArgs) -> Result<(), VelosError> {
+    let limit_bytes = match args.limit_bytes {
+        Some(ref size) => Some(
+            velos_config::parse_memory_string(size)
+                .map_err(|e| VelosError::ProtocolError(format!("invalid --limit-bytes: {e}")))?,
+        ),
+        None => None,
+    };
+
+    let fields = args.fields.as_deref().map(parse_fields).transpose()?;
+    let bucket_ms = parse_duration("bucket", &args.bucket)?;
+    let compare_ms = args
+        .compare_window
+        .as_deref()
+        .map(|spec| parse_duration("compare-window", spec))
+        .transpose()?;
+    let extractor = args
+        .regex_extract
+        .as_deref()
+        .map(parse_extractor)
+        .transpose()?;
+    let default_window = args
+        .default_window
+        .clone()
+        .or_else(|| args.config.as_ref()?.default_window.clone());
+
+    // --head and --since-seq need the whole available buffer to find their
+    // start; --compare-window counts by time, not by line
+    let whole_buffer = args.since_seq.is_some() || compare_ms.is_some();
+    let fetch_lines = match (args.head, whole_buffer) {
+        (Some(n), _) => n.max(HEAD_FETCH_LINES),
+        (None, true) => args.lines.max(HEAD_FETCH_LINES),
+        (None, false) => args.lines,
+    };
 
-    // Filter by grep pattern
-    if let Some(ref pattern) = args.grep {
-        let re = regex::Regex::new(pattern)
-            .map_err(|e| VelosError::ProtocolError(format!("invalid grep pattern: {e}")))?;
-        processed.retain(|e| re.is_match(&e.message));
+    // Don't auto-start the daemon just to read logs: if it is down, fall back
+    // to the log files on disk so crash output is still reachable.
+    let mut detail = None;
+    let mut session = None;
+    let mut cluster = None;
+    let mut internal = Vec::new();
+    let entries = match VelosClient::connect().await {
+        Ok(mut client) => {
+            let ids = if args.merge_clusters {
+                super::resolve_ids(&mut client, &args.name).await?
+            } else {
+                vec![super::resolve_id(&mut client, &args.name).await?]
+            };
+            let id = ids[0];
+            if args.since_seq.is_some() && client.protocol_version() < VERSION_2 {
+                return Err(VelosError::ProtocolError(
+                    "--since-seq needs a newer daemon (its log lines carry no seq)".into(),
+                ));
+            }
+            let plain_text = args.merge.is_none() && !(args.json || args.ai);
+            if args.summary
+                || args.ndjson
+                || args.follow
+                || args.since_restart
+                || fields.is_some()
+                || plain_text
+            {
+                detail = Some(client.info(id).await?);
+            }
+            let entries = if ids.len() > 1 {
+                cluster =
+                    Some(fetch_cluster_logs(&mut client, &args.name, &ids, fetch_lines).await?);
+                Vec::new()
+            } else {
+                client.logs(id, fetch_lines).await?
+            };
+            if args.include_internal {
+                internal = client.history(id).await?;
+            }
+            session = Some((client, id));
+            entries
+        }
+        Err(VelosError::DaemonNotRunning(_)) if args.follow => {
+            return Err(VelosError::ProtocolError(
+                "--follow needs a running daemon".into(),
+            ));
+        }
+        // Lines read from disk carry no sequence numbers
+        Err(VelosError::DaemonNotRunning(_)) if args.since_restart => {
+            return Err(VelosError::ProtocolError(
+                "--since-restart needs a running daemon".into(),
+            ));
+        }
+        Err(VelosError::DaemonNotRunning(_)) if args.since_seq.is_some() => {
+            return Err(VelosError::ProtocolError(
+                "--since-seq needs a running daemon".into(),
+            ));
+        }
+        Err(VelosError::DaemonNotRunning(_)) => {
+            let paths = LogPaths::saved(&velos_client::disk::default_log_dir(), &args.name);
+            let entries = velos_client::disk::read_process_logs(&paths, fetch_lines)
+                .ok_or_else(|| VelosError::ProcessNotFound(args.name.clone()))?;
+            eprintln!("[velos] (daemon offline, reading from disk)");
+            if args.include_internal {
+                eprintln!("[velos] (internal events live in the daemon; none shown)");
+            }
+            entries
+        }
+        Err(e) => return Err(e),
+    };
+
+    let entries = match args.since_seq {
+        Some(seq) => {
+            let (entries, missed) = entries_after_seq(entries, seq);
+            if missed > 0 {
+                eprintln!("[velos] {missed} line(s) after seq {seq} already left the log buffer");
+            }
+            entries
+        }
+        None => entries,
+    };
+
+    // A read that filled its line budget may not reach back far enough for
+    // --compare-window; remember where it starts
+    let truncated_at = if entries.len() >= fetch_lines as usize {
+        entries.iter().map(|e| e.timestamp_ms).min()
+    } else {
+        None
+    };
+
+    let classifier = configured_classifier(args.preset.as_deref(), args.config.as_ref())?;
+    let line_filter = LineFilter::from_args(&args)?;
+
+    if args.follow && args.summary {
+        if let Some((client, id)) = session {
+            let mut cursor = FollowCursor::default();
+            cursor.take_new(&entries);
+            let mut window = SummaryWindow::new(args.lines as usize);
+            window.push(entries);
+            return follow_summary(client, id, &args, &classifier, &line_filter, cursor, window)
+                .await;
+        }
     }
 
-    // Filter by time range
-    if let Some(ref since) = args.since {
-        let since_ms = parse_time_spec(since)?;
-        processed.retain(|e| e.timestamp_ms >= since_ms);
+    // Classify (cluster instances one at a time, before their lines are tagged)
+    let mut processed = match cluster {
+        Some(batches) => merge_cluster_logs(
+            batches
+                .into_iter()
+                .map(|(index, entries)| (index, classifier.classify_batch_parallel(&entries)))
+                .collect(),
+        ),
+        None => classifier.classify_batch_parallel(&entries),
+    };
+
+    // Sample before the other filters; error+ lines are always kept
+    if let Some(ref spec) = args.sample {
+        processed = filter::sample(&processed, parse_percent(spec)?);
     }
-    if let Some(ref until) = args.until {
-        let until_ms = parse_time_spec(until)?;
-        processed.retain(|e| e.timestamp_ms <= until_ms);
+
+    // Filter by level and grep pattern
+    processed.retain(|e| line_filter.keep(e));
+
+    // velos's own events sit between the process's lines, unfiltered by
+    // level or grep, so the window and time range apply to both
+    if !internal.is_empty() {
+        processed = interleave_internal(processed, &internal);
     }
 
+    // Filter by time range
+    let restart_since = detail
+        .as_ref()
+        .filter(|_| args.since_restart)
+        .map(since_restart);
+    let bounds = time_bounds(
+        restart_since.as_deref().or(args.since.as_deref()),
+        args.until.as_deref(),
+        args.until_now,
+        default_window.as_deref(),
+        now_ms(),
+    )?;
+    retain_time_range(&mut processed, bounds);
+
+    // A --since-seq reader takes the oldest lines first so the next poll,
+    // starting from the last seq printed, misses nothing.
+    let window = match (args.head, args.since_seq) {
+        (Some(n), _) => filter::Window::Head(n as usize),
+        (None, Some(_)) => filter::Window::Head(args.lines as usize),
+        (None, None) if compare_ms.is_some() => filter::Window::Tail(fetch_lines as usize),
+        (None, None) => filter::Window::Tail(args.lines as usize),
+    };
+    processed = filter::select_window(processed, window);
+
     // Summary mode
     if args.summary {
         let detector = PatternDetector::with_defaults();
-        let patterns = detector.detect(&processed);
-        let log_summary = summary::generate_summary(&args.name, &processed, &patterns, &[], 0);
+        let process_name = detail
+            .as_ref()
+            .map_or(args.name.as_str(), |d| d.name.as_str());
+        let history_path = PatternHistory::default_path(process_name);
+        let mut history = PatternHistory::load(&history_path);
+        let patterns = trend_filter(
+            detector.detect_with_history(&processed, &mut history),
+            args.trend.as_deref(),
+        );
+        let _ = history.save(&history_path);
+        let (restart_count, last_exit_reason) = detail
+            .as_ref()
+            .map(|d| (d.restart_count, d.last_exit_reason))
+            .unwrap_or_default();
+        let mut log_summary = summary::generate_summary(
+            &args.name,
+            &processed,
+            &patterns,
+            &summary_anomalies(&processed, args.config.as_ref()),
+            restart_count,
+            last_exit_reason,
+        );
+        log_summary.suppressed_lines = detail.as_ref().map_or(0, |d| d.suppressed_lines);
 
-        if args.json || args.ai {
-            println!(
-                "{}",
-                serde_json::to_string_pretty(&log_summary).unwrap_or_default()
-            );
+        let explanations = if args.explain_anomalies {
+            anomaly::explain_anomalies(&processed, &log_summary.anomalies, EXPLAIN_LINES)
         } else {
-            print!("{}", summary::format_summary(&log_summary));
+            Vec::new()
+        };
+
+        let format = SummaryFormat::resolve(args.summary_format, args.json || args.ai);
+        if args.explain_anomalies && matches!(format, SummaryFormat::Md | SummaryFormat::Ai) {
+            eprintln!("[velos] --explain-anomalies only applies to text and JSON summaries");
+        }
+        match format {
+            SummaryFormat::Json => {
+                let mut value = serde_json::to_value(&log_summary).unwrap_or_default();
+                if args.explain_anomalies {
+                    value["explanations"] = serde_json::to_value(&explanations).unwrap_or_default();
+                }
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&value).unwrap_or_default()
+                );
+            }
+            SummaryFormat::Md => print!("{}", summary::format_summary_markdown(&log_summary)),
+            SummaryFormat::Ai => println!("{}", summary::format_summary_ai(&log_summary)),
+            SummaryFormat::Text => print_summary(&log_summary, &explanations),
         }
         return Ok(());
     }
@@ -105,12 +451,120 @@ pub async fn run(args: LogsArgs) -> Result<(), VelosError> {
         return Ok(());
     }
 
+    // Aggregate mode
+    if let Some(ref field) = args.aggregate {
+        let counts = aggregate::aggregate_by_field(&processed, field);
+        if args.json || args.ai {
+            let map: serde_json::Map<_, _> = counts
+                .into_iter()
+                .map(|(value, count)| (value, serde_json::Value::from(count)))
+                .collect();
+            println!("{}", serde_json::Value::Object(map));
+        } else if counts.is_empty() {
+            println!("[velos] No log entries for '{}'", args.name);
+        } else {
+            println!("{:>8}  {}", "COUNT", field);
+            for (value, count) in &counts {
+                println!("{count:>8}  {value}");
+            }
+        }
+        return Ok(());
+    }
+
+    // Extract mode
+    if let Some(ref extractor) = extractor {
+        let rows = extractor.extract_all(&processed, args.keep_unmatched);
+        if args.json || args.ai {
+            let rows: Vec<_> = rows.iter().map(|r| extracted_json(extractor, r)).collect();
+            println!("{}", serde_json::to_string(&rows).unwrap_or_default());
+        } else if rows.is_empty() {
+            println!("[velos] No lines matched --regex-extract");
+        } else {
+            print!("{}", format_extract_table(extractor.columns(), &rows));
+        }
+        return Ok(());
+    }
+
+    // Stats mode
+    if args.stats {
+        if let Some(window_ms) = compare_ms {
+            let cmp = anomaly::compare_windows(&processed, now_ms(), window_ms);
+            if let Some(oldest_ms) = comparison_gap(truncated_at, &cmp) {
+                eprintln!(
+                    "[velos] Warning: only lines since {} were read ({fetch_lines} lines); the earlier window is incomplete",
+                    format::format_timestamp_short(oldest_ms)
+                );
+            }
+            if args.json || args.ai {
+                println!("{}", serde_json::to_string(&cmp).unwrap_or_default());
+            } else {
+                let window = args.compare_window.as_deref().unwrap_or_default();
+                println!("{}", format_comparison(&cmp, window.trim()));
+            }
+        } else if args.timeline {
+            let timeline = aggregate::level_timeline(&processed, bucket_ms);
+            if args.json || args.ai {
+                println!("{}", serde_json::to_string(&timeline).unwrap_or_default());
+            } else if timeline.is_empty() {
+                println!("[velos] No log entries for '{}'", args.name);
+            } else {
+                for bucket in &timeline {
+                    let counts: Vec<_> = bucket
+                        .counts
+                        .iter()
+                        .map(|(level, n)| format!("{level}={n}"))
+                        .collect();
+                    println!(
+                        "{}  {}",
+                        format::format_timestamp_short(bucket.bucket_start_ms),
+                        counts.join(" ")
+                    );
+                }
+            }
+        } else {
+            let counts = aggregate::level_counts(&processed);
+            if args.json || args.ai {
+                println!("{}", serde_json::to_string(&counts).unwrap_or_default());
+            } else if counts.is_empty() {
+                println!("[velos] No log entries for '{}'", args.name);
+            } else {
+                println!("{:>8}  LEVEL", "COUNT");
+                for (level, count) in &counts {
+                    println!("{count:>8}  {level}");
+                }
+            }
+        }
+        return Ok(());
+    }
+
     // Normal output
+    limit_line_length(&mut processed, &args);
+    if let Some(dir) = args.output_dir.as_deref().map(std::path::Path::new) {
+        let merged = resolve_merge(args.merge, detail.as_ref().is_some_and(|d| d.merge_logs));
+        // Files keep the leveled layout whether or not stdout is a terminal
+        let written = sink::write_by_level(dir, &processed, |e| {
+            render_line(e, merged, LogFormat::Leveled)
+        })
+        .map_err(|e| {
+            VelosError::ProtocolError(format!("cannot write to {}: {e}", dir.display()))
+        })?;
+        if written.is_empty() {
+            println!("[velos] No log entries for '{}'", args.name);
+        }
+        for (path, count) in written {
+            println!("[velos] Wrote {count} line(s) to {}", path.display());
+        }
+        return Ok(());
+    }
+
     if args.json {
-        println!(
-            "{}",
-            serde_json::to_string_pretty(&processed).unwrap_or_default()
-        );
+        let json = if args.flatten_parsed {
+            let flat: Vec<_> = processed.iter().map(format::flatten_parsed).collect();
+            serde_json::to_string_pretty(&flat)
+        } else {
+            serde_json::to_string_pretty(&processed)
+        };
+        println!("{}", json.unwrap_or_default());
         return Ok(());
     }
 
@@ -129,62 +583,943 @@ pub async fn run(args: LogsArgs) -> Result<(), VelosError> {
         return Ok(());
     }
 
-    if processed.is_empty() {
+    let pid = detail.as_ref().map(|d| d.pid).unwrap_or(0);
+    if let Some(ref fields) = fields {
+        for e in &processed {
+            println!("{}", format_fields(e, fields, pid));
+        }
+    } else if args.ndjson {
+        print!("{}", format::format_ndjson(&processed, pid));
+    } else if processed.is_empty() && !args.follow {
         println!("[velos] No log entries for '{}'", args.name);
         return Ok(());
+    } else {
+        let merged = resolve_merge(args.merge, detail.as_ref().is_some_and(|d| d.merge_logs));
+        let mut rendered: Vec<String> = processed
+            .iter()
+            .map(|e| render_line(e, merged, args.format))
+            .collect();
+        let mut dropped = 0;
+        if let Some(max) = limit_bytes {
+            (rendered, dropped) = format::truncate_to_bytes(rendered, max);
+        }
+
+        for (line, e) in rendered.iter().zip(&processed) {
+            println!(
+                "{}",
+                args.format
+                    .paint(&args.level_colors, args.color, e.level, line)
+            );
+        }
+        if dropped > 0 {
+            println!("... truncated ({dropped} more lines)");
+        }
     }
 
-    for entry in &processed {
-        println!("{}", format::format_plain_with_level(entry));
+    if let (true, Some((client, id)), Some(detail)) = (args.follow, session, detail) {
+        let mut cursor = FollowCursor::default();
+        cursor.take_new(&entries);
+        let watch = RestartWatch::new(&detail);
+        follow(client, id, &args, &classifier, &line_filter, cursor, watch).await?;
     }
 
     Ok(())
 }
 
-/// Parse time spec: "1h", "30m", "2d", or ISO-like "2026-02-12 10:00".
-fn parse_time_spec(spec: &str) -> Result<u64, VelosError> {
+/// Keep entries with a sequence number above `seq`. Also returns how many
+/// lines after the cursor were already evicted from the daemon's buffer.
+fn entries_after_seq(entries: Vec<LogEntry>, seq: u64) -> (Vec<LogEntry>, u64) {
+    let fresh: Vec<LogEntry> = entries.into_iter().filter(|e| e.seq > seq).collect();
+    let missed = fresh.first().map_or(0, |e| e.seq - seq - 1);
+    (fresh, missed)
+}
+
+/// Render the daemon's lifecycle events as `[velos]` lines and merge them
+/// into `entries` by timestamp. On a tie the process's own line comes first,
+/// since a crash or restart decision follows the output that led to it.
+fn interleave_internal(
+    entries: Vec<velos_log_engine::ProcessedEntry>,
+    events: &[LifecycleEvent],
+) -> Vec<velos_log_engine::ProcessedEntry> {
+    let mut merged = entries;
+    merged.extend(events.iter().map(|e| velos_log_engine::ProcessedEntry {
+        timestamp_ms: e.timestamp_ms,
+        level: match e.kind {
+            1 => LogLevel::Error,
+            2 => LogLevel::Warn,
+            _ => LogLevel::Info,
+        },
+        stream: 0,
+        message: format!("[velos] {}", e.describe()),
+        seq: 0,
+    }));
+    velos_log_engine::sort_entries_stable(&mut merged);
+    merged
+}
+
+/// Fetch each cluster instance's logs, paired with its instance index
+/// (`api:2` → 2; list position for names outside the known templates).
+async fn fetch_cluster_logs(
+    client: &mut VelosClient,
+    base: &str,
+    ids: &[u32],
+    lines: u32,
+) -> Result<Vec<(u32, Vec<LogEntry>)>, VelosError> {
+    let procs = client.list().await?;
+    let mut batches = Vec::with_capacity(ids.len());
+    for (pos, &id) in ids.iter().enumerate() {
+        let index = procs
+            .iter()
+            .find(|p| p.id == id)
+            .and_then(|p| {
+                velos_core::process::INSTANCE_NAME_TEMPLATES
+                    .iter()
+                    .find_map(|t| velos_core::process::match_instance(t, base, &p.name))
+            })
+            .unwrap_or(pos as u32);
+        batches.push((index, client.logs(id, lines).await?));
+    }
+    Ok(batches)
+}
+
+/// Tag each instance's lines with `[index]` and merge them into one stream
+/// ordered by timestamp. The sort is stable, so lines sharing a timestamp
+/// keep their per-instance order.
+fn merge_cluster_logs(
+    batches: Vec<(u32, Vec<velos_log_engine::ProcessedEntry>)>,
+) -> Vec<velos_log_engine::ProcessedEntry> {
+    let mut merged: Vec<_> = batches
+        .into_iter()
+        .flat_map(|(index, entries)| {
+            entries.into_iter().map(move |mut e| {
+                e.message = format!("[{index}] {}", e.message);
+                e
+            })
+        })
+        .collect();
+    velos_log_engine::sort_entries_stable(&mut merged);
+    merged
+}
+
+/// Apply `--trend`; clap has already limited it to known trend names.
+fn trend_filter(patterns: Vec<DetectedPattern>, trend: Option<&str>) -> Vec<DetectedPattern> {
+    match trend.and_then(Trend::parse) {
+        Some(trend) => pattern::filter_by_trend(patterns, trend),
+        None => patterns,
+    }
+}
+
+fn print_summary(log_summary: &summary::LogSummary, explanations: &[anomaly::AnomalyExplanation]) {
+    print!("{}", summary::format_summary(log_summary));
+    for ex in explanations {
+        println!(
+            "\n{} at {}-{} ({} lines in bucket):",
+            ex.anomaly.metric,
+            format::format_timestamp_short(ex.bucket_start_ms),
+            format::format_timestamp_short(ex.bucket_end_ms),
+            ex.bucket_lines
+        );
+        for e in &ex.lines {
+            println!("  {}", format::format_plain_with_level(e));
+        }
+    }
+}
+
+/// Whether to show stdout and stderr as one untagged stream: `--merge` /
+/// `--no-merge` win, otherwise the process's `merge_logs` setting decides.
+fn resolve_merge(flag: Option<bool>, configured: bool) -> bool {
+    flag.unwrap_or(configured)
+}
+
+/// One text line: `[LEVEL|out|time] msg`, or `[LEVEL|time] msg` when merged.
+/// The plain format drops the level.
+fn render_line(e: &velos_log_engine::ProcessedEntry, merged: bool, layout: LogFormat) -> String {
+    match (layout, merged) {
+        (LogFormat::Leveled, true) => format::format_merged_with_level(e),
+        (LogFormat::Leveled, false) => format::format_plain_with_level(e),
+        (LogFormat::Plain, true) => format::format_merged(e),
+        (LogFormat::Plain, false) => format::format_plain(e),
+    }
+}
+
+/// Level (`--level`, `--errors-only`, `--warnings`) and pattern (`--grep`)
+/// filters, shared by the initial batch and `--follow`.
+struct LineFilter {
+    levels: Option<Vec<LogLevel>>,
+    grep: Option<regex::Regex>,
+}
+
+/// Levels kept by the level flags: the shortcuts and `--min-level` expand
+/// to the same lists `--level` would take. `--exclude-level` then keeps only
+/// the levels it doesn't name. `None` = no level filter.
+fn level_filter(
+    level: Option<&str>,
+    min_level: Option<LogLevel>,
+    errors_only: bool,
+    warnings: bool,
+    exclude_level: Option<&str>,
+) -> Option<Vec<LogLevel>> {
+    let included = if errors_only {
+        Some(LogLevel::Error.and_above())
+    } else if warnings {
+        Some(LogLevel::Warn.and_above())
+    } else if let Some(min) = min_level {
+        Some(min.and_above())
+    } else {
+        level.and_then(LogLevel::parse_list)
+    };
+    let Some(exclude_level) = exclude_level else {
+        return included;
+    };
+    let all = || LogLevel::Debug.and_above();
+    let excluded = LogLevel::parse_list(exclude_level).unwrap_or_else(all);
+    let mut levels = included.unwrap_or_else(all);
+    levels.retain(|l| !excluded.contains(l));
+    Some(levels)
+}
+
+impl LineFilter {
+    fn from_args(args: &LogsArgs) -> Result<Self, VelosError> {
+        let min_level = match args.min_level {
+            Some(ref name) => Some(LogLevel::parse(name).ok_or_else(|| {
+                VelosError::ProtocolError(format!(
+                    "invalid --min-level '{name}': use debug, info, warn, error, fatal or 0-7"
+                ))
+            })?),
+            None => None,
+        };
+        let levels = level_filter(
+            args.level.as_deref(),
+            min_level,
+            args.errors_only,
+            args.warnings,
+            args.exclude_level.as_deref(),
+        );
+        let grep = match args.grep {
+            Some(ref pattern) => Some(
+                regex::Regex::new(pattern)
+                    .map_err(|e| VelosError::ProtocolError(format!("invalid grep pattern: {e}")))?,
+            ),
+            None => None,
+        };
+        Ok(Self { levels, grep })
+    }
+
+    fn keep(&self, e: &velos_log_engine::ProcessedEntry) -> bool {
+        self.levels.as_ref().is_none_or(|l| l.contains(&e.level))
+            && self.grep.as_ref().is_none_or(|re| re.is_match(&e.message))
+    }
+}
+
+/// Tracks what `--follow` has already printed. Entries carry only a
+/// millisecond timestamp, so lines sharing the newest timestamp are counted.
+#[derive(Debug, Default)]
+struct FollowCursor {
+    last_ts: u64,
+    seen_at_last_ts: usize,
+}
+
+impl FollowCursor {
+    /// Return entries not seen before (input oldest first) and advance.
+    fn take_new(&mut self, entries: &[LogEntry]) -> Vec<LogEntry> {
+        let mut skip = self.seen_at_last_ts;
+        let fresh = entries
+            .iter()
+            .filter(|e| {
+                if e.timestamp_ms < self.last_ts {
+                    return false;
+                }
+                if e.timestamp_ms == self.last_ts && skip > 0 {
+                    skip -= 1;
+                    return false;
+                }
+                true
+            })
+            .cloned()
+            .collect();
+        if let Some(max) = entries.iter().map(|e| e.timestamp_ms).max() {
+            if max >= self.last_ts {
+                self.seen_at_last_ts = entries.iter().filter(|e| e.timestamp_ms == max).count();
+                self.last_ts = max;
+            }
+        }
+        fresh
+    }
+}
+
+/// Notices restarts between `--follow` polls from the process's restart
+/// counter, so a separator can be printed and the pid kept current.
+#[derive(Debug)]
+struct RestartWatch {
+    restart_count: u32,
+    pid: u32,
+}
+
+impl RestartWatch {
+    fn new(detail: &ProcessDetail) -> Self {
+        Self {
+            restart_count: detail.restart_count,
+            pid: detail.pid,
+        }
+    }
+
+    /// Returns the restart time if the process restarted since the last call.
+    fn observe(&mut self, detail: &ProcessDetail) -> Option<u64> {
+        self.pid = detail.pid;
+        if detail.restart_count == self.restart_count {
+            return None;
+        }
+        self.restart_count = detail.restart_count;
+        Some(detail.last_restart_ms)
+    }
+}
+
+/// Split fresh lines (oldest first) into those from before and after a
+/// restart at `restart_ms`.
+fn split_at_restart(fresh: Vec<LogEntry>, restart_ms: u64) -> (Vec<LogEntry>, Vec<LogEntry>) {
+    fresh.into_iter().partition(|e| e.timestamp_ms < restart_ms)
+}
+
+/// Poll for new lines and print them until Ctrl+C or the process goes away.
+///
+/// Logs are fetched before `info` on every poll, so a restart is always
+/// noticed no later than the poll that first returns its output. The daemon
+/// keeps one log buffer per process across restarts, so the watermark stays
+/// valid; post-restart lines in the same batch are printed after the
+/// separator rather than dropped.
+async fn follow(
+    mut client: VelosClient,
+    id: u32,
+    args: &LogsArgs,
+    classifier: &Classifier,
+    line_filter: &LineFilter,
+    mut cursor: FollowCursor,
+    mut watch: RestartWatch,
+) -> Result<(), VelosError> {
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    let fields = args.fields.as_deref().map(parse_fields).transpose()?;
+    let mut pause = MatchPause::new(PAUSE_HELD_LINES);
+    let mut enter = args.pause_on_match.then(enter_presses);
+
+    loop {
+        let next_enter = async {
+            match enter.as_mut() {
+                Some(rx) => rx.recv().await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            _ = &mut ctrl_c => return Ok(()),
+            _ = tokio::time::sleep(FOLLOW_POLL_INTERVAL) => {}
+            Some(()) = next_enter => {
+                let (held, dropped) = pause.resume();
+                for line in held {
+                    println!("{line}");
+                }
+                if dropped > 0 {
+                    eprintln!(
+                        "[velos] Warning: {dropped} lines dropped while paused (only the first {PAUSE_HELD_LINES} are held)"
+                    );
+                }
+                continue;
+            }
+        }
+
+        let polled = async {
+            let entries = client.logs(id, FOLLOW_FETCH_LINES).await?;
+            let detail = client.info(id).await?;
+            Ok::<_, VelosError>((entries, detail))
+        };
+        let (entries, detail) = match polled.await {
+            Ok(polled) => polled,
+            Err(VelosError::ProcessNotFound(_)) => {
+                eprintln!("[velos] '{}' is gone, stopping follow", args.name);
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+
+        let fresh = cursor.take_new(&entries);
+        let old_pid = watch.pid;
+        let merged = resolve_merge(args.merge, detail.merge_logs);
+        let render = |entries: &[LogEntry], pid| {
+            follow_lines(
+                entries,
+                pid,
+                merged,
+                args,
+                fields.as_deref(),
+                classifier,
+                line_filter,
+            )
+        };
+        let lines = match watch.observe(&detail) {
+            Some(restart_ms) => {
+                let (before, after) = split_at_restart(fresh, restart_ms);
+                let mut lines = render(&before, old_pid);
+                if !args.ndjson {
+                    lines.push(("--- process restarted ---".to_string(), false));
+                }
+                lines.extend(render(&after, watch.pid));
+                lines
+            }
+            None => render(&fresh, watch.pid),
+        };
+
+        if !args.pause_on_match {
+            for (line, _) in lines {
+                println!("{line}");
+            }
+            continue;
+        }
+        let was_paused = pause.is_paused();
+        for line in pause.offer(lines) {
+            println!("{line}");
+        }
+        if pause.is_paused() && !was_paused {
+            eprintln!("[velos] Paused on match, press Enter to resume");
+        }
+    }
+}
+
+/// Render a batch of new lines for `--follow`, each flagged with whether it
+/// matches `--grep`. With `--pause-on-match` the matched text in the message
+/// is highlighted; the `[LEVEL|out|time]` prefix is left as it is.
+fn follow_lines(
+    entries: &[LogEntry],
+    pid: u32,
+    merged: bool,
+    args: &LogsArgs,
+    fields: Option<&[Field]>,
+    classifier: &Classifier,
+    line_filter: &LineFilter,
+) -> Vec<(String, bool)> {
+    let mut processed: Vec<_> = classifier
+        .classify_batch(entries)
+        .into_iter()
+        .filter(|e| line_filter.keep(e))
+        .collect();
+    limit_line_length(&mut processed, args);
+    let is_match = |e: &velos_log_engine::ProcessedEntry| {
+        line_filter
+            .grep
+            .as_ref()
+            .is_some_and(|re| re.is_match(&e.message))
+    };
+
+    if let Some(fields) = fields {
+        processed
+            .iter()
+            .map(|e| (format_fields(e, fields, pid), is_match(e)))
+            .collect()
+    } else if args.ndjson {
+        processed
+            .iter()
+            .map(|e| (format::format_structured(e, pid), is_match(e)))
+            .collect()
+    } else {
+        processed
+            .iter()
+            .map(|e| {
+                let line = match (args.pause_on_match, args.color.enabled(), &line_filter.grep) {
+                    (true, true, Some(re)) if re.is_match(&e.message) => {
+                        // Reverse video, switched off with 27 so the level
+                        // color carries on after the match
+                        let mut shown = e.clone();
+                        shown.message = re
+                            .replace_all(&e.message, "\x1b[7m${0}\x1b[27m")
+                            .into_owned();
+                        render_line(&shown, merged, args.format)
+                    }
+                    _ => render_line(e, merged, args.format),
+                };
+                (
+                    args.format
+                        .paint(&args.level_colors, args.color, e.level, &line),
+                    is_match(e),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Output gate for `--follow --pause-on-match`. Lines pass through until one
+/// matches `--grep`; that one is shown and everything after it is held back
+/// until [`MatchPause::resume`], up to `limit` lines; the rest are counted
+/// and dropped.
+#[derive(Debug)]
+struct MatchPause<T> {
+    paused: bool,
+    held: Vec<T>,
+    limit: usize,
+    dropped: usize,
+}
+
+impl<T> MatchPause<T> {
+    fn new(limit: usize) -> Self {
+        Self {
+            paused: false,
+            held: Vec::new(),
+            limit,
+            dropped: 0,
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Take a batch of `(line, matched)` pairs, oldest first, and return
+    /// the lines to show now.
+    fn offer(&mut self, lines: impl IntoIterator<Item = (T, bool)>) -> Vec<T> {
+        let mut shown = Vec::new();
+        for (line, matched) in lines {
+            if !self.paused {
+                shown.push(line);
+                self.paused = matched;
+            } else if self.held.len() < self.limit {
+                self.held.push(line);
+            } else {
+                self.dropped += 1;
+            }
+        }
+        shown
+    }
+
+    /// Unpause and hand back everything held meanwhile, in order, with the
+    /// number of lines dropped past the limit.
+    fn resume(&mut self) -> (Vec<T>, usize) {
+        self.paused = false;
+        (
+            std::mem::take(&mut self.held),
+            std::mem::take(&mut self.dropped),
+        )
+    }
+}
+
+/// One message per line read from stdin, i.e. per Enter press. The channel
+/// closes when stdin does.
+fn enter_presses() -> tokio::sync::mpsc::UnboundedReceiver<()> {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        let mut line = String::new();
+        while std::io::stdin().read_line(&mut line).is_ok_and(|n| n > 0) {
+            line.clear();
+            if tx.send(()).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+/// The most recent `cap` raw entries seen by `--follow --summary`: grows
+/// until full, then slides forward as new lines arrive.
+struct SummaryWindow {
+    entries: VecDeque<LogEntry>,
+    cap: usize,
+}
+
+impl SummaryWindow {
+    fn new(cap: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            cap: cap.max(1),
+        }
+    }
+
+    fn push(&mut self, fresh: Vec<LogEntry>) {
+        self.entries.extend(fresh);
+        let excess = self.entries.len().saturating_sub(self.cap);
+        self.entries.drain(..excess);
+    }
+
+    fn entries(&mut self) -> &[LogEntry] {
+        self.entries.make_contiguous()
+    }
+}
+
+/// Re-render the summary every `--interval` seconds over a sliding window of
+/// the last `--lines` lines, until Ctrl+C or the process goes away.
+async fn follow_summary(
+    mut client: VelosClient,
+    id: u32,
+    args: &LogsArgs,
+    classifier: &Classifier,
+    line_filter: &LineFilter,
+    mut cursor: FollowCursor,
+    mut window: SummaryWindow,
+) -> Result<(), VelosError> {
+    let detector = PatternDetector::with_defaults();
+    let interval = std::time::Duration::from_secs(args.interval.max(1));
+    // Patterns count as new relative to the history at startup, so the flag
+    // stays up for the whole session instead of one refresh.
+    let history_path = PatternHistory::default_path(&client.info(id).await?.name);
+    let baseline = PatternHistory::load(&history_path);
+    let mut history = baseline.clone();
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    loop {
+        // Restart count and exit reason change while we watch
+        let detail = match client.info(id).await {
+            Ok(detail) => detail,
+            Err(VelosError::ProcessNotFound(_)) => {
+                eprintln!("[velos] '{}' is gone, stopping follow", args.name);
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+
+        let processed: Vec<_> = classifier
+            .classify_batch(window.entries())
+            .into_iter()
+            .filter(|e| line_filter.keep(e))
+            .collect();
+        let patterns = detector.detect_with_history(&processed, &mut baseline.clone());
+        for p in &patterns {
+            history.record(&p.template, p.first_seen_ms);
+        }
+        let patterns = trend_filter(patterns, args.trend.as_deref());
+        let _ = history.save(&history_path);
+        let mut log_summary = summary::generate_summary(
+            &args.name,
+            &processed,
+            &patterns,
+            &summary_anomalies(&processed, args.config.as_ref()),
+            detail.restart_count,
+            detail.last_exit_reason,
+        );
+        log_summary.suppressed_lines = detail.suppressed_lines;
+        let explanations = if args.explain_anomalies {
+            anomaly::explain_anomalies(&processed, &log_summary.anomalies, EXPLAIN_LINES)
+        } else {
+            Vec::new()
+        };
+
+        // Clear the screen and home the cursor
+        print!("\x1b[2J\x1b[H");
+        println!(
+            "[velos] Last {} line(s) of '{}', refreshing every {}s (Ctrl+C to exit)\n",
+            processed.len(),
+            args.name,
+            interval.as_secs()
+        );
+        print_summary(&log_summary, &explanations);
+
+        tokio::select! {
+            _ = &mut ctrl_c => return Ok(()),
+            _ = tokio::time::sleep(interval) => {}
+        }
+
+        match client.logs(id, FOLLOW_FETCH_LINES).await {
+            Ok(entries) => window.push(cursor.take_new(&entries)),
+            Err(VelosError::ProcessNotFound(_)) => {
+                eprintln!("[velos] '{}' is gone, stopping follow", args.name);
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// A column selectable with `--fields`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Ts,
+    Time,
+    Level,
+    Stream,
+    Pid,
+    Msg,
+}
+
+const FIELD_NAMES: &[&str] = &["ts", "time", "level", "stream", "pid", "msg"];
+
+/// Parse a comma-separated column list, keeping the requested order.
+fn parse_fields(spec: &str) -> Result<Vec<Field>, VelosError> {
+    spec.split(',')
+        .map(|name| match name.trim() {
+            "ts" => Ok(Field::Ts),
+            "time" => Ok(Field::Time),
+            "level" => Ok(Field::Level),
+            "stream" => Ok(Field::Stream),
+            "pid" => Ok(Field::Pid),
+            "msg" => Ok(Field::Msg),
+            other => Err(VelosError::ProtocolError(format!(
+                "invalid --fields column '{other}': use {}",
+                FIELD_NAMES.join(", ")
+            ))),
+        })
+        .collect()
+}
+
+/// Render the selected columns of one entry, tab-separated. Tabs inside
+/// the message become spaces so every line keeps the same column count.
+fn format_fields(e: &velos_log_engine::ProcessedEntry, fields: &[Field], pid: u32) -> String {
+    fields
+        .iter()
+        .map(|f| match f {
+            Field::Ts => e.timestamp_ms.to_string(),
+            Field::Time => format::format_timestamp_short(e.timestamp_ms),
+            Field::Level => e.level.as_str().to_string(),
+            Field::Stream => if e.stream == 1 { "err" } else { "out" }.to_string(),
+            Field::Pid => pid.to_string(),
+            Field::Msg => e.message.replace('\t', " "),
+        })
+        .collect::<Vec<_>>()
+        .join("\t")
+}
+
+fn parse_extractor(pattern: &str) -> Result<FieldExtractor, VelosError> {
+    let regex = regex::Regex::new(pattern)
+        .map_err(|e| VelosError::ProtocolError(format!("invalid --regex-extract: {e}")))?;
+    FieldExtractor::new(regex).ok_or_else(|| {
+        VelosError::ProtocolError(
+            "--regex-extract needs at least one named group, e.g. (?P<status>\\d+)".into(),
+        )
+    })
+}
+
+/// `{column: value}` for a matched line, `{"raw": line}` for a kept
+/// unmatched one.
+fn extracted_json(extractor: &FieldExtractor, row: &Extracted) -> serde_json::Value {
+    match row {
+        Extracted::Fields(values) => extractor
+            .columns()
+            .iter()
+            .cloned()
+            .zip(values.iter().cloned().map(serde_json::Value::from))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+        Extracted::Unmatched(line) => serde_json::json!({ "raw": line }),
+    }
+}
+
+/// Header plus one row per match, columns padded to their widest value.
+/// Unmatched lines (with --keep-unmatched) are printed whole.
+fn format_extract_table(columns: &[String], rows: &[Extracted]) -> String {
+    let mut widths: Vec<usize> = columns.iter().map(|c| c.len()).collect();
+    for row in rows {
+        if let Extracted::Fields(values) = row {
+            for (w, v) in widths.iter_mut().zip(values) {
+                *w = (*w).max(v.chars().count());
+            }
+        }
+    }
+    let line = |cells: &mut dyn Iterator<Item = &str>| {
+        let padded: Vec<String> = cells
+            .zip(&widths)
+            .map(|(cell, &w)| format!("{cell:<w$}"))
+            .collect();
+        format!("{}\n", padded.join("  ").trim_end())
+    };
+
+    let header: Vec<String> = columns.iter().map(|c| c.to_uppercase()).collect();
+    let mut out = line(&mut header.iter().map(String::as_str));
+    for row in rows {
+        match row {
+            Extracted::Fields(values) => {
+                out.push_str(&line(&mut values.iter().map(String::as_str)))
+            }
+            Extracted::Unmatched(raw) => {
+                out.push_str(raw);
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+/// Parse a sampling rate like "10%" or "10" (0 < n <= 100).
+fn parse_percent(spec: &str) -> Result<f64, VelosError> {
+    let num = spec.trim().trim_end_matches('%');
+    match num.trim().parse::<f64>() {
+        Ok(p) if p > 0.0 && p <= 100.0 => Ok(p),
+        _ => Err(VelosError::ProtocolError(format!(
+            "invalid --sample '{spec}': use a percentage between 0 and 100 (e.g. 10%)"
+        ))),
+    }
+}
+
+/// Lines shown when neither `--lines`, `VELOS_LOG_LINES` nor
+/// `logs.default_log_lines` says otherwise.
+const DEFAULT_LOG_LINES: u32 = 50;
+
+/// The `[logs]` table of ./velos.toml, read once per `velos logs` run. A
+/// broken table is reported and the built-in settings used instead, so logs
+/// stay readable; the apps in the file don't matter here.
+pub fn logs_config() -> Option<velos_config::LogEngineConfig> {
+    match velos_config::load_logs(std::path::Path::new("velos.toml")) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("[velos] Warning: ignoring [logs] in velos.toml: {e}");
+            None
+        }
+    }
+}
+
+/// Line count for `velos logs` when `--lines` may be absent: reads
+/// `VELOS_LOG_LINES` and `logs.default_log_lines`.
+pub fn log_lines(flag: Option<u32>, config: Option<&velos_config::LogEngineConfig>) -> u32 {
+    if let Some(n) = flag {
+        return n;
+    }
+    let env = std::env::var("VELOS_LOG_LINES").ok();
+    let config = config.and_then(|l| l.default_log_lines);
+    resolve_log_lines(flag, env.as_deref(), config)
+}
+
+/// Classifier for `velos logs`: the `[logs]` settings (preset, JSON level
+/// keys, `[[logs.rules]]`). `--preset` replaces the configured preset but
+/// keeps the rest.
+fn configured_classifier(
+    preset: Option<&str>,
+    config: Option<&velos_config::LogEngineConfig>,
+) -> Result<Classifier, VelosError> {
+    let Some(name) = preset else {
+        return Ok(config
+            .map(Classifier::from_config)
+            .unwrap_or_else(Classifier::with_defaults));
+    };
+    let mut classifier = Classifier::preset(name)
+        .ok_or_else(|| VelosError::ProtocolError(format!("unknown classifier preset '{name}'")))?;
+    if let Some(config) = config {
+        classifier.set_json_level_keys(config.json_level_keys.clone());
+        classifier.add_config_rules(config);
+    }
+    Ok(classifier)
+}
+
+/// Error-rate and volume spikes in the newest minute of `processed`, with
+/// the thresholds from `[logs]`.
+fn summary_anomalies(
+    processed: &[velos_log_engine::ProcessedEntry],
+    config: Option<&velos_config::LogEngineConfig>,
+) -> Vec<anomaly::Anomaly> {
+    let defaults = velos_config::LogEngineConfig::default();
+    anomaly::detect_from_entries(processed, config.unwrap_or(&defaults))
+}
+
+/// `--max-line-length`, or `logs.max_line_length`.
+pub fn max_line_length(
+    flag: Option<u32>,
+    config: Option<&velos_config::LogEngineConfig>,
+) -> Option<u32> {
+    flag.or_else(|| config.and_then(|l| l.max_line_length))
+}
+
+/// Cut messages to the max line length for display. Machine-readable output
+/// (`--json`, `--ai`, `--ndjson`, `--output-dir`) keeps whole lines unless
+/// `--truncate` is set too.
+fn limit_line_length(processed: &mut [velos_log_engine::ProcessedEntry], args: &LogsArgs) {
+    let machine = args.json || args.ai || args.ndjson || args.output_dir.is_some();
+    if let Some(max) = args.max_line_length.filter(|_| args.truncate || !machine) {
+        for e in processed {
+            format::truncate_message(&mut e.message, max as usize);
+        }
+    }
+}
+
+/// Level colors for `velos logs`: the defaults, then `logs.level_colors`,
+/// then `--color-map`.
+pub fn level_colors(
+    color_map: Option<&str>,
+    config: Option<&velos_config::LogEngineConfig>,
+) -> Result<LevelColors, VelosError> {
+    let mut configured: Vec<(&str, &str)> = config
+        .into_iter()
+        .flat_map(|l| &l.level_colors)
+        .map(|(level, color)| (level.as_str(), color.as_str()))
+        .collect();
+    configured.sort();
+    let flag = color_map.map(color::parse_color_map).transpose()?;
+    LevelColors::default()
+        .with_overrides(configured)?
+        .with_overrides(flag.unwrap_or_default())
+}
+
+/// Precedence: flag > env > config > 50. An env value that isn't a positive
+/// number is ignored rather than failing the command.
+fn resolve_log_lines(flag: Option<u32>, env: Option<&str>, config: Option<u32>) -> u32 {
+    let env = env
+        .and_then(|v| v.trim().parse::<u32>().ok())
+        .filter(|&n| n > 0);
+    flag.or(env).or(config).unwrap_or(DEFAULT_LOG_LINES)
+}
+
+/// Start of a truncated read (`oldest_ms`) when it falls after the start of
+/// the earlier compared window, so that window's counts are too low.
+fn comparison_gap(oldest_ms: Option<u64>, cmp: &anomaly::WindowComparison) -> Option<u64> {
+    oldest_ms.filter(|&t| t > cmp.previous_start_ms)
+}
+
+/// `--stats --compare-window 5m`: "[velos] Errors: 12 in the last 5m vs 4
+/// in the 5m before ↑ +8 (3.0x)".
+fn format_comparison(cmp: &anomaly::WindowComparison, window: &str) -> String {
+    let change = match (cmp.delta(), cmp.ratio) {
+        (0, _) => String::new(),
+        (d, Some(ratio)) => format!(" {d:+} ({ratio:.1}x)"),
+        (d, None) => format!(" {d:+} (none before)"),
+    };
+    format!(
+        "[velos] Errors: {} in the last {window} vs {} in the {window} before {}{change}",
+        cmp.current_errors,
+        cmp.previous_errors,
+        cmp.indicator()
+    )
+}
+
+/// Parse a duration flag such as `--bucket`: "30s", "1m", "1h" or "1d".
+fn parse_duration(flag: &str, spec: &str) -> Result<u64, VelosError> {
+    let spec = spec.trim();
+    let invalid = || {
+        VelosError::ProtocolError(format!(
+            "invalid --{flag} '{spec}': use a duration such as 30s, 1m or 1h"
+        ))
+    };
+    for (suffix, unit_ms) in [
+        ('h', 3_600_000),
+        ('m', 60_000),
+        ('d', 86_400_000),
+        ('s', 1000),
+    ] {
+        if let Some(num_str) = spec.strip_suffix(suffix) {
+            return match num_str.parse::<u64>() {
+                Ok(n) if n > 0 => Ok(n * unit_ms),
+                _ => Err(invalid()),
+            };
+        }
+    }
+    Err(invalid())
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Parse time spec: "1h", "30m", "2d", "45s" (counted back from `now`), or
+/// a ms timestamp.
+fn parse_time_spec(spec: &str, now: u64) -> Result<u64, VelosError> {
     let spec = spec.trim();
 
-    // Relative: ends with h/m/s/d
-    if let Some(num_str) = spec.strip_suffix('h') {
-        let hours: u64 = num_str
-            .parse()
-            .map_err(|_| VelosError::ProtocolError(format!("invalid time: {spec}")))?;
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
-        return Ok(now - hours * 3_600_000);
-    }
-    if let Some(num_str) = spec.strip_suffix('m') {
-        let mins: u64 = num_str
-            .parse()
-            .map_err(|_| VelosError::ProtocolError(format!("invalid time: {spec}")))?;
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
-        return Ok(now - mins * 60_000);
-    }
-    if let Some(num_str) = spec.strip_suffix('d') {
-        let days: u64 = num_str
-            .parse()
-            .map_err(|_| VelosError::ProtocolError(format!("invalid time: {spec}")))?;
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
-        return Ok(now - days * 86_400_000);
-    }
-    if let Some(num_str) = spec.strip_suffix('s') {
-        let secs: u64 = num_str
-            .parse()
-            .map_err(|_| VelosError::ProtocolError(format!("invalid time: {spec}")))?;
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
-        return Ok(now - secs * 1000);
+    // Relative: ends with h/m/d/s
+    for (suffix, unit_ms) in [
+        ('h', 3_600_000),
+        ('m', 60_000),
+        ('d', 86_400_000),
+        ('s', 1000),
+    ] {
+        if let Some(num_str) = spec.strip_suffix(suffix) {
+            let n: u64 = num_str
+                .parse()
+                .map_err(|_| VelosError::ProtocolError(format!("invalid time: {spec}")))?;
+            return Ok(now.saturating_sub(n * unit_ms));
+        }
     }
 
     // Absolute timestamp in ms
@@ -196,3 +1531,614 @@ fn parse_time_spec(spec: &str) -> Result<u64, VelosError> {
         "unsupported time format: {spec} (use: 1h, 30m, 2d, or ms timestamp)"
     )))
 }
+
+/// `--since-restart` as a `--since` spec: the absolute time the process was
+/// last (re)started.
+fn since_restart(detail: &ProcessDetail) -> String {
+    detail.last_restart_ms.to_string()
+}
+
+/// Keep the lines inside the bounds from [`time_bounds`], both inclusive.
+fn retain_time_range(
+    entries: &mut Vec<velos_log_engine::ProcessedEntry>,
+    (since_ms, until_ms): (Option<u64>, Option<u64>),
+) {
+    if let Some(since_ms) = since_ms {
+        entries.retain(|e| e.timestamp_ms >= since_ms);
+    }
+    if let Some(until_ms) = until_ms {
+        entries.retain(|e| e.timestamp_ms <= until_ms);
+    }
+}
+
+/// Resolve `--since`/`--until` into millisecond bounds. With neither given,
+/// `default_window` (from `--default-window` or `[logs] default_window` in
+/// velos.toml) limits the output to that much recent history instead of
+/// the last `--lines` regardless of age. `--until-now` pins the upper bound.
+fn time_bounds(
+    since: Option<&str>,
+    until: Option<&str>,
+    until_now: bool,
+    default_window: Option<&str>,
+    now: u64,
+) -> Result<(Option<u64>, Option<u64>), VelosError> {
+    let since_ms = match (since, until, default_window) {
+        (Some(spec), _, _) => Some(parse_time_spec(spec, now)?),
+        (None, None, Some(window)) => Some(parse_time_spec(window, now)?),
+        _ => None,
+    };
+    let until_ms = match until {
+        Some(spec) => Some(parse_time_spec(spec, now)?),
+        None => until_now.then_some(now),
+    };
+    Ok((since_ms, until_ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(ts: u64, msg: &str) -> LogEntry {
+        LogEntry {
+            timestamp_ms: ts,
+            level: 1,
+            stream: 0,
+            message: msg.into(),
+            seq: 0,
+        }
+    }
+
+    #[test]
+    fn test_comparison_gap() {
+        let cmp = anomaly::WindowComparison {
+            window_ms: 60_000,
+            previous_start_ms: 1_000_000,
+            previous_errors: 0,
+            previous_total: 0,
+            current_errors: 0,
+            current_total: 0,
+            ratio: None,
+        };
+        assert_eq!(comparison_gap(Some(1_030_000), &cmp), Some(1_030_000));
+        assert_eq!(comparison_gap(Some(1_000_000), &cmp), None);
+        // The read wasn't cut short
+        assert_eq!(comparison_gap(None, &cmp), None);
+    }
+
+    #[test]
+    fn test_follow_cursor_returns_only_new_lines() {
+        let mut cursor = FollowCursor::default();
+        let first = vec![entry(100, "a"), entry(200, "b"), entry(200, "c")];
+        assert_eq!(cursor.take_new(&first).len(), 3);
+
+        // Same window again: nothing new
+        assert!(cursor.take_new(&first).is_empty());
+
+        // A new line sharing the last timestamp, plus a later one
+        let second = vec![
+            entry(200, "b"),
+            entry(200, "c"),
+            entry(200, "d"),
+            entry(300, "e"),
+        ];
+        let msgs: Vec<String> = cursor
+            .take_new(&second)
+            .into_iter()
+            .map(|e| e.message)
+            .collect();
+        assert_eq!(msgs, vec!["d", "e"]);
+    }
+
+    fn detail(pid: u32, restart_count: u32, last_restart_ms: u64) -> ProcessDetail {
+        ProcessDetail {
+            pid,
+            restart_count,
+            last_restart_ms,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_follow_detects_restart_between_polls() {
+        let mut cursor = FollowCursor::default();
+        let mut watch = RestartWatch::new(&detail(100, 0, 0));
+
+        // Poll 1: no restart yet
+        let poll1 = vec![entry(100, "boot"), entry(200, "serving")];
+        cursor.take_new(&poll1);
+        assert_eq!(watch.observe(&detail(100, 0, 0)), None);
+
+        // Poll 2: restarted at 250; the buffer still holds the old output and
+        // the old process's last line landed just before the restart
+        let poll2 = vec![
+            entry(100, "boot"),
+            entry(200, "serving"),
+            entry(240, "shutting down"),
+            entry(260, "boot"),
+            entry(270, "serving"),
+        ];
+        let fresh = cursor.take_new(&poll2);
+        let restart_ms = watch.observe(&detail(101, 1, 250)).unwrap();
+        assert_eq!(restart_ms, 250);
+        assert_eq!(watch.pid, 101);
+
+        let (before, after) = split_at_restart(fresh, restart_ms);
+        let msgs = |v: &[LogEntry]| v.iter().map(|e| e.message.clone()).collect::<Vec<_>>();
+        assert_eq!(msgs(&before), vec!["shutting down"]);
+        assert_eq!(msgs(&after), vec!["boot", "serving"]);
+
+        // Poll 3: nothing repeated, nothing new, no second separator
+        assert!(cursor.take_new(&poll2).is_empty());
+        assert_eq!(watch.observe(&detail(101, 1, 250)), None);
+    }
+
+    #[test]
+    fn test_match_pause_holds_lines_until_resume() {
+        let mut pause = MatchPause::new(4);
+        let batch = |lines: &[(&'static str, bool)]| lines.to_vec();
+
+        // Lines flow until a match, which is shown and pauses the rest
+        let shown = pause.offer(batch(&[("a", false), ("hit 1", true), ("b", false)]));
+        assert_eq!(shown, ["a", "hit 1"]);
+        assert!(pause.is_paused());
+
+        // While paused, matches and non-matches all accumulate
+        assert!(pause
+            .offer(batch(&[("hit 2", true), ("c", false)]))
+            .is_empty());
+        assert!(pause.offer(batch(&[("hit 3", true)])).is_empty());
+
+        // Resume flushes them in order; the next match pauses again
+        assert_eq!(pause.resume(), (vec!["b", "hit 2", "c", "hit 3"], 0));
+        assert!(!pause.is_paused());
+        assert_eq!(pause.resume(), (vec![], 0));
+        assert_eq!(pause.offer(batch(&[("d", false)])), ["d"]);
+        assert_eq!(
+            pause.offer(batch(&[("hit 4", true), ("e", false)])),
+            ["hit 4"]
+        );
+        assert_eq!(pause.resume(), (vec!["e"], 0));
+
+        // Past the limit, later lines are dropped and counted once
+        pause.offer(batch(&[("hit 5", true)]));
+        let flood: Vec<_> = (0..6).map(|_| ("x", false)).collect();
+        assert!(pause.offer(flood).is_empty());
+        assert_eq!(pause.resume(), (vec!["x"; 4], 2));
+        assert_eq!(pause.resume(), (vec![], 0));
+    }
+
+    #[test]
+    fn test_entries_after_seq_cursor() {
+        let batch = |seqs: &[u64]| -> Vec<LogEntry> {
+            seqs.iter()
+                .map(|&seq| LogEntry {
+                    seq,
+                    ..entry(seq * 10, "line")
+                })
+                .collect()
+        };
+
+        // First poll from the start, then resume from the last seq seen
+        let (first, missed) = entries_after_seq(batch(&[1, 2, 3]), 0);
+        assert_eq!(first.iter().map(|e| e.seq).collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(missed, 0);
+        let cursor = first.last().unwrap().seq;
+
+        let (second, missed) = entries_after_seq(batch(&[2, 3, 4, 5]), cursor);
+        assert_eq!(second.iter().map(|e| e.seq).collect::<Vec<_>>(), [4, 5]);
+        assert_eq!(missed, 0);
+
+        // Buffer rolled past the cursor: 6 and 7 are gone
+        let (third, missed) = entries_after_seq(batch(&[8, 9]), 5);
+        assert_eq!(third.len(), 2);
+        assert_eq!(missed, 2);
+
+        let (none, missed) = entries_after_seq(batch(&[4, 5]), 5);
+        assert!(none.is_empty());
+        assert_eq!(missed, 0);
+    }
+
+    #[test]
+    fn test_fields_selection_order() {
+        let e = Classifier::with_defaults().classify_batch(&[entry(36303000, "db\tfailed error")]);
+        let fields = parse_fields("level,msg").unwrap();
+        assert_eq!(format_fields(&e[0], &fields, 42), "error\tdb failed error");
+
+        let fields = parse_fields("msg, level,time,pid").unwrap();
+        assert_eq!(fields, [Field::Msg, Field::Level, Field::Time, Field::Pid]);
+        assert_eq!(
+            format_fields(&e[0], &fields, 42),
+            "db failed error\terror\t10:05:03\t42"
+        );
+    }
+
+    #[test]
+    fn test_fields_invalid_name() {
+        let err = parse_fields("level,message").unwrap_err().to_string();
+        assert!(err.contains("'message'"));
+        assert!(err.contains("ts, time, level, stream, pid, msg"));
+    }
+
+    #[test]
+    fn test_errors_only_keeps_error_and_fatal() {
+        let batch = vec![
+            entry(1, "Server started"),
+            entry(2, "Deprecated API called"),
+            entry(3, "Connection error: ECONNREFUSED"),
+            entry(4, "[debug] cache miss"),
+            entry(5, "FATAL: out of memory"),
+        ];
+        let processed = Classifier::with_defaults().classify_batch(&batch);
+        let kept = |levels| {
+            let filter = LineFilter { levels, grep: None };
+            processed
+                .iter()
+                .filter(|e| filter.keep(e))
+                .map(|e| e.timestamp_ms)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(kept(level_filter(None, None, true, false, None)), [3, 5]);
+        assert_eq!(kept(level_filter(None, None, false, true, None)), [2, 3, 5]);
+        // Shortcuts match the explicit --level they stand for
+        assert_eq!(
+            level_filter(None, None, true, false, None),
+            level_filter(Some("error,fatal"), None, false, false, None)
+        );
+        assert_eq!(kept(level_filter(None, None, false, false, None)).len(), 5);
+
+        // --min-level warn: warn and above, nothing below
+        let min_warn = level_filter(None, LogLevel::parse("warn"), false, false, None);
+        assert_eq!(kept(min_warn.clone()), [2, 3, 5]);
+        assert_eq!(
+            min_warn,
+            level_filter(Some("warn,error,fatal"), None, false, false, None)
+        );
+        assert_eq!(
+            kept(level_filter(
+                None,
+                Some(LogLevel::Fatal),
+                false,
+                false,
+                None
+            )),
+            [5]
+        );
+
+        // "all" turns the level filter off, even alongside other names
+        assert_eq!(level_filter(Some("all"), None, false, false, None), None);
+        assert_eq!(
+            level_filter(Some("error,ALL"), None, false, false, None),
+            None
+        );
+
+        // Syslog numbers map to the nearest level
+        assert_eq!(LogLevel::parse("4"), Some(LogLevel::Warn));
+        assert_eq!(LogLevel::parse("0"), Some(LogLevel::Fatal));
+        assert_eq!(LogLevel::parse("6"), Some(LogLevel::Info));
+        assert_eq!(LogLevel::parse("8"), None);
+        assert_eq!(
+            level_filter(Some("3,4"), None, false, false, None),
+            level_filter(Some("error,warn"), None, false, false, None)
+        );
+        assert_eq!(
+            level_filter(None, LogLevel::parse("3"), false, false, None),
+            level_filter(None, None, true, false, None)
+        );
+    }
+
+    #[test]
+    fn test_exclude_level_subtracts() {
+        use LogLevel::{Error, Fatal, Info, Warn};
+        assert_eq!(
+            level_filter(None, None, false, false, Some("debug,info")),
+            Some(vec![Warn, Error, Fatal])
+        );
+        // Intersection with the inclusive filter, minus the exclusions
+        assert_eq!(
+            level_filter(Some("info,warn,error"), None, false, false, Some("warn")),
+            Some(vec![Info, Error])
+        );
+        assert_eq!(
+            level_filter(None, Some(Warn), false, false, Some("fatal,7")),
+            Some(vec![Warn, Error])
+        );
+        assert_eq!(
+            level_filter(None, None, true, false, Some("error")),
+            Some(vec![Fatal])
+        );
+        // "--level all" still leaves the exclusions to apply
+        assert_eq!(
+            level_filter(Some("all"), None, false, false, Some("debug")),
+            Some(vec![Info, Warn, Error, Fatal])
+        );
+        assert_eq!(
+            level_filter(None, None, false, false, Some("all")),
+            Some(vec![])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clear_after_only_on_success() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let cleared = AtomicBool::new(false);
+        let clear = || async {
+            cleared.store(true, Ordering::SeqCst);
+            Ok(())
+        };
+
+        let failed = clear_on_success(
+            async { Err(VelosError::ProcessNotFound("api".into())) },
+            Some(clear()),
+        )
+        .await;
+        assert!(matches!(failed, Err(VelosError::ProcessNotFound(_))));
+        assert!(!cleared.load(Ordering::SeqCst));
+
+        clear_on_success(async { Ok(()) }, Some(clear()))
+            .await
+            .unwrap();
+        assert!(cleared.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_merge_cluster_logs_interleaves_and_tags() {
+        let classify = |batch: Vec<LogEntry>| Classifier::with_defaults().classify_batch(&batch);
+        let first = classify(vec![entry(100, "a1"), entry(300, "a2"), entry(500, "a3")]);
+        let second = classify(vec![entry(200, "b1"), entry(300, "b2"), entry(400, "b3")]);
+
+        let merged = merge_cluster_logs(vec![(0, first), (1, second)]);
+        let lines: Vec<_> = merged
+            .iter()
+            .map(|e| (e.timestamp_ms, e.message.as_str()))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                (100, "[0] a1"),
+                (200, "[1] b1"),
+                (300, "[0] a2"),
+                (300, "[1] b2"),
+                (400, "[1] b3"),
+                (500, "[0] a3"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_interleave_internal_by_timestamp() {
+        let processed = Classifier::with_defaults()
+            .classify_batch(&[entry(100, "boot"), entry(300, "heap grows")]);
+        let events = [
+            LifecycleEvent {
+                timestamp_ms: 300,
+                kind: 2,
+                exit_code: 0,
+            },
+            LifecycleEvent {
+                timestamp_ms: 50,
+                kind: 6,
+                exit_code: 0,
+            },
+            LifecycleEvent {
+                timestamp_ms: 200,
+                kind: 1,
+                exit_code: 137,
+            },
+        ];
+
+        let merged = interleave_internal(processed, &events);
+        let lines: Vec<_> = merged
+            .iter()
+            .map(|e| (e.timestamp_ms, e.level, e.message.as_str()))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                (50, LogLevel::Info, "[velos] started"),
+                (100, LogLevel::Info, "boot"),
+                (200, LogLevel::Error, "[velos] crashed (exit code 137)"),
+                (300, LogLevel::Info, "heap grows"),
+                (
+                    300,
+                    LogLevel::Warn,
+                    "[velos] restarting due to max_memory_restart"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_log_lines_precedence() {
+        assert_eq!(resolve_log_lines(None, None, None), 50);
+        assert_eq!(resolve_log_lines(None, None, Some(500)), 500);
+        assert_eq!(resolve_log_lines(None, Some("200"), Some(500)), 200);
+        assert_eq!(resolve_log_lines(Some(10), Some("200"), Some(500)), 10);
+        // Junk in the env falls through to the config
+        assert_eq!(resolve_log_lines(None, Some("lots"), Some(500)), 500);
+        assert_eq!(resolve_log_lines(None, Some("0"), None), 50);
+    }
+
+    #[test]
+    fn test_format_extract_table() {
+        let extractor = parse_extractor(r"(?P<method>\w+) (?P<path>/\S+)").unwrap();
+        let rows = [
+            Extracted::Fields(vec!["GET".into(), "/api/users".into()]),
+            Extracted::Unmatched("worker started"),
+            Extracted::Fields(vec!["DELETE".into(), "/x".into()]),
+        ];
+        assert_eq!(
+            format_extract_table(extractor.columns(), &rows),
+            "METHOD  PATH\nGET     /api/users\nworker started\nDELETE  /x\n"
+        );
+        assert_eq!(
+            extracted_json(&extractor, &rows[0]),
+            serde_json::json!({"method": "GET", "path": "/api/users"})
+        );
+        assert_eq!(
+            extracted_json(&extractor, &rows[1]),
+            serde_json::json!({"raw": "worker started"})
+        );
+        assert!(parse_extractor(r"(\w+)").is_err());
+        assert!(parse_extractor(r"(?P<open").is_err());
+    }
+
+    #[test]
+    fn test_resolve_merge() {
+        // No flag: follow the process's merge_logs
+        assert!(resolve_merge(None, true));
+        assert!(!resolve_merge(None, false));
+        // --merge / --no-merge override it either way
+        assert!(resolve_merge(Some(true), false));
+        assert!(!resolve_merge(Some(false), true));
+
+        let e = velos_log_engine::ProcessedEntry {
+            timestamp_ms: 0,
+            level: LogLevel::Warn,
+            stream: 1,
+            message: "disk low".into(),
+            seq: 0,
+        };
+        assert!(render_line(&e, false, LogFormat::Leveled).contains("|err|"));
+        assert!(!render_line(&e, true, LogFormat::Leveled).contains("err"));
+        assert!(render_line(&e, true, LogFormat::Leveled).ends_with("] disk low"));
+    }
+
+    #[test]
+    fn test_log_format_defaults_by_tty() {
+        // A pipe gets plain lines; a terminal the leveled, colored view
+        assert_eq!(LogFormat::resolve(None, false), LogFormat::Plain);
+        assert_eq!(LogFormat::resolve(None, true), LogFormat::Leveled);
+        // --format wins either way
+        assert_eq!(
+            LogFormat::resolve(Some(LogFormat::Leveled), false),
+            LogFormat::Leveled
+        );
+        assert_eq!(
+            LogFormat::resolve(Some(LogFormat::Plain), true),
+            LogFormat::Plain
+        );
+
+        let e = velos_log_engine::ProcessedEntry {
+            timestamp_ms: 0,
+            level: LogLevel::Error,
+            stream: 0,
+            message: "boom".into(),
+            seq: 0,
+        };
+        let colors = LevelColors::default();
+        let color = ColorChoice::resolve(crate::color::ColorWhen::Always, true, false);
+        let render = |layout: LogFormat| {
+            let line = render_line(&e, false, layout);
+            layout.paint(&colors, color, e.level, &line)
+        };
+
+        let plain = render(LogFormat::resolve(None, false));
+        assert!(
+            plain.starts_with("[out|") && plain.ends_with("] boom"),
+            "{plain}"
+        );
+        assert!(!plain.contains('\x1b'));
+        assert_eq!(
+            render_line(&e, true, LogFormat::Plain),
+            plain.replace("out|", "")
+        );
+
+        let leveled = render(LogFormat::resolve(None, true));
+        assert!(leveled.starts_with("\x1b[31m[ERROR|out|"), "{leveled:?}");
+    }
+
+    #[test]
+    fn test_parse_bucket() {
+        let parse_bucket = |spec| parse_duration("bucket", spec);
+        assert_eq!(parse_bucket("1m").unwrap(), 60_000);
+        assert_eq!(parse_bucket("30s").unwrap(), 30_000);
+        assert_eq!(parse_bucket("2h").unwrap(), 7_200_000);
+        assert!(parse_bucket("0m").is_err());
+        assert!(parse_bucket("60").is_err());
+        let err = parse_duration("compare-window", "soon").unwrap_err();
+        assert!(err.to_string().contains("--compare-window 'soon'"));
+    }
+
+    #[test]
+    fn test_time_bounds_default_window() {
+        let now = 1_000_000_000;
+        let day = 86_400_000;
+
+        // Nothing asked for, no default: unbounded, as before
+        assert_eq!(
+            time_bounds(None, None, false, None, now).unwrap(),
+            (None, None)
+        );
+
+        // The configured window applies only when neither bound is given
+        assert_eq!(
+            time_bounds(None, None, false, Some("24h"), now).unwrap(),
+            (Some(now - day), None)
+        );
+        assert_eq!(
+            time_bounds(Some("30m"), None, false, Some("24h"), now).unwrap(),
+            (Some(now - 30 * 60_000), None)
+        );
+        assert_eq!(
+            time_bounds(None, Some("1h"), false, Some("24h"), now).unwrap(),
+            (None, Some(now - 3_600_000))
+        );
+
+        // --until-now pins the upper bound, alongside the default window
+        assert_eq!(
+            time_bounds(None, None, true, Some("2d"), now).unwrap(),
+            (Some(now - 2 * day), Some(now))
+        );
+        assert!(time_bounds(None, None, false, Some("soon"), now).is_err());
+    }
+
+    #[test]
+    fn test_since_restart_drops_pre_restart_lines() {
+        let now = 1_000_000;
+        let batch = vec![
+            entry(900_000, "boot"),
+            entry(949_999, "panic: out of memory"),
+            entry(950_000, "boot"),
+            entry(960_000, "serving"),
+        ];
+        let mut processed = Classifier::with_defaults().classify_batch(&batch);
+
+        // The restart replaces --since and overrides the default window
+        let spec = since_restart(&detail(100, 3, 950_000));
+        let bounds = time_bounds(Some(&spec), None, false, Some("1m"), now).unwrap();
+        assert_eq!(bounds, (Some(950_000), None));
+
+        retain_time_range(&mut processed, bounds);
+        let messages: Vec<_> = processed.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, ["boot", "serving"]);
+    }
+
+    #[test]
+    fn test_summary_window_slides_across_fetches() {
+        let mut cursor = FollowCursor::default();
+        let mut window = SummaryWindow::new(4);
+
+        // First fetch: the window grows
+        let first = vec![entry(100, "a"), entry(200, "b"), entry(300, "c")];
+        window.push(cursor.take_new(&first));
+        assert_eq!(window.entries().len(), 3);
+
+        // Second fetch overlaps the first; only new lines are added and the
+        // oldest fall off once the cap is reached
+        let second = vec![
+            entry(200, "b"),
+            entry(300, "c"),
+            entry(400, "d"),
+            entry(500, "e"),
+        ];
+        window.push(cursor.take_new(&second));
+        let msgs: Vec<&str> = window
+            .entries()
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect();
+        assert_eq!(msgs, vec!["b", "c", "d", "e"]);
+    }
+}
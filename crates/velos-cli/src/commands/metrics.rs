@@ -2,7 +2,13 @@ use std::time::Duration;
 use velos_core::VelosError;
 
 /// Start the Prometheus metrics server (and optionally init OTel tracing).
-pub async fn run(port: u16, otel_endpoint: Option<String>) -> Result<(), VelosError> {
+/// With `once`, print a single scrape to stdout and exit instead.
+pub async fn run(port: u16, otel_endpoint: Option<String>, once: bool) -> Result<(), VelosError> {
+    if once {
+        print!("{}", velos_metrics::prometheus::scrape_once().await?);
+        return Ok(());
+    }
+
     // Optionally initialise OpenTelemetry
     let _provider = if let Some(ref ep) = otel_endpoint {
         let p = velos_metrics::otel::init_tracer_provider(ep)?;
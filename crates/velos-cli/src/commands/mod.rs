@@ -2,6 +2,11 @@
 // n = name, i = id, s = status, p = pid
 // m = memory (bytes), u = uptime (ms), r = restarts
 // c = cpu (percent), t = timestamp (ms), l = level
+// e = lifecycle events, k = event kind, x = exit code
+// le = last exit reason
+// h = health score, g = line count, lv = lines by level, er = last error
+// pt = patterns (tp = template, f = frequency, tr = trend)
+// an = anomalies (mt = metric, v = value, sv = severity)
 
 pub mod ai;
 pub mod api;
@@ -9,7 +14,12 @@ pub mod completions;
 pub mod config;
 pub mod daemon;
 pub mod delete;
+pub mod events;
+pub mod export;
 pub mod flush;
+pub mod group;
+pub mod hooks;
+pub mod import;
 pub mod info;
 pub mod list;
 pub mod logs;
@@ -18,6 +28,7 @@ pub mod monit;
 pub mod notify_crash;
 pub mod notify_error;
 pub mod ping;
+pub mod ps;
 pub mod reload;
 pub mod restart;
 pub mod resurrect;
@@ -25,12 +36,26 @@ pub mod save;
 pub mod scale;
 pub mod start;
 pub mod startup;
+pub mod status;
 pub mod stop;
 pub mod telegram_poller;
+pub mod validate;
 
 use velos_client::VelosClient;
 use velos_core::VelosError;
 
+/// Version of the `info` and `list` JSON shapes (both `--json` and `--ai`).
+/// Bump it whenever a field is added, renamed or removed.
+///
+/// 1: `list` wraps its array as `{"schema_version", "processes"}`; `info`
+///    carries `schema_version` next to the process fields.
+/// 2: `list --json` processes carry `group` (null when ungrouped).
+/// 3: `info --json` carries `log_rate_limit` and `suppressed_lines`.
+/// 4: `info --json` carries `merge_logs`, `max_cpu_restart`,
+///    `cpu_restart_window_secs`, `out_log` and `err_log`.
+/// 5: `info --json` carries `env`, secret values masked.
+pub const JSON_SCHEMA_VERSION: u32 = 5;
+
 /// Helper: connect to the daemon, auto-starting it if not running.
 pub async fn connect() -> Result<VelosClient, VelosError> {
     match VelosClient::connect().await {
@@ -84,60 +109,6 @@ fn ensure_daemon_running() -> Result<(), VelosError> {
     Ok(())
 }
 
-/// Resolve a name-or-ID string to a numeric process ID.
-/// If it parses as u32, use it directly. Otherwise, query the process list
-/// and find the first process whose name matches.
-pub async fn resolve_id(client: &mut VelosClient, name_or_id: &str) -> Result<u32, VelosError> {
-    if let Ok(id) = name_or_id.parse::<u32>() {
-        return Ok(id);
-    }
-    let procs = client.list().await?;
-    procs
-        .iter()
-        .find(|p| p.name == name_or_id)
-        .map(|p| p.id)
-        .ok_or_else(|| VelosError::ProcessNotFound(name_or_id.to_string()))
-}
-
-/// Resolve a name-or-ID to one or more process IDs (cluster-aware).
-/// "api" matches all instances: "api", "api:0", "api:1", etc.
-/// "api:2" matches only that specific instance.
-/// A numeric string matches exactly one process by ID.
-pub async fn resolve_ids(
-    client: &mut VelosClient,
-    name_or_id: &str,
-) -> Result<Vec<u32>, VelosError> {
-    if let Ok(id) = name_or_id.parse::<u32>() {
-        return Ok(vec![id]);
-    }
-
-    let procs = client.list().await?;
-
-    // First try exact match
-    let exact: Vec<u32> = procs
-        .iter()
-        .filter(|p| p.name == name_or_id)
-        .map(|p| p.id)
-        .collect();
-    if !exact.is_empty() {
-        return Ok(exact);
-    }
-
-    // Then try as base name (match "name:N" pattern for cluster instances)
-    let cluster: Vec<u32> = procs
-        .iter()
-        .filter(|p| {
-            p.name.len() > name_or_id.len()
-                && p.name.starts_with(name_or_id)
-                && p.name.as_bytes().get(name_or_id.len()) == Some(&b':')
-                && p.name[name_or_id.len() + 1..].parse::<u32>().is_ok()
-        })
-        .map(|p| p.id)
-        .collect();
-
-    if !cluster.is_empty() {
-        return Ok(cluster);
-    }
-
-    Err(VelosError::ProcessNotFound(name_or_id.to_string()))
-}
+/// Name-or-ID resolution shared with the MCP server and the REST API; see
+/// [`velos_client::resolve::match_many`] for the accepted forms.
+pub use velos_client::resolve::{resolve_many as resolve_ids, resolve_one as resolve_id};
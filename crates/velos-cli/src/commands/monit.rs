@@ -15,8 +15,12 @@ use ratatui::widgets::{
 };
 use ratatui::Terminal;
 
+use velos_client::resolve::resolve_one;
+use velos_core::protocol::{ExitReason, ProcessDetail, ProcessInfo};
 use velos_core::VelosError;
 
+use crate::color::ColorChoice;
+
 // ── Catppuccin Mocha palette ────────────────────────────────────────
 mod cat {
     use ratatui::style::Color;
@@ -89,6 +93,72 @@ struct AppState {
     signal_selected: usize,
     notifications: Vec<Notification>,
     prev_restarts: Vec<(u32, u32)>, // (id, restart_count)
+    /// Set by `--process`: only this process is listed and drawn.
+    focus: Option<Focus>,
+}
+
+impl AppState {
+    fn new(processes: Vec<ProcessRow>, focus: Option<Focus>) -> Self {
+        Self {
+            prev_restarts: processes.iter().map(|p| (p.id, p.restarts)).collect(),
+            processes,
+            selected: 0,
+            logs: Vec::new(),
+            log_scroll: 0,
+            should_quit: false,
+            mem_history: Vec::new(),
+            cpu_history: Vec::new(),
+            mode: Mode::Normal,
+            filter_text: String::new(),
+            sort_by: SortColumn::Name,
+            sort_asc: true,
+            signal_selected: 0,
+            notifications: Vec::new(),
+            focus,
+        }
+    }
+
+    /// State for `--process`: the resolved process as the only row.
+    fn focused(detail: &ProcessDetail) -> Self {
+        let row = ProcessRow {
+            id: detail.id,
+            name: detail.name.clone(),
+            pid: detail.pid,
+            status: detail.status_str().to_string(),
+            memory: detail.memory_bytes,
+            // Not in the detail; filled in by the first refresh
+            cpu_percent: 0.0,
+            uptime: detail.uptime_ms,
+            restarts: detail.restart_count,
+        };
+        Self::new(vec![row], Some(Focus::from_detail(detail)))
+    }
+}
+
+/// Settings of the focused process shown in its info pane, refreshed from
+/// `info` along with the process list.
+struct Focus {
+    id: u32,
+    script: String,
+    cwd: String,
+    interpreter: String,
+    max_memory_restart: u64,
+    max_cpu_restart: f32,
+    last_exit_reason: ExitReason,
+}
+
+impl Focus {
+    fn from_detail(detail: &ProcessDetail) -> Self {
+        Self {
+            id: detail.id,
+            script: detail.script.clone(),
+            cwd: detail.cwd.clone(),
+            interpreter: detail.interpreter.clone(),
+            max_memory_restart: detail.max_memory_restart,
+            max_cpu_restart: detail.max_cpu_restart,
+            last_exit_reason: detail.last_exit_reason,
+        }
+    }
 }
 
 struct ProcessRow {
@@ -102,6 +172,21 @@ struct ProcessRow {
     restarts: u32,
 }
 
+impl ProcessRow {
+    fn from_info(p: &ProcessInfo) -> Self {
+        Self {
+            id: p.id,
+            name: p.name.clone(),
+            pid: p.pid,
+            status: p.status_str().to_string(),
+            memory: p.memory_bytes,
+            cpu_percent: p.cpu_percent,
+            uptime: p.uptime_ms,
+            restarts: p.restart_count,
+        }
+    }
+}
+
 struct LogLine {
     stream: &'static str,
     message: String,
@@ -109,37 +194,23 @@ struct LogLine {
 
 const SIGNALS: &[&str] = &["SIGTERM", "SIGKILL", "SIGHUP", "SIGUSR1", "SIGUSR2"];
 
-pub async fn run() -> Result<(), VelosError> {
+/// Log lines fetched for the selected process, and for a `--process` focus.
+const LOG_LINES: u32 = 50;
+const FOCUS_LOG_LINES: u32 = 500;
+
+/// `process` (`--process`) locks the dashboard onto one process, resolved
+/// like any name or ID (a cluster name picks its first instance).
+pub async fn run(process: Option<String>, color: ColorChoice) -> Result<(), VelosError> {
     let mut client = super::connect().await?;
-    let procs = client.list().await?;
-
-    let mut state = AppState {
-        processes: procs
-            .iter()
-            .map(|p| ProcessRow {
-                id: p.id,
-                name: p.name.clone(),
-                pid: p.pid,
-                status: p.status_str().to_string(),
-                memory: p.memory_bytes,
-                cpu_percent: p.cpu_percent,
-                uptime: p.uptime_ms,
-                restarts: p.restart_count,
-            })
-            .collect(),
-        selected: 0,
-        logs: Vec::new(),
-        log_scroll: 0,
-        should_quit: false,
-        mem_history: Vec::new(),
-        cpu_history: Vec::new(),
-        mode: Mode::Normal,
-        filter_text: String::new(),
-        sort_by: SortColumn::Name,
-        sort_asc: true,
-        signal_selected: 0,
-        notifications: Vec::new(),
-        prev_restarts: procs.iter().map(|p| (p.id, p.restart_count)).collect(),
+    let mut state = match process {
+        Some(name) => {
+            let id = resolve_one(&mut client, &name).await?;
+            AppState::focused(&client.info(id).await?)
+        }
+        None => {
+            let procs = client.list().await?;
+            AppState::new(procs.iter().map(ProcessRow::from_info).collect(), None)
+        }
     };
 
     enable_raw_mode().map_err(|e| VelosError::ProtocolError(format!("terminal: {e}")))?;
@@ -151,7 +222,7 @@ pub async fn run() -> Result<(), VelosError> {
     let mut terminal =
         Terminal::new(backend).map_err(|e| VelosError::ProtocolError(format!("terminal: {e}")))?;
 
-    let result = run_loop(&mut terminal, &mut state).await;
+    let result = run_loop(&mut terminal, &mut state, color).await;
 
     disable_raw_mode().ok();
     io::stdout().execute(LeaveAlternateScreen).ok();
@@ -162,10 +233,16 @@ pub async fn run() -> Result<(), VelosError> {
 async fn run_loop(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     state: &mut AppState,
+    color: ColorChoice,
 ) -> Result<(), VelosError> {
     loop {
         terminal
-            .draw(|f| draw_ui(f, state))
+            .draw(|f| {
+                draw_ui(f, state);
+                if !color.enabled() {
+                    strip_colors(f.buffer_mut());
+                }
+            })
             .map_err(|e| VelosError::ProtocolError(format!("draw: {e}")))?;
 
         if event::poll(Duration::from_secs(2)).unwrap_or(false) {
@@ -198,15 +275,11 @@ async fn run_loop(
                         KeyCode::Esc => {
                             state.mode = Mode::Normal;
                         }
-                        KeyCode::Up => {
-                            if state.signal_selected > 0 {
-                                state.signal_selected -= 1;
-                            }
+                        KeyCode::Up if state.signal_selected > 0 => {
+                            state.signal_selected -= 1;
                         }
-                        KeyCode::Down => {
-                            if state.signal_selected + 1 < SIGNALS.len() {
-                                state.signal_selected += 1;
-                            }
+                        KeyCode::Down if state.signal_selected + 1 < SIGNALS.len() => {
+                            state.signal_selected += 1;
                         }
                         KeyCode::Enter => {
                             if let Some(proc) =
@@ -239,11 +312,9 @@ async fn run_loop(
                         KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                             state.should_quit = true;
                         }
-                        KeyCode::Up | KeyCode::Char('k') => {
-                            if state.selected > 0 {
-                                state.selected -= 1;
-                                state.log_scroll = 0;
-                            }
+                        KeyCode::Up | KeyCode::Char('k') if state.selected > 0 => {
+                            state.selected -= 1;
+                            state.log_scroll = 0;
                         }
                         KeyCode::Down | KeyCode::Char('j') => {
                             let max = filtered_processes(state).len();
@@ -286,16 +357,12 @@ async fn run_loop(
                         KeyCode::Tab => {
                             state.sort_by = state.sort_by.next();
                         }
-                        KeyCode::Enter => {
-                            if !filtered_processes(state).is_empty() {
-                                state.mode = Mode::Detail;
-                            }
+                        KeyCode::Enter if !filtered_processes(state).is_empty() => {
+                            state.mode = Mode::Detail;
                         }
-                        KeyCode::Char('K') => {
-                            if !filtered_processes(state).is_empty() {
-                                state.mode = Mode::Signal;
-                                state.signal_selected = 0;
-                            }
+                        KeyCode::Char('K') if !filtered_processes(state).is_empty() => {
+                            state.mode = Mode::Signal;
+                            state.signal_selected = 0;
                         }
                         KeyCode::Char('?') => {
                             state.mode = Mode::Help;
@@ -322,6 +389,11 @@ async fn run_loop(
         // Refresh data
         if let Ok(mut client) = super::connect().await {
             if let Ok(procs) = client.list().await {
+                let procs: Vec<ProcessInfo> = procs
+                    .into_iter()
+                    .filter(|p| state.focus.as_ref().is_none_or(|focus| focus.id == p.id))
+                    .collect();
+
                 // Detect crashes by restart_count increase
                 for p in &procs {
                     if let Some(prev) = state.prev_restarts.iter().find(|(id, _)| *id == p.id) {
@@ -335,19 +407,7 @@ async fn run_loop(
                 }
                 state.prev_restarts = procs.iter().map(|p| (p.id, p.restart_count)).collect();
 
-                state.processes = procs
-                    .iter()
-                    .map(|p| ProcessRow {
-                        id: p.id,
-                        name: p.name.clone(),
-                        pid: p.pid,
-                        status: p.status_str().to_string(),
-                        memory: p.memory_bytes,
-                        cpu_percent: p.cpu_percent,
-                        uptime: p.uptime_ms,
-                        restarts: p.restart_count,
-                    })
-                    .collect();
+                state.processes = procs.iter().map(ProcessRow::from_info).collect();
                 if state.selected >= state.processes.len() && !state.processes.is_empty() {
                     state.selected = state.processes.len() - 1;
                 }
@@ -376,7 +436,12 @@ async fn run_loop(
             // Auto-fetch logs for selected process
             let selected_id = filtered_processes(state).get(state.selected).map(|p| p.id);
             if let Some(id) = selected_id {
-                if let Ok(entries) = client.logs(id, 50).await {
+                let lines = if state.focus.is_some() {
+                    FOCUS_LOG_LINES
+                } else {
+                    LOG_LINES
+                };
+                if let Ok(entries) = client.logs(id, lines).await {
                     state.logs = entries
                         .iter()
                         .map(|e| LogLine {
@@ -386,6 +451,12 @@ async fn run_loop(
                         .collect();
                 }
             }
+
+            if let Some(id) = state.focus.as_ref().map(|focus| focus.id) {
+                if let Ok(detail) = client.info(id).await {
+                    state.focus = Some(Focus::from_detail(&detail));
+                }
+            }
         }
     }
 }
@@ -409,9 +480,9 @@ fn filtered_processes(state: &AppState) -> Vec<&ProcessRow> {
                 .partial_cmp(&a.cpu_percent)
                 .unwrap_or(std::cmp::Ordering::Equal)
         }),
-        SortColumn::Mem => procs.sort_by(|a, b| b.memory.cmp(&a.memory)),
-        SortColumn::Uptime => procs.sort_by(|a, b| b.uptime.cmp(&a.uptime)),
-        SortColumn::Restarts => procs.sort_by(|a, b| b.restarts.cmp(&a.restarts)),
+        SortColumn::Mem => procs.sort_by_key(|p| std::cmp::Reverse(p.memory)),
+        SortColumn::Uptime => procs.sort_by_key(|p| std::cmp::Reverse(p.uptime)),
+        SortColumn::Restarts => procs.sort_by_key(|p| std::cmp::Reverse(p.restarts)),
     }
 
     if !state.sort_asc && state.sort_by != SortColumn::Name {
@@ -428,22 +499,43 @@ fn draw_ui(f: &mut ratatui::Frame, state: &AppState) {
     let bg = Block::default().style(Style::default().bg(cat::BASE));
     f.render_widget(bg, f.area());
 
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(1), // header
-            Constraint::Min(6),    // process table
-            Constraint::Length(5), // graphs (cpu + mem)
-            Constraint::Min(6),    // logs
-            Constraint::Length(1), // footer
-        ])
-        .split(f.area());
+    if let Some(ref focus) = state.focus {
+        // One process: its settings instead of the table, taller graphs
+        // and the rest of the screen for logs
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),  // header
+                Constraint::Length(5),  // process info
+                Constraint::Length(12), // graphs (cpu + mem)
+                Constraint::Min(8),     // logs
+                Constraint::Length(1),  // footer
+            ])
+            .split(f.area());
+
+        draw_header(f, state, chunks[0]);
+        draw_focus_info(f, state, focus, chunks[1]);
+        draw_graphs(f, state, chunks[2]);
+        draw_logs(f, state, chunks[3]);
+        draw_footer(f, state, chunks[4]);
+    } else {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1), // header
+                Constraint::Min(6),    // process table
+                Constraint::Length(5), // graphs (cpu + mem)
+                Constraint::Min(6),    // logs
+                Constraint::Length(1), // footer
+            ])
+            .split(f.area());
 
-    draw_header(f, state, chunks[0]);
-    draw_process_table(f, state, chunks[1]);
-    draw_graphs(f, state, chunks[2]);
-    draw_logs(f, state, chunks[3]);
-    draw_footer(f, state, chunks[4]);
+        draw_header(f, state, chunks[0]);
+        draw_process_table(f, state, chunks[1]);
+        draw_graphs(f, state, chunks[2]);
+        draw_logs(f, state, chunks[3]);
+        draw_footer(f, state, chunks[4]);
+    }
 
     // Overlays
     match state.mode {
@@ -457,6 +549,19 @@ fn draw_ui(f: &mut ratatui::Frame, state: &AppState) {
     draw_notifications(f, state);
 }
 
+/// Drop all colors for `--color=never`. Cells drawn on a highlight
+/// background (selection, popups) are shown reversed instead so they stay
+/// distinguishable.
+fn strip_colors(buf: &mut ratatui::buffer::Buffer) {
+    for cell in buf.content.iter_mut() {
+        if cell.bg != Color::Reset && cell.bg != cat::BASE {
+            cell.modifier.insert(Modifier::REVERSED);
+        }
+        cell.set_fg(Color::Reset);
+        cell.set_bg(Color::Reset);
+    }
+}
+
 fn draw_header(f: &mut ratatui::Frame, state: &AppState, area: Rect) {
     let total_cpu: f32 = state.processes.iter().map(|p| p.cpu_percent).sum();
     let total_mem: u64 = state.processes.iter().map(|p| p.memory).sum();
@@ -618,6 +723,125 @@ fn draw_process_table(f: &mut ratatui::Frame, state: &AppState, area: Rect) {
     f.render_widget(table, area);
 }
 
+fn draw_focus_info(f: &mut ratatui::Frame, state: &AppState, focus: &Focus, area: Rect) {
+    let Some(proc) = state.processes.first() else {
+        let gone = Paragraph::new(Span::styled(
+            " Process is gone (deleted?) ",
+            Style::default().fg(cat::RED),
+        ))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(cat::SURFACE2))
+                .style(Style::default().bg(cat::BASE)),
+        );
+        f.render_widget(gone, area);
+        return;
+    };
+
+    let field = |label: &str, value: String, color: Color| {
+        [
+            Span::styled(
+                format!("  {label} "),
+                Style::default()
+                    .fg(cat::OVERLAY0)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(value, Style::default().fg(color)),
+        ]
+    };
+    let status_color = match proc.status.as_str() {
+        "running" => cat::GREEN,
+        "errored" => cat::RED,
+        "stopped" => cat::YELLOW,
+        "starting" => cat::MAUVE,
+        _ => cat::TEXT,
+    };
+    let limit = |set: bool, value: String| if set { value } else { "none".into() };
+
+    let lines = vec![
+        Line::from(
+            [
+                field("Status", proc.status.clone(), status_color),
+                field(
+                    "PID",
+                    if proc.pid > 0 {
+                        proc.pid.to_string()
+                    } else {
+                        "-".into()
+                    },
+                    cat::TEXT,
+                ),
+                field("Uptime", format_uptime(proc.uptime), cat::GREEN),
+                field(
+                    "Restarts",
+                    proc.restarts.to_string(),
+                    if proc.restarts > 0 {
+                        cat::YELLOW
+                    } else {
+                        cat::TEXT
+                    },
+                ),
+                field(
+                    "Last exit",
+                    focus.last_exit_reason.as_str().to_string(),
+                    cat::SUBTEXT1,
+                ),
+            ]
+            .concat(),
+        ),
+        Line::from(
+            [
+                field("Script", focus.script.clone(), cat::TEXT),
+                field(
+                    "Interpreter",
+                    limit(!focus.interpreter.is_empty(), focus.interpreter.clone()),
+                    cat::SUBTEXT1,
+                ),
+            ]
+            .concat(),
+        ),
+        Line::from(
+            [
+                field("Cwd", focus.cwd.clone(), cat::SUBTEXT1),
+                field(
+                    "Max mem",
+                    limit(
+                        focus.max_memory_restart > 0,
+                        format_bytes(focus.max_memory_restart),
+                    ),
+                    cat::BLUE,
+                ),
+                field(
+                    "Max CPU",
+                    limit(
+                        focus.max_cpu_restart > 0.0,
+                        format!("{:.0}%", focus.max_cpu_restart),
+                    ),
+                    cat::PEACH,
+                ),
+            ]
+            .concat(),
+        ),
+    ];
+
+    let info = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(cat::SURFACE2))
+            .title(Span::styled(
+                format!(" {} (id {}) ", proc.name, proc.id),
+                Style::default()
+                    .fg(cat::LAVENDER)
+                    .add_modifier(Modifier::BOLD),
+            ))
+            .style(Style::default().bg(cat::BASE)),
+    );
+    f.render_widget(info, area);
+}
+
 fn draw_graphs(f: &mut ratatui::Frame, state: &AppState, area: Rect) {
     let graph_chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -761,18 +985,31 @@ fn draw_footer(f: &mut ratatui::Frame, state: &AppState, area: Rect) {
     } else {
         let mut spans = Vec::new();
         spans.push(Span::raw(" "));
-        let keys: &[(&str, &str)] = &[
-            ("q", "quit"),
-            ("\u{2191}\u{2193}/jk", "select"),
-            ("/", "filter"),
-            ("Tab", "sort"),
-            ("Enter", "detail"),
-            ("r", "restart"),
-            ("s", "stop"),
-            ("d", "delete"),
-            ("K", "signal"),
-            ("?", "help"),
-        ];
+        let keys: &[(&str, &str)] = if state.focus.is_some() {
+            &[
+                ("q", "quit"),
+                ("PgUp/PgDn", "scroll"),
+                ("Enter", "detail"),
+                ("r", "restart"),
+                ("s", "stop"),
+                ("d", "delete"),
+                ("K", "signal"),
+                ("?", "help"),
+            ]
+        } else {
+            &[
+                ("q", "quit"),
+                ("\u{2191}\u{2193}/jk", "select"),
+                ("/", "filter"),
+                ("Tab", "sort"),
+                ("Enter", "detail"),
+                ("r", "restart"),
+                ("s", "stop"),
+                ("d", "delete"),
+                ("K", "signal"),
+                ("?", "help"),
+            ]
+        };
         for (i, (key, desc)) in keys.iter().enumerate() {
             if i > 0 {
                 spans.push(Span::styled(
@@ -1049,3 +1286,51 @@ fn format_uptime(ms: u64) -> String {
         format!("{}d {}h", secs / 86400, (secs % 86400) / 3600)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_focused_state_from_detail() {
+        let detail = ProcessDetail {
+            id: 7,
+            name: "api".into(),
+            pid: 4242,
+            status: 1,
+            memory_bytes: 64 * 1024 * 1024,
+            uptime_ms: 90_000,
+            restart_count: 3,
+            script: "server.js".into(),
+            cwd: "/srv/api".into(),
+            interpreter: "node".into(),
+            max_memory_restart: 512 * 1024 * 1024,
+            last_exit_reason: ExitReason::Crashed,
+            ..Default::default()
+        };
+        let state = AppState::focused(&detail);
+
+        let rows = filtered_processes(&state);
+        assert_eq!(rows.len(), 1);
+        let row = rows[0];
+        assert_eq!((row.id, row.name.as_str(), row.pid), (7, "api", 4242));
+        assert_eq!(row.status, "running");
+        assert_eq!(
+            (row.memory, row.uptime, row.restarts),
+            (64 << 20, 90_000, 3)
+        );
+        assert_eq!(state.selected, 0);
+        // A restart already counted isn't reported as a new crash
+        assert_eq!(state.prev_restarts, [(7, 3)]);
+
+        let focus = state.focus.as_ref().unwrap();
+        assert_eq!(focus.id, 7);
+        assert_eq!(
+            (focus.script.as_str(), focus.cwd.as_str()),
+            ("server.js", "/srv/api")
+        );
+        assert_eq!(focus.interpreter, "node");
+        assert_eq!(focus.max_memory_restart, 512 << 20);
+        assert_eq!(focus.last_exit_reason, ExitReason::Crashed);
+    }
+}
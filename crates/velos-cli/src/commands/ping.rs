@@ -1,8 +1,110 @@
 use velos_core::VelosError;
 
-pub async fn run() -> Result<(), VelosError> {
+const CLI_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+pub async fn run(check_version: bool) -> Result<(), VelosError> {
     let mut client = super::connect().await?;
     let msg = client.ping().await?;
-    println!("[velos] {msg}");
+    let daemon = daemon_version(&msg);
+    let skew = classify_skew(CLI_VERSION, daemon);
+
+    if check_version {
+        println!(
+            "{}",
+            serde_json::json!({
+                "cli": CLI_VERSION,
+                "daemon": daemon,
+                "match": skew == VersionSkew::Exact,
+                "skew": skew.as_str(),
+            })
+        );
+    } else {
+        println!("[velos] {msg}");
+    }
+
+    if matches!(skew, VersionSkew::Minor | VersionSkew::Major) {
+        eprintln!(
+            "[velos] Warning: CLI is {} but the daemon is {} ({} version skew).",
+            CLI_VERSION,
+            daemon.unwrap_or_default(),
+            skew.as_str()
+        );
+        eprintln!(
+            "  Restart the daemon on the new binary: velos save, stop the running daemon, then velos resurrect"
+        );
+    }
     Ok(())
 }
+
+/// How far apart the CLI and daemon versions are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VersionSkew {
+    Exact,
+    /// Same major version, different minor or patch.
+    Minor,
+    Major,
+    /// The daemon predates version reporting, or sent something unparsable.
+    Unknown,
+}
+
+impl VersionSkew {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Exact => "exact",
+            Self::Minor => "minor",
+            Self::Major => "major",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// Version from a ping reply: "pong 0.1.14 5000" → "0.1.14"; bare "pong" → None.
+pub(crate) fn daemon_version(pong: &str) -> Option<&str> {
+    pong.strip_prefix("pong")?.split_whitespace().next()
+}
+
+/// Daemon uptime from a ping reply: "pong 0.1.14 5000" → 5000. Daemons
+/// before uptime reporting send only the version.
+pub(crate) fn daemon_uptime_ms(pong: &str) -> Option<u64> {
+    pong.strip_prefix("pong")?
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()
+}
+
+fn classify_skew(cli: &str, daemon: Option<&str>) -> VersionSkew {
+    let Some(daemon) = daemon else {
+        return VersionSkew::Unknown;
+    };
+    if cli == daemon {
+        return VersionSkew::Exact;
+    }
+    let major = |v: &str| v.split('.').next().and_then(|m| m.parse::<u64>().ok());
+    match (major(cli), major(daemon)) {
+        (Some(a), Some(b)) if a == b => VersionSkew::Minor,
+        (Some(_), Some(_)) => VersionSkew::Major,
+        _ => VersionSkew::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_skew_classification() {
+        assert_eq!(classify_skew("0.1.14", Some("0.1.14")), VersionSkew::Exact);
+        assert_eq!(classify_skew("0.1.14", Some("0.1.12")), VersionSkew::Minor);
+        assert_eq!(classify_skew("0.2.0", Some("0.1.14")), VersionSkew::Minor);
+        assert_eq!(classify_skew("1.0.0", Some("0.1.14")), VersionSkew::Major);
+        assert_eq!(classify_skew("0.1.14", None), VersionSkew::Unknown);
+        assert_eq!(classify_skew("0.1.14", Some("dev")), VersionSkew::Unknown);
+
+        assert_eq!(daemon_version("pong 0.1.14"), Some("0.1.14"));
+        assert_eq!(daemon_version("pong"), None);
+        assert_eq!(daemon_version("pong 0.1.14 5000"), Some("0.1.14"));
+        assert_eq!(daemon_uptime_ms("pong 0.1.14 5000"), Some(5000));
+        assert_eq!(daemon_uptime_ms("pong 0.1.14"), None);
+    }
+}
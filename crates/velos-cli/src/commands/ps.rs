@@ -0,0 +1,192 @@
+use velos_core::protocol::ProcessInfo;
+use velos_core::VelosError;
+
+use super::list::{format_bytes, format_uptime};
+
+/// Column `velos ps --sort` orders by. `id` and `name` sort ascending, the
+/// resource columns busiest first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum PsSort {
+    #[default]
+    Id,
+    Name,
+    Cpu,
+    Mem,
+    Uptime,
+    Restarts,
+}
+
+/// One `--filter key=value`. Several filters must all match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Filter {
+    /// "running", "stopped", "errored" or "starting".
+    Status(String),
+    /// Anything `velos stop` accepts: a name, glob, cluster base or @group.
+    Name(String),
+}
+
+const STATUSES: &[&str] = &["running", "stopped", "errored", "starting"];
+
+impl Filter {
+    fn parse(spec: &str) -> Result<Self, VelosError> {
+        let invalid =
+            |why: &str| VelosError::ProtocolError(format!("invalid --filter '{spec}': {why}"));
+        let (key, value) = spec
+            .split_once('=')
+            .ok_or_else(|| invalid("use status=<status> or name=<name>"))?;
+        let value = value.trim();
+        match key.trim() {
+            "status" if STATUSES.contains(&value) => Ok(Self::Status(value.to_string())),
+            "status" => Err(invalid(&format!(
+                "status is one of {}",
+                STATUSES.join(", ")
+            ))),
+            "name" if !value.is_empty() => Ok(Self::Name(value.to_string())),
+            "name" => Err(invalid("name is empty")),
+            _ => Err(invalid("filter on status or name")),
+        }
+    }
+}
+
+pub async fn run(sort: PsSort, filters: Vec<String>) -> Result<(), VelosError> {
+    let filters = filters
+        .iter()
+        .map(|f| Filter::parse(f))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut client = super::connect().await?;
+    let list = client.list_lenient().await?;
+    if let Some(ref why) = list.truncated {
+        eprintln!("[velos] Warning: incomplete reply from the daemon, {why}");
+    }
+
+    let rows = select(&list.procs, &filters, sort);
+    if rows.is_empty() {
+        println!("[velos] No matching processes");
+        return Ok(());
+    }
+    print!("{}", render(&rows));
+    Ok(())
+}
+
+/// The processes matching every filter, ordered by `sort` (ties by id).
+fn select<'a>(procs: &'a [ProcessInfo], filters: &[Filter], sort: PsSort) -> Vec<&'a ProcessInfo> {
+    let mut rows: Vec<&ProcessInfo> = procs.iter().collect();
+    for filter in filters {
+        match filter {
+            Filter::Status(status) => rows.retain(|p| p.status_str() == status),
+            Filter::Name(name) => {
+                // Nothing matching is an empty listing here, not an error
+                let ids = velos_client::resolve::match_many(procs, name).unwrap_or_default();
+                rows.retain(|p| ids.contains(&p.id));
+            }
+        }
+    }
+
+    rows.sort_by_key(|p| p.id);
+    match sort {
+        PsSort::Id => {}
+        PsSort::Name => rows.sort_by(|a, b| a.name.cmp(&b.name)),
+        PsSort::Cpu => rows.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent)),
+        PsSort::Mem => rows.sort_by_key(|p| std::cmp::Reverse(p.memory_bytes)),
+        PsSort::Uptime => rows.sort_by_key(|p| std::cmp::Reverse(p.uptime_ms)),
+        PsSort::Restarts => rows.sort_by_key(|p| std::cmp::Reverse(p.restart_count)),
+    }
+    rows
+}
+
+/// `ps aux`-style text: a header, then one space-aligned line per process.
+fn render(rows: &[&ProcessInfo]) -> String {
+    let header = ["ID", "NAME", "PID", "STATUS", "CPU", "MEM", "UPTIME"];
+    let mut lines = vec![header.map(String::from)];
+    for p in rows {
+        lines.push([
+            p.id.to_string(),
+            p.name.clone(),
+            if p.pid > 0 {
+                p.pid.to_string()
+            } else {
+                "-".to_string()
+            },
+            p.status_str().to_string(),
+            format!("{:.1}%", p.cpu_percent),
+            format_bytes(p.memory_bytes),
+            format_uptime(p.uptime_ms),
+        ]);
+    }
+
+    let mut widths = [0; 7];
+    for line in &lines {
+        for (width, cell) in widths.iter_mut().zip(line) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut out = String::new();
+    for line in &lines {
+        let cells: Vec<String> = line
+            .iter()
+            .zip(widths)
+            .enumerate()
+            .map(|(i, (cell, width))| match i {
+                // Numbers right-aligned, text left-aligned
+                0 | 2 | 4 | 5 => format!("{cell:>width$}"),
+                _ => format!("{cell:<width$}"),
+            })
+            .collect();
+        out.push_str(cells.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proc(id: u32, name: &str, status: u8, cpu: f32, group: Option<&str>) -> ProcessInfo {
+        ProcessInfo {
+            id,
+            name: name.into(),
+            pid: if status == 1 { 1000 + id } else { 0 },
+            status,
+            memory_bytes: 1024 * 1024 * (id as u64 + 1),
+            uptime_ms: 90_000,
+            restart_count: 0,
+            cpu_percent: cpu,
+            group: group.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn test_filter_and_sort_compose() {
+        let procs = vec![
+            proc(0, "api:0", 1, 12.5, Some("backend")),
+            proc(1, "api:1", 0, 0.0, Some("backend")),
+            proc(2, "web", 1, 40.0, None),
+            proc(3, "worker", 1, 3.0, Some("backend")),
+            proc(4, "api:2", 1, 80.0, Some("backend")),
+        ];
+        let ids = |rows: Vec<&ProcessInfo>| rows.iter().map(|p| p.id).collect::<Vec<_>>();
+
+        assert_eq!(ids(select(&procs, &[], PsSort::Id)), [0, 1, 2, 3, 4]);
+
+        let running = Filter::parse("status=running").unwrap();
+        assert_eq!(
+            ids(select(&procs, std::slice::from_ref(&running), PsSort::Cpu)),
+            [4, 2, 0, 3]
+        );
+
+        let backend = Filter::parse("name=@backend").unwrap();
+        let both = [running, backend];
+        assert_eq!(ids(select(&procs, &both, PsSort::Cpu)), [4, 0, 3]);
+        assert_eq!(ids(select(&procs, &both, PsSort::Name)), [0, 4, 3]);
+        assert_eq!(ids(select(&procs, &both, PsSort::Mem)), [4, 3, 0]);
+
+        let none = Filter::parse("name=db").unwrap();
+        assert!(select(&procs, &[none], PsSort::Id).is_empty());
+
+        for bad in ["status", "status=online", "name=", "pid=3"] {
+            assert!(Filter::parse(bad).is_err(), "{bad}");
+        }
+    }
+}
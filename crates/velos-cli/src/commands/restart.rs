@@ -1,7 +1,37 @@
+use std::time::Duration;
+
+use serde_json::Value;
+use velos_client::VelosClient;
+use velos_core::protocol::ExitReason;
 use velos_core::VelosError;
+use velos_log_engine::classifier::Classifier;
+use velos_log_engine::pattern::PatternDetector;
+use velos_log_engine::summary;
+
+/// Recent lines scored by `--if-unhealthy`.
+const HEALTH_LINES: u32 = 500;
+
+/// `threshold` is set by `--if-unhealthy`: only processes whose log health
+/// score is below it are restarted. `throttle` (`--throttle 2/10s`) restarts
+/// every matching process, paced so no more than `n` start per duration.
+/// `reset_counters` zeroes each process's restart and crash counters right
+/// after its restart, so health scores start over.
+pub async fn run(
+    name_or_id: String,
+    json: bool,
+    threshold: Option<u8>,
+    throttle: Option<String>,
+    reset_counters: bool,
+) -> Result<(), VelosError> {
+    if let Some(spec) = throttle {
+        let throttle = Throttle::parse(&spec)?;
+        return run_throttled(&name_or_id, json, throttle, reset_counters).await;
+    }
 
-pub async fn run(name_or_id: String, json: bool) -> Result<(), VelosError> {
     let mut client = super::connect().await?;
+    if reset_counters {
+        require_reset(&client)?;
+    }
 
     if name_or_id == "all" {
         let procs = client.list().await?;
@@ -15,30 +45,398 @@ pub async fn run(name_or_id: String, json: bool) -> Result<(), VelosError> {
         }
         let mut restarted = Vec::new();
         for p in &procs {
-            client.restart(p.id).await?;
-            restarted.push(serde_json::json!({ "id": p.id, "name": p.name }));
+            let score = match threshold {
+                Some(threshold) => {
+                    let score = health_score(&mut client, p.id).await?;
+                    if !should_restart(score, threshold) {
+                        if !json {
+                            print_skipped(&p.name, score, threshold);
+                        }
+                        continue;
+                    }
+                    Some(score)
+                }
+                None => None,
+            };
+            let last_auto = last_auto_restart(&mut client, p.id, json).await?;
+            restart_one(&mut client, p.id, reset_counters).await?;
+            if !json {
+                println!("[velos] Restarted '{}' (id={})", p.name, p.id);
+            }
+            restarted.push(manual_restart(
+                serde_json::json!({ "id": p.id, "name": p.name, "health_score": score }),
+                last_auto,
+            ));
         }
         if json {
             println!(
                 "{}",
                 serde_json::to_string_pretty(&restarted).unwrap_or_default()
             );
-        } else {
-            for p in &procs {
-                println!("[velos] Restarted '{}' (id={})", p.name, p.id);
-            }
         }
         return Ok(());
     }
 
     let id = super::resolve_id(&mut client, &name_or_id).await?;
-    client.restart(id).await?;
+
+    if let Some(threshold) = threshold {
+        let score = health_score(&mut client, id).await?;
+        if !should_restart(score, threshold) {
+            if json {
+                println!(
+                    "{}",
+                    serde_json::json!({ "restarted": null, "skipped": id, "health_score": score })
+                );
+            } else {
+                print_skipped(&name_or_id, score, threshold);
+            }
+            return Ok(());
+        }
+        let last_auto = last_auto_restart(&mut client, id, json).await?;
+        restart_one(&mut client, id, reset_counters).await?;
+        if json {
+            println!(
+                "{}",
+                manual_restart(
+                    serde_json::json!({ "restarted": id, "health_score": score }),
+                    last_auto
+                )
+            );
+        } else {
+            println!(
+                "[velos] Restarted process '{name_or_id}' (id={id}, health score {score} < {threshold})"
+            );
+        }
+        return Ok(());
+    }
+
+    let last_auto = last_auto_restart(&mut client, id, json).await?;
+    restart_one(&mut client, id, reset_counters).await?;
 
     if json {
-        println!("{}", serde_json::json!({ "restarted": id }));
+        println!(
+            "{}",
+            manual_restart(serde_json::json!({ "restarted": id }), last_auto)
+        );
     } else {
         println!("[velos] Restarted process '{name_or_id}' (id={id})");
     }
 
     Ok(())
 }
+
+/// Restart rate for `--throttle`: at most `count` restarts per `per`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Throttle {
+    count: u32,
+    per: Duration,
+}
+
+impl Throttle {
+    /// Parse `n/duration`, e.g. `2/10s`, `1/500ms`, `5/1m`. A bare number is seconds.
+    fn parse(spec: &str) -> Result<Self, VelosError> {
+        let invalid = || {
+            VelosError::ProtocolError(format!(
+                "invalid --throttle '{spec}': use <n>/<duration>, e.g. 2/10s"
+            ))
+        };
+        let (count, per) = spec.split_once('/').ok_or_else(invalid)?;
+        let count: u32 = count.trim().parse().map_err(|_| invalid())?;
+        let per = per.trim();
+        let (num, unit_ms) = if let Some(n) = per.strip_suffix("ms") {
+            (n, 1)
+        } else if let Some(n) = per.strip_suffix('s') {
+            (n, 1000)
+        } else if let Some(n) = per.strip_suffix('m') {
+            (n, 60_000)
+        } else if let Some(n) = per.strip_suffix('h') {
+            (n, 3_600_000)
+        } else {
+            (per, 1000)
+        };
+        let per_ms = num
+            .parse::<u64>()
+            .ok()
+            .and_then(|n| n.checked_mul(unit_ms))
+            .ok_or_else(invalid)?;
+        if count == 0 || per_ms == 0 {
+            return Err(invalid());
+        }
+        Ok(Self {
+            count,
+            per: Duration::from_millis(per_ms),
+        })
+    }
+
+    /// When each of `n` restarts may start, measured from the first: evenly
+    /// spaced `per / count` apart, so no `per`-long window holds more than
+    /// `count` of them.
+    fn schedule(self, n: usize) -> Vec<Duration> {
+        let gap = self.per / self.count;
+        (0..n as u32).map(|i| gap * i).collect()
+    }
+}
+
+/// `--throttle`: restart every process `name_or_id` resolves to (cluster
+/// instances, `@group` members, or `all`), one at a time on the schedule.
+async fn run_throttled(
+    name_or_id: &str,
+    json: bool,
+    throttle: Throttle,
+    reset_counters: bool,
+) -> Result<(), VelosError> {
+    let mut client = super::connect().await?;
+    if reset_counters {
+        require_reset(&client)?;
+    }
+    let procs = client.list().await?;
+    let ids = if name_or_id == "all" {
+        procs.iter().map(|p| p.id).collect()
+    } else {
+        super::resolve_ids(&mut client, name_or_id).await?
+    };
+
+    let start = tokio::time::Instant::now();
+    let mut restarted = Vec::with_capacity(ids.len());
+    for (&id, offset) in ids.iter().zip(throttle.schedule(ids.len())) {
+        tokio::time::sleep_until(start + offset).await;
+        let last_auto = last_auto_restart(&mut client, id, json).await?;
+        restart_one(&mut client, id, reset_counters).await?;
+        let name = procs
+            .iter()
+            .find(|p| p.id == id)
+            .map_or_else(|| id.to_string(), |p| p.name.clone());
+        if !json {
+            println!(
+                "[velos] Restarted '{}' (id={}) at +{:.1}s",
+                name,
+                id,
+                start.elapsed().as_secs_f64()
+            );
+        }
+        restarted.push(manual_restart(
+            serde_json::json!({
+                "id": id,
+                "name": name,
+                "offset_ms": offset.as_millis() as u64,
+            }),
+            last_auto,
+        ));
+    }
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&restarted).unwrap_or_default()
+        );
+    } else if restarted.is_empty() {
+        println!("[velos] No processes to restart");
+    }
+    Ok(())
+}
+
+/// The daemon calls `velos restart` makes. A trait so the restart/reset
+/// sequencing can be tested without a daemon.
+trait RestartOps {
+    async fn restart(&mut self, id: u32) -> Result<(), VelosError>;
+    async fn reset(&mut self, id: u32) -> Result<(), VelosError>;
+}
+
+impl RestartOps for VelosClient {
+    async fn restart(&mut self, id: u32) -> Result<(), VelosError> {
+        VelosClient::restart(self, id).await
+    }
+
+    async fn reset(&mut self, id: u32) -> Result<(), VelosError> {
+        VelosClient::reset(self, id).await
+    }
+}
+
+/// Restart one process, then reset its counters if asked. The reset comes
+/// second: the restart itself would count against a fresh counter.
+async fn restart_one(
+    ops: &mut impl RestartOps,
+    id: u32,
+    reset_counters: bool,
+) -> Result<(), VelosError> {
+    ops.restart(id).await?;
+    if reset_counters {
+        ops.reset(id).await?;
+    }
+    Ok(())
+}
+
+/// Fail before restarting anything if the daemon predates PROCESS_RESET,
+/// rather than restarting and then failing the reset.
+fn require_reset(client: &VelosClient) -> Result<(), VelosError> {
+    if client.supports_reset() {
+        return Ok(());
+    }
+    Err(VelosError::ProtocolError(
+        "--reset-counters needs a newer daemon; restart it with `velos daemon` after upgrading"
+            .into(),
+    ))
+}
+
+/// Why the daemon last restarted the process on its own (crash, OOM, CPU,
+/// signal), if it did. Read before restarting, since a manual restart
+/// replaces it; only fetched for `--json`.
+async fn last_auto_restart(
+    client: &mut VelosClient,
+    id: u32,
+    json: bool,
+) -> Result<Option<ExitReason>, VelosError> {
+    if !json {
+        return Ok(None);
+    }
+    let detail = client.info(id).await?;
+    Ok(auto_restart_reason(detail.last_exit_reason))
+}
+
+/// `None` and `Manual` aren't automatic restarts. Daemons that don't track
+/// exit reasons always report `None`.
+fn auto_restart_reason(reason: ExitReason) -> Option<ExitReason> {
+    match reason {
+        ExitReason::None | ExitReason::Manual => None,
+        reason => Some(reason),
+    }
+}
+
+/// Add the restart reason to a `--json` result: restarts from this command
+/// are always "manual", with the previous automatic one (or null) alongside.
+fn manual_restart(mut result: Value, last_auto: Option<ExitReason>) -> Value {
+    result["reason"] = "manual".into();
+    result["last_auto_restart_reason"] = serde_json::json!(last_auto);
+    result
+}
+
+/// Score a process's recent logs with the same scorer as `velos logs --summary`.
+async fn health_score(client: &mut VelosClient, id: u32) -> Result<u8, VelosError> {
+    let detail = client.info(id).await?;
+    let entries = client.logs(id, HEALTH_LINES).await?;
+    let processed = Classifier::with_defaults().classify_batch(&entries);
+    let patterns = PatternDetector::with_defaults().detect(&processed);
+    let log_summary = summary::generate_summary(
+        &detail.name,
+        &processed,
+        &patterns,
+        &[],
+        detail.restart_count,
+        detail.last_exit_reason,
+    );
+    Ok(log_summary.health_score)
+}
+
+/// Restart only when the score is strictly below the threshold.
+fn should_restart(score: u8, threshold: u8) -> bool {
+    score < threshold
+}
+
+fn print_skipped(name: &str, score: u8, threshold: u8) {
+    println!("[velos] '{name}' is healthy (score {score} >= {threshold}), not restarting");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_restart_below_threshold() {
+        assert!(should_restart(39, 40));
+        assert!(should_restart(0, 40));
+        // At the threshold counts as healthy
+        assert!(!should_restart(40, 40));
+        assert!(!should_restart(100, 40));
+        // A zero threshold never restarts
+        assert!(!should_restart(0, 0));
+    }
+
+    #[test]
+    fn test_restart_json_reason() {
+        let result = manual_restart(
+            serde_json::json!({ "restarted": 3 }),
+            auto_restart_reason(ExitReason::Oom),
+        );
+        assert_eq!(
+            result,
+            serde_json::json!({
+                "restarted": 3,
+                "reason": "manual",
+                "last_auto_restart_reason": "oom",
+            })
+        );
+
+        // A previous manual stop, or an old daemon, has no automatic reason
+        for reason in [ExitReason::Manual, ExitReason::None] {
+            let result = manual_restart(
+                serde_json::json!({ "id": 1, "name": "api", "health_score": null }),
+                auto_restart_reason(reason),
+            );
+            assert_eq!(result["reason"], "manual");
+            assert!(result["last_auto_restart_reason"].is_null());
+            assert_eq!(result["name"], "api");
+        }
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        calls: Vec<(&'static str, u32)>,
+    }
+
+    impl RestartOps for Recorder {
+        async fn restart(&mut self, id: u32) -> Result<(), VelosError> {
+            self.calls.push(("restart", id));
+            Ok(())
+        }
+
+        async fn reset(&mut self, id: u32) -> Result<(), VelosError> {
+            self.calls.push(("reset", id));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reset_counters_after_restart() {
+        let mut ops = Recorder::default();
+        restart_one(&mut ops, 3, true).await.unwrap();
+        restart_one(&mut ops, 4, false).await.unwrap();
+        assert_eq!(ops.calls, [("restart", 3), ("reset", 3), ("restart", 4)]);
+    }
+
+    #[test]
+    fn test_throttle_schedule() {
+        let throttle = Throttle::parse("2/10s").unwrap();
+        assert_eq!(
+            throttle,
+            Throttle {
+                count: 2,
+                per: Duration::from_secs(10)
+            }
+        );
+        let secs: Vec<u64> = throttle.schedule(6).iter().map(|d| d.as_secs()).collect();
+        assert_eq!(secs, [0, 5, 10, 15, 20, 25]);
+
+        assert_eq!(
+            Throttle::parse("1/500ms").unwrap().schedule(3),
+            [
+                Duration::ZERO,
+                Duration::from_millis(500),
+                Duration::from_millis(1000)
+            ]
+        );
+        assert_eq!(
+            Throttle::parse("3/1m").unwrap().per,
+            Duration::from_secs(60)
+        );
+        for bad in [
+            "2",
+            "0/10s",
+            "2/0s",
+            "x/10s",
+            "2/ten",
+            "1/99999999999999999h",
+        ] {
+            assert!(Throttle::parse(bad).is_err(), "{bad}");
+        }
+    }
+}
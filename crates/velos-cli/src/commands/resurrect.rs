@@ -1,11 +1,33 @@
+use std::path::Path;
+
+use velos_client::state::{self, SavedState, StateFileError};
 use velos_core::VelosError;
 
-pub async fn run(json: bool) -> Result<(), VelosError> {
+pub async fn run(dry_run: bool, json: bool) -> Result<(), VelosError> {
+    let path = state::default_state_path();
+
+    if dry_run {
+        return print_dry_run(&path, json);
+    }
+
     let mut client = super::connect().await?;
     let result = client.resurrect().await?;
 
+    // The daemon reports 0 both for "nothing saved" and "couldn't read the
+    // file", so look at the file ourselves before saying there was nothing.
+    let diagnostic = if result.count == 0 {
+        diagnose(&path, &state::read_state_file(&path))
+    } else {
+        None
+    };
+
     if json {
-        println!("{}", serde_json::json!({ "restored": result.count }));
+        println!(
+            "{}",
+            serde_json::json!({ "restored": result.count, "diagnostic": diagnostic })
+        );
+    } else if let Some(diag) = diagnostic {
+        println!("[velos] No processes restored: {diag}");
     } else if result.count == 0 {
         println!("[velos] No saved processes to restore");
     } else {
@@ -14,3 +36,98 @@ pub async fn run(json: bool) -> Result<(), VelosError> {
 
     Ok(())
 }
+
+fn print_dry_run(path: &Path, json: bool) -> Result<(), VelosError> {
+    let saved = state::read_state_file(path)
+        .map_err(|e| VelosError::ProtocolError(format!("cannot read {}: {e}", path.display())))?;
+    let processes = saved
+        .as_ref()
+        .map(|s| s.processes.as_slice())
+        .unwrap_or(&[]);
+
+    if json {
+        let list: Vec<_> = processes
+            .iter()
+            .map(|p| {
+                serde_json::json!({
+                    "name": p.name,
+                    "script": p.script,
+                    "cwd": p.cwd,
+                    "instances": p.instances,
+                })
+            })
+            .collect();
+        println!(
+            "{}",
+            serde_json::json!({ "dry_run": true, "would_restore": list })
+        );
+        return Ok(());
+    }
+
+    if processes.is_empty() {
+        println!("[velos] No saved processes to restore");
+        return Ok(());
+    }
+    println!(
+        "[velos] Would restore {} process(es) from {}:",
+        processes.len(),
+        path.display()
+    );
+    for p in processes {
+        println!("  {} ({}) in {}", p.name, p.script, p.cwd);
+    }
+    Ok(())
+}
+
+/// Explain a zero-count resurrect from the state file contents, or `None`
+/// when the file is absent or genuinely empty.
+fn diagnose(path: &Path, saved: &Result<Option<SavedState>, StateFileError>) -> Option<String> {
+    match saved {
+        Ok(None) => None,
+        Ok(Some(s)) if s.processes.is_empty() && s.trailing_bytes == 0 => None,
+        Ok(Some(s)) if s.trailing_bytes > 0 => Some(format!(
+            "{} has {} unexpected byte(s) after {} declared process(es); the file may be corrupt",
+            path.display(),
+            s.trailing_bytes,
+            s.processes.len()
+        )),
+        Ok(Some(s)) => Some(format!(
+            "{} lists {} process(es) but the daemon restored none; they may already be running",
+            path.display(),
+            s.processes.len()
+        )),
+        Err(e) => Some(format!("{} is corrupt: {e}", path.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnose_zero_restore() {
+        let path = Path::new("/home/u/.velos/state.bin");
+        assert!(diagnose(path, &Ok(None)).is_none());
+
+        let empty = SavedState {
+            processes: Vec::new(),
+            trailing_bytes: 0,
+        };
+        assert!(diagnose(path, &Ok(Some(empty))).is_none());
+
+        let truncated = Err(StateFileError::Truncated {
+            declared: 3,
+            parsed: 1,
+        });
+        let msg = diagnose(path, &truncated).unwrap();
+        assert!(msg.contains("corrupt") && msg.contains("declares 3"));
+
+        let trailing = SavedState {
+            processes: Vec::new(),
+            trailing_bytes: 12,
+        };
+        assert!(diagnose(path, &Ok(Some(trailing)))
+            .unwrap()
+            .contains("12 unexpected"));
+    }
+}
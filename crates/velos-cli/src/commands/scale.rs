@@ -1,23 +1,64 @@
+use std::time::{Duration, Instant};
+
+use velos_core::protocol::{ProcessInfo, ScaleResult};
 use velos_core::VelosError;
 
-pub async fn run(name: String, count_str: String, json: bool) -> Result<(), VelosError> {
+/// How often `--wait` polls the process list.
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+pub async fn run(
+    name: String,
+    count_str: String,
+    max: Option<u32>,
+    config: Option<String>,
+    wait: Option<u64>,
+    json: bool,
+    dry_run: bool,
+) -> Result<(), VelosError> {
+    // --max wins over the app's max_instances in the config file
+    let max_instances = match (max, config) {
+        (Some(max), _) => Some(max),
+        (None, Some(path)) => config_max_instances(&path, &name)?,
+        (None, None) => None,
+    };
+
     let mut client = super::connect().await?;
 
     // Resolve target count: absolute (4), relative (+2, -1), or "max"
     let target_count = resolve_target_count(&mut client, &name, &count_str).await?;
+    let is_max = count_str.trim().eq_ignore_ascii_case("max");
+    let target_count = apply_max_instances(target_count, is_max, max_instances)?;
 
+    let before = ScaleProgress::from_list(&client.list().await?, &name).total;
+    if dry_run {
+        let preview = preview(before, target_count);
+        if json {
+            let mut value = scale_json(&name, target_count, before, &preview, None);
+            value["dry_run"] = true.into();
+            println!("{value}");
+        } else {
+            println!(
+                "[velos] Dry run: would start {} / stop {} instances of '{}' ({} -> {})",
+                preview.started, preview.stopped, name, before, target_count
+            );
+        }
+        return Ok(());
+    }
     let result = client.scale(&name, target_count).await?;
 
+    let waited = match wait {
+        Some(secs) => Some(
+            wait_for_instances(&mut client, &name, target_count, Duration::from_secs(secs)).await?,
+        ),
+        None => None,
+    };
+
     if json {
         println!(
             "{}",
-            serde_json::json!({
-                "name": name,
-                "target": target_count,
-                "started": result.started,
-                "stopped": result.stopped,
-            })
+            scale_json(&name, target_count, before, &result, waited)
         );
+        return Ok(());
     } else if result.started > 0 {
         println!(
             "[velos] Scaled '{}' up: +{} instances (target={})",
@@ -31,10 +72,112 @@ pub async fn run(name: String, count_str: String, json: bool) -> Result<(), Velo
     } else {
         println!("[velos] '{name}' already at {target_count} instances");
     }
+    if let Some(elapsed) = waited {
+        println!(
+            "[velos] All {} instances of '{}' running ({:.1}s)",
+            target_count,
+            name,
+            elapsed.as_secs_f64()
+        );
+    }
 
     Ok(())
 }
 
+/// `--json` output. `noop` is true when the app was already at the target,
+/// so automation can tell "nothing to do" apart from a failed scale.
+fn scale_json(
+    name: &str,
+    target: u32,
+    before: u32,
+    result: &ScaleResult,
+    waited: Option<Duration>,
+) -> serde_json::Value {
+    let changed = result.started > 0 || result.stopped > 0;
+    serde_json::json!({
+        "name": name,
+        "target": target,
+        "started": result.started,
+        "stopped": result.stopped,
+        "before": before,
+        "after": (before + result.started).saturating_sub(result.stopped),
+        "changed": changed,
+        "noop": !changed,
+        "waited_ms": waited.map(|d| d.as_millis() as u64),
+    })
+}
+
+/// What scaling from `current` to `target` instances would do, for
+/// `--dry-run`.
+fn preview(current: u32, target: u32) -> ScaleResult {
+    ScaleResult {
+        started: target.saturating_sub(current),
+        stopped: current.saturating_sub(target),
+    }
+}
+
+/// Instances of one app in a single `list` poll.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ScaleProgress {
+    total: u32,
+    running: u32,
+}
+
+impl ScaleProgress {
+    fn from_list(procs: &[ProcessInfo], name: &str) -> Self {
+        let instances: Vec<_> = procs
+            .iter()
+            .filter(|p| {
+                p.name == name
+                    || velos_core::process::INSTANCE_NAME_TEMPLATES
+                        .iter()
+                        .any(|t| velos_core::process::match_instance(t, name, &p.name).is_some())
+            })
+            .collect();
+        Self {
+            total: instances.len() as u32,
+            running: instances
+                .iter()
+                .filter(|p| p.status_str() == "running")
+                .count() as u32,
+        }
+    }
+
+    /// Ready once exactly `target` instances exist and all of them run, so a
+    /// scale-down also waits for the removed instances to be gone.
+    fn ready(self, target: u32) -> bool {
+        self.total == target && self.running == target
+    }
+}
+
+/// Poll until `target` instances of `name` are running, returning how long
+/// it took, or fail once `timeout` has passed.
+async fn wait_for_instances(
+    client: &mut velos_client::VelosClient,
+    name: &str,
+    target: u32,
+    timeout: Duration,
+) -> Result<Duration, VelosError> {
+    let started = Instant::now();
+    loop {
+        let progress = ScaleProgress::from_list(&client.list().await?, name);
+        if progress.ready(target) {
+            return Ok(started.elapsed());
+        }
+        if started.elapsed() >= timeout {
+            return Err(VelosError::ProtocolError(format!(
+                "timed out after {}s waiting for '{}': {}/{} instances running ({} present)",
+                timeout.as_secs(),
+                name,
+                progress.running,
+                target,
+                progress.total
+            )));
+        }
+        tokio::time::sleep(WAIT_POLL_INTERVAL).await;
+    }
+}
+
 async fn resolve_target_count(
     client: &mut velos_client::VelosClient,
     name: &str,
@@ -56,24 +199,199 @@ async fn resolve_target_count(
             .parse()
             .map_err(|_| VelosError::ProtocolError(format!("invalid count: '{s}'")))?;
 
-        // Get current instance count
         let procs = client.list().await?;
-        let current = procs
-            .iter()
-            .filter(|p| {
-                p.name == name
-                    || (p.name.len() > name.len()
-                        && p.name.starts_with(name)
-                        && p.name.as_bytes().get(name.len()) == Some(&b':')
-                        && p.name[name.len() + 1..].parse::<u32>().is_ok())
-            })
-            .count() as i32;
-
-        let target = (current + delta).max(0) as u32;
-        return Ok(target);
+        return Ok(relative_target(&procs, name, delta));
     }
 
     // Absolute number
     s.parse::<u32>()
         .map_err(|_| VelosError::ProtocolError(format!("invalid count: '{s}'")))
 }
+
+/// Apply a +N/-N `delta` to the current instance count of `name`, matching
+/// instances under every name template as absolute scaling does.
+fn relative_target(procs: &[ProcessInfo], name: &str, delta: i32) -> u32 {
+    let current = ScaleProgress::from_list(procs, name).total as i32;
+    (current + delta).max(0) as u32
+}
+
+/// Look up `max_instances` for the app named `name` (TOML key or `name` field).
+fn config_max_instances(path: &str, name: &str) -> Result<Option<u32>, VelosError> {
+    let config = velos_config::load(std::path::Path::new(path))
+        .map_err(|e| VelosError::ProtocolError(format!("config error: {e}")))?;
+    let app = config
+        .apps
+        .iter()
+        .find(|(key, app)| app.name.as_deref().unwrap_or(key) == name)
+        .map(|(_, app)| app)
+        .ok_or_else(|| VelosError::ProtocolError(format!("app '{name}' not found in {path}")))?;
+    Ok(app.max_instances)
+}
+
+/// Enforce the instance ceiling: "max" is clamped to it, explicit targets
+/// above it are rejected before anything is sent to the daemon.
+fn apply_max_instances(
+    target: u32,
+    is_max: bool,
+    max_instances: Option<u32>,
+) -> Result<u32, VelosError> {
+    match max_instances {
+        Some(limit) if is_max => Ok(target.min(limit)),
+        Some(limit) if target > limit => Err(VelosError::ProtocolError(format!(
+            "target {target} exceeds max_instances ({limit})"
+        ))),
+        _ => Ok(target),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proc(name: &str, status: u8) -> ProcessInfo {
+        ProcessInfo {
+            id: 0,
+            name: name.into(),
+            pid: 0,
+            status,
+            memory_bytes: 0,
+            uptime_ms: 0,
+            restart_count: 0,
+            cpu_percent: 0.0,
+            group: None,
+        }
+    }
+
+    #[test]
+    fn test_scale_readiness_across_polls() {
+        // Scaling api from 2 to 4: new instances start, one errors, then recovers
+        let polls = [
+            vec![proc("api:0", 1), proc("api:1", 1)],
+            vec![proc("api:0", 1), proc("api:1", 1), proc("api:2", 3)],
+            vec![
+                proc("api:0", 1),
+                proc("api:1", 1),
+                proc("api:2", 1),
+                proc("api:3", 2),
+                proc("web", 1),
+            ],
+            vec![
+                proc("api:0", 1),
+                proc("api:1", 1),
+                proc("api:2", 1),
+                proc("api:3", 1),
+                proc("web", 1),
+            ],
+        ];
+        let ready: Vec<bool> = polls
+            .iter()
+            .map(|p| ScaleProgress::from_list(p, "api").ready(4))
+            .collect();
+        assert_eq!(ready, [false, false, false, true]);
+        assert_eq!(
+            ScaleProgress::from_list(&polls[2], "api"),
+            ScaleProgress {
+                total: 4,
+                running: 3
+            }
+        );
+
+        // Scaling down to 1 waits until the extra instance is gone
+        let down = [
+            vec![proc("api:0", 1), proc("api:1", 1)],
+            vec![proc("api:0", 1), proc("api:1", 0)],
+            vec![proc("api:0", 1)],
+        ];
+        let ready: Vec<bool> = down
+            .iter()
+            .map(|p| ScaleProgress::from_list(p, "api").ready(1))
+            .collect();
+        assert_eq!(ready, [false, false, true]);
+    }
+
+    #[test]
+    fn test_relative_target_under_each_template() {
+        for template in velos_core::process::INSTANCE_NAME_TEMPLATES {
+            let name_of = |i| velos_core::process::instance_name(template, "api", i);
+            let procs = vec![
+                proc(&name_of(0), 1),
+                proc(&name_of(1), 1),
+                proc("api-worker", 1),
+                proc("other:0", 1),
+            ];
+            assert_eq!(relative_target(&procs, "api", 2), 4, "{template}");
+            assert_eq!(relative_target(&procs, "api", -1), 1, "{template}");
+            assert_eq!(relative_target(&procs, "api", -5), 0, "{template}");
+        }
+        // A single, unclustered process counts as one instance
+        assert_eq!(relative_target(&[proc("api", 1)], "api", 1), 2);
+    }
+
+    #[test]
+    fn test_scale_json_noop_and_change() {
+        let noop = ScaleResult {
+            started: 0,
+            stopped: 0,
+        };
+        let value = scale_json("api", 4, 4, &noop, None);
+        assert_eq!(value["changed"], false);
+        assert_eq!(value["noop"], true);
+        assert_eq!(
+            (value["before"].as_u64(), value["after"].as_u64()),
+            (Some(4), Some(4))
+        );
+
+        let up = ScaleResult {
+            started: 2,
+            stopped: 0,
+        };
+        let value = scale_json("api", 6, 4, &up, None);
+        assert_eq!(value["changed"], true);
+        assert_eq!(value["noop"], false);
+        assert_eq!(
+            (value["before"].as_u64(), value["after"].as_u64()),
+            (Some(4), Some(6))
+        );
+    }
+
+    #[test]
+    fn test_dry_run_preview() {
+        let delta = |current, target| {
+            let p = preview(current, target);
+            (p.started, p.stopped)
+        };
+        assert_eq!(delta(2, 4), (2, 0));
+        assert_eq!(delta(4, 1), (0, 3));
+        assert_eq!(delta(3, 3), (0, 0));
+        assert_eq!(delta(0, 8), (8, 0));
+        assert_eq!(delta(5, 0), (0, 5));
+
+        // The JSON preview has the same shape as a real scale
+        let value = scale_json("api", 6, 4, &preview(4, 6), None);
+        assert_eq!(
+            (value["started"].as_u64(), value["after"].as_u64()),
+            (Some(2), Some(6))
+        );
+        assert_eq!(value["noop"], false);
+    }
+
+    #[test]
+    fn test_apply_max_instances() {
+        // No ceiling: anything goes
+        assert_eq!(apply_max_instances(1000, false, None).unwrap(), 1000);
+        assert_eq!(apply_max_instances(64, true, None).unwrap(), 64);
+
+        // Within the ceiling
+        assert_eq!(apply_max_instances(4, false, Some(8)).unwrap(), 4);
+        assert_eq!(apply_max_instances(8, false, Some(8)).unwrap(), 8);
+        assert_eq!(apply_max_instances(0, false, Some(8)).unwrap(), 0);
+
+        // Above the ceiling is rejected
+        let err = apply_max_instances(9, false, Some(8)).unwrap_err();
+        assert!(err.to_string().contains("exceeds max_instances (8)"));
+
+        // "max" is clamped instead of rejected
+        assert_eq!(apply_max_instances(64, true, Some(8)).unwrap(), 8);
+        assert_eq!(apply_max_instances(4, true, Some(8)).unwrap(), 4);
+    }
+}
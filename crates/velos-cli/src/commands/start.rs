@@ -1,3 +1,8 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, SystemTime};
+
+use velos_client::VelosClient;
+use velos_config::ReconcileAction;
 use velos_core::protocol::StartPayload;
 use velos_core::VelosError;
 
@@ -8,14 +13,25 @@ pub struct StartArgs {
     pub interpreter: Option<String>,
     pub json: bool,
     pub config: Option<String>,
+    pub watch_config: bool,
     pub watch: bool,
     pub max_restarts: Option<i32>,
     pub no_autorestart: bool,
     pub max_memory: Option<String>,
+    pub max_cpu: Option<f32>,
     pub cron_restart: Option<String>,
     pub wait_ready: bool,
     pub shutdown_with_message: bool,
     pub instances: Option<String>,
+    pub name_template: Option<String>,
+    pub out_log: Option<String>,
+    pub err_log: Option<String>,
+    pub merge_logs: bool,
+    pub group: Option<String>,
+    /// `--env KEY=VALUE` pairs, applied on top of the config's env
+    pub env: Vec<String>,
+    /// Arguments after `--`, passed through to the script
+    pub args: Vec<String>,
 }
 
 pub async fn run(args: StartArgs) -> Result<(), VelosError> {
@@ -24,13 +40,104 @@ pub async fn run(args: StartArgs) -> Result<(), VelosError> {
         return run_from_config(config_path, &args).await;
     }
 
-    let script = args.script.ok_or_else(|| {
-        VelosError::ProtocolError("script argument is required when not using --config".into())
-    })?;
+    let payload = cli_payload(&args)?;
+    let process_name = payload.name.clone();
+    let instances = payload.instances;
+
+    let mut client = super::connect().await?;
+    let result = client.start(payload).await?;
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "id": result.id,
+                "name": process_name,
+                "instances": instances,
+            })
+        );
+    } else if instances > 1 {
+        println!(
+            "[velos] Started '{}' in cluster mode ({} instances, first id={})",
+            process_name, instances, result.id
+        );
+    } else {
+        println!("[velos] Started '{}' (id={})", process_name, result.id);
+    }
+
+    // Check if process survives initial startup (poll for up to 1.5s)
+    for _ in 0..6 {
+        tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+        if let Ok(procs) = client.list().await {
+            if let Some(proc) = procs.iter().find(|p| p.id == result.id) {
+                if proc.status_str() == "errored" {
+                    eprintln!(
+                        "[velos] Warning: '{}' has crashed (restarts: {}). Check logs: velos logs {}",
+                        process_name, proc.restart_count, process_name
+                    );
+                    break;
+                }
+                // If still running with 0 restarts after first check, it's healthy
+                if proc.restart_count == 0 {
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_from_config(config_path: &str, args: &StartArgs) -> Result<(), VelosError> {
+    let path = std::path::Path::new(config_path);
+    let config = velos_config::load(path)
+        .map_err(|e| VelosError::ProtocolError(format!("config error: {e}")))?;
+
+    // Resolve config file's parent directory as base for relative paths
+    let config_dir = std::fs::canonicalize(path)
+        .map_err(|e| VelosError::ProtocolError(format!("cannot resolve config path: {e}")))?;
+    let config_dir = config_dir
+        .parent()
+        .ok_or_else(|| VelosError::ProtocolError("config path has no parent".into()))?
+        .to_path_buf();
 
     let mut client = super::connect().await?;
 
-    let process_name = args.name.unwrap_or_else(|| {
+    for (key, app) in &config.apps {
+        // Re-running `start --config` should bring up whatever is missing
+        // rather than stop at the first app that is already up.
+        match start_app(&mut client, key, app, &config_dir, args).await {
+            Err(VelosError::NameInUse(name)) => {
+                if args.json {
+                    println!(
+                        "{}",
+                        serde_json::json!({
+                            "name": name,
+                            "already_running": true,
+                        })
+                    );
+                } else {
+                    println!("[velos] '{name}' is already running, skipped");
+                }
+            }
+            result => result?,
+        }
+    }
+
+    if args.watch_config {
+        watch_config(path, &config_dir, config, args).await?;
+    }
+
+    Ok(())
+}
+
+/// Build the start payload for `velos start <script>` from command-line flags.
+fn cli_payload(args: &StartArgs) -> Result<StartPayload, VelosError> {
+    let script = args.script.clone().ok_or_else(|| {
+        VelosError::ProtocolError("script argument is required when not using --config".into())
+    })?;
+
+    let process_name = args.name.clone().unwrap_or_else(|| {
         std::path::Path::new(&script)
             .file_stem()
             .and_then(|s| s.to_str())
@@ -38,11 +145,11 @@ pub async fn run(args: StartArgs) -> Result<(), VelosError> {
             .to_string()
     });
 
-    let cwd = args.cwd.unwrap_or_else(|| {
-        std::env::current_dir()
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_else(|_| ".".to_string())
-    });
+    let current_dir = std::env::current_dir().unwrap_or_else(|_| ".".into());
+    let cwd = args
+        .cwd
+        .clone()
+        .unwrap_or_else(|| current_dir.to_string_lossy().to_string());
 
     let autorestart = !args.no_autorestart;
     let max_restarts = args.max_restarts.unwrap_or(15);
@@ -54,15 +161,37 @@ pub async fn run(args: StartArgs) -> Result<(), VelosError> {
         0
     };
 
+    let max_cpu_restart = args.max_cpu.map(check_max_cpu).transpose()?;
+    if let Some(ref cron) = args.cron_restart {
+        velos_config::validate_cron(cron)
+            .map_err(|e| VelosError::ProtocolError(format!("invalid --cron-restart: {e}")))?;
+    }
     let instances = parse_instances(&args.instances)?;
 
-    let env_vars: String = std::env::vars()
-        .map(|(k, v)| format!("{k}={v}"))
-        .collect::<Vec<_>>()
-        .join("\n");
+    let instance_name_template = args.name_template.clone().unwrap_or_default();
+    if !instance_name_template.is_empty()
+        && !velos_core::process::INSTANCE_NAME_TEMPLATES.contains(&instance_name_template.as_str())
+    {
+        return Err(VelosError::ProtocolError(format!(
+            "invalid --name-template '{}': use one of {}",
+            instance_name_template,
+            velos_core::process::INSTANCE_NAME_TEMPLATES.join(", ")
+        )));
+    }
 
-    let payload = StartPayload {
-        name: process_name.clone(),
+    let out_log = match args.out_log {
+        Some(ref p) => resolve_log_path(p, &current_dir)?,
+        None => String::new(),
+    };
+    let err_log = match args.err_log {
+        Some(ref p) => resolve_log_path(p, &current_dir)?,
+        None => String::new(),
+    };
+
+    let env_vars = env_block(&HashMap::new(), &parse_env_pairs(&args.env)?);
+
+    Ok(StartPayload {
+        name: process_name,
         script,
         cwd,
         interpreter: args.interpreter.clone(),
@@ -83,143 +212,356 @@ pub async fn run(args: StartArgs) -> Result<(), VelosError> {
         shutdown_with_message: args.shutdown_with_message,
         instances,
         env_vars,
+        instance_name_template,
+        out_log,
+        err_log,
+        merge_logs: args.merge_logs,
+        args: args.args.clone(),
+        group: args.group.clone().unwrap_or_default(),
+        log_rate_limit: 0,
+        success_exit_codes: vec![0],
+        stop_on_success: false,
+        max_cpu_restart: max_cpu_restart.unwrap_or(0.0),
+        cpu_restart_window_secs: 60,
+    })
+}
+
+/// `--max-cpu` is a percentage of one core, like `max_cpu_restart`.
+fn check_max_cpu(pct: f32) -> Result<f32, VelosError> {
+    if pct > 0.0 && pct <= 100.0 {
+        Ok(pct)
+    } else {
+        Err(VelosError::ProtocolError(format!(
+            "invalid --max-cpu {pct}: must be a percentage in (0, 100]"
+        )))
+    }
+}
+
+/// Parse repeated `--env KEY=VALUE` flags. The value may itself contain
+/// `=`; a later flag for the same key wins.
+fn parse_env_pairs(pairs: &[String]) -> Result<BTreeMap<String, String>, VelosError> {
+    let mut env = BTreeMap::new();
+    for pair in pairs {
+        let (key, value) = pair
+            .split_once('=')
+            .filter(|(key, _)| !key.is_empty() && !key.contains(char::is_whitespace))
+            .ok_or_else(|| {
+                VelosError::ProtocolError(format!("invalid --env '{pair}': use KEY=VALUE"))
+            })?;
+        // The daemon receives env as newline-separated KEY=VALUE lines
+        if value.contains('\n') {
+            return Err(VelosError::ProtocolError(format!(
+                "invalid --env '{key}': values cannot contain newlines"
+            )));
+        }
+        env.insert(key.to_string(), value.to_string());
+    }
+    Ok(env)
+}
+
+/// The env block sent to the daemon: velos's own environment, then the
+/// app's config env, then `--env` overrides, one `KEY=VALUE` per line.
+fn env_block(config_env: &HashMap<String, String>, overrides: &BTreeMap<String, String>) -> String {
+    let mut env: BTreeMap<String, String> = std::env::vars().collect();
+    env.extend(config_env.iter().map(|(k, v)| (k.clone(), v.clone())));
+    env.extend(overrides.iter().map(|(k, v)| (k.clone(), v.clone())));
+    env.iter()
+        .map(|(k, v)| format!("{k}={v}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Make a log path absolute (relative to `base`) and check that its
+/// directory exists and is writable, so a typo fails here rather than
+/// silently inside the daemon.
+fn resolve_log_path(path: &str, base: &std::path::Path) -> Result<String, VelosError> {
+    let p = std::path::Path::new(path);
+    let full = if p.is_relative() {
+        base.join(p)
+    } else {
+        p.to_path_buf()
     };
+    let dir = full
+        .parent()
+        .filter(|d| !d.as_os_str().is_empty())
+        .ok_or_else(|| VelosError::ProtocolError(format!("invalid log path '{path}'")))?;
+    if !dir.is_dir() {
+        return Err(VelosError::ProtocolError(format!(
+            "log directory '{}' does not exist",
+            dir.display()
+        )));
+    }
+    let probe = dir.join(format!(".velos-write-test-{}", std::process::id()));
+    std::fs::write(&probe, b"").map_err(|e| {
+        VelosError::ProtocolError(format!(
+            "log directory '{}' is not writable: {e}",
+            dir.display()
+        ))
+    })?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(full.to_string_lossy().to_string())
+}
 
-    let result = client.start(payload).await?;
+/// Build the start payload for a single app from the config file.
+fn app_payload(
+    key: &str,
+    app: &velos_config::AppConfig,
+    config_dir: &std::path::Path,
+    args: &StartArgs,
+) -> Result<StartPayload, VelosError> {
+    let app_name = app.name.clone().unwrap_or_else(|| key.to_string());
+    let autorestart = if args.no_autorestart {
+        false
+    } else {
+        app.autorestart
+    };
+    let max_restarts = args.max_restarts.unwrap_or(app.max_restarts);
+
+    let max_memory_restart = if let Some(ref mem) = app.max_memory_restart {
+        velos_config::parse_memory_string(mem).unwrap_or(0)
+    } else {
+        0
+    };
+    let max_cpu_restart = match args.max_cpu {
+        Some(pct) => check_max_cpu(pct)?,
+        None => app.max_cpu_restart.unwrap_or(0.0),
+    };
+    let watch_paths = app.watch_paths.join(";");
+    let watch_ignore = app.watch_ignore.join(";");
+
+    let cwd = app_cwd(app, config_dir);
+
+    let env = velos_config::merged_env(app, None)
+        .map_err(|e| VelosError::ProtocolError(format!("config error: {e}")))?;
+    let env_vars = env_block(&env, &parse_env_pairs(&args.env)?);
+
+    let out_log = match app.log_file {
+        Some(ref p) => resolve_log_path(p, config_dir)?,
+        None => String::new(),
+    };
+
+    Ok(StartPayload {
+        name: app_name,
+        script: app.script.clone(),
+        cwd,
+        interpreter: app.interpreter.clone(),
+        kill_timeout_ms: app.kill_timeout as u32,
+        autorestart,
+        max_restarts,
+        min_uptime_ms: app.min_uptime,
+        restart_delay_ms: app.restart_delay as u32,
+        exp_backoff: app.exp_backoff_restart_delay,
+        max_memory_restart,
+        watch: app.watch,
+        watch_delay_ms: app.watch_delay as u32,
+        watch_paths,
+        watch_ignore,
+        cron_restart: app.cron_restart.clone().unwrap_or_default(),
+        wait_ready: false,
+        listen_timeout_ms: 8000,
+        shutdown_with_message: false,
+        instances: app.instances.resolve(app.max_instances),
+        env_vars,
+        instance_name_template: args
+            .name_template
+            .clone()
+            .or_else(|| app.instance_name_template.clone())
+            .unwrap_or_default(),
+        out_log,
+        err_log: String::new(),
+        merge_logs: args.merge_logs || app.merge_logs,
+        args: app.args.clone(),
+        group: args
+            .group
+            .clone()
+            .or_else(|| app.group.clone())
+            .unwrap_or_default(),
+        log_rate_limit: app.log_rate_limit.unwrap_or(0),
+        success_exit_codes: app.success_exit_codes.clone(),
+        stop_on_success: app.stop_on_success,
+        max_cpu_restart,
+        cpu_restart_window_secs: app.cpu_restart_window_secs,
+    })
+}
+
+/// An app's working directory; a relative `cwd` is taken from the config
+/// file's directory.
+pub fn app_cwd(app: &velos_config::AppConfig, config_dir: &std::path::Path) -> String {
+    match &app.cwd {
+        Some(c) => {
+            let p = std::path::Path::new(c);
+            if p.is_relative() {
+                config_dir.join(p).to_string_lossy().to_string()
+            } else {
+                c.clone()
+            }
+        }
+        None => config_dir.to_string_lossy().to_string(),
+    }
+}
+
+async fn start_app(
+    client: &mut VelosClient,
+    key: &str,
+    app: &velos_config::AppConfig,
+    config_dir: &std::path::Path,
+    args: &StartArgs,
+) -> Result<(), VelosError> {
+    let payload = app_payload(key, app, config_dir, args)?;
+    let app_name = payload.name.clone();
+    let hooks = super::hooks::AppHooks::from_app(app, config_dir)?;
+    let result =
+        super::hooks::around(hooks.pre_start.as_ref(), client.start(payload), None).await?;
 
     if args.json {
         println!(
             "{}",
             serde_json::json!({
                 "id": result.id,
-                "name": process_name,
-                "instances": instances,
+                "name": app_name,
             })
         );
-    } else if instances > 1 {
-        println!(
-            "[velos] Started '{}' in cluster mode ({} instances, first id={})",
-            process_name, instances, result.id
-        );
     } else {
-        println!("[velos] Started '{}' (id={})", process_name, result.id);
+        println!("[velos] Started '{}' (id={})", app_name, result.id);
     }
+    Ok(())
+}
 
-    // Check if process survives initial startup (poll for up to 1.5s)
-    for _ in 0..6 {
-        tokio::time::sleep(std::time::Duration::from_millis(250)).await;
-        if let Ok(procs) = client.list().await {
-            if let Some(proc) = procs.iter().find(|p| p.id == result.id) {
-                if proc.status_str() == "errored" {
-                    eprintln!(
-                        "[velos] Warning: '{}' has crashed (restarts: {}). Check logs: velos logs {}",
-                        process_name, proc.restart_count, process_name
-                    );
-                    break;
-                }
-                // If still running with 0 restarts after first check, it's healthy
-                if proc.restart_count == 0 {
-                    break;
-                }
-            }
-        }
+/// Stop and delete every instance of an app. Missing processes are ignored.
+async fn remove_app(client: &mut VelosClient, name: &str) -> Result<(), VelosError> {
+    let ids = match super::resolve_ids(client, name).await {
+        Ok(ids) => ids,
+        Err(VelosError::ProcessNotFound(_)) => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    for id in ids {
+        client.stop(id).await?;
+        client.delete(id).await?;
     }
-
     Ok(())
 }
 
-async fn run_from_config(config_path: &str, args: &StartArgs) -> Result<(), VelosError> {
-    let path = std::path::Path::new(config_path);
-    let config = velos_config::load(path)
-        .map_err(|e| VelosError::ProtocolError(format!("config error: {e}")))?;
+/// How often the config file's mtime is checked. `--watch-config` polls
+/// instead of subscribing to inotify/kqueue: the daemon's watcher lives in
+/// Zig and isn't reachable from the CLI, and one stat of one file every
+/// 500ms isn't worth a native-watcher dependency. Polling also keeps
+/// working when an editor replaces the file rather than writing to it.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// The file must stay unchanged for this long before it is reloaded,
+/// so editors that write in several steps trigger a single reconcile.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
 
-    // Resolve config file's parent directory as base for relative paths
-    let config_dir = std::fs::canonicalize(path)
-        .map_err(|e| VelosError::ProtocolError(format!("cannot resolve config path: {e}")))?;
-    let config_dir = config_dir
-        .parent()
-        .ok_or_else(|| VelosError::ProtocolError("config path has no parent".into()))?
-        .to_path_buf();
+fn config_mtime(path: &std::path::Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
 
-    let mut client = super::connect().await?;
+/// Watch the config file and reconcile the running apps on every change
+/// until interrupted with Ctrl+C.
+async fn watch_config(
+    path: &std::path::Path,
+    config_dir: &std::path::Path,
+    mut current: velos_config::VelosConfig,
+    args: &StartArgs,
+) -> Result<(), VelosError> {
+    if !args.json {
+        println!(
+            "[velos] Watching {} for changes (Ctrl+C to stop)",
+            path.display()
+        );
+    }
 
-    let env_vars: String = std::env::vars()
-        .map(|(k, v)| format!("{k}={v}"))
-        .collect::<Vec<_>>()
-        .join("\n");
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    let mut last_mtime = config_mtime(path);
 
-    for (key, app) in &config.apps {
-        let app_name = app.name.clone().unwrap_or_else(|| key.clone());
-        let autorestart = if args.no_autorestart {
-            false
-        } else {
-            app.autorestart
-        };
-        let max_restarts = args.max_restarts.unwrap_or(app.max_restarts);
+    loop {
+        tokio::select! {
+            _ = &mut ctrl_c => break,
+            _ = tokio::time::sleep(WATCH_POLL_INTERVAL) => {}
+        }
 
-        let max_memory_restart = if let Some(ref mem) = app.max_memory_restart {
-            velos_config::parse_memory_string(mem).unwrap_or(0)
-        } else {
-            0
-        };
-        let watch_paths = app.watch_paths.join(";");
-        let watch_ignore = app.watch_ignore.join(";");
-
-        // Resolve cwd: use config file directory as base for relative paths
-        let cwd = match &app.cwd {
-            Some(c) => {
-                let p = std::path::Path::new(c);
-                if p.is_relative() {
-                    config_dir.join(p).to_string_lossy().to_string()
-                } else {
-                    c.clone()
-                }
+        let mut mtime = config_mtime(path);
+        if mtime == last_mtime {
+            continue;
+        }
+        // Debounce: wait for the file to settle before reloading.
+        loop {
+            tokio::time::sleep(WATCH_DEBOUNCE).await;
+            let settled = config_mtime(path);
+            if settled == mtime {
+                break;
             }
-            None => config_dir.to_string_lossy().to_string(),
-        };
+            mtime = settled;
+        }
+        last_mtime = mtime;
 
-        let payload = StartPayload {
-            name: app_name.clone(),
-            script: app.script.clone(),
-            cwd,
-            interpreter: app.interpreter.clone(),
-            kill_timeout_ms: app.kill_timeout as u32,
-            autorestart,
-            max_restarts,
-            min_uptime_ms: app.min_uptime,
-            restart_delay_ms: app.restart_delay as u32,
-            exp_backoff: app.exp_backoff_restart_delay,
-            max_memory_restart,
-            watch: app.watch,
-            watch_delay_ms: app.watch_delay as u32,
-            watch_paths,
-            watch_ignore,
-            cron_restart: app.cron_restart.clone().unwrap_or_default(),
-            wait_ready: false,
-            listen_timeout_ms: 8000,
-            shutdown_with_message: false,
-            instances: app.instances,
-            env_vars: env_vars.clone(),
+        let new = match velos_config::load(path) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("[velos] Ignoring config change: {e}");
+                continue;
+            }
         };
 
-        let result = client.start(payload).await?;
+        let plan = velos_config::diff(&current, &new).reconcile_plan();
+        if plan.is_empty() {
+            current = new;
+            continue;
+        }
 
-        if args.json {
-            println!(
-                "{}",
-                serde_json::json!({
-                    "id": result.id,
-                    "name": app_name,
-                })
-            );
-        } else {
-            println!("[velos] Started '{}' (id={})", app_name, result.id);
+        let mut client = super::connect().await?;
+        for action in &plan {
+            if let Err(e) =
+                apply_action(&mut client, action, &current, &new, config_dir, args).await
+            {
+                eprintln!("[velos] Failed to apply {action:?}: {e}");
+            }
         }
+        current = new;
     }
 
     Ok(())
 }
 
+async fn apply_action(
+    client: &mut VelosClient,
+    action: &ReconcileAction,
+    old: &velos_config::VelosConfig,
+    new: &velos_config::VelosConfig,
+    config_dir: &std::path::Path,
+    args: &StartArgs,
+) -> Result<(), VelosError> {
+    let old_name = |key: &str| {
+        old.get_app(key)
+            .and_then(|a| a.name.clone())
+            .unwrap_or_else(|| key.to_string())
+    };
+    match action {
+        ReconcileAction::Stop(key) => {
+            let name = old_name(key);
+            remove_app(client, &name).await?;
+            if !args.json {
+                println!("[velos] Stopped '{name}' (removed from config)");
+            }
+        }
+        ReconcileAction::Restart(key) => {
+            let Some(app) = new.get_app(key) else {
+                return Ok(());
+            };
+            // Settings such as script or env can only be applied by
+            // re-creating the process, so restart means delete + start.
+            remove_app(client, &old_name(key)).await?;
+            start_app(client, key, app, config_dir, args).await?;
+        }
+        ReconcileAction::Start(key) => {
+            if let Some(app) = new.get_app(key) {
+                start_app(client, key, app, config_dir, args).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
 fn parse_instances(instances_arg: &Option<String>) -> Result<u32, VelosError> {
     match instances_arg {
         None => Ok(1),
@@ -240,3 +582,127 @@ fn parse_instances(instances_arg: &Option<String>) -> Result<u32, VelosError> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(script: &str) -> StartArgs {
+        StartArgs {
+            script: Some(script.into()),
+            name: None,
+            cwd: None,
+            interpreter: None,
+            json: false,
+            config: None,
+            watch_config: false,
+            watch: false,
+            max_restarts: None,
+            no_autorestart: false,
+            max_memory: None,
+            max_cpu: None,
+            cron_restart: None,
+            wait_ready: false,
+            shutdown_with_message: false,
+            instances: None,
+            name_template: None,
+            out_log: None,
+            err_log: None,
+            merge_logs: false,
+            group: None,
+            env: Vec::new(),
+            args: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_env_pairs() {
+        let flags = [
+            "PORT=3000",
+            "URL=postgres://h/db?a=b",
+            "PORT=4000",
+            "EMPTY=",
+        ]
+        .map(String::from);
+        let env = parse_env_pairs(&flags).unwrap();
+        assert_eq!(env.len(), 3);
+        assert_eq!(env["PORT"], "4000");
+        assert_eq!(env["URL"], "postgres://h/db?a=b");
+        assert_eq!(env["EMPTY"], "");
+
+        for bad in ["PORT", "=3000", "MY VAR=1"] {
+            let err = parse_env_pairs(&[bad.to_string()]).unwrap_err();
+            assert!(err.to_string().contains("KEY=VALUE"), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_env_overrides_config_env() {
+        let config = velos_config::parse(
+            r#"
+[apps.api]
+script = "api.js"
+env = { MODE = "config", ONLY_CONFIG = "yes" }
+"#,
+        )
+        .unwrap();
+        let mut a = args("");
+        a.env = vec!["MODE=cli".into()];
+        let payload = app_payload("api", &config.apps["api"], &std::env::temp_dir(), &a).unwrap();
+        let lines: Vec<&str> = payload.env_vars.lines().collect();
+        assert!(lines.contains(&"MODE=cli"));
+        assert!(lines.contains(&"ONLY_CONFIG=yes"));
+        assert!(!lines.contains(&"MODE=config"));
+    }
+
+    #[test]
+    fn test_log_flags_populate_payload() {
+        let payload = cli_payload(&args("app.js")).unwrap();
+        assert_eq!(payload.name, "app");
+        assert_eq!(payload.out_log, "");
+        assert_eq!(payload.err_log, "");
+        assert!(!payload.merge_logs);
+
+        let dir = std::env::temp_dir();
+        let out = dir.join("velos-start-test-out.log");
+        let err = dir.join("velos-start-test-err.log");
+        let mut a = args("app.js");
+        a.out_log = Some(out.to_string_lossy().to_string());
+        a.err_log = Some(err.to_string_lossy().to_string());
+        a.merge_logs = true;
+        let payload = cli_payload(&a).unwrap();
+        assert_eq!(payload.out_log, out.to_string_lossy());
+        assert_eq!(payload.err_log, err.to_string_lossy());
+        assert!(payload.merge_logs);
+    }
+
+    #[test]
+    fn test_script_args_reach_payload() {
+        let mut a = args("app.js");
+        a.args = vec!["--port".into(), "3000".into()];
+        assert_eq!(cli_payload(&a).unwrap().args, ["--port", "3000"]);
+
+        let config = velos_config::parse(
+            r#"
+[apps.worker]
+script = "worker.py"
+args = ["--queue", "emails"]
+"#,
+        )
+        .unwrap();
+        let app = &config.apps["worker"];
+        let payload = app_payload("worker", app, &std::env::temp_dir(), &args("")).unwrap();
+        assert_eq!(payload.args, ["--queue", "emails"]);
+    }
+
+    #[test]
+    fn test_log_path_validation() {
+        let base = std::env::temp_dir();
+        // Relative paths resolve against the base directory
+        let resolved = resolve_log_path("app.log", &base).unwrap();
+        assert_eq!(resolved, base.join("app.log").to_string_lossy());
+
+        let err = resolve_log_path("/nonexistent-velos-dir/app.log", &base).unwrap_err();
+        assert!(err.to_string().contains("does not exist"));
+    }
+}
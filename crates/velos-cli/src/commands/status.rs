@@ -0,0 +1,179 @@
+use std::path::Path;
+
+use velos_client::VelosClient;
+use velos_core::protocol::DaemonInfo;
+use velos_core::VelosError;
+
+use super::info::format_uptime;
+use super::ping::{daemon_uptime_ms, daemon_version};
+
+/// Everything `velos status` reports. Fields the daemon has to answer are
+/// `None` when it is down or too old to report them.
+#[derive(Debug, PartialEq, Eq, serde::Serialize)]
+struct Status {
+    daemon_running: bool,
+    socket: String,
+    pid: Option<i32>,
+    uptime_ms: Option<u64>,
+    version: Option<String>,
+    process_count: Option<usize>,
+    state_dir: Option<String>,
+    started_ms: Option<u64>,
+    /// 0 = unlimited.
+    max_processes: Option<u32>,
+}
+
+impl Status {
+    /// `reply` is the ping reply and process count, `None` if the daemon
+    /// could not be reached. `info` is its DAEMON_INFO answer, `None` for
+    /// daemons too old to give one.
+    fn assemble(
+        socket: &Path,
+        pid: Option<i32>,
+        reply: Option<(&str, usize)>,
+        info: Option<&DaemonInfo>,
+    ) -> Self {
+        let info = reply.and(info);
+        Self {
+            daemon_running: reply.is_some(),
+            socket: socket.display().to_string(),
+            pid: info.map(|i| i.pid as i32).or(reply.and(pid)),
+            uptime_ms: reply.and_then(|(pong, _)| daemon_uptime_ms(pong)),
+            version: reply.and_then(|(pong, _)| daemon_version(pong).map(String::from)),
+            process_count: reply.map(|(_, count)| count),
+            state_dir: info
+                .map(|i| i.state_dir.clone())
+                .filter(|dir| !dir.is_empty()),
+            started_ms: info.map(|i| i.started_ms),
+            max_processes: info.map(|i| i.max_processes),
+        }
+    }
+
+    fn render(&self) -> String {
+        if !self.daemon_running {
+            return format!("[velos] Daemon not running (socket {})", self.socket);
+        }
+        let mut parts = Vec::new();
+        if let Some(pid) = self.pid {
+            parts.push(format!("pid {pid}"));
+        }
+        if let Some(ref version) = self.version {
+            parts.push(format!("v{version}"));
+        }
+        if let Some(uptime) = self.uptime_ms {
+            parts.push(format!("up {}", format_uptime(uptime)));
+        }
+        parts.push(format!("{} process(es)", self.process_count.unwrap_or(0)));
+        let mut out = format!(
+            "[velos] Daemon running ({}) on {}",
+            parts.join(", "),
+            self.socket
+        );
+        if let Some(ref dir) = self.state_dir {
+            let limit = match self.max_processes {
+                Some(0) | None => "no process limit".to_string(),
+                Some(n) => format!("max {n} processes"),
+            };
+            out.push_str(&format!("\n[velos] State dir {dir} ({limit})"));
+        }
+        out
+    }
+}
+
+/// Never auto-starts the daemon: a monitor asking "is it up?" must not
+/// change the answer.
+pub async fn run(json: bool) -> Result<(), VelosError> {
+    let socket = velos_client::default_socket_path();
+    let (reply, info) = match VelosClient::connect_to(&socket).await {
+        Ok(mut client) => {
+            let pong = client.ping().await?;
+            let count = client.list().await?.len();
+            // Older daemons reject the command; report what ping gave us
+            let info = client.daemon_info().await.ok();
+            (Some((pong, count)), info)
+        }
+        Err(VelosError::DaemonNotRunning(_)) | Err(VelosError::ConnectionFailed(_)) => (None, None),
+        Err(e) => return Err(e),
+    };
+    let status = Status::assemble(
+        &socket,
+        velos_client::daemon_pid(),
+        reply.as_ref().map(|(pong, count)| (pong.as_str(), *count)),
+        info.as_ref(),
+    );
+
+    if json {
+        println!("{}", serde_json::to_string(&status).unwrap_or_default());
+    } else {
+        println!("{}", status.render());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_assembly() {
+        let socket = Path::new("/home/u/.velos/velos.sock");
+
+        let up = Status::assemble(socket, Some(4242), Some(("pong 0.1.14 7200000", 3)), None);
+        assert_eq!(
+            up,
+            Status {
+                daemon_running: true,
+                socket: "/home/u/.velos/velos.sock".into(),
+                pid: Some(4242),
+                uptime_ms: Some(7_200_000),
+                version: Some("0.1.14".into()),
+                process_count: Some(3),
+                state_dir: None,
+                started_ms: None,
+                max_processes: None,
+            }
+        );
+        assert_eq!(
+            up.render(),
+            "[velos] Daemon running (pid 4242, v0.1.14, up 2h 0m, 3 process(es)) on /home/u/.velos/velos.sock"
+        );
+
+        // Down: a stale PID file is not reported
+        let down = Status::assemble(socket, Some(4242), None, None);
+        assert!(!down.daemon_running);
+        assert_eq!(
+            (down.pid, down.uptime_ms, down.process_count),
+            (None, None, None)
+        );
+        let value = serde_json::to_value(&down).unwrap();
+        assert_eq!(value["daemon_running"], false);
+        assert!(value["version"].is_null());
+
+        // Older daemon: bare "pong"
+        let old = Status::assemble(socket, None, Some(("pong", 0)), None);
+        assert_eq!((old.version, old.uptime_ms), (None, None));
+
+        // With DAEMON_INFO: the daemon's own pid and settings
+        let info = DaemonInfo {
+            socket_path: "/home/u/.velos/velos.sock".into(),
+            state_dir: "/home/u/.velos".into(),
+            version: "0.1.14".into(),
+            started_ms: 1_700_000_000_000,
+            pid: 5151,
+            max_processes: 0,
+            max_clients: 64,
+        };
+        let full = Status::assemble(
+            socket,
+            Some(4242),
+            Some(("pong 0.1.14 1000", 1)),
+            Some(&info),
+        );
+        assert_eq!(full.pid, Some(5151));
+        assert_eq!(full.state_dir.as_deref(), Some("/home/u/.velos"));
+        assert_eq!(full.started_ms, Some(1_700_000_000_000));
+        assert!(full
+            .render()
+            .ends_with("\n[velos] State dir /home/u/.velos (no process limit)"));
+    }
+}
@@ -1,12 +1,26 @@
 use velos_core::VelosError;
 
-pub async fn run(name_or_id: String, json: bool) -> Result<(), VelosError> {
+use super::hooks::{self, AppHooks};
+
+/// `kill_tree` also signals the process's descendants, for apps whose
+/// children would otherwise outlive them. An app's `pre_stop` and
+/// `post_stop` hooks from ./velos.toml run around stopping all its instances.
+pub async fn run(name_or_id: String, json: bool, kill_tree: bool) -> Result<(), VelosError> {
     let mut client = super::connect().await?;
     let ids = super::resolve_ids(&mut client, &name_or_id).await?;
 
-    for id in &ids {
-        client.stop(*id).await?;
-    }
+    let hooks = AppHooks::for_name(&name_or_id);
+    let stop_all = async {
+        for id in &ids {
+            if kill_tree {
+                client.stop_tree(*id).await?;
+            } else {
+                client.stop(*id).await?;
+            }
+        }
+        Ok(())
+    };
+    hooks::around(hooks.pre_stop.as_ref(), stop_all, hooks.post_stop.as_ref()).await?;
 
     if json {
         let stopped: Vec<_> = ids
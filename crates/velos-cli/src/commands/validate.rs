@@ -0,0 +1,179 @@
+use std::path::Path;
+
+use velos_config::VelosConfig;
+use velos_core::VelosError;
+
+/// One line of the `velos validate` report. `app` is the table key, or
+/// "[logs]" for the logs section.
+#[derive(Debug, PartialEq, Eq, serde::Serialize)]
+struct Check {
+    app: String,
+    valid: bool,
+    error: Option<String>,
+    warnings: Vec<String>,
+}
+
+/// One row per app (sorted by key) from [`velos_config::validate_all`], so
+/// every bad app is reported, plus each valid app's `env_file` check.
+/// `env_file`s are looked up from `config_dir`.
+fn check_all(config: &VelosConfig, config_dir: &Path) -> Vec<Check> {
+    // validate_all reports failures in the same key order
+    let mut failures = velos_config::validate_all(config).into_iter().peekable();
+    let mut keys: Vec<_> = config.apps.keys().collect();
+    keys.sort();
+    let mut checks: Vec<Check> = keys
+        .into_iter()
+        .map(|key| {
+            let app = &config.apps[key];
+            let name = app.name.as_deref().unwrap_or(key);
+            let checked = match failures.next_if(|(failed, _)| failed == name) {
+                Some((_, e)) => Err(e),
+                None => velos_config::resolve_env_file(key, &mut app.clone(), config_dir),
+            };
+            match checked {
+                Ok(()) => Check {
+                    app: key.clone(),
+                    valid: true,
+                    error: None,
+                    warnings: velos_config::app_warnings(app),
+                },
+                Err(e) => Check {
+                    app: key.clone(),
+                    valid: false,
+                    error: Some(e.to_string()),
+                    warnings: Vec::new(),
+                },
+            }
+        })
+        .collect();
+    if let Some(ref logs) = config.logs {
+        let error = velos_config::validate_logs(logs)
+            .err()
+            .map(|e| e.to_string());
+        checks.push(Check {
+            app: "[logs]".into(),
+            valid: error.is_none(),
+            error,
+            warnings: Vec::new(),
+        });
+    }
+    checks
+}
+
+fn render(check: &Check) -> Vec<String> {
+    let mut lines = vec![match check.error {
+        Some(ref error) => format!("[velos] {:<16} ERROR  {error}", check.app),
+        None => format!("[velos] {:<16} OK", check.app),
+    }];
+    for warning in &check.warnings {
+        lines.push(format!("[velos] {:<16} WARN   {warning}", ""));
+    }
+    lines
+}
+
+/// `velos validate`: check a config file without starting anything. Exits
+/// non-zero if any app is invalid; warnings alone don't fail it.
+pub async fn run(config: String, json: bool) -> Result<(), VelosError> {
+    let loaded = velos_config::load_unchecked(Path::new(&config))
+        .map_err(|e| VelosError::ProtocolError(format!("{config}: {e}")))?;
+    let config_dir = Path::new(&config).parent().unwrap_or(Path::new(""));
+    let checks = check_all(&loaded, config_dir);
+
+    if json {
+        println!("{}", serde_json::to_string(&checks).unwrap_or_default());
+    } else if checks.is_empty() {
+        println!("[velos] {config}: no apps defined");
+    } else {
+        for check in &checks {
+            for line in render(check) {
+                println!("{line}");
+            }
+        }
+    }
+
+    let failed = checks.iter().filter(|c| !c.valid).count();
+    if failed > 0 {
+        if !json {
+            eprintln!("[velos] {failed} of {} failed validation", checks.len());
+        }
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_all_reports_every_app() {
+        let dir = std::env::temp_dir().join(format!("velos_validate_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("velos.toml");
+        std::fs::write(
+            &path,
+            r#"
+[apps.web]
+script = "web.js"
+max_restarts = 0
+
+[apps.api]
+script = ""
+
+[apps.worker]
+script = "worker.py"
+kill_timeout = 10
+
+[apps.db]
+script = "db.sh"
+env_file = "db.env"
+"#,
+        )
+        .unwrap();
+
+        let config = velos_config::load_unchecked(&path).unwrap();
+        let checks = check_all(&config, &dir);
+        let summary: Vec<_> = checks
+            .iter()
+            .map(|c| (c.app.as_str(), c.valid, c.warnings.len()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("api", false, 0),
+                ("db", false, 0),
+                ("web", true, 1),
+                ("worker", false, 0)
+            ]
+        );
+        assert!(checks[0]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("script must not be empty"));
+        assert!(checks[1]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("db.env' not found"));
+        assert!(checks[3].error.as_deref().unwrap().contains("kill_timeout"));
+        assert!(checks[2].warnings[0].contains("max_restarts = 0"));
+        assert_eq!(
+            render(&checks[2]),
+            [
+                "[velos] web              OK",
+                "[velos]                  WARN   max_restarts = 0 with autorestart = true: the app is never restarted",
+            ]
+        );
+
+        let value = serde_json::to_value(&checks).unwrap();
+        assert_eq!(value[2]["app"], "web");
+        assert_eq!(value[2]["valid"], true);
+        assert!(value[2]["error"].is_null());
+
+        std::fs::write(dir.join("db.env"), "PASSWORD=x\n").unwrap();
+        assert!(check_all(&config, &dir)[1].valid);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
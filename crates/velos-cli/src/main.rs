@@ -1,3 +1,4 @@
+mod color;
 mod commands;
 
 use clap::{Parser, Subcommand};
@@ -24,6 +25,7 @@ fn version_string() -> &'static str {
   velos start app.js          Start a process
   velos start app.js -i 4     Start 4 instances (cluster mode)
   velos list                  List all processes
+  velos ps --sort cpu         One line per process, busiest first
   velos info app              Show detailed process info
   velos logs app --level error  Show error logs only
   velos logs app --summary    Show log health summary
@@ -38,6 +40,9 @@ fn version_string() -> &'static str {
 pub struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// When to use colors; auto colors only a terminal and honors NO_COLOR
+    #[arg(long, global = true, value_enum, default_value_t = color::ColorWhen::Auto)]
+    color: color::ColorWhen,
 }
 
 #[derive(Subcommand)]
@@ -64,9 +69,12 @@ enum Commands {
         /// Interpreter to run the script with (e.g. node, python3, bun)
         #[arg(long)]
         interpreter: Option<String>,
-        /// Load processes from TOML config file
+        /// Load processes from a config file (.toml, .yaml/.yml or .json)
         #[arg(long)]
         config: Option<String>,
+        /// Keep running and reconcile processes when the config file changes
+        #[arg(long, requires = "config")]
+        watch_config: bool,
         /// Enable watch mode (restart on file changes)
         #[arg(long)]
         watch: bool,
@@ -76,9 +84,12 @@ enum Commands {
         /// Disable autorestart
         #[arg(long)]
         no_autorestart: bool,
-        /// Max memory before restart (e.g. "150M", "1G")
+        /// Max memory before restart (e.g. "150M", "1.5G")
         #[arg(long)]
         max_memory: Option<String>,
+        /// Restart when CPU% stays above this for cpu_restart_window_secs (default 60s)
+        #[arg(long, value_name = "PERCENT")]
+        max_cpu: Option<f32>,
         /// Cron expression for periodic restart
         #[arg(long)]
         cron_restart: Option<String>,
@@ -91,9 +102,30 @@ enum Commands {
         /// Number of instances for cluster mode (number or "max" for CPU count)
         #[arg(short, long)]
         instances: Option<String>,
+        /// Cluster instance name template: "{name}:{index}", "{name}-{index}" or "{name}_{index}"
+        #[arg(long)]
+        name_template: Option<String>,
+        /// Write stdout to this file instead of ~/.velos/logs/<name>-out.log
+        #[arg(long, conflicts_with = "config")]
+        out_log: Option<String>,
+        /// Write stderr to this file instead of ~/.velos/logs/<name>-err.log
+        #[arg(long, conflicts_with = "config")]
+        err_log: Option<String>,
+        /// Write stdout and stderr to the same (stdout) log file
+        #[arg(long)]
+        merge_logs: bool,
+        /// Logical group, targetable as @group (e.g. `velos stop @backend`)
+        #[arg(long)]
+        group: Option<String>,
+        /// Set an environment variable (repeatable), overriding the config's env
+        #[arg(short, long = "env", value_name = "KEY=VALUE")]
+        env: Vec<String>,
         /// Output as JSON
         #[arg(long)]
         json: bool,
+        /// Arguments passed to the script (after `--`)
+        #[arg(last = true, conflicts_with = "config")]
+        args: Vec<String>,
     },
     /// Stop a running process
     Stop {
@@ -102,11 +134,26 @@ enum Commands {
         /// Output as JSON
         #[arg(long)]
         json: bool,
+        /// Also stop child processes the app spawned (its whole process group)
+        #[arg(long)]
+        kill_tree: bool,
     },
     /// Restart a running process
     Restart {
         /// Process name, ID, or "all"
         name_or_id: String,
+        /// Only restart if the log health score is below --threshold
+        #[arg(long)]
+        if_unhealthy: bool,
+        /// Health score (0-100) below which --if-unhealthy restarts
+        #[arg(long, default_value = "50", requires = "if_unhealthy", value_parser = clap::value_parser!(u8).range(0..=100))]
+        threshold: u8,
+        /// Restart every matching instance, at most N per duration (e.g. 2/10s)
+        #[arg(long, value_name = "N/DURATION", conflicts_with = "if_unhealthy")]
+        throttle: Option<String>,
+        /// Zero the restart count and crash streak after restarting
+        #[arg(long)]
+        reset_counters: bool,
         /// Output as JSON
         #[arg(long)]
         json: bool,
@@ -129,6 +176,15 @@ enum Commands {
         #[arg(long)]
         ai: bool,
     },
+    /// One line per process: id, name, pid, status, cpu, mem, uptime
+    Ps {
+        /// Order by this column (id and name ascending, the rest highest first)
+        #[arg(long, value_enum, default_value_t = commands::ps::PsSort::Id)]
+        sort: commands::ps::PsSort,
+        /// Only show matching processes: status=running, name=api* (repeatable)
+        #[arg(long, value_name = "KEY=VALUE")]
+        filter: Vec<String>,
+    },
     /// Show detailed process info
     Info {
         /// Process name or ID
@@ -139,38 +195,185 @@ enum Commands {
         /// Compact AI-friendly output
         #[arg(long)]
         ai: bool,
+        /// Show recent restart/crash timeline
+        #[arg(long)]
+        history: bool,
+        /// Compare the running settings with the app declared in this config file
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["ai", "history"])]
+        diff_config: Option<String>,
+        /// Print secret env values (tokens, passwords) instead of ****
+        #[arg(long)]
+        show_secrets: bool,
     },
     /// Show process logs
     Logs {
         /// Process name or ID
         name: String,
-        /// Number of lines to show
-        #[arg(short, long, default_value = "50")]
-        lines: u32,
+        /// Number of lines to show (default: $VELOS_LOG_LINES, then logs.default_log_lines, then 50)
+        #[arg(short, long, visible_alias = "tail")]
+        lines: Option<u32>,
         /// Output as JSON
         #[arg(long)]
         json: bool,
+        /// With --json, put the fields of JSON messages at the top level of
+        /// each entry instead of in a "message" string
+        #[arg(long, requires = "json")]
+        flatten_parsed: bool,
         /// Compact AI-friendly output
         #[arg(long)]
         ai: bool,
         /// Filter by regex pattern
         #[arg(long)]
         grep: Option<String>,
-        /// Filter by level (comma-separated: error,warn)
+        /// Filter by level (comma-separated: error,warn); syslog numbers 0-7
+        /// map to the nearest level (0-2 fatal, 3 error, 4 warn, 5-6 info, 7 debug); "all" = no filter
         #[arg(long)]
         level: Option<String>,
+        /// Only this level and above (e.g. warn = warn,error,fatal)
+        #[arg(long, value_name = "LEVEL", conflicts_with_all = ["level", "errors_only", "warnings"])]
+        min_level: Option<String>,
+        /// Only error and fatal lines (same as --level error,fatal)
+        #[arg(short = 'e', long, conflicts_with_all = ["level", "warnings"])]
+        errors_only: bool,
+        /// Only warn, error and fatal lines (same as --level warn,error,fatal)
+        #[arg(long, conflicts_with = "level")]
+        warnings: bool,
+        /// Drop these levels (comma-separated: debug,info), after any of the filters above
+        #[arg(long, value_name = "LEVELS")]
+        exclude_level: Option<String>,
         /// Show logs since time (e.g. "1h", "30m", "2d")
         #[arg(long)]
         since: Option<String>,
         /// Show logs until time
         #[arg(long)]
         until: Option<String>,
+        /// Only lines since the process last (re)started
+        #[arg(long, conflicts_with_all = ["since", "merge_clusters"])]
+        since_restart: bool,
+        /// Only show lines up to the current moment
+        #[arg(long, conflicts_with_all = ["until", "follow"])]
+        until_now: bool,
+        /// Without --since/--until, only show this much recent history (e.g. "24h");
+        /// overrides [logs] default_window in velos.toml
+        #[arg(long, value_name = "DURATION")]
+        default_window: Option<String>,
         /// Deduplicate similar messages
         #[arg(long)]
         dedupe: bool,
+        /// Count JSON lines by the value of this field (dots reach nested fields)
+        #[arg(long, value_name = "FIELD", conflicts_with_all = ["dedupe", "summary", "follow", "fields", "ndjson"])]
+        aggregate: Option<String>,
+        /// Show the named groups of this regex as columns, e.g. '(?P<method>\w+) (?P<path>/\S+)'
+        #[arg(long, value_name = "REGEX", conflicts_with_all = ["dedupe", "summary", "aggregate", "follow", "fields", "ndjson"])]
+        regex_extract: Option<String>,
+        /// With --regex-extract, show lines that don't match as they are instead of skipping them
+        #[arg(long, requires = "regex_extract")]
+        keep_unmatched: bool,
+        /// Count lines per level
+        #[arg(long, conflicts_with_all = ["dedupe", "summary", "aggregate", "regex_extract", "follow", "fields", "ndjson"])]
+        stats: bool,
+        /// With --stats: per-level counts for each --bucket interval
+        #[arg(long, requires = "stats")]
+        timeline: bool,
+        /// Bucket width for --timeline (e.g. 30s, 1m, 1h)
+        #[arg(long, default_value = "1m", requires = "timeline")]
+        bucket: String,
+        /// With --stats: errors in the last DURATION vs the DURATION before it
+        #[arg(
+            long,
+            value_name = "DURATION",
+            requires = "stats",
+            conflicts_with = "timeline"
+        )]
+        compare_window: Option<String>,
         /// Show summary (health score, patterns, anomalies)
         #[arg(long)]
         summary: bool,
+        /// With --summary, how to print it (default: text, or json with --json/--ai)
+        #[arg(long, value_enum, requires = "summary", conflicts_with_all = ["json", "ai", "follow"])]
+        summary_format: Option<commands::logs::SummaryFormat>,
+        /// With --summary, only list patterns with this trend (combine with --min-level error)
+        #[arg(long, requires = "summary", value_parser = ["rising", "declining", "stable"])]
+        trend: Option<String>,
+        /// Cap total text output size (e.g. "64K", "1M")
+        #[arg(long)]
+        limit_bytes: Option<String>,
+        /// Cut each message to N chars, noting the rest as "…(+K chars)";
+        /// defaults to logs.max_line_length in velos.toml
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        max_line_length: Option<u32>,
+        /// Apply --max-line-length to --json, --ai, --ndjson and --output-dir too
+        #[arg(long)]
+        truncate: bool,
+        /// Keep only N% of lines below error level (e.g. "10%"); errors are always kept
+        #[arg(long)]
+        sample: Option<String>,
+        /// Show the first N lines of the buffer instead of the last
+        #[arg(long, alias = "first", value_name = "N", conflicts_with = "lines")]
+        head: Option<u32>,
+        /// With --summary, show the log lines from each anomalous minute
+        #[arg(long, requires = "summary")]
+        explain_anomalies: bool,
+        /// Output one JSON object per line (newline-delimited JSON)
+        #[arg(long, conflicts_with_all = ["json", "ai"])]
+        ndjson: bool,
+        /// Write the lines to DIR/error.log, DIR/warn.log, ... (one file per level) instead of printing them
+        #[arg(long, value_name = "DIR", conflicts_with_all = ["json", "ai", "ndjson", "fields", "follow", "stats", "summary", "dedupe", "aggregate", "regex_extract", "merge_clusters"])]
+        output_dir: Option<String>,
+        /// Keep printing new lines as they arrive (Ctrl+C to stop)
+        /// With --summary, re-render the summary in place instead of printing lines
+        #[arg(short, long, alias = "tail-follow", conflicts_with_all = ["json", "ai", "dedupe", "head"])]
+        follow: bool,
+        /// With --follow --grep: stop at each highlighted match until Enter is pressed
+        #[arg(long, requires_all = ["follow", "grep"], conflicts_with = "summary")]
+        pause_on_match: bool,
+        /// Seconds between refreshes for --follow --summary
+        #[arg(long, value_name = "SECS", default_value = "5")]
+        interval: u64,
+        /// For a cluster, merge all instances' logs into one stream tagged [index]
+        #[arg(long, conflicts_with = "follow")]
+        merge_clusters: bool,
+        /// Only lines after this sequence number, oldest first (cursor for log shippers)
+        #[arg(long, value_name = "N", conflicts_with_all = ["head", "merge_clusters"])]
+        since_seq: Option<u64>,
+        /// Print only these tab-separated columns: ts, time, level, stream, pid, msg
+        #[arg(long, alias = "tsv", value_name = "LIST", conflicts_with_all = ["json", "ai", "ndjson", "summary", "dedupe"])]
+        fields: Option<String>,
+        /// Classifier rules tuned for a log format
+        #[arg(long, value_parser = clap::builder::PossibleValuesParser::new(velos_config::CLASSIFIER_PRESETS))]
+        preset: Option<String>,
+        /// Empty the process's log files once the lines have been printed
+        #[arg(long, conflicts_with_all = ["follow", "merge_clusters"])]
+        clear_after: bool,
+        /// Interleave velos's own events (starts, crashes, restart decisions) by timestamp
+        #[arg(long, conflicts_with_all = ["follow", "merge_clusters", "summary", "dedupe"])]
+        include_internal: bool,
+        /// Show stdout and stderr as one stream without out/err tags, even if the process keeps them apart
+        #[arg(long, conflicts_with = "no_merge")]
+        merge: bool,
+        /// Tag each line with its stream (out/err), even if the process has merge_logs set
+        #[arg(long)]
+        no_merge: bool,
+        /// Recolor levels, e.g. "error=magenta,warn=cyan" (also: [logs.level_colors] in velos.toml)
+        #[arg(long, value_name = "LEVEL=COLOR,...")]
+        color_map: Option<String>,
+        /// Line layout (default: leveled on a terminal, plain when piped)
+        #[arg(long, value_enum, conflicts_with = "output_dir")]
+        format: Option<commands::logs::LogFormat>,
+    },
+    /// Show daemon lifecycle events: starts, stops, crashes and restart decisions
+    Events {
+        /// Process name, ID or @group (all processes when omitted)
+        name: Option<String>,
+        /// Number of events to show
+        #[arg(short, long, default_value = "50")]
+        lines: usize,
+        /// Output one JSON object per line
+        #[arg(long)]
+        json: bool,
+        /// Keep printing new events as they happen
+        #[arg(short, long)]
+        follow: bool,
     },
     /// Delete a process
     Delete {
@@ -188,10 +391,49 @@ enum Commands {
     },
     /// Restore previously saved processes
     Resurrect {
+        /// List what the state file would restore without starting anything
+        #[arg(long)]
+        dry_run: bool,
         /// Output as JSON
         #[arg(long)]
         json: bool,
     },
+    /// Convert another process manager's config into a velos.toml
+    Import {
+        /// PM2 ecosystem file (ecosystem.config.js or .json)
+        #[arg(long, value_name = "FILE")]
+        pm2: String,
+        /// Where to write the config ("-" prints it)
+        #[arg(short, long, default_value = "velos.toml")]
+        output: String,
+        /// Overwrite the output file if it exists
+        #[arg(long)]
+        force: bool,
+    },
+    /// Check a config file without starting anything
+    Validate {
+        /// Config file to check
+        #[arg(short, long, default_value = "velos.toml")]
+        config: String,
+        /// Output as JSON: [{app, valid, error, warnings}]
+        #[arg(long)]
+        json: bool,
+    },
+    /// Convert a velos.toml into another process manager's config
+    Export {
+        /// Write a PM2 ecosystem file
+        #[arg(long, required = true)]
+        pm2: bool,
+        /// velos config to read
+        #[arg(short, long, default_value = "velos.toml")]
+        config: String,
+        /// Where to write it ("-" prints JSON; a .js file gets `module.exports =`)
+        #[arg(short, long, default_value = "ecosystem.config.js")]
+        output: String,
+        /// Overwrite the output file if it exists
+        #[arg(long)]
+        force: bool,
+    },
     /// Flush log files
     Flush {
         /// Process name or ID (all if omitted)
@@ -208,6 +450,9 @@ enum Commands {
         /// OpenTelemetry OTLP endpoint (e.g. http://localhost:4318)
         #[arg(long)]
         otel_endpoint: Option<String>,
+        /// Print one scrape to stdout and exit instead of serving /metrics
+        #[arg(long, conflicts_with_all = ["port", "otel_endpoint"])]
+        once: bool,
     },
     /// Start REST API server with WebSocket support
     Api {
@@ -224,16 +469,32 @@ enum Commands {
         name: String,
         /// Target instance count (number, +N, -N, or "max")
         count: String,
+        /// Refuse targets above N instances ("max" is clamped to N)
+        #[arg(long)]
+        max: Option<u32>,
+        /// Read max_instances for the app from a TOML config file
+        #[arg(long)]
+        config: Option<String>,
+        /// Block until all target instances are running (timeout in seconds, default 30)
+        #[arg(long, value_name = "SECS", num_args = 0..=1, require_equals = true, default_missing_value = "30")]
+        wait: Option<u64>,
         /// Output as JSON
         #[arg(long)]
         json: bool,
+        /// Show how many instances would start/stop without scaling
+        #[arg(long, conflicts_with = "wait")]
+        dry_run: bool,
     },
     /// Generate startup script for init system (systemd/launchd/openrc)
     Startup,
     /// Remove startup script
     Unstartup,
     /// TUI monitoring dashboard
-    Monit,
+    Monit {
+        /// Focus on one process: larger CPU/memory graphs and log pane
+        #[arg(long, value_name = "NAME")]
+        process: Option<String>,
+    },
     /// Start MCP server for AI agents (stdio or HTTP transport)
     ///
     /// Without --port: stdio transport (for local AI clients like Claude Code)
@@ -248,7 +509,17 @@ enum Commands {
         port: Option<u16>,
     },
     /// Ping the daemon (IPC)
-    Ping,
+    Ping {
+        /// Compare CLI and daemon versions, printing {cli, daemon, match} as JSON
+        #[arg(long)]
+        check_version: bool,
+    },
+    /// Daemon liveness, version, uptime and process count in one call
+    Status {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Ping the Zig core (FFI, for testing)
     PingFfi,
     /// Configure global settings (notifications, etc.)
@@ -258,8 +529,16 @@ enum Commands {
     },
     /// Generate shell completions
     Completions {
-        /// Shell to generate completions for (bash, zsh, fish, elvish, powershell)
-        shell: String,
+        /// Shell to generate completions for (bash, zsh, fish, elvish, powershell; default: $SHELL)
+        shell: Option<String>,
+        /// Write the script to the shell's completion directory instead of stdout
+        #[arg(long)]
+        install: bool,
+    },
+    /// Manage logical process groups (set with `start --group`)
+    Group {
+        #[command(subcommand)]
+        action: GroupAction,
     },
     /// AI crash analysis and auto-fix
     Ai {
@@ -285,6 +564,32 @@ enum Commands {
     TelegramPoller,
 }
 
+#[derive(Subcommand)]
+enum GroupAction {
+    /// List groups and their member counts
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Restart every process in a group
+    Restart {
+        /// Group name (without the @)
+        group: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Stop every process in a group
+    Stop {
+        /// Group name (without the @)
+        group: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
 #[derive(Subcommand)]
 enum AiAction {
     /// Auto-fix a crash using AI agent
@@ -324,12 +629,27 @@ enum ConfigAction {
     Get {
         /// Config key (optional)
         key: Option<String>,
+        /// Print secret values (API keys, tokens) instead of ****
+        #[arg(long)]
+        show_secrets: bool,
+    },
+    /// Print a velos.toml in canonical order (comments are not kept)
+    Fmt {
+        /// Path to velos.toml
+        path: String,
+        /// Rewrite the file instead of printing it
+        #[arg(long)]
+        write: bool,
+        /// Print secret env values (tokens, passwords) instead of ****
+        #[arg(long, conflicts_with = "write")]
+        show_secrets: bool,
     },
 }
 
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
+    let color = color::ColorChoice::from_env(cli.color);
     let result = match cli.command {
         Commands::Daemon { socket, state_dir } => commands::daemon::run(socket, state_dir),
         Commands::Start {
@@ -339,14 +659,23 @@ async fn main() {
             interpreter,
             json,
             config,
+            watch_config,
             watch,
             max_restarts,
             no_autorestart,
             max_memory,
+            max_cpu,
             cron_restart,
             wait_ready,
             shutdown_with_message,
             instances,
+            name_template,
+            out_log,
+            err_log,
+            merge_logs,
+            group,
+            env,
+            args,
         } => {
             commands::start::run(commands::start::StartArgs {
                 script,
@@ -355,65 +684,213 @@ async fn main() {
                 interpreter,
                 json,
                 config,
+                watch_config,
                 watch,
                 max_restarts,
                 no_autorestart,
                 max_memory,
+                max_cpu,
                 cron_restart,
                 wait_ready,
                 shutdown_with_message,
                 instances,
+                name_template,
+                out_log,
+                err_log,
+                merge_logs,
+                group,
+                env,
+                args,
             })
             .await
         }
-        Commands::Stop { name_or_id, json } => commands::stop::run(name_or_id, json).await,
-        Commands::Restart { name_or_id, json } => commands::restart::run(name_or_id, json).await,
+        Commands::Stop {
+            name_or_id,
+            json,
+            kill_tree,
+        } => commands::stop::run(name_or_id, json, kill_tree).await,
+        Commands::Restart {
+            name_or_id,
+            if_unhealthy,
+            threshold,
+            throttle,
+            reset_counters,
+            json,
+        } => {
+            commands::restart::run(
+                name_or_id,
+                json,
+                if_unhealthy.then_some(threshold),
+                throttle,
+                reset_counters,
+            )
+            .await
+        }
         Commands::Reload { name_or_id, json } => commands::reload::run(name_or_id, json).await,
-        Commands::List { json, ai } => commands::list::run(json, ai).await,
+        Commands::List { json, ai } => commands::list::run(json, ai, color).await,
+        Commands::Ps { sort, filter } => commands::ps::run(sort, filter).await,
         Commands::Info {
             name_or_id,
             json,
             ai,
-        } => commands::info::run(name_or_id, json, ai).await,
+            history,
+            diff_config,
+            show_secrets,
+        } => commands::info::run(name_or_id, json, ai, history, diff_config, show_secrets).await,
         Commands::Logs {
             name,
             lines,
             json,
+            flatten_parsed,
             ai,
             grep,
             level,
+            min_level,
+            errors_only,
+            warnings,
+            exclude_level,
             since,
             until,
+            since_restart,
             dedupe,
+            aggregate,
+            regex_extract,
+            keep_unmatched,
+            stats,
+            timeline,
+            bucket,
+            compare_window,
             summary,
+            summary_format,
+            trend,
+            limit_bytes,
+            max_line_length,
+            truncate,
+            sample,
+            head,
+            explain_anomalies,
+            ndjson,
+            output_dir,
+            follow,
+            pause_on_match,
+            interval,
+            merge_clusters,
+            since_seq,
+            fields,
+            preset,
+            clear_after,
+            include_internal,
+            until_now,
+            default_window,
+            merge,
+            no_merge,
+            color_map,
+            format,
         } => {
-            commands::logs::run(commands::logs::LogsArgs {
-                name,
-                lines,
-                json,
-                ai,
-                grep,
-                level,
-                since,
-                until,
-                dedupe,
-                summary,
-            })
+            async {
+                let config = commands::logs::logs_config();
+                let level_colors =
+                    commands::logs::level_colors(color_map.as_deref(), config.as_ref())?;
+                commands::logs::run(commands::logs::LogsArgs {
+                    name,
+                    lines: commands::logs::log_lines(lines, config.as_ref()),
+                    json,
+                    flatten_parsed,
+                    ai,
+                    grep,
+                    level,
+                    min_level,
+                    errors_only,
+                    warnings,
+                    exclude_level,
+                    since,
+                    until,
+                    since_restart,
+                    dedupe,
+                    aggregate,
+                    regex_extract,
+                    keep_unmatched,
+                    stats,
+                    timeline,
+                    bucket,
+                    compare_window,
+                    summary,
+                    summary_format,
+                    trend,
+                    limit_bytes,
+                    max_line_length: commands::logs::max_line_length(
+                        max_line_length,
+                        config.as_ref(),
+                    ),
+                    truncate,
+                    sample,
+                    head,
+                    explain_anomalies,
+                    ndjson,
+                    output_dir,
+                    follow,
+                    pause_on_match,
+                    interval,
+                    merge_clusters,
+                    since_seq,
+                    fields,
+                    preset,
+                    clear_after,
+                    include_internal,
+                    until_now,
+                    default_window,
+                    format: commands::logs::LogFormat::from_env(format),
+                    color,
+                    level_colors,
+                    merge: match (merge, no_merge) {
+                        (true, _) => Some(true),
+                        (_, true) => Some(false),
+                        _ => None,
+                    },
+                    config,
+                })
+                .await
+            }
             .await
         }
+        Commands::Events {
+            name,
+            lines,
+            json,
+            follow,
+        } => commands::events::run(name, lines, json, follow).await,
         Commands::Delete { name_or_id, json } => commands::delete::run(name_or_id, json).await,
         Commands::Save { json } => commands::save::run(json).await,
-        Commands::Resurrect { json } => commands::resurrect::run(json).await,
+        Commands::Resurrect { dry_run, json } => commands::resurrect::run(dry_run, json).await,
         Commands::Flush { name_or_id, json } => commands::flush::run(name_or_id, json).await,
-        Commands::Scale { name, count, json } => commands::scale::run(name, count, json).await,
+        Commands::Validate { config, json } => commands::validate::run(config, json).await,
+        Commands::Export {
+            pm2: _,
+            config,
+            output,
+            force,
+        } => commands::export::run_pm2(config, output, force).await,
+        Commands::Import { pm2, output, force } => {
+            commands::import::run_pm2(pm2, output, force).await
+        }
+        Commands::Scale {
+            name,
+            count,
+            max,
+            config,
+            wait,
+            json,
+            dry_run,
+        } => commands::scale::run(name, count, max, config, wait, json, dry_run).await,
         Commands::Api { port, token } => commands::api::run(port, token).await,
         Commands::Metrics {
             port,
             otel_endpoint,
-        } => commands::metrics::run(port, otel_endpoint).await,
+            once,
+        } => commands::metrics::run(port, otel_endpoint, once).await,
         Commands::Startup => commands::startup::run_startup().await,
         Commands::Unstartup => commands::startup::run_unstartup().await,
-        Commands::Monit => commands::monit::run().await,
+        Commands::Monit { process } => commands::monit::run(process, color).await,
         Commands::McpServer { port } => {
             if let Some(port) = port {
                 velos_mcp::http::run_http(port)
@@ -427,7 +904,8 @@ async fn main() {
                     .map_err(|e| velos_core::VelosError::ProtocolError(e.to_string()))
             }
         }
-        Commands::Ping => commands::ping::run().await,
+        Commands::Ping { check_version } => commands::ping::run(check_version).await,
+        Commands::Status { json } => commands::status::run(json).await,
         Commands::PingFfi => {
             let response = velos_ffi::ping();
             println!("{response}");
@@ -435,7 +913,19 @@ async fn main() {
         }
         Commands::Config { action } => match action {
             ConfigAction::Set { key, value } => commands::config::run_set(key, value).await,
-            ConfigAction::Get { key } => commands::config::run_get(key).await,
+            ConfigAction::Get { key, show_secrets } => {
+                commands::config::run_get(key, show_secrets).await
+            }
+            ConfigAction::Fmt {
+                path,
+                write,
+                show_secrets,
+            } => commands::config::run_fmt(path, write, show_secrets).await,
+        },
+        Commands::Group { action } => match action {
+            GroupAction::List { json } => commands::group::run_list(json).await,
+            GroupAction::Restart { group, json } => commands::group::run_restart(group, json).await,
+            GroupAction::Stop { group, json } => commands::group::run_stop(group, json).await,
         },
         Commands::Ai { action } => match action {
             AiAction::Fix { crash_id } => commands::ai::run_fix(crash_id).await,
@@ -448,13 +938,14 @@ async fn main() {
         }
         Commands::NotifyError { name } => commands::notify_error::run(name).await,
         Commands::TelegramPoller => commands::telegram_poller::run_poller(),
-        Commands::Completions { shell } => commands::completions::run(shell),
+        Commands::Completions { shell, install } => commands::completions::run(shell, install),
     };
 
     if let Err(e) = result {
         match &e {
-            velos_core::VelosError::DaemonNotRunning => {
+            velos_core::VelosError::DaemonNotRunning(socket) => {
                 eprintln!("Error: Daemon is not running.");
+                eprintln!("  tried: {}", socket.display());
                 eprintln!("  Start it with: velos daemon");
             }
             velos_core::VelosError::ConnectionFailed(msg) => {
@@ -469,6 +960,14 @@ async fn main() {
                 eprintln!("Error: Process '{name}' not found.");
                 eprintln!("  Run 'velos list' to see running processes.");
             }
+            velos_core::VelosError::ScriptNotFound(script) => {
+                eprintln!("Error: Script '{script}' not found.");
+                eprintln!("  Check the path and --cwd.");
+            }
+            velos_core::VelosError::NameInUse(name) => {
+                eprintln!("Error: A process named '{name}' already exists.");
+                eprintln!("  Pick another --name, or delete it first: velos delete {name}");
+            }
             velos_core::VelosError::ProtocolError(msg) => {
                 eprintln!("Error: Protocol error: {msg}");
                 eprintln!("  This may indicate a version mismatch. Try restarting the daemon.");
@@ -480,3 +979,29 @@ async fn main() {
         std::process::exit(1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_trailing_args() {
+        let cli = Cli::try_parse_from([
+            "velos", "start", "app.js", "--name", "api", "--", "--port", "3000", "-v",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Start {
+                script, name, args, ..
+            } => {
+                assert_eq!(script.as_deref(), Some("app.js"));
+                assert_eq!(name.as_deref(), Some("api"));
+                assert_eq!(args, ["--port", "3000", "-v"]);
+            }
+            _ => panic!("expected start"),
+        }
+
+        // Script arguments come from the config file there
+        assert!(Cli::try_parse_from(["velos", "start", "--config", "v.toml", "--", "x"]).is_err());
+    }
+}
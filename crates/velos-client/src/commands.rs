@@ -21,6 +21,11 @@ impl VelosClient {
         Ok(Self { conn })
     }
 
+    /// Protocol version settled on with the daemon when connecting.
+    pub fn protocol_version(&self) -> u8 {
+        self.conn.protocol_version()
+    }
+
     /// Start a new process. Returns the assigned process ID.
     pub async fn start(&mut self, payload: StartPayload) -> Result<StartResult, VelosError> {
         let resp = self
@@ -33,10 +38,20 @@ impl VelosClient {
 
     /// Stop a process by ID.
     pub async fn stop(&mut self, id: u32) -> Result<(), VelosError> {
+        self.stop_with_flags(id, 0).await
+    }
+
+    /// Stop a process and every descendant in its process group.
+    pub async fn stop_tree(&mut self, id: u32) -> Result<(), VelosError> {
+        self.stop_with_flags(id, STOP_FLAG_KILL_TREE).await
+    }
+
+    async fn stop_with_flags(&mut self, id: u32, flags: u8) -> Result<(), VelosError> {
         let payload = StopPayload {
             process_id: id,
             signal: 15, // SIGTERM
             timeout_ms: 5000,
+            flags,
         };
         let resp = self
             .conn
@@ -51,6 +66,7 @@ impl VelosClient {
             process_id: id,
             signal,
             timeout_ms: 0,
+            flags: 0,
         };
         let resp = self
             .conn
@@ -69,8 +85,24 @@ impl VelosClient {
         decode_process_list(&resp.payload)
     }
 
+    /// List all processes, keeping what arrived if the reply was cut off.
+    /// See [`decode_process_list_with`].
+    pub async fn list_lenient(&mut self) -> Result<ProcessList, VelosError> {
+        let resp = self
+            .conn
+            .request(CommandCode::ProcessList, Vec::new())
+            .await?;
+        self.check_response(&resp)?;
+        decode_process_list_with(&resp.payload, true)
+    }
+
     /// Read log entries for a process.
     pub async fn logs(&mut self, id: u32, lines: u32) -> Result<Vec<LogEntry>, VelosError> {
+        Ok(self.log_read(id, lines).await?.entries)
+    }
+
+    /// Read log entries along with the daemon's rate-limit suppression count.
+    pub async fn log_read(&mut self, id: u32, lines: u32) -> Result<LogRead, VelosError> {
         let payload = LogReadPayload {
             process_id: id,
             lines,
@@ -80,7 +112,7 @@ impl VelosClient {
             .request(CommandCode::LogRead, payload.encode())
             .await?;
         self.check_response(&resp)?;
-        decode_log_entries(&resp.payload)
+        decode_log_read(&resp.payload, self.conn.protocol_version())
     }
 
     /// Delete a process.
@@ -110,6 +142,24 @@ impl VelosClient {
         self.check_response(&resp)
     }
 
+    /// Zero a process's restart count and crash streak, and clear its last
+    /// exit reason. Older daemons don't know the command; check
+    /// [`supports_reset`](Self::supports_reset) first.
+    pub async fn reset(&mut self, id: u32) -> Result<(), VelosError> {
+        let payload = ResetPayload { process_id: id };
+        let resp = self
+            .conn
+            .request(CommandCode::ProcessReset, payload.encode())
+            .await?;
+        self.check_response(&resp)
+    }
+
+    /// Whether the daemon handles [`reset`](Self::reset): it came with
+    /// protocol version 2, so it depends on what HELLO settled on.
+    pub fn supports_reset(&self) -> bool {
+        self.protocol_version() >= VERSION_2
+    }
+
     /// Get detailed info for a process by ID.
     pub async fn info(&mut self, id: u32) -> Result<ProcessDetail, VelosError> {
         let payload = InfoPayload { process_id: id };
@@ -121,6 +171,39 @@ impl VelosClient {
         decode_process_detail(&resp.payload)
     }
 
+    /// Get the recent lifecycle events (restarts, crashes, OOM kills) for a process.
+    pub async fn history(&mut self, id: u32) -> Result<Vec<LifecycleEvent>, VelosError> {
+        let payload = HistoryPayload { process_id: id };
+        let resp = self
+            .conn
+            .request(CommandCode::ProcessHistory, payload.encode())
+            .await?;
+        self.check_response(&resp)?;
+        decode_process_history(&resp.payload)
+    }
+
+    /// Lifecycle events across all processes newer than `since_ms`, oldest first.
+    pub async fn events_since(&mut self, since_ms: u64) -> Result<Vec<DaemonEvent>, VelosError> {
+        let payload = EventStreamPayload { since_ms };
+        let resp = self
+            .conn
+            .request(CommandCode::EventStream, payload.encode())
+            .await?;
+        self.check_response(&resp)?;
+        decode_event_stream(&resp.payload)
+    }
+
+    /// The daemon's runtime settings (socket, state dir, version, limits).
+    /// Daemons that predate the command answer with an error.
+    pub async fn daemon_info(&mut self) -> Result<DaemonInfo, VelosError> {
+        let resp = self
+            .conn
+            .request(CommandCode::DaemonInfo, Vec::new())
+            .await?;
+        self.check_response(&resp)?;
+        decode_daemon_info(&resp.payload)
+    }
+
     /// Save current process list to disk.
     pub async fn save(&mut self) -> Result<(), VelosError> {
         let resp = self
@@ -167,7 +250,7 @@ impl VelosClient {
     fn check_response(&self, resp: &Response) -> Result<(), VelosError> {
         match resp.status {
             ResponseStatus::Ok | ResponseStatus::Streaming => Ok(()),
-            ResponseStatus::Error => Err(VelosError::ProtocolError(resp.error_message())),
+            ResponseStatus::Error => Err(resp.to_error()),
         }
     }
 }
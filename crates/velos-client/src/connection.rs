@@ -4,14 +4,41 @@ use std::sync::atomic::{AtomicU32, Ordering};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::UnixStream;
 
-use velos_core::protocol::{self, CommandCode, Request, Response, HEADER_SIZE};
+use velos_core::protocol::{
+    self, CommandCode, HelloPayload, HelloResult, Request, Response, ResponseStatus, HEADER_SIZE,
+};
 use velos_core::VelosError;
 
 /// Low-level IPC connection to the Velos daemon.
 pub struct VelosConnection {
     stream: UnixStream,
     socket_path: PathBuf,
-    next_id: AtomicU32,
+    next_id: RequestIds,
+    version: u8,
+}
+
+/// Request ID allocator for one connection.
+///
+/// IDs run 1..=`u32::MAX` and then wrap back to 1; 0 is reserved and never
+/// sent. Requests on a connection are strictly sequential (`request` waits
+/// for the response before returning), so at most one ID is in flight and a
+/// wrapped ID cannot collide with a pending one.
+#[derive(Debug)]
+struct RequestIds(AtomicU32);
+
+impl RequestIds {
+    fn starting_at(id: u32) -> Self {
+        Self(AtomicU32::new(id.max(1)))
+    }
+
+    fn next(&self) -> u32 {
+        // The closure always returns Some, so this never takes the Err arm
+        self.0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |id| {
+                Some(id.checked_add(1).unwrap_or(1))
+            })
+            .unwrap_or_else(|id| id)
+    }
 }
 
 impl VelosConnection {
@@ -21,16 +48,40 @@ impl VelosConnection {
             if e.kind() == std::io::ErrorKind::ConnectionRefused
                 || e.kind() == std::io::ErrorKind::NotFound
             {
-                VelosError::DaemonNotRunning
+                VelosError::DaemonNotRunning(socket_path.to_path_buf())
             } else {
-                VelosError::ConnectionFailed(e.to_string())
+                VelosError::ConnectionFailed(format!("{}: {e}", socket_path.display()))
             }
         })?;
-        Ok(Self {
+        let mut conn = Self {
             stream,
             socket_path: socket_path.to_path_buf(),
-            next_id: AtomicU32::new(1),
-        })
+            next_id: RequestIds::starting_at(1),
+            version: protocol::VERSION,
+        };
+        conn.version = conn.negotiate().await?;
+        Ok(conn)
+    }
+
+    /// Exchange supported protocol versions with the daemon. The hello itself
+    /// is sent with the base version every daemon understands; daemons that
+    /// predate the handshake answer with an error and we stay on that version.
+    async fn negotiate(&mut self) -> Result<u8, VelosError> {
+        let payload = HelloPayload {
+            versions: protocol::SUPPORTED_VERSIONS.to_vec(),
+        };
+        let resp = self.request(CommandCode::Hello, payload.encode()).await?;
+        if resp.status != ResponseStatus::Ok {
+            return Ok(protocol::VERSION);
+        }
+        let chosen = HelloResult::decode(&resp.payload)?.version;
+        if protocol::SUPPORTED_VERSIONS.contains(&chosen) {
+            Ok(chosen)
+        } else {
+            Err(VelosError::ProtocolError(format!(
+                "daemon selected unsupported protocol version {chosen}"
+            )))
+        }
     }
 
     /// Connect using the default socket path.
@@ -43,9 +94,14 @@ impl VelosConnection {
         &self.socket_path
     }
 
-    /// Allocate the next request ID.
+    /// Protocol version negotiated with the daemon.
+    pub fn protocol_version(&self) -> u8 {
+        self.version
+    }
+
+    /// Allocate the next request ID (never 0, see [`RequestIds`]).
     fn next_request_id(&self) -> u32 {
-        self.next_id.fetch_add(1, Ordering::Relaxed)
+        self.next_id.next()
     }
 
     /// Send a request and wait for the response.
@@ -65,7 +121,7 @@ impl VelosConnection {
 
     /// Send a raw request to the daemon.
     async fn send_request(&mut self, req: &Request) -> Result<(), VelosError> {
-        let bytes = req.encode()?;
+        let bytes = req.encode_with_version(self.version)?;
         self.stream.write_all(&bytes).await?;
         self.stream.flush().await?;
         Ok(())
@@ -85,3 +141,27 @@ impl VelosConnection {
         Response::from_body(&body)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_ids_wrap_to_one() {
+        let ids = RequestIds::starting_at(u32::MAX - 1);
+        assert_eq!(ids.next(), u32::MAX - 1);
+        assert_eq!(ids.next(), u32::MAX);
+        assert_eq!(ids.next(), 1);
+        assert_eq!(ids.next(), 2);
+
+        assert_eq!(RequestIds::starting_at(0).next(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_connect_missing_socket_names_path() {
+        let path = std::env::temp_dir().join("velos_no_daemon_here/custom.sock");
+        let err = VelosConnection::connect(&path).await.err().unwrap();
+        assert!(matches!(&err, VelosError::DaemonNotRunning(tried) if *tried == path));
+        assert!(err.to_string().contains("custom.sock"));
+    }
+}
@@ -0,0 +1,163 @@
+//! Direct access to the on-disk process log files, used when the daemon
+//! is not running and the IPC log buffer is unavailable.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use velos_core::LogEntry;
+
+const TAIL_CHUNK: u64 = 8192;
+
+/// Log directory: ~/.velos/logs
+pub fn default_log_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
+    PathBuf::from(home).join(".velos").join("logs")
+}
+
+/// Where a process's stdout and stderr logs are written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogPaths {
+    pub out: PathBuf,
+    pub err: PathBuf,
+}
+
+impl LogPaths {
+    /// `out_log`/`err_log` as the daemon reports them; an empty path is the
+    /// default `<log_dir>/<name>-out.log` / `-err.log`.
+    pub fn new(log_dir: &Path, name: &str, out_log: &str, err_log: &str) -> Self {
+        let path = |custom: &str, suffix: &str| {
+            if custom.is_empty() {
+                log_dir.join(format!("{name}-{suffix}.log"))
+            } else {
+                PathBuf::from(custom)
+            }
+        };
+        Self {
+            out: path(out_log, "out"),
+            err: path(err_log, "err"),
+        }
+    }
+
+    /// Paths for `name` while the daemon is down: the custom paths saved in
+    /// `state.bin`, or the defaults in `log_dir`.
+    pub fn saved(log_dir: &Path, name: &str) -> Self {
+        let saved = crate::state::read_state_file(&crate::state::default_state_path())
+            .ok()
+            .flatten()
+            .and_then(|s| s.processes.into_iter().find(|p| p.name == name));
+        match saved {
+            Some(p) => Self::new(log_dir, name, &p.out_log, &p.err_log),
+            None => Self::new(log_dir, name, "", ""),
+        }
+    }
+
+    /// Empty both log files.
+    pub fn truncate(&self) {
+        let _ = std::fs::write(&self.out, b"");
+        let _ = std::fs::write(&self.err, b"");
+    }
+}
+
+/// Read the last `n` lines of a file, reading backwards in chunks so large
+/// log files are not loaded into memory.
+pub fn tail_lines(path: &Path, n: usize) -> std::io::Result<Vec<String>> {
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+    let mut file = File::open(path)?;
+    let mut pos = file.metadata()?.len();
+    let mut buf: Vec<u8> = Vec::new();
+
+    // Stop once we hold more than `n` newlines: the first line is then complete.
+    while pos > 0 && buf.iter().filter(|&&b| b == b'\n').count() <= n {
+        let read = TAIL_CHUNK.min(pos);
+        pos -= read;
+        file.seek(SeekFrom::Start(pos))?;
+        let mut chunk = vec![0u8; read as usize];
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&buf);
+        buf = chunk;
+    }
+
+    let text = String::from_utf8_lossy(&buf);
+    let lines: Vec<&str> = text.lines().collect();
+    let start = lines.len().saturating_sub(n);
+    Ok(lines[start..].iter().map(|s| s.to_string()).collect())
+}
+
+/// Read the last `lines` lines of a process's stdout and stderr log files.
+/// Log files carry no per-line timestamps, so every entry gets the file's
+/// modification time; stdout lines come before stderr lines.
+/// Returns `None` if neither log file exists.
+pub fn read_process_logs(paths: &LogPaths, lines: u32) -> Option<Vec<LogEntry>> {
+    let mut entries = Vec::new();
+    let mut found = false;
+
+    for (stream, path) in [(0u8, &paths.out), (1u8, &paths.err)] {
+        // Both streams pointed at one file: read it once
+        if stream == 1 && paths.err == paths.out {
+            continue;
+        }
+        let Ok(tail) = tail_lines(path, lines as usize) else {
+            continue;
+        };
+        found = true;
+        let timestamp_ms = std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        entries.extend(tail.into_iter().map(|message| LogEntry {
+            timestamp_ms,
+            level: 1,
+            stream,
+            message,
+            seq: 0,
+        }));
+    }
+
+    found.then_some(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tail_reads_last_lines() {
+        let dir = std::env::temp_dir().join(format!("velos_disk_tail_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app-out.log");
+
+        // Enough data to span several chunks.
+        let content: String = (0..5000).map(|i| format!("line {i}\n")).collect();
+        std::fs::write(&path, content).unwrap();
+
+        let tail = tail_lines(&path, 3).unwrap();
+        assert_eq!(tail, vec!["line 4997", "line 4998", "line 4999"]);
+
+        // Asking for more lines than exist returns the whole file.
+        std::fs::write(&path, "a\nb\n").unwrap();
+        assert_eq!(tail_lines(&path, 10).unwrap(), vec!["a", "b"]);
+
+        let entries = read_process_logs(&LogPaths::new(&dir, "app", "", ""), 10).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|e| e.stream == 0));
+        assert!(read_process_logs(&LogPaths::new(&dir, "missing", "", ""), 10).is_none());
+
+        // Custom paths, both streams in one file
+        let custom = dir.join("custom.log");
+        std::fs::write(&custom, "x\n").unwrap();
+        let custom = custom.to_string_lossy();
+        let paths = LogPaths::new(&dir, "app", &custom, &custom);
+        assert_eq!(paths.out, dir.join("custom.log"));
+        assert_eq!(read_process_logs(&paths, 10).unwrap().len(), 1);
+        paths.truncate();
+        assert_eq!(std::fs::read(dir.join("custom.log")).unwrap(), b"");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
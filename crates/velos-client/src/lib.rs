@@ -1,5 +1,8 @@
 pub mod commands;
 pub mod connection;
+pub mod disk;
+pub mod resolve;
+pub mod state;
 
 pub use commands::VelosClient;
 pub use connection::VelosConnection;
@@ -26,13 +29,13 @@ pub fn default_pid_path() -> std::path::PathBuf {
 /// Check if the daemon is likely running by checking PID file existence
 /// and whether the process is alive.
 pub fn is_daemon_running() -> bool {
-    let pid_path = default_pid_path();
-    let Ok(content) = std::fs::read_to_string(&pid_path) else {
-        return false;
-    };
-    let Ok(pid) = content.trim().parse::<i32>() else {
-        return false;
-    };
+    daemon_pid().is_some()
+}
+
+/// PID from the PID file, if that process is alive.
+pub fn daemon_pid() -> Option<i32> {
+    let content = std::fs::read_to_string(default_pid_path()).ok()?;
+    let pid = content.trim().parse::<i32>().ok()?;
     // Check if process is alive via kill(pid, 0)
-    unsafe { libc::kill(pid, 0) == 0 }
+    (unsafe { libc::kill(pid, 0) } == 0).then_some(pid)
 }
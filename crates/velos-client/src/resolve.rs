@@ -0,0 +1,129 @@
+use velos_core::process::{match_instance, INSTANCE_NAME_TEMPLATES};
+use velos_core::protocol::ProcessInfo;
+use velos_core::redact::glob_match;
+use velos_core::VelosError;
+
+use crate::VelosClient;
+
+/// Resolve a name-or-ID to one process ID: the first process
+/// [`resolve_many`] would return.
+pub async fn resolve_one(client: &mut VelosClient, name_or_id: &str) -> Result<u32, VelosError> {
+    resolve_many(client, name_or_id).await.map(|ids| ids[0])
+}
+
+/// Resolve a name-or-ID to one or more process IDs. See [`match_many`] for
+/// the accepted forms; a numeric ID is used as-is without listing.
+pub async fn resolve_many(
+    client: &mut VelosClient,
+    name_or_id: &str,
+) -> Result<Vec<u32>, VelosError> {
+    if let Ok(id) = name_or_id.parse::<u32>() {
+        return Ok(vec![id]);
+    }
+    let procs = client.list().await?;
+    match_many(&procs, name_or_id)
+}
+
+/// Match a name-or-ID against a process list, in list order. The first rule
+/// that matches anything wins:
+/// - a numeric string is that process ID
+/// - "@backend" is every process started with `--group backend`
+/// - an exact process name
+/// - a glob with `*` ("api-*", "*worker")
+/// - a cluster base name: "api" matches "api:0", "api:1", ... (or "api-0",
+///   "api_0" for apps started with a different instance name template)
+pub fn match_many(procs: &[ProcessInfo], name_or_id: &str) -> Result<Vec<u32>, VelosError> {
+    if let Ok(id) = name_or_id.parse::<u32>() {
+        return Ok(vec![id]);
+    }
+    let ids_where = |pred: &dyn Fn(&ProcessInfo) -> bool| -> Vec<u32> {
+        procs.iter().filter(|p| pred(p)).map(|p| p.id).collect()
+    };
+
+    let ids = if let Some(group) = name_or_id.strip_prefix('@') {
+        group_members(procs, group)
+    } else {
+        let mut ids = ids_where(&|p| p.name == name_or_id);
+        if ids.is_empty() && name_or_id.contains('*') {
+            ids = ids_where(&|p| glob_match(name_or_id, &p.name));
+        }
+        for template in INSTANCE_NAME_TEMPLATES {
+            if !ids.is_empty() {
+                break;
+            }
+            ids = ids_where(&|p| match_instance(template, name_or_id, &p.name).is_some());
+        }
+        ids
+    };
+
+    if ids.is_empty() {
+        return Err(VelosError::ProcessNotFound(name_or_id.to_string()));
+    }
+    Ok(ids)
+}
+
+/// IDs of the processes tagged with `group`, in list order.
+pub fn group_members(procs: &[ProcessInfo], group: &str) -> Vec<u32> {
+    procs
+        .iter()
+        .filter(|p| p.group.as_deref() == Some(group))
+        .map(|p| p.id)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proc(id: u32, name: &str, group: Option<&str>) -> ProcessInfo {
+        ProcessInfo {
+            id,
+            name: name.into(),
+            pid: 1000 + id,
+            status: 1,
+            memory_bytes: 0,
+            uptime_ms: 0,
+            restart_count: 0,
+            cpu_percent: 0.0,
+            group: group.map(Into::into),
+        }
+    }
+
+    fn procs() -> Vec<ProcessInfo> {
+        vec![
+            proc(0, "api:0", Some("backend")),
+            proc(1, "api:1", Some("backend")),
+            proc(2, "web", None),
+            proc(3, "worker-0", None),
+            proc(4, "worker-1", None),
+            proc(5, "api", None),
+        ]
+    }
+
+    #[test]
+    fn test_match_exact_numeric_and_not_found() {
+        let procs = procs();
+        assert_eq!(match_many(&procs, "web").unwrap(), [2]);
+        assert_eq!(match_many(&procs, "api:1").unwrap(), [1]);
+        // An exact name beats the cluster it also prefixes
+        assert_eq!(match_many(&procs, "api").unwrap(), [5]);
+        // IDs are taken at face value, even if not in the list
+        assert_eq!(match_many(&procs, "4").unwrap(), [4]);
+        assert_eq!(match_many(&procs, "99").unwrap(), [99]);
+
+        for missing in ["db", "wor", "@frontend", "db-*"] {
+            let err = match_many(&procs, missing).unwrap_err();
+            assert!(matches!(err, VelosError::ProcessNotFound(ref n) if n == missing));
+        }
+    }
+
+    #[test]
+    fn test_match_cluster_group_and_glob() {
+        let procs = procs();
+        assert_eq!(match_many(&procs, "worker").unwrap(), [3, 4]);
+        assert_eq!(match_many(&procs, "@backend").unwrap(), [0, 1]);
+        assert_eq!(match_many(&procs, "api*").unwrap(), [0, 1, 5]);
+        assert_eq!(match_many(&procs, "*-1").unwrap(), [4]);
+        assert_eq!(group_members(&procs, "Backend"), Vec::<u32>::new());
+    }
+}
@@ -0,0 +1,324 @@
+//! Client-side reader for the daemon's saved process list (`state.bin`),
+//! used to diagnose `velos resurrect` without going through the daemon.
+//!
+//! The file is the little-endian binary layout written by the daemon's
+//! `saveState`: a u32 entry count followed by one record per process. Each
+//! record starts with the required fields (name, script, cwd, interpreter,
+//! kill timeout, autorestart, max restarts, min uptime, restart delay,
+//! backoff); later fields were appended over time and are read only when
+//! bytes remain, exactly as the daemon does.
+//!
+//! Version 2 files put [`STATE_MAGIC`] and a version before the count, and
+//! end each record with a length-prefixed extension block (args, log paths,
+//! group, exit codes, CPU limit, ...); only the log paths are read from it.
+
+use std::path::{Path, PathBuf};
+
+/// First u32 of a versioned state file; legacy files start with the count.
+pub const STATE_MAGIC: u32 = 0x534C_4556;
+
+/// Saved state file: ~/.velos/state.bin
+pub fn default_state_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
+    PathBuf::from(home).join(".velos").join("state.bin")
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StateFileError {
+    #[error("state file is too short to hold an entry count ({0} bytes)")]
+    MissingHeader(usize),
+
+    #[error("state file is truncated: header declares {declared} process(es), only {parsed} could be read")]
+    Truncated { declared: u32, parsed: u32 },
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// One process record from the state file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedProcess {
+    pub name: String,
+    pub script: String,
+    pub cwd: String,
+    pub instances: u32,
+    /// Custom log paths (version 2 files); empty = the default location.
+    pub out_log: String,
+    pub err_log: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedState {
+    pub processes: Vec<SavedProcess>,
+    /// Bytes left over after the last declared record. Non-zero means the
+    /// header count disagrees with the file contents.
+    pub trailing_bytes: usize,
+}
+
+/// Read and validate a state file. Returns `Ok(None)` if it does not exist.
+pub fn read_state_file(path: &Path) -> Result<Option<SavedState>, StateFileError> {
+    match std::fs::read(path) {
+        Ok(data) => parse_state(&data).map(Some),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Parse the binary state layout, failing if any declared record is cut short.
+pub fn parse_state(data: &[u8]) -> Result<SavedState, StateFileError> {
+    if data.len() < 4 {
+        return Err(StateFileError::MissingHeader(data.len()));
+    }
+    let mut r = Reader { data, off: 0 };
+    let mut declared = r.u32().unwrap_or_default();
+    let mut version = 1;
+    if declared == STATE_MAGIC {
+        match (r.u32(), r.u32()) {
+            (Some(v), Some(count)) => (version, declared) = (v, count),
+            _ => return Err(StateFileError::MissingHeader(data.len())),
+        }
+    }
+
+    let mut processes = Vec::new();
+    for parsed in 0..declared {
+        match r.record(version) {
+            Some(p) => processes.push(p),
+            None => return Err(StateFileError::Truncated { declared, parsed }),
+        }
+    }
+
+    Ok(SavedState {
+        processes,
+        trailing_bytes: data.len() - r.off,
+    })
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    off: usize,
+}
+
+impl Reader<'_> {
+    fn remaining(&self) -> usize {
+        self.data.len() - self.off
+    }
+
+    fn bytes(&mut self, n: usize) -> Option<&[u8]> {
+        if self.remaining() < n {
+            return None;
+        }
+        let b = &self.data[self.off..self.off + n];
+        self.off += n;
+        Some(b)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.bytes(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let len = self.u32()? as usize;
+        self.bytes(len)
+            .map(|b| String::from_utf8_lossy(b).into_owned())
+    }
+
+    /// Skip `n` bytes of an optional trailing field if they are present.
+    fn skip_optional(&mut self, n: usize) {
+        if self.remaining() >= n {
+            self.off += n;
+        }
+    }
+
+    fn record(&mut self, version: u32) -> Option<SavedProcess> {
+        let name = self.string()?;
+        let script = self.string()?;
+        let cwd = self.string()?;
+        let _interpreter = self.string()?;
+        // kill_timeout u32, autorestart u8, max_restarts i32, min_uptime u64,
+        // restart_delay u32, exp_backoff u8
+        self.bytes(4 + 1 + 4 + 8 + 4 + 1)?;
+
+        if self.remaining() >= 8 {
+            self.off += 8; // max_memory_restart
+            self.skip_optional(1); // watch
+            self.skip_optional(4); // watch_delay_ms
+            for _ in 0..3 {
+                // watch_paths, watch_ignore, cron_restart
+                if self.remaining() >= 4 {
+                    self.string()?;
+                }
+            }
+            self.skip_optional(1); // wait_ready
+            self.skip_optional(4); // listen_timeout_ms
+            self.skip_optional(1); // shutdown_with_message
+        }
+        let instances = if self.remaining() >= 4 {
+            self.u32()?
+        } else {
+            1
+        };
+        self.skip_optional(4); // instance_id
+        let (mut out_log, mut err_log) = (String::new(), String::new());
+        if version >= 2 {
+            self.string()?; // env_vars
+            let ext_len = self.u32()? as usize;
+            let ext = self.bytes(ext_len)?;
+            (out_log, err_log) = Reader { data: ext, off: 0 }.log_paths().unwrap_or_default();
+        } else if self.remaining() >= 4 {
+            self.string()?; // env_vars
+        }
+
+        Some(SavedProcess {
+            name,
+            script,
+            cwd,
+            instances,
+            out_log,
+            err_log,
+        })
+    }
+
+    /// out_log and err_log from the front of an extension block.
+    fn log_paths(&mut self) -> Option<(String, String)> {
+        self.string()?; // args
+        self.string()?; // instance_name_template
+        self.bytes(1)?; // merge_logs
+        Some((self.string()?, self.string()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put_str(buf: &mut Vec<u8>, s: &str) {
+        buf.extend_from_slice(&(s.len() as u32).to_le_bytes());
+        buf.extend_from_slice(s.as_bytes());
+    }
+
+    /// Encode one record the way the daemon's saveState does.
+    fn put_record(buf: &mut Vec<u8>, name: &str, script: &str, instances: u32) {
+        put_str(buf, name);
+        put_str(buf, script);
+        put_str(buf, "/srv/app");
+        put_str(buf, "");
+        buf.extend_from_slice(&5000u32.to_le_bytes());
+        buf.push(1);
+        buf.extend_from_slice(&15i32.to_le_bytes());
+        buf.extend_from_slice(&1000u64.to_le_bytes());
+        buf.extend_from_slice(&0u32.to_le_bytes());
+        buf.push(0);
+        buf.extend_from_slice(&0u64.to_le_bytes());
+        buf.push(0);
+        buf.extend_from_slice(&1000u32.to_le_bytes());
+        put_str(buf, "");
+        put_str(buf, "");
+        put_str(buf, "0 3 * * *");
+        buf.push(0);
+        buf.extend_from_slice(&8000u32.to_le_bytes());
+        buf.push(0);
+        buf.extend_from_slice(&instances.to_le_bytes());
+        buf.extend_from_slice(&0u32.to_le_bytes());
+        put_str(buf, "PORT=3000");
+    }
+
+    fn valid_state() -> Vec<u8> {
+        let mut buf = 2u32.to_le_bytes().to_vec();
+        put_record(&mut buf, "api", "server.js", 4);
+        put_record(&mut buf, "worker", "worker.py", 1);
+        buf
+    }
+
+    #[test]
+    fn test_parse_valid_state() {
+        let state = parse_state(&valid_state()).unwrap();
+        assert_eq!(state.trailing_bytes, 0);
+        assert_eq!(state.processes.len(), 2);
+        assert_eq!(state.processes[0].name, "api");
+        assert_eq!(state.processes[0].script, "server.js");
+        assert_eq!(state.processes[0].cwd, "/srv/app");
+        assert_eq!(state.processes[0].instances, 4);
+        assert_eq!(state.processes[1].name, "worker");
+
+        let empty = parse_state(&0u32.to_le_bytes()).unwrap();
+        assert!(empty.processes.is_empty());
+    }
+
+    #[test]
+    fn test_parse_versioned_state() {
+        let mut buf = STATE_MAGIC.to_le_bytes().to_vec();
+        buf.extend_from_slice(&2u32.to_le_bytes());
+        buf.extend_from_slice(&2u32.to_le_bytes());
+        put_record(&mut buf, "api", "server.js", 4);
+        let mut ext = Vec::new();
+        put_str(&mut ext, "");
+        put_str(&mut ext, "");
+        ext.push(0);
+        put_str(&mut ext, "/var/log/api.out");
+        put_str(&mut ext, "");
+        ext.extend_from_slice(&[0; 8]); // the rest isn't read
+        buf.extend_from_slice(&(ext.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&ext);
+        // An extension this reader can't make sense of is still skipped
+        put_record(&mut buf, "worker", "server.js", 1);
+        let ext = b"any extension bytes";
+        buf.extend_from_slice(&(ext.len() as u32).to_le_bytes());
+        buf.extend_from_slice(ext);
+
+        let state = parse_state(&buf).unwrap();
+        assert_eq!(state.trailing_bytes, 0);
+        let names: Vec<_> = state.processes.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["api", "worker"]);
+        assert_eq!(state.processes[0].instances, 4);
+        assert_eq!(state.processes[0].out_log, "/var/log/api.out");
+        assert_eq!(state.processes[1].out_log, "");
+
+        // A cut extension block is a truncated record
+        let err = parse_state(&buf[..buf.len() - 3]).unwrap_err();
+        assert!(matches!(
+            err,
+            StateFileError::Truncated {
+                declared: 2,
+                parsed: 1
+            }
+        ));
+        assert!(matches!(
+            parse_state(&buf[..6]),
+            Err(StateFileError::MissingHeader(6))
+        ));
+    }
+
+    #[test]
+    fn test_parse_truncated_state() {
+        let data = valid_state();
+        let mut first = Vec::new();
+        put_record(&mut first, "api", "server.js", 4);
+        // Cut into the middle of the second record's script.
+        let cut = 4 + first.len() + (4 + "worker".len()) + 4 + 3;
+        let err = parse_state(&data[..cut]).unwrap_err();
+        assert!(matches!(
+            err,
+            StateFileError::Truncated {
+                declared: 2,
+                parsed: 1
+            }
+        ));
+
+        assert!(matches!(
+            parse_state(&[2, 0]),
+            Err(StateFileError::MissingHeader(2))
+        ));
+
+        // Header claims fewer records than the file holds.
+        let mut data = valid_state();
+        data[0] = 1;
+        assert!(parse_state(&data).unwrap().trailing_bytes > 0);
+    }
+
+    #[test]
+    fn test_read_state_file_missing() {
+        let path = std::env::temp_dir().join("velos_state_missing_does_not_exist.bin");
+        assert!(read_state_file(&path).unwrap().is_none());
+    }
+}
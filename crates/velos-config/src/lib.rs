@@ -1,10 +1,12 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use velos_core::ProcessConfig;
 
+pub mod pm2;
+
 // ---------------------------------------------------------------------------
 // Errors
 // ---------------------------------------------------------------------------
@@ -17,6 +19,18 @@ pub enum ConfigError {
     #[error("failed to parse TOML: {0}")]
     Parse(#[from] toml::de::Error),
 
+    #[error("failed to parse YAML: {0}")]
+    ParseYaml(#[from] serde_yaml::Error),
+
+    #[error("failed to parse JSON: {0}")]
+    ParseJson(#[from] serde_json::Error),
+
+    #[error("unsupported config format '{0}' (use .toml, .yaml, .yml or .json)")]
+    UnsupportedFormat(String),
+
+    #[error("failed to write TOML: {0}")]
+    Serialize(#[from] toml::ser::Error),
+
     #[error("validation error: {0}")]
     Validation(String),
 
@@ -34,26 +48,133 @@ pub type Result<T> = std::result::Result<T, ConfigError>;
 // ---------------------------------------------------------------------------
 
 /// Configuration for the smart log engine pipeline.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct LogEngineConfig {
     /// Enable auto-classifier (default: true).
-    #[serde(default = "default_true")]
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
     pub classifier: bool,
     /// Dedup sliding window in seconds (default: 60).
-    #[serde(default = "default_dedup_window")]
+    #[serde(
+        default = "default_dedup_window",
+        skip_serializing_if = "is_default_dedup_window"
+    )]
     pub dedup_window: u64,
     /// Pattern detection time window in seconds (default: 300).
-    #[serde(default = "default_pattern_window")]
+    #[serde(
+        default = "default_pattern_window",
+        skip_serializing_if = "is_default_pattern_window"
+    )]
     pub pattern_window: u64,
-    /// Anomaly detection window size in minutes (default: 60).
-    #[serde(default = "default_anomaly_window")]
+    /// Anomaly detection window size in buckets (default: 60, an hour of
+    /// one-minute buckets).
+    #[serde(
+        default = "default_anomaly_window",
+        skip_serializing_if = "is_default_anomaly_window"
+    )]
     pub anomaly_window: u64,
+    /// Width of the anomaly detector's buckets in seconds (default: 60).
+    #[serde(
+        default = "default_anomaly_bucket_secs",
+        skip_serializing_if = "is_default_anomaly_bucket_secs"
+    )]
+    pub anomaly_bucket_secs: u64,
     /// Sigma threshold for anomaly warning (default: 2.0).
-    #[serde(default = "default_sigma_warn")]
+    #[serde(
+        default = "default_sigma_warn",
+        skip_serializing_if = "is_default_sigma_warn"
+    )]
     pub anomaly_sigma_warn: f64,
     /// Sigma threshold for anomaly critical (default: 3.0).
-    #[serde(default = "default_sigma_crit")]
+    #[serde(
+        default = "default_sigma_crit",
+        skip_serializing_if = "is_default_sigma_crit"
+    )]
     pub anomaly_sigma_crit: f64,
+    /// Classifier ruleset tuned for a log format (one of [`CLASSIFIER_PRESETS`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub classifier_preset: Option<String>,
+    /// JSON fields holding a line's level, checked in order (default: ["level"]).
+    #[serde(
+        default = "default_json_level_keys",
+        skip_serializing_if = "is_default_json_level_keys"
+    )]
+    pub json_level_keys: Vec<String>,
+    /// Lines `velos logs` shows when `--lines` isn't passed (default: 50).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_log_lines: Option<u32>,
+    /// Chars of a message `velos logs` shows when `--max-line-length`
+    /// isn't passed (default: no limit).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_line_length: Option<u32>,
+    /// Time window `velos logs` shows when neither `--since` nor `--until`
+    /// is passed (e.g. "24h"). Unset shows the last `--lines`, however old.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_window: Option<String>,
+    /// Level -> color name for `velos logs` (e.g. error = "magenta").
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_sorted"
+    )]
+    pub level_colors: HashMap<String, String>,
+    /// Custom classification rules (`[[logs.rules]]`), checked along with
+    /// the built-in ones by priority.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<ClassifyRule>,
+}
+
+/// One `[[logs.rules]]` entry: lines matching `pattern` get `level`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClassifyRule {
+    /// Regex matched against the message.
+    pub pattern: String,
+    /// A level name or syslog severity, as [`velos_core::level::from_name`]
+    /// reads it ("warn", "warning", "err", "3", ...).
+    pub level: String,
+    /// Higher is checked first (default: 15, ahead of the built-in rules at
+    /// 4-10 but behind a `classifier_preset`'s at 20).
+    #[serde(
+        default = "default_rule_priority",
+        skip_serializing_if = "is_default_rule_priority"
+    )]
+    pub priority: u8,
+}
+
+/// Named classifier presets accepted by `logs.classifier_preset`.
+pub const CLASSIFIER_PRESETS: &[&str] = &["nginx", "rails", "json"];
+
+/// SGR codes for the color names accepted by `--color-map` and
+/// `logs.level_colors`. "none" leaves the line uncolored.
+pub const COLOR_NAMES: &[(&str, &str)] = &[
+    ("black", "30"),
+    ("red", "31"),
+    ("green", "32"),
+    ("yellow", "33"),
+    ("blue", "34"),
+    ("magenta", "35"),
+    ("cyan", "36"),
+    ("white", "37"),
+    ("gray", "90"),
+    ("grey", "90"),
+    ("bright_red", "91"),
+    ("bright_green", "92"),
+    ("bright_yellow", "93"),
+    ("bright_blue", "94"),
+    ("bright_magenta", "95"),
+    ("bright_cyan", "96"),
+    ("bright_white", "97"),
+    ("bold", "1"),
+    ("dim", "2"),
+    ("none", ""),
+];
+
+/// SGR code for a color name, case-insensitive; `-` works like `_`.
+pub fn color_code(name: &str) -> Option<&'static str> {
+    let name = name.trim().to_ascii_lowercase().replace('-', "_");
+    COLOR_NAMES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, code)| *code)
 }
 
 impl Default for LogEngineConfig {
@@ -63,100 +184,283 @@ impl Default for LogEngineConfig {
             dedup_window: default_dedup_window(),
             pattern_window: default_pattern_window(),
             anomaly_window: default_anomaly_window(),
+            anomaly_bucket_secs: default_anomaly_bucket_secs(),
             anomaly_sigma_warn: default_sigma_warn(),
             anomaly_sigma_crit: default_sigma_crit(),
+            classifier_preset: None,
+            json_level_keys: default_json_level_keys(),
+            default_log_lines: None,
+            max_line_length: None,
+            default_window: None,
+            level_colors: HashMap::new(),
+            rules: Vec::new(),
         }
     }
 }
 
 /// Top-level TOML config file (`velos.toml`).
-#[derive(Debug, Clone, Deserialize)]
+///
+/// Serializing omits anything left at its default and sorts every map, so
+/// the output is the canonical form `velos config fmt` writes.
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct VelosConfig {
-    /// Per-app configs, keyed by app name.
-    #[serde(default)]
-    pub apps: HashMap<String, AppConfig>,
     /// Log engine pipeline configuration.
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub logs: Option<LogEngineConfig>,
+    /// `[defaults]`: any `[apps.*]` field, inherited by every app that
+    /// doesn't set it. Already merged into `apps` once parsed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub defaults: Option<toml::Table>,
+    /// Per-app configs, keyed by app name.
+    #[serde(default, serialize_with = "serialize_sorted")]
+    pub apps: HashMap<String, AppConfig>,
 }
 
 /// Configuration for a single application.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct AppConfig {
     /// Process name (defaults to the TOML key if omitted).
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
     /// Path to the script or binary to execute.
     pub script: String,
     /// Working directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cwd: Option<String>,
     /// Interpreter (e.g. "node", "python3"). None = auto-detect or run directly.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub interpreter: Option<String>,
     /// Script arguments.
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub args: Vec<String>,
-    /// Number of instances (1 = fork mode).
-    #[serde(default = "default_instances")]
-    pub instances: u32,
+    /// Number of instances (1 = fork mode), or "max" for one per CPU.
+    #[serde(
+        default = "default_instances",
+        skip_serializing_if = "is_default_instances"
+    )]
+    pub instances: Instances,
+    /// Cluster instance naming, e.g. "{name}-{index}" (default "{name}:{index}").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance_name_template: Option<String>,
+    /// Upper bound for `velos scale` and `instances = "max"` (None = unlimited).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_instances: Option<u32>,
     /// Auto-restart on crash.
-    #[serde(default = "default_true")]
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
     pub autorestart: bool,
     /// Max restart attempts (-1 = unlimited).
-    #[serde(default = "default_max_restarts")]
+    #[serde(
+        default = "default_max_restarts",
+        skip_serializing_if = "is_default_max_restarts"
+    )]
     pub max_restarts: i32,
     /// Minimum uptime in ms to be considered stable.
-    #[serde(default = "default_min_uptime")]
+    #[serde(
+        default = "default_min_uptime",
+        skip_serializing_if = "is_default_min_uptime"
+    )]
     pub min_uptime: u64,
     /// Delay between restarts in ms.
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "is_zero")]
     pub restart_delay: u64,
     /// Enable exponential backoff for restart delay.
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "is_false")]
     pub exp_backoff_restart_delay: bool,
+    /// Exit codes that count as a clean exit rather than a crash.
+    #[serde(
+        default = "default_success_exit_codes",
+        skip_serializing_if = "is_default_success_exit_codes"
+    )]
+    pub success_exit_codes: Vec<i32>,
+    /// Don't autorestart after a clean exit (one of `success_exit_codes`).
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub stop_on_success: bool,
     /// Kill timeout in ms (SIGTERM → wait → SIGKILL).
-    #[serde(default = "default_kill_timeout")]
+    #[serde(
+        default = "default_kill_timeout",
+        skip_serializing_if = "is_default_kill_timeout"
+    )]
     pub kill_timeout: u64,
     /// Max memory before forced restart (human-readable, e.g. "150M").
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_memory_restart: Option<String>,
+    /// CPU% (0-100] that, sustained for `cpu_restart_window_secs`, forces a restart.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_cpu_restart: Option<f32>,
+    /// How long CPU must stay above `max_cpu_restart`, in seconds.
+    #[serde(
+        default = "default_cpu_restart_window",
+        skip_serializing_if = "is_default_cpu_restart_window"
+    )]
+    pub cpu_restart_window_secs: u32,
     /// Enable file watching.
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "is_false")]
     pub watch: bool,
     /// Paths to watch (relative to cwd).
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub watch_paths: Vec<String>,
     /// Patterns to ignore when watching.
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub watch_ignore: Vec<String>,
     /// Watch debounce delay in ms.
-    #[serde(default = "default_watch_delay")]
+    #[serde(
+        default = "default_watch_delay",
+        skip_serializing_if = "is_default_watch_delay"
+    )]
     pub watch_delay: u64,
     /// Max log file size (human-readable, e.g. "10M").
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub log_max_size: Option<String>,
     /// Number of rotated log files to keep.
-    #[serde(default = "default_log_retain")]
+    #[serde(
+        default = "default_log_retain",
+        skip_serializing_if = "is_default_log_retain"
+    )]
     pub log_retain_count: u32,
     /// Cron expression for periodic restarts.
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub cron_restart: Option<String>,
     /// Custom log file path.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub log_file: Option<String>,
     /// Merge stdout and stderr into a single log.
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "is_false")]
     pub merge_logs: bool,
-
+    /// Logical group, targetable as `@group` (e.g. `velos restart @backend`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Max log lines per second; the daemon drops the excess and logs a
+    /// "(N lines suppressed)" marker. None = unlimited.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_rate_limit: Option<u32>,
+    /// Shell command `velos start` runs (in the app's cwd) before starting
+    /// the app, e.g. a migration. A non-zero exit aborts the start.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_start: Option<String>,
+    /// Shell command `velos stop` runs before stopping the app.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_stop: Option<String>,
+    /// Shell command `velos stop` runs after the app has stopped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_stop: Option<String>,
+    /// How long each hook may run, in ms, before it is killed.
+    #[serde(
+        default = "default_hook_timeout",
+        skip_serializing_if = "is_default_hook_timeout"
+    )]
+    pub hook_timeout: u64,
+
+    /// Dotenv file (`KEY=VALUE` lines) loaded underneath `env`. A relative
+    /// path is taken from the app's `cwd`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env_file: Option<String>,
     /// Base environment variables.
-    #[serde(default)]
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_sorted"
+    )]
     pub env: HashMap<String, String>,
     /// Environment profile overrides (e.g. env_production, env_staging).
-    #[serde(flatten, deserialize_with = "deserialize_env_profiles")]
+    #[serde(
+        flatten,
+        deserialize_with = "deserialize_env_profiles",
+        serialize_with = "serialize_env_profiles"
+    )]
     pub env_profiles: HashMap<String, HashMap<String, String>>,
 }
 
+/// `instances` as written in the config: a count, or `"max"`, which is
+/// only turned into a number by [`Instances::resolve`] when the app starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instances {
+    Count(u32),
+    Max,
+}
+
+impl Instances {
+    /// The instance count to start: "max" is this machine's CPU count,
+    /// capped at `max_instances` when the app sets one.
+    pub fn resolve(self, max_instances: Option<u32>) -> u32 {
+        match self {
+            Self::Count(n) => n,
+            Self::Max => {
+                let cpus = std::thread::available_parallelism().map_or(1, |n| n.get() as u32);
+                max_instances.map_or(cpus, |max| cpus.min(max))
+            }
+        }
+    }
+}
+
+impl From<u32> for Instances {
+    fn from(n: u32) -> Self {
+        Self::Count(n)
+    }
+}
+
+impl std::fmt::Display for Instances {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Count(n) => write!(f, "{n}"),
+            Self::Max => f.write_str("max"),
+        }
+    }
+}
+
+impl Serialize for Instances {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Self::Count(n) => serializer.serialize_u32(*n),
+            Self::Max => serializer.serialize_str("max"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Instances {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        struct Visitor;
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = Instances;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("an instance count or \"max\"")
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, n: u64) -> std::result::Result<Instances, E> {
+                u32::try_from(n)
+                    .map(Instances::Count)
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Unsigned(n), &self))
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, n: i64) -> std::result::Result<Instances, E> {
+                u64::try_from(n)
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Signed(n), &self))
+                    .and_then(|n| self.visit_u64(n))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> std::result::Result<Instances, E> {
+                if s.eq_ignore_ascii_case("max") {
+                    Ok(Instances::Max)
+                } else {
+                    Err(E::invalid_value(serde::de::Unexpected::Str(s), &self))
+                }
+            }
+        }
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Serde defaults
 // ---------------------------------------------------------------------------
 
-fn default_instances() -> u32 {
-    1
+fn default_instances() -> Instances {
+    Instances::Count(1)
 }
 fn default_true() -> bool {
     true
@@ -167,9 +471,18 @@ fn default_max_restarts() -> i32 {
 fn default_min_uptime() -> u64 {
     1000
 }
+fn default_success_exit_codes() -> Vec<i32> {
+    vec![0]
+}
 fn default_kill_timeout() -> u64 {
     5000
 }
+fn default_cpu_restart_window() -> u32 {
+    60
+}
+fn default_hook_timeout() -> u64 {
+    60_000
+}
 fn default_watch_delay() -> u64 {
     1000
 }
@@ -185,12 +498,97 @@ fn default_pattern_window() -> u64 {
 fn default_anomaly_window() -> u64 {
     60
 }
+fn default_anomaly_bucket_secs() -> u64 {
+    60
+}
 fn default_sigma_warn() -> f64 {
     2.0
 }
 fn default_sigma_crit() -> f64 {
     3.0
 }
+fn default_json_level_keys() -> Vec<String> {
+    vec!["level".into()]
+}
+fn default_rule_priority() -> u8 {
+    15
+}
+
+// Serialization leaves out fields still at their default, so `config fmt`
+// doesn't spell out every knob the user never touched.
+
+fn is_true(v: &bool) -> bool {
+    *v
+}
+fn is_false(v: &bool) -> bool {
+    !*v
+}
+fn is_zero(v: &u64) -> bool {
+    *v == 0
+}
+fn is_default_rule_priority(v: &u8) -> bool {
+    *v == default_rule_priority()
+}
+fn is_default_instances(v: &Instances) -> bool {
+    *v == default_instances()
+}
+fn is_default_max_restarts(v: &i32) -> bool {
+    *v == default_max_restarts()
+}
+fn is_default_min_uptime(v: &u64) -> bool {
+    *v == default_min_uptime()
+}
+fn is_default_success_exit_codes(v: &[i32]) -> bool {
+    v == default_success_exit_codes()
+}
+fn is_default_cpu_restart_window(v: &u32) -> bool {
+    *v == default_cpu_restart_window()
+}
+fn is_default_hook_timeout(v: &u64) -> bool {
+    *v == default_hook_timeout()
+}
+fn is_default_kill_timeout(v: &u64) -> bool {
+    *v == default_kill_timeout()
+}
+fn is_default_watch_delay(v: &u64) -> bool {
+    *v == default_watch_delay()
+}
+fn is_default_log_retain(v: &u32) -> bool {
+    *v == default_log_retain()
+}
+fn is_default_dedup_window(v: &u64) -> bool {
+    *v == default_dedup_window()
+}
+fn is_default_pattern_window(v: &u64) -> bool {
+    *v == default_pattern_window()
+}
+fn is_default_anomaly_window(v: &u64) -> bool {
+    *v == default_anomaly_window()
+}
+fn is_default_anomaly_bucket_secs(v: &u64) -> bool {
+    *v == default_anomaly_bucket_secs()
+}
+fn is_default_sigma_warn(v: &f64) -> bool {
+    *v == default_sigma_warn()
+}
+fn is_default_sigma_crit(v: &f64) -> bool {
+    *v == default_sigma_crit()
+}
+fn is_default_json_level_keys(v: &[String]) -> bool {
+    v == default_json_level_keys()
+}
+
+/// Write a map with its keys in order rather than hash order.
+fn serialize_sorted<S, V>(
+    map: &HashMap<String, V>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+    V: Serialize,
+{
+    serializer.collect_map(map.iter().collect::<BTreeMap<_, _>>())
+}
 
 // ---------------------------------------------------------------------------
 // Custom deserializer for env_* profile fields
@@ -224,20 +622,81 @@ where
     Ok(profiles)
 }
 
+/// Inverse of [`deserialize_env_profiles`]: one sorted `env_<profile>`
+/// table per profile.
+fn serialize_env_profiles<S>(
+    profiles: &HashMap<String, HashMap<String, String>>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.collect_map(
+        profiles
+            .iter()
+            .map(|(name, env)| {
+                (
+                    format!("env_{name}"),
+                    env.iter().collect::<BTreeMap<_, _>>(),
+                )
+            })
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
 // ---------------------------------------------------------------------------
 // Public API
 // ---------------------------------------------------------------------------
 
-/// Load and parse a TOML config file.
+/// Load and parse a config file. The extension picks the format: `.yaml`
+/// or `.yml` for YAML, `.json` for JSON, `.toml` (or none) for TOML.
+///
+/// Relative `env_file`s are pointed at their app's directory but not read:
+/// [`merged_env`] reports a missing one for the app that needs it, so it
+/// doesn't fail commands about other apps.
 pub fn load(path: &Path) -> Result<VelosConfig> {
+    let mut config = validate(load_unchecked(path)?)?;
+    let config_dir = path.parent().unwrap_or(Path::new(""));
+    for app in config.apps.values_mut() {
+        anchor_env_file(app, config_dir);
+    }
+    Ok(config)
+}
+
+/// Only the `[logs]` table of a config file, validated, for commands that
+/// read logs: apps are neither checked nor have their env files read, so a
+/// broken app doesn't cost the log settings. `Ok(None)` if the file doesn't
+/// exist or has no `[logs]` table.
+pub fn load_logs(path: &Path) -> Result<Option<LogEngineConfig>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let logs = load_unchecked(path)?.logs;
+    if let Some(ref logs) = logs {
+        validate_logs(logs)?;
+    }
+    Ok(logs)
+}
+
+/// Read a config file like [`load`] without validating it, for reporting on
+/// every app at once (`velos validate`).
+pub fn load_unchecked(path: &Path) -> Result<VelosConfig> {
+    let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase());
+    let deserialize: fn(&str) -> Result<VelosConfig> = match extension.as_deref() {
+        None | Some("toml") => from_toml,
+        Some("yaml" | "yml") => from_yaml,
+        Some("json") => from_json,
+        Some(other) => return Err(ConfigError::UnsupportedFormat(other.to_string())),
+    };
     let content = std::fs::read_to_string(path)?;
-    parse(&content)
+    Ok(fill_names(deserialize(&content)?))
 }
 
 /// Load a TOML config file and apply an environment profile.
 pub fn load_with_env(path: &Path, env_profile: &str) -> Result<VelosConfig> {
     let mut config = load(path)?;
     for app in config.apps.values_mut() {
+        app.env = merged_env(app, None)?;
         apply_env_profile(app, env_profile);
     }
     Ok(config)
@@ -245,23 +704,265 @@ pub fn load_with_env(path: &Path, env_profile: &str) -> Result<VelosConfig> {
 
 /// Parse TOML string into a VelosConfig.
 pub fn parse(toml_str: &str) -> Result<VelosConfig> {
-    let mut config: VelosConfig = toml::from_str(toml_str)?;
+    validate(fill_names(from_toml(toml_str)?))
+}
+
+/// Parse a YAML document with the same layout as velos.toml.
+pub fn parse_yaml(yaml_str: &str) -> Result<VelosConfig> {
+    validate(fill_names(from_yaml(yaml_str)?))
+}
 
-    // Back-fill name from TOML key if not explicitly set.
+/// Parse a JSON document with the same layout as velos.toml.
+pub fn parse_json(json_str: &str) -> Result<VelosConfig> {
+    validate(fill_names(from_json(json_str)?))
+}
+
+// Each format is read twice when there is a `[defaults]` table: once as a
+// plain document to merge it into the apps, then into `VelosConfig`.
+// Without one, deserializing straight from the text keeps line numbers in
+// type errors.
+
+fn from_toml(s: &str) -> Result<VelosConfig> {
+    let mut doc: serde_json::Value = toml::from_str(s)?;
+    if !apply_defaults(&mut doc) {
+        return Ok(toml::from_str(s)?);
+    }
+    Ok(toml::Value::try_from(doc)?.try_into()?)
+}
+
+fn from_yaml(s: &str) -> Result<VelosConfig> {
+    let mut doc: serde_json::Value = serde_yaml::from_str(s)?;
+    if !apply_defaults(&mut doc) {
+        return Ok(serde_yaml::from_str(s)?);
+    }
+    Ok(serde_yaml::from_value(serde_yaml::to_value(doc)?)?)
+}
+
+fn from_json(s: &str) -> Result<VelosConfig> {
+    let mut doc: serde_json::Value = serde_json::from_str(s)?;
+    apply_defaults(&mut doc);
+    Ok(serde_json::from_value(doc)?)
+}
+
+/// Fill each app's unset fields from the document's `defaults` table.
+/// Precedence: a field an app sets always wins, even if it equals the
+/// built-in default; tables (`env`, `env_<profile>`) are merged key by key
+/// the same way; lists such as `watch_ignore` are replaced, not appended to.
+/// Returns whether there was a `defaults` table.
+fn apply_defaults(doc: &mut serde_json::Value) -> bool {
+    let Some(defaults) = doc.get("defaults").and_then(|d| d.as_object()).cloned() else {
+        return false;
+    };
+    if let Some(apps) = doc.get_mut("apps").and_then(|a| a.as_object_mut()) {
+        for app in apps.values_mut().filter_map(|a| a.as_object_mut()) {
+            inherit(app, &defaults);
+        }
+    }
+    true
+}
+
+fn inherit(
+    app: &mut serde_json::Map<String, serde_json::Value>,
+    defaults: &serde_json::Map<String, serde_json::Value>,
+) {
+    for (key, default) in defaults {
+        match (app.get_mut(key), default) {
+            (None, _) => {
+                app.insert(key.clone(), default.clone());
+            }
+            (Some(serde_json::Value::Object(set)), serde_json::Value::Object(default)) => {
+                inherit(set, default)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Back-fill each app's name from its table key if not explicitly set.
+fn fill_names(mut config: VelosConfig) -> VelosConfig {
     for (key, app) in config.apps.iter_mut() {
         if app.name.is_none() {
             app.name = Some(key.clone());
         }
     }
+    config
+}
 
-    // Validate all apps.
-    for (key, app) in &config.apps {
-        validate_app(key, app)?;
+/// Reports every invalid app, not just the first: one failure is returned
+/// as is, several are joined into a single `Validation` error.
+fn validate(config: VelosConfig) -> Result<VelosConfig> {
+    if config
+        .defaults
+        .as_ref()
+        .is_some_and(|d| d.contains_key("name"))
+    {
+        return Err(ConfigError::Validation(
+            "[defaults] cannot set name: each app is named by its table key or its own name".into(),
+        ));
+    }
+    let mut errors = validate_all(&config);
+    if errors.len() == 1 {
+        return Err(errors.remove(0).1);
+    }
+    if !errors.is_empty() {
+        let messages: Vec<String> = errors
+            .into_iter()
+            .map(|(_, e)| match e {
+                ConfigError::Validation(why) => why,
+                e => e.to_string(),
+            })
+            .collect();
+        return Err(ConfigError::Validation(messages.join("; ")));
+    }
+    if let Some(logs) = &config.logs {
+        validate_logs(logs)?;
     }
 
     Ok(config)
 }
 
+/// Render a config in canonical form: `[logs]` first, apps sorted by key,
+/// fields in declaration order, env tables sorted, defaults omitted.
+/// Formatting the output again yields the same text.
+///
+/// With `[defaults]`, each app is written relative to it: values it inherits
+/// unchanged are left out. If an app explicitly sets a field back to the
+/// built-in default that `[defaults]` overrides, which that can't express,
+/// every app is written in full and `[defaults]` is dropped.
+pub fn format(config: &VelosConfig) -> Result<String> {
+    let mut config = config.clone();
+    // `parse` back-fills names from the table key; don't write those out
+    for (key, app) in config.apps.iter_mut() {
+        if app.name.as_deref() == Some(key.as_str()) {
+            app.name = None;
+        }
+    }
+    let Some(ref defaults) = config.defaults else {
+        return Ok(toml::to_string(&config)?);
+    };
+
+    let mut apps = BTreeMap::new();
+    for (key, app) in &config.apps {
+        let mut table = toml::Table::try_from(app)?;
+        if defaults
+            .iter()
+            .any(|(field, value)| !table.contains_key(field) && !is_builtin_default(field, value))
+        {
+            config.defaults = None;
+            return Ok(toml::to_string(&config)?);
+        }
+        strip_inherited(&mut table, defaults);
+        apps.insert(key.clone(), table);
+    }
+
+    #[derive(Serialize)]
+    struct Relative<'a> {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        logs: &'a Option<LogEngineConfig>,
+        defaults: &'a toml::Table,
+        apps: BTreeMap<String, toml::Table>,
+    }
+    Ok(toml::to_string(&Relative {
+        logs: &config.logs,
+        defaults,
+        apps,
+    })?)
+}
+
+/// Drop what an app inherits unchanged from `[defaults]`, table entries
+/// one by one.
+fn strip_inherited(app: &mut toml::Table, defaults: &toml::Table) {
+    for (field, default) in defaults {
+        let inherited = match (app.get_mut(field), default) {
+            (Some(toml::Value::Table(set)), toml::Value::Table(default)) => {
+                strip_inherited(set, default);
+                set.is_empty()
+            }
+            (Some(value), default) => value == default,
+            (None, _) => false,
+        };
+        if inherited {
+            app.remove(field);
+        }
+    }
+}
+
+/// What [`format`] would lose from the config text `original`, one line per
+/// key: keys velos doesn't know (they are parsed and ignored, so formatting
+/// leaves them out) and `env_*` values that aren't strings (written back
+/// quoted). Keys left out because they restate a default aren't listed.
+pub fn format_losses(original: &str) -> Result<Vec<String>> {
+    let source: toml::Table = toml::from_str(original)?;
+    let formatted: toml::Table = toml::from_str(&format(&parse(original)?)?)?;
+    let mut losses = Vec::new();
+    collect_losses(&source, &source, &formatted, "", &mut losses);
+    Ok(losses)
+}
+
+fn collect_losses(
+    root: &toml::Table,
+    source: &toml::Table,
+    formatted: &toml::Table,
+    prefix: &str,
+    losses: &mut Vec<String>,
+) {
+    for (key, value) in source {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match (value, formatted.get(key)) {
+            (toml::Value::Table(inner), Some(toml::Value::Table(out))) => {
+                collect_losses(root, inner, out, &path, losses)
+            }
+            (value, Some(toml::Value::String(out)))
+                if !value.is_str() && value.to_string() == *out =>
+            {
+                losses.push(format!("{path}: {value} would be written as \"{out}\""));
+            }
+            (_, Some(_)) => {}
+            (_, None) if is_ignored_key(root, &path) => {
+                losses.push(format!("{path}: unknown key would be dropped"));
+            }
+            (_, None) => {}
+        }
+    }
+}
+
+/// Whether the key at `path` is ignored by [`parse`]: putting a value of a
+/// type no field takes (a datetime) there still parses.
+fn is_ignored_key(root: &toml::Table, path: &str) -> bool {
+    let mut probe = root.clone();
+    let mut table = &mut probe;
+    let mut keys = path.split('.').peekable();
+    while let Some(key) = keys.next() {
+        if keys.peek().is_none() {
+            let stamp = "1979-05-27T07:32:00Z".parse().expect("valid datetime");
+            table.insert(key.into(), toml::Value::Datetime(stamp));
+            break;
+        }
+        match table.get_mut(key) {
+            Some(toml::Value::Table(inner)) => table = inner,
+            _ => return false,
+        }
+    }
+    toml::to_string(&probe).is_ok_and(|text| parse(&text).is_ok())
+}
+
+/// Whether `value` is what an app gets for `field` when it sets nothing,
+/// so serializing an app leaves it out.
+fn is_builtin_default(field: &str, value: &toml::Value) -> bool {
+    let mut probe = toml::Table::new();
+    probe.insert("script".into(), "probe".into());
+    probe.insert(field.into(), value.clone());
+    toml::Value::Table(probe)
+        .try_into::<AppConfig>()
+        .ok()
+        .and_then(|app| toml::Table::try_from(&app).ok())
+        .is_some_and(|t| !t.contains_key(field))
+}
+
 impl VelosConfig {
     /// Get config for a specific app by name.
     pub fn get_app(&self, name: &str) -> Option<&AppConfig> {
@@ -278,7 +979,96 @@ impl VelosConfig {
 // Validation
 // ---------------------------------------------------------------------------
 
-fn validate_app(key: &str, app: &AppConfig) -> Result<()> {
+/// Check the `[logs]` section.
+pub fn validate_logs(logs: &LogEngineConfig) -> Result<()> {
+    if let Some(preset) = &logs.classifier_preset {
+        if !CLASSIFIER_PRESETS.contains(&preset.as_str()) {
+            return Err(ConfigError::Validation(format!(
+                "logs: unknown classifier_preset '{}' (expected one of: {})",
+                preset,
+                CLASSIFIER_PRESETS.join(", ")
+            )));
+        }
+    }
+    if logs.json_level_keys.iter().any(|k| k.is_empty()) {
+        return Err(ConfigError::Validation(
+            "logs: json_level_keys must not contain empty names".into(),
+        ));
+    }
+    if logs.default_log_lines == Some(0) {
+        return Err(ConfigError::Validation(
+            "logs: default_log_lines must be at least 1".into(),
+        ));
+    }
+    if logs.anomaly_bucket_secs == 0 {
+        return Err(ConfigError::Validation(
+            "logs: anomaly_bucket_secs must be at least 1".into(),
+        ));
+    }
+    if logs.max_line_length == Some(0) {
+        return Err(ConfigError::Validation(
+            "logs: max_line_length must be at least 1 (omit it for no limit)".into(),
+        ));
+    }
+    if let Some(window) = &logs.default_window {
+        if !window.ends_with(['s', 'm', 'h', 'd'])
+            || window[..window.len() - 1].parse::<u64>().is_err()
+        {
+            return Err(ConfigError::Validation(format!(
+                "logs: default_window '{window}' must be a duration like 30m, 24h or 7d"
+            )));
+        }
+    }
+    let mut level_colors: Vec<_> = logs.level_colors.iter().collect();
+    level_colors.sort();
+    for (level, color) in level_colors {
+        if velos_core::level::from_name(level).is_none() {
+            return Err(ConfigError::Validation(format!(
+                "logs.level_colors: unknown level '{level}' (use debug, info, warn, error or fatal)"
+            )));
+        }
+        if color_code(color).is_none() {
+            let names: Vec<&str> = COLOR_NAMES.iter().map(|(n, _)| *n).collect();
+            return Err(ConfigError::Validation(format!(
+                "logs.level_colors: unknown color '{color}' for {level} (one of: {})",
+                names.join(", ")
+            )));
+        }
+    }
+    for (i, rule) in logs.rules.iter().enumerate() {
+        if let Err(e) = regex::Regex::new(&rule.pattern) {
+            return Err(ConfigError::Validation(format!(
+                "logs.rules[{i}]: invalid pattern '{}': {e}",
+                rule.pattern
+            )));
+        }
+        if velos_core::level::from_name(&rule.level).is_none() {
+            return Err(ConfigError::Validation(format!(
+                "logs.rules[{i}]: unknown level '{}' (expected debug, info, warn, error, fatal or a syslog severity 0-7)",
+                rule.level
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Check every app, in table-key order, collecting each failure with the
+/// app's name instead of stopping at the first. Empty when all are valid.
+pub fn validate_all(config: &VelosConfig) -> Vec<(String, ConfigError)> {
+    let mut keys: Vec<&String> = config.apps.keys().collect();
+    keys.sort();
+    keys.into_iter()
+        .filter_map(|key| {
+            let app = &config.apps[key];
+            validate_app(key, app)
+                .err()
+                .map(|e| (app.name.clone().unwrap_or_else(|| key.clone()), e))
+        })
+        .collect()
+}
+
+/// Check one app; `key` is its table key, used when `name` is unset.
+pub fn validate_app(key: &str, app: &AppConfig) -> Result<()> {
     let name = app.name.as_deref().unwrap_or(key);
 
     // Name must be non-empty, alphanumeric + dash + underscore.
@@ -317,6 +1107,21 @@ fn validate_app(key: &str, app: &AppConfig) -> Result<()> {
         )));
     }
 
+    if app.success_exit_codes.is_empty() {
+        return Err(ConfigError::Validation(format!(
+            "app '{name}': success_exit_codes must list at least one exit code"
+        )));
+    }
+    if let Some(code) = app
+        .success_exit_codes
+        .iter()
+        .find(|c| !(0..=255).contains(*c))
+    {
+        return Err(ConfigError::Validation(format!(
+            "app '{name}': success_exit_codes entry {code} is not an exit code (0-255)"
+        )));
+    }
+
     // Validate max_memory_restart if present.
     if let Some(ref mem) = app.max_memory_restart {
         parse_memory_string(mem).map_err(|_| {
@@ -326,6 +1131,19 @@ fn validate_app(key: &str, app: &AppConfig) -> Result<()> {
         })?;
     }
 
+    if let Some(cpu) = app.max_cpu_restart {
+        if !(cpu > 0.0 && cpu <= 100.0) {
+            return Err(ConfigError::Validation(format!(
+                "app '{name}': max_cpu_restart must be a percentage in (0, 100], got {cpu}"
+            )));
+        }
+    }
+    if app.cpu_restart_window_secs == 0 {
+        return Err(ConfigError::Validation(format!(
+            "app '{name}': cpu_restart_window_secs must be > 0"
+        )));
+    }
+
     // Validate log_max_size if present.
     if let Some(ref size) = app.log_max_size {
         parse_memory_string(size).map_err(|_| {
@@ -335,70 +1153,216 @@ fn validate_app(key: &str, app: &AppConfig) -> Result<()> {
         })?;
     }
 
-    // Basic cron validation (5 space-separated fields).
+    for (hook, command) in [
+        ("pre_start", &app.pre_start),
+        ("pre_stop", &app.pre_stop),
+        ("post_stop", &app.post_stop),
+    ] {
+        if command.as_ref().is_some_and(|c| c.trim().is_empty()) {
+            return Err(ConfigError::Validation(format!(
+                "app '{name}': {hook} must not be empty (omit it for no hook)"
+            )));
+        }
+    }
+    if app.hook_timeout == 0 {
+        return Err(ConfigError::Validation(format!(
+            "app '{name}': hook_timeout must be > 0ms"
+        )));
+    }
+
+    if app.log_rate_limit == Some(0) {
+        return Err(ConfigError::Validation(format!(
+            "app '{name}': log_rate_limit must be > 0 lines/sec (omit it for no limit)"
+        )));
+    }
+
     if let Some(ref cron) = app.cron_restart {
-        if !cron.is_empty() {
-            let fields: Vec<&str> = cron.split_whitespace().collect();
-            if fields.len() != 5 {
+        if let Err(ConfigError::Validation(why)) = validate_cron(cron) {
+            return Err(ConfigError::Validation(format!("app '{name}': {why}")));
+        }
+    }
+
+    // Instance name template must be one the daemon and resolvers understand.
+    if let Some(ref template) = app.instance_name_template {
+        if !velos_core::process::INSTANCE_NAME_TEMPLATES.contains(&template.as_str()) {
+            return Err(ConfigError::Validation(format!(
+                "app '{}': unsupported instance_name_template '{}' (expected one of: {})",
+                name,
+                template,
+                velos_core::process::INSTANCE_NAME_TEMPLATES.join(", ")
+            )));
+        }
+    }
+
+    // instances must be >= 1 ("max" always is).
+    let instances = app.instances.resolve(None);
+    if instances < 1 {
+        return Err(ConfigError::Validation(format!(
+            "app '{name}': instances must be >= 1, got {instances}"
+        )));
+    }
+
+    // max_instances must be >= 1 and cover the configured instance count.
+    if let Some(max) = app.max_instances {
+        if max < 1 {
+            return Err(ConfigError::Validation(format!(
+                "app '{name}': max_instances must be >= 1, got {max}"
+            )));
+        }
+        // "max" is capped at max_instances when resolved, not checked here:
+        // the CPU count of the machine validating isn't the one that starts it
+        if let Instances::Count(count) = app.instances {
+            if count > max {
                 return Err(ConfigError::Validation(format!(
-                    "app '{}': cron_restart must have 5 fields, got {}",
-                    name,
-                    fields.len()
+                    "app '{name}': instances ({count}) exceeds max_instances ({max})"
                 )));
             }
         }
     }
 
-    // instances must be >= 1.
-    if app.instances < 1 {
+    Ok(())
+}
+
+/// The five cron fields in order, with their valid ranges. Weekday 7 is
+/// Sunday, same as 0.
+const CRON_FIELDS: [(&str, u32, u32); 5] = [
+    ("minute", 0, 59),
+    ("hour", 0, 23),
+    ("day", 1, 31),
+    ("month", 1, 12),
+    ("weekday", 0, 7),
+];
+
+/// Check a `cron_restart` expression the way the daemon parses it: five
+/// fields, each a comma-separated list of `*`, `N`, `N-M`, `*/S` or `N-M/S`
+/// within the field's range. An empty string means no cron and passes.
+pub fn validate_cron(expr: &str) -> Result<()> {
+    if expr.is_empty() {
+        return Ok(());
+    }
+    let fields: Vec<&str> = expr.split_whitespace().collect();
+    if fields.len() != CRON_FIELDS.len() {
         return Err(ConfigError::Validation(format!(
-            "app '{}': instances must be >= 1, got {}",
-            name, app.instances
+            "cron_restart must have 5 fields, got {}",
+            fields.len()
         )));
     }
+    for (field, spec) in fields.into_iter().zip(CRON_FIELDS) {
+        validate_cron_field(field, spec)?;
+    }
+    Ok(())
+}
+
+fn validate_cron_field(field: &str, (name, min, max): (&str, u32, u32)) -> Result<()> {
+    let invalid = |why: String| {
+        ConfigError::Validation(format!("cron_restart {name} field '{field}': {why}"))
+    };
+    let number = |s: &str| {
+        s.bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| s.parse::<u32>().ok())
+            .flatten()
+            .ok_or_else(|| invalid(format!("'{s}' is not a number")))
+    };
 
+    for part in field.split(',') {
+        if part.is_empty() {
+            return Err(invalid("empty list item".into()));
+        }
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, Some(step)),
+            None => (part, None),
+        };
+        if let Some(step) = step {
+            if number(step)? == 0 {
+                return Err(invalid("step must be > 0".into()));
+            }
+        }
+        if range == "*" {
+            continue;
+        }
+        let (start, end) = match range.split_once('-') {
+            Some((start, end)) => (number(start)?, number(end)?),
+            None if step.is_none() => (number(range)?, number(range)?),
+            None => {
+                return Err(invalid(format!(
+                    "a step needs '*' or a range before it, not '{range}'"
+                )))
+            }
+        };
+        if let Some(v) = [start, end].into_iter().find(|v| !(min..=max).contains(v)) {
+            return Err(invalid(format!("{v} is out of range {min}-{max}")));
+        }
+        if start > end {
+            return Err(invalid(format!("range {start}-{end} is backwards")));
+        }
+    }
     Ok(())
 }
 
+/// Settings that are legal but probably not what was meant, e.g. an
+/// autorestarting app that may never restart. Assumes `validate_app` passed.
+pub fn app_warnings(app: &AppConfig) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if app.autorestart && app.max_restarts == 0 {
+        warnings.push(
+            "max_restarts = 0 with autorestart = true: the app is never restarted".to_string(),
+        );
+    }
+    if app.stop_on_success && !app.autorestart {
+        warnings.push("stop_on_success has no effect with autorestart = false".to_string());
+    }
+    if !app.watch && (!app.watch_paths.is_empty() || !app.watch_ignore.is_empty()) {
+        warnings.push("watch_paths/watch_ignore have no effect without watch = true".to_string());
+    }
+    warnings
+}
+
 // ---------------------------------------------------------------------------
 // Memory string parsing
 // ---------------------------------------------------------------------------
 
-/// Parse a human-readable memory string (e.g. "150M", "1G", "512K") into bytes.
+/// Parse a human-readable memory string (e.g. "150M", "1.5G", "2T", "512K") into
+/// bytes, rounded to the nearest byte.
 pub fn parse_memory_string(s: &str) -> std::result::Result<u64, ConfigError> {
     let s = s.trim();
     if s.is_empty() {
         return Err(ConfigError::InvalidMemory("empty string".into()));
     }
 
-    // Try to find suffix.
-    let (num_part, multiplier) = if let Some(num) = s.strip_suffix('G') {
-        (num, 1024u64 * 1024 * 1024)
-    } else if let Some(num) = s.strip_suffix('g') {
-        (num, 1024u64 * 1024 * 1024)
-    } else if let Some(num) = s.strip_suffix('M') {
-        (num, 1024u64 * 1024)
-    } else if let Some(num) = s.strip_suffix('m') {
-        (num, 1024u64 * 1024)
-    } else if let Some(num) = s.strip_suffix('K') {
-        (num, 1024u64)
-    } else if let Some(num) = s.strip_suffix('k') {
-        (num, 1024u64)
-    } else if let Some(num) = s.strip_suffix('B') {
-        (num, 1u64)
-    } else if let Some(num) = s.strip_suffix('b') {
-        (num, 1u64)
-    } else {
+    // Try to find suffix (case-insensitive).
+    const KIB: u64 = 1024;
+    let units = [
+        ('P', KIB.pow(5)),
+        ('T', KIB.pow(4)),
+        ('G', KIB.pow(3)),
+        ('M', KIB.pow(2)),
+        ('K', KIB),
+        ('B', 1),
+    ];
+    let (num_part, multiplier) = units
+        .into_iter()
+        .find_map(|(suffix, multiplier)| {
+            s.strip_suffix(suffix)
+                .or_else(|| s.strip_suffix(suffix.to_ascii_lowercase()))
+                .map(|num| (num, multiplier))
+        })
         // No suffix — assume bytes.
-        (s, 1u64)
-    };
+        .unwrap_or((s, 1));
 
-    let num: u64 = num_part
-        .trim()
-        .parse()
-        .map_err(|_| ConfigError::InvalidMemory(format!("cannot parse number: '{num_part}'")))?;
+    // Decimals are allowed ("1.5G"); signs, exponents and "inf" are not.
+    let num_part = num_part.trim();
+    let cannot_parse = || ConfigError::InvalidMemory(format!("cannot parse number: '{num_part}'"));
+    if !num_part.bytes().all(|b| b.is_ascii_digit() || b == b'.') {
+        return Err(cannot_parse());
+    }
+    let num: f64 = num_part.parse().map_err(|_| cannot_parse())?;
 
-    Ok(num * multiplier)
+    let bytes = (num * multiplier as f64).round();
+    if bytes >= u64::MAX as f64 {
+        return Err(ConfigError::InvalidMemory(format!("'{s}' is too large")));
+    }
+    Ok(bytes as u64)
 }
 
 // ---------------------------------------------------------------------------
@@ -416,8 +1380,16 @@ fn apply_env_profile(app: &mut AppConfig, profile: &str) {
 }
 
 /// Merge an app's env with a specific profile, returning the resulting HashMap.
-pub fn merged_env(app: &AppConfig, profile: Option<&str>) -> HashMap<String, String> {
-    let mut env = app.env.clone();
+/// Precedence, lowest first: `env_file`, `env`, the profile.
+/// Fails if the app's `env_file` is missing or malformed.
+pub fn merged_env(app: &AppConfig, profile: Option<&str>) -> Result<HashMap<String, String>> {
+    let mut env = match app.env_file {
+        Some(ref file) => {
+            read_app_env_file(app.name.as_deref().unwrap_or_default(), Path::new(file))?
+        }
+        None => HashMap::new(),
+    };
+    env.extend(app.env.clone());
     if let Some(profile) = profile {
         if let Some(profile_env) = app.env_profiles.get(profile) {
             for (k, v) in profile_env {
@@ -425,24 +1397,124 @@ pub fn merged_env(app: &AppConfig, profile: Option<&str>) -> HashMap<String, Str
             }
         }
     }
-    env
+    Ok(env)
 }
 
-// ---------------------------------------------------------------------------
-// CLI merge: AppConfig → ProcessConfig
-// ---------------------------------------------------------------------------
+/// Point a relative `env_file` at the app's `cwd` (itself relative to the
+/// config file's directory), and check that the file can be read.
+pub fn resolve_env_file(key: &str, app: &mut AppConfig, config_dir: &Path) -> Result<()> {
+    anchor_env_file(app, config_dir);
+    match app.env_file {
+        Some(ref file) => {
+            let name = app.name.as_deref().unwrap_or(key);
+            read_app_env_file(name, Path::new(file)).map(drop)
+        }
+        None => Ok(()),
+    }
+}
 
-/// Options from CLI that can override config values.
-#[derive(Debug, Default)]
-pub struct CliOverrides {
-    pub name: Option<String>,
-    pub script: Option<String>,
+/// Point a relative `env_file` at the app's `cwd`, itself relative to the
+/// config file's directory.
+fn anchor_env_file(app: &mut AppConfig, config_dir: &Path) {
+    let Some(ref file) = app.env_file else {
+        return;
+    };
+    let base = match app.cwd {
+        Some(ref cwd) => config_dir.join(cwd),
+        None => config_dir.to_path_buf(),
+    };
+    app.env_file = Some(base.join(file).to_string_lossy().into_owned());
+}
+
+/// [`read_env_file`] with errors naming the app.
+fn read_app_env_file(name: &str, path: &Path) -> Result<HashMap<String, String>> {
+    if !path.is_file() {
+        return Err(ConfigError::Validation(format!(
+            "app '{name}': env_file '{}' not found",
+            path.display()
+        )));
+    }
+    read_env_file(path).map_err(|e| {
+        let why = match e {
+            ConfigError::Validation(why) => why,
+            e => format!("'{}': {e}", path.display()),
+        };
+        ConfigError::Validation(format!("app '{name}': env_file {why}"))
+    })
+}
+
+/// Read a dotenv file: `KEY=VALUE` lines, optionally prefixed with `export`.
+/// Blank lines and `#` comments are skipped. Values may be single-quoted
+/// (taken literally) or double-quoted (`\n`, `\"` and `\\` escapes);
+/// unquoted values end at a ` #` comment.
+pub fn read_env_file(path: &Path) -> Result<HashMap<String, String>> {
+    let content = std::fs::read_to_string(path)?;
+    let mut env = HashMap::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let invalid =
+            |why: &str| ConfigError::Validation(format!("{}:{}: {why}", path.display(), index + 1));
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| invalid("expected KEY=VALUE"))?;
+        let key = key.trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err(invalid("invalid variable name"));
+        }
+        env.insert(
+            key.to_string(),
+            env_file_value(value.trim()).ok_or_else(|| invalid("unterminated quote"))?,
+        );
+    }
+    Ok(env)
+}
+
+fn env_file_value(raw: &str) -> Option<String> {
+    if let Some(rest) = raw.strip_prefix('\'') {
+        return rest.split_once('\'').map(|(value, _)| value.to_string());
+    }
+    if let Some(rest) = raw.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return Some(value),
+                '\\' => match chars.next()? {
+                    'n' => value.push('\n'),
+                    other => value.push(other),
+                },
+                c => value.push(c),
+            }
+        }
+        return None;
+    }
+    let value = match raw.find(" #") {
+        Some(comment) => &raw[..comment],
+        None => raw,
+    };
+    Some(value.trim_end().to_string())
+}
+
+// ---------------------------------------------------------------------------
+// CLI merge: AppConfig → ProcessConfig
+// ---------------------------------------------------------------------------
+
+/// Options from CLI that can override config values.
+#[derive(Debug, Default)]
+pub struct CliOverrides {
+    pub name: Option<String>,
+    pub script: Option<String>,
     pub cwd: Option<String>,
     pub interpreter: Option<String>,
     pub kill_timeout_ms: Option<u32>,
     pub autorestart: Option<bool>,
     pub max_restarts: Option<i32>,
     pub max_memory_restart: Option<u64>,
+    pub max_cpu_restart: Option<f32>,
 }
 
 /// Convert an AppConfig into a ProcessConfig, applying CLI overrides.
@@ -475,6 +1547,10 @@ pub fn merge_with_cli(app: &AppConfig, overrides: &CliOverrides) -> ProcessConfi
         restart_delay_ms: app.restart_delay as u32,
         exp_backoff_restart_delay: app.exp_backoff_restart_delay,
         max_memory_restart: max_memory,
+        success_exit_codes: app.success_exit_codes.clone(),
+        stop_on_success: app.stop_on_success,
+        max_cpu_restart: overrides.max_cpu_restart.or(app.max_cpu_restart),
+        cpu_restart_window_secs: app.cpu_restart_window_secs,
     }
 }
 
@@ -496,6 +1572,73 @@ pub fn resolve_app(
     Ok(merge_with_cli(app, overrides))
 }
 
+// ---------------------------------------------------------------------------
+// Config diffing
+// ---------------------------------------------------------------------------
+
+/// Difference between two configs, keyed by app name (TOML key).
+/// Each list is sorted for deterministic output.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ConfigDiff {
+    /// Apps present only in the new config.
+    pub added: Vec<String>,
+    /// Apps present only in the old config.
+    pub removed: Vec<String>,
+    /// Apps present in both configs whose settings differ.
+    pub changed: Vec<String>,
+}
+
+impl ConfigDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Turn the diff into the actions needed to reconcile the running set.
+    /// Stops come first so freed names/ports are available to new apps.
+    pub fn reconcile_plan(&self) -> Vec<ReconcileAction> {
+        let mut plan =
+            Vec::with_capacity(self.added.len() + self.removed.len() + self.changed.len());
+        plan.extend(self.removed.iter().cloned().map(ReconcileAction::Stop));
+        plan.extend(self.changed.iter().cloned().map(ReconcileAction::Restart));
+        plan.extend(self.added.iter().cloned().map(ReconcileAction::Start));
+        plan
+    }
+}
+
+/// A single step of a reconcile plan. The payload is the app's TOML key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReconcileAction {
+    /// Start an app that was added to the config.
+    Start(String),
+    /// Stop (and remove) an app that was dropped from the config.
+    Stop(String),
+    /// Re-create an app whose config changed so new settings take effect.
+    Restart(String),
+}
+
+/// Compare two configs and report which apps were added, removed or changed.
+pub fn diff(old: &VelosConfig, new: &VelosConfig) -> ConfigDiff {
+    let mut result = ConfigDiff::default();
+
+    for (key, new_app) in &new.apps {
+        match old.apps.get(key) {
+            None => result.added.push(key.clone()),
+            Some(old_app) if old_app != new_app => result.changed.push(key.clone()),
+            Some(_) => {}
+        }
+    }
+    for key in old.apps.keys() {
+        if !new.apps.contains_key(key) {
+            result.removed.push(key.clone());
+        }
+    }
+
+    result.added.sort();
+    result.removed.sort();
+    result.changed.sort();
+    result
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -536,7 +1679,7 @@ DATABASE_URL = "postgres://localhost:5432/db"
         assert!(api.autorestart);
         assert_eq!(api.max_restarts, 15);
         assert_eq!(api.kill_timeout, 5000);
-        assert_eq!(api.instances, 1);
+        assert_eq!(api.instances, Instances::Count(1));
     }
 
     #[test]
@@ -554,19 +1697,19 @@ DATABASE_URL = "postgres://localhost:5432/db"
         let api = config.get_app("api").unwrap();
 
         // Base env.
-        let base = merged_env(api, None);
+        let base = merged_env(api, None).unwrap();
         assert_eq!(base.get("NODE_ENV").unwrap(), "production");
         assert_eq!(base.get("PORT").unwrap(), "3000");
-        assert!(base.get("DATABASE_URL").is_none());
+        assert!(!base.contains_key("DATABASE_URL"));
 
         // Production profile: adds DATABASE_URL, keeps PORT from base.
-        let prod = merged_env(api, Some("production"));
+        let prod = merged_env(api, Some("production")).unwrap();
         assert_eq!(prod.get("NODE_ENV").unwrap(), "production");
         assert_eq!(prod.get("PORT").unwrap(), "3000");
         assert_eq!(prod.get("DATABASE_URL").unwrap(), "postgres://prod:5432/db");
 
         // Development profile: overrides NODE_ENV, adds DATABASE_URL.
-        let dev = merged_env(api, Some("development"));
+        let dev = merged_env(api, Some("development")).unwrap();
         assert_eq!(dev.get("NODE_ENV").unwrap(), "development");
         assert_eq!(
             dev.get("DATABASE_URL").unwrap(),
@@ -582,6 +1725,89 @@ DATABASE_URL = "postgres://localhost:5432/db"
         assert_eq!(parse_memory_string("1024").unwrap(), 1024);
         assert_eq!(parse_memory_string("100B").unwrap(), 100);
         assert_eq!(parse_memory_string("2g").unwrap(), 2 * 1024 * 1024 * 1024);
+        assert_eq!(parse_memory_string("1.5G").unwrap(), 1_610_612_736);
+        assert_eq!(parse_memory_string("0.5M").unwrap(), 512 * 1024);
+        assert_eq!(parse_memory_string("2T").unwrap(), 2 * 1024u64.pow(4));
+        assert_eq!(parse_memory_string("1p").unwrap(), 1024u64.pow(5));
+        // Fractions of a byte round to the nearest byte
+        assert_eq!(parse_memory_string("1.1K").unwrap(), 1126);
+        assert_eq!(parse_memory_string("0.5B").unwrap(), 1);
+    }
+
+    #[test]
+    fn env_file_sits_under_env_and_profile() {
+        let dir = std::env::temp_dir().join(format!("velos_env_file_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("api")).unwrap();
+        std::fs::write(
+            dir.join("api/.env"),
+            r#"# database
+DATABASE_URL="postgres://localhost/app" # trailing comment
+export SECRET='a#b "c"'
+GREETING="hello\n\"world\""
+PORT=3000 # overridden by env
+
+LOG_LEVEL=debug
+EMPTY=
+"#,
+        )
+        .unwrap();
+        let config_path = dir.join("velos.toml");
+        let toml_str = r#"
+[apps.api]
+script = "server.js"
+cwd = "api"
+env_file = ".env"
+
+[apps.api.env]
+PORT = "8080"
+LOG_LEVEL = "info"
+
+[apps.api.env_production]
+LOG_LEVEL = "warn"
+"#;
+        std::fs::write(&config_path, toml_str).unwrap();
+
+        let config = load(&config_path).unwrap();
+        let api = &config.apps["api"];
+        assert_eq!(
+            api.env_file.as_deref().map(Path::new),
+            Some(dir.join("api/.env").as_path())
+        );
+        let env = merged_env(api, Some("production")).unwrap();
+        assert_eq!(env["DATABASE_URL"], "postgres://localhost/app");
+        assert_eq!(env["SECRET"], "a#b \"c\"");
+        assert_eq!(env["GREETING"], "hello\n\"world\"");
+        assert_eq!(env["EMPTY"], "");
+        // file < env < profile
+        assert_eq!(env["PORT"], "8080");
+        assert_eq!(env["LOG_LEVEL"], "warn");
+        assert_eq!(
+            load_with_env(&config_path, "production").unwrap().apps["api"].env,
+            env
+        );
+
+        std::fs::write(
+            &config_path,
+            toml_str.replace("\".env\"", "\"missing.env\""),
+        )
+        .unwrap();
+        // Loading still works; the app's env can't be built
+        let config = load(&config_path).unwrap();
+        let err = merged_env(&config.apps["api"], None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("app 'api': env_file"), "{err}");
+        assert!(err.contains("missing.env' not found"), "{err}");
+
+        std::fs::write(dir.join("api/.env"), "OK=1\nBROKEN='x\n").unwrap();
+        std::fs::write(&config_path, toml_str).unwrap();
+        let err = load_with_env(&config_path, "production")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains(".env:2: unterminated quote"), "{err}");
+
+        let _ = std::fs::remove_dir_all(&dir);
     }
 
     #[test]
@@ -589,6 +1815,12 @@ DATABASE_URL = "postgres://localhost:5432/db"
         assert!(parse_memory_string("").is_err());
         assert!(parse_memory_string("abc").is_err());
         assert!(parse_memory_string("M").is_err());
+        assert!(parse_memory_string("1.2.3M").is_err());
+        assert!(parse_memory_string(".M").is_err());
+        assert!(parse_memory_string("-1G").is_err());
+        assert!(parse_memory_string("1e3K").is_err());
+        assert!(parse_memory_string("inf").is_err());
+        assert!(parse_memory_string("99999999P").is_err());
     }
 
     #[test]
@@ -632,6 +1864,118 @@ kill_timeout = 50
         assert!(err.to_string().contains("kill_timeout must be >= 100"));
     }
 
+    #[test]
+    fn validate_log_rate_limit() {
+        let toml_str = r#"
+[apps.api]
+script = "server.js"
+log_rate_limit = 0
+"#;
+        let err = parse(toml_str).unwrap_err();
+        assert!(err.to_string().contains("log_rate_limit must be > 0"));
+
+        let config = parse(&toml_str.replace("= 0", "= 200")).unwrap();
+        assert_eq!(config.apps["api"].log_rate_limit, Some(200));
+    }
+
+    #[test]
+    fn validate_hooks() {
+        let toml_str = r#"
+[apps.api]
+script = "server.js"
+pre_start = "./migrate.sh"
+post_stop = "  "
+"#;
+        let err = parse(toml_str).unwrap_err();
+        assert!(err.to_string().contains("post_stop must not be empty"));
+
+        let config = parse(&toml_str.replace("\"  \"", "\"rm -f app.lock\"")).unwrap();
+        let app = &config.apps["api"];
+        assert_eq!(app.pre_start.as_deref(), Some("./migrate.sh"));
+        assert_eq!(app.pre_stop, None);
+        assert_eq!(app.hook_timeout, 60_000);
+    }
+
+    #[test]
+    fn app_warnings_flag_suspicious_settings() {
+        let config = parse(
+            r#"
+[apps.api]
+script = "server.js"
+max_restarts = 0
+watch_paths = ["src"]
+
+[apps.web]
+script = "web.js"
+autorestart = false
+stop_on_success = true
+"#,
+        )
+        .unwrap();
+        let api = app_warnings(&config.apps["api"]);
+        assert_eq!(api.len(), 2);
+        assert!(api[0].starts_with("max_restarts = 0"));
+        assert!(api[1].contains("without watch = true"));
+        // autorestart = false makes max_restarts = 0 unremarkable
+        let web = app_warnings(&config.apps["web"]);
+        assert_eq!(
+            web,
+            ["stop_on_success has no effect with autorestart = false"]
+        );
+    }
+
+    #[test]
+    fn validate_max_cpu_restart() {
+        let toml_str = r#"
+[apps.api]
+script = "server.js"
+max_cpu_restart = 101
+"#;
+        let err = parse(toml_str).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("max_cpu_restart must be a percentage"));
+        assert!(parse(&toml_str.replace("101", "0")).is_err());
+
+        let config = parse(&toml_str.replace("101", "90.5\ncpu_restart_window_secs = 30")).unwrap();
+        let api = &config.apps["api"];
+        assert_eq!(
+            (api.max_cpu_restart, api.cpu_restart_window_secs),
+            (Some(90.5), 30)
+        );
+        let config = parse(&toml_str.replace("101", "100")).unwrap();
+        assert_eq!(config.apps["api"].cpu_restart_window_secs, 60);
+
+        let err = parse(&toml_str.replace("101", "50\ncpu_restart_window_secs = 0")).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("cpu_restart_window_secs must be > 0"));
+    }
+
+    #[test]
+    fn parse_success_exit_codes() {
+        let toml_str = r#"
+[apps.batch]
+script = "job.sh"
+success_exit_codes = [0, 2, 75]
+stop_on_success = true
+
+[apps.api]
+script = "server.js"
+"#;
+        let config = parse(toml_str).unwrap();
+        let batch = &config.apps["batch"];
+        assert_eq!(batch.success_exit_codes, [0, 2, 75]);
+        assert!(batch.stop_on_success);
+        assert_eq!(config.apps["api"].success_exit_codes, [0]);
+        assert!(!config.apps["api"].stop_on_success);
+
+        let err = parse(&toml_str.replace("[0, 2, 75]", "[]")).unwrap_err();
+        assert!(err.to_string().contains("at least one exit code"));
+        let err = parse(&toml_str.replace("75]", "256]")).unwrap_err();
+        assert!(err.to_string().contains("entry 256 is not an exit code"));
+    }
+
     #[test]
     fn validate_max_restarts_too_low() {
         let toml_str = r#"
@@ -643,6 +1987,56 @@ max_restarts = -2
         assert!(err.to_string().contains("max_restarts must be >= -1"));
     }
 
+    #[test]
+    fn instances_max_resolves_per_cpu() {
+        let toml_str = r#"
+[apps.api]
+script = "server.js"
+instances = "max"
+"#;
+        let config = parse(toml_str).unwrap();
+        let api = config.get_app("api").unwrap();
+        assert_eq!(api.instances, Instances::Max);
+        assert!(api.instances.resolve(None) >= 1);
+
+        // max_instances caps "max" instead of rejecting it on a big machine
+        let capped = parse(&format!("{toml_str}max_instances = 1\n")).unwrap();
+        assert_eq!(capped.apps["api"].instances.resolve(Some(1)), 1);
+        assert_eq!(Instances::Count(4).resolve(Some(1)), 4);
+
+        // Written back as "max", not as this machine's CPU count
+        assert!(format(&config).unwrap().contains(r#"instances = "max""#));
+
+        let err = parse("[apps.api]\nscript = \"a.js\"\ninstances = \"all\"\n").unwrap_err();
+        assert!(
+            err.to_string().contains(r#"an instance count or "max""#),
+            "{err}"
+        );
+        let err = parse("[apps.api]\nscript = \"a.js\"\ninstances = 0\n").unwrap_err();
+        assert!(err.to_string().contains("instances must be >= 1"), "{err}");
+    }
+
+    #[test]
+    fn validate_max_instances() {
+        let toml_str = r#"
+[apps.api]
+script = "server.js"
+instances = 4
+max_instances = 8
+"#;
+        let config = parse(toml_str).unwrap();
+        assert_eq!(config.apps["api"].max_instances, Some(8));
+
+        let toml_str = r#"
+[apps.api]
+script = "server.js"
+instances = 4
+max_instances = 2
+"#;
+        let err = parse(toml_str).unwrap_err();
+        assert!(err.to_string().contains("exceeds max_instances"));
+    }
+
     #[test]
     fn validate_invalid_memory_format() {
         let toml_str = r#"
@@ -663,6 +2057,47 @@ cron_restart = "* *"
 "#;
         let err = parse(toml_str).unwrap_err();
         assert!(err.to_string().contains("cron_restart must have 5 fields"));
+
+        let err = parse(&toml_str.replace("* *", "99 99 99 99 99")).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("app 'api': cron_restart minute field '99': 99 is out of range 0-59"));
+    }
+
+    #[test]
+    fn validate_cron_fields() {
+        for ok in [
+            "",
+            "0 0 * * *",
+            "*/5 * * * *",
+            "0,15,30,45 9-17 * * 1-5",
+            "1-30/5 */2 1,15 1-12/3 0",
+            // 7 is Sunday, same as 0
+            "0 3 * * 7",
+            "0 3 * * 5-7",
+        ] {
+            assert!(validate_cron(ok).is_ok(), "{ok:?}");
+        }
+
+        for (bad, why) in [
+            ("  ", "must have 5 fields, got 0"),
+            ("* * * *", "must have 5 fields, got 4"),
+            ("60 * * * *", "minute field '60': 60 is out of range 0-59"),
+            ("* 24 * * *", "hour field '24': 24 is out of range 0-23"),
+            ("* * 0 * *", "day field '0': 0 is out of range 1-31"),
+            ("* * * 13 *", "month field '13': 13 is out of range 1-12"),
+            ("* * * * 8", "weekday field '8': 8 is out of range 0-7"),
+            ("* * * * 1-9", "weekday field '1-9': 9 is out of range 0-7"),
+            ("*/0 * * * *", "step must be > 0"),
+            ("5/10 * * * *", "a step needs '*' or a range"),
+            ("1,,2 * * * *", "empty list item"),
+            ("30-10 * * * *", "range 30-10 is backwards"),
+            ("* * * jan *", "'jan' is not a number"),
+            ("+5 * * * *", "'+5' is not a number"),
+        ] {
+            let err = validate_cron(bad).unwrap_err().to_string();
+            assert!(err.contains(why), "{bad:?}: {err}");
+        }
     }
 
     #[test]
@@ -715,6 +2150,41 @@ script = "worker.js"
         assert_eq!(config.all_apps().len(), 2);
     }
 
+    #[test]
+    fn validate_all_reports_every_invalid_app() {
+        let toml_str = r#"
+[apps.api]
+script = "server.js"
+kill_timeout = 10
+
+[apps.web]
+script = "web.js"
+
+[apps.worker]
+script = "worker.py"
+max_memory_restart = "lots"
+"#;
+        let config: VelosConfig = fill_names(toml::from_str(toml_str).unwrap());
+        let errors = validate_all(&config);
+        let names: Vec<&str> = errors.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["api", "worker"]);
+        assert!(errors[0].1.to_string().contains("kill_timeout"));
+        assert!(errors[1].1.to_string().contains("max_memory_restart"));
+
+        // parse reports both at once
+        let err = parse(toml_str).unwrap_err().to_string();
+        assert!(
+            err.contains("app 'api'") && err.contains("app 'worker'"),
+            "{err}"
+        );
+        assert_eq!(err.matches("validation error").count(), 1, "{err}");
+
+        let fixed = toml_str
+            .replace("kill_timeout = 10", "")
+            .replace("lots", "1G");
+        assert!(validate_all(&parse(&fixed).unwrap()).is_empty());
+    }
+
     #[test]
     fn multi_app_config() {
         let toml_str = r#"
@@ -733,14 +2203,14 @@ autorestart = false
         let config = parse(toml_str).unwrap();
 
         let web = config.get_app("web").unwrap();
-        assert_eq!(web.instances, 4);
+        assert_eq!(web.instances, Instances::Count(4));
         assert_eq!(
             parse_memory_string(web.max_memory_restart.as_deref().unwrap()).unwrap(),
             256 * 1024 * 1024
         );
 
         let worker = config.get_app("worker").unwrap();
-        assert_eq!(worker.instances, 2);
+        assert_eq!(worker.instances, Instances::Count(2));
         assert_eq!(worker.interpreter.as_deref(), Some("python3"));
         assert!(!worker.autorestart);
     }
@@ -776,7 +2246,7 @@ script = "app.js"
         assert_eq!(app.min_uptime, 1000);
         assert_eq!(app.restart_delay, 0);
         assert_eq!(app.kill_timeout, 5000);
-        assert_eq!(app.instances, 1);
+        assert_eq!(app.instances, Instances::Count(1));
         assert!(!app.watch);
         assert_eq!(app.watch_delay, 1000);
         assert_eq!(app.log_retain_count, 30);
@@ -784,6 +2254,77 @@ script = "app.js"
         assert!(!app.exp_backoff_restart_delay);
     }
 
+    #[test]
+    fn parse_yaml_and_json() {
+        let yaml = r#"
+apps:
+  api:
+    script: server.js
+    instances: 2
+    max_memory_restart: 150M
+    env:
+      PORT: "3000"
+    env_production:
+      NODE_ENV: production
+      WORKERS: 4
+"#;
+        let config = parse_yaml(yaml).unwrap();
+        let api = config.get_app("api").unwrap();
+        assert_eq!(api.name.as_deref(), Some("api"));
+        assert_eq!(api.instances, Instances::Count(2));
+        assert_eq!(api.env["PORT"], "3000");
+        assert_eq!(api.env_profiles["production"]["NODE_ENV"], "production");
+        assert_eq!(api.env_profiles["production"]["WORKERS"], "4");
+
+        let json = r#"{"apps": {"api": {"script": "server.js", "instances": 2,
+            "max_memory_restart": "150M", "env": {"PORT": "3000"},
+            "env_production": {"NODE_ENV": "production", "WORKERS": 4}}}}"#;
+        assert_eq!(parse_json(json).unwrap().apps, config.apps);
+
+        // Same validation as TOML
+        let err = parse_yaml("apps:\n  api:\n    script: \"\"\n").unwrap_err();
+        assert!(err.to_string().contains("script must not be empty"));
+        assert!(matches!(
+            parse_json("{\"apps\": 3}").unwrap_err(),
+            ConfigError::ParseJson(_)
+        ));
+    }
+
+    #[test]
+    fn load_picks_format_by_extension() {
+        let dir = std::env::temp_dir().join(format!("velos_config_formats_{}", std::process::id()));
+        let _ = std::fs::create_dir_all(&dir);
+        let write = |name: &str, text: &str| {
+            let path = dir.join(name);
+            std::fs::write(&path, text).unwrap();
+            path
+        };
+
+        let yaml = "apps:\n  api:\n    script: server.js\n";
+        assert!(load(&write("velos.yaml", yaml))
+            .unwrap()
+            .apps
+            .contains_key("api"));
+        assert!(load(&write("velos.YML", yaml))
+            .unwrap()
+            .apps
+            .contains_key("api"));
+        let json = r#"{"apps": {"api": {"script": "server.js"}}}"#;
+        assert!(load(&write("velos.json", json))
+            .unwrap()
+            .apps
+            .contains_key("api"));
+        // YAML is not valid TOML: the extension decides
+        assert!(matches!(
+            load(&write("velos.toml", yaml)).unwrap_err(),
+            ConfigError::Parse(_)
+        ));
+        let err = load(&write("velos.ini", "")).unwrap_err();
+        assert!(matches!(err, ConfigError::UnsupportedFormat(ref ext) if ext == "ini"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn load_with_env_applies_profile() {
         let toml_str = BASIC_TOML;
@@ -836,6 +2377,7 @@ classifier = true
 dedup_window = 120
 pattern_window = 600
 anomaly_window = 30
+anomaly_bucket_secs = 10
 anomaly_sigma_warn = 1.5
 anomaly_sigma_crit = 2.5
 
@@ -848,6 +2390,7 @@ script = "server.js"
         assert_eq!(logs.dedup_window, 120);
         assert_eq!(logs.pattern_window, 600);
         assert_eq!(logs.anomaly_window, 30);
+        assert_eq!(logs.anomaly_bucket_secs, 10);
         assert!((logs.anomaly_sigma_warn - 1.5).abs() < f64::EPSILON);
         assert!((logs.anomaly_sigma_crit - 2.5).abs() < f64::EPSILON);
     }
@@ -867,7 +2410,370 @@ script = "server.js"
         assert_eq!(defaults.dedup_window, 60);
         assert_eq!(defaults.pattern_window, 300);
         assert_eq!(defaults.anomaly_window, 60);
+        assert_eq!(defaults.anomaly_bucket_secs, 60);
         assert!((defaults.anomaly_sigma_warn - 2.0).abs() < f64::EPSILON);
         assert!((defaults.anomaly_sigma_crit - 3.0).abs() < f64::EPSILON);
+        assert!(defaults.classifier_preset.is_none());
+        assert_eq!(defaults.json_level_keys, ["level"]);
+    }
+
+    #[test]
+    fn parse_classifier_preset() {
+        let config = parse("[logs]\nclassifier_preset = \"nginx\"\n").unwrap();
+        assert_eq!(
+            config.logs.unwrap().classifier_preset.as_deref(),
+            Some("nginx")
+        );
+
+        let err = parse("[logs]\nclassifier_preset = \"apache\"\n").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("unknown classifier_preset 'apache'"));
+    }
+
+    #[test]
+    fn parse_json_level_keys() {
+        let config = parse("[logs]\njson_level_keys = [\"severity\", \"level\"]\n").unwrap();
+        assert_eq!(config.logs.unwrap().json_level_keys, ["severity", "level"]);
+
+        let err = parse("[logs]\njson_level_keys = [\"\"]\n").unwrap_err();
+        assert!(err.to_string().contains("json_level_keys"));
+    }
+
+    #[test]
+    fn format_is_canonical_and_idempotent() {
+        let messy = r#"
+[apps.worker]
+instances = 2
+script = "worker.js"
+autorestart = true
+
+[apps.worker.env]
+ZED = "1"
+ALPHA = "2"
+
+[apps.api]
+name = "api"
+kill_timeout = 8000
+script = "api.js"
+args = ["--port", "3000"]
+
+[apps.api.env_production]
+NODE_ENV = "production"
+DB = "prod"
+
+[logs]
+dedup_window = 120
+"#;
+        let once = format(&parse(messy).unwrap()).unwrap();
+        assert_eq!(format(&parse(&once).unwrap()).unwrap(), once);
+
+        // Keys in a different order format the same way
+        let reordered = r#"
+[logs]
+dedup_window = 120
+
+[apps.api]
+args = ["--port", "3000"]
+script = "api.js"
+kill_timeout = 8000
+env_production = { DB = "prod", NODE_ENV = "production" }
+
+[apps.worker]
+script = "worker.js"
+instances = 2
+env = { ALPHA = "2", ZED = "1" }
+"#;
+        assert_eq!(format(&parse(reordered).unwrap()).unwrap(), once);
+
+        // Logs first, apps sorted, defaults and key-derived names dropped
+        let logs = once.find("[logs]").unwrap();
+        let api = once.find("[apps.api]").unwrap();
+        let worker = once.find("[apps.worker]").unwrap();
+        assert!(logs < api && api < worker);
+        assert!(once.find("ALPHA").unwrap() < once.find("ZED").unwrap());
+        assert!(once.contains("[apps.api.env_production]"));
+        assert!(!once.contains("autorestart"));
+        assert!(!once.contains("name ="));
+        assert!(once.contains("kill_timeout = 8000"));
+    }
+
+    #[test]
+    fn diff_and_reconcile_plan() {
+        let old = parse(
+            r#"
+[apps.web]
+script = "web.js"
+
+[apps.worker]
+script = "worker.js"
+
+[apps.cron]
+script = "cron.js"
+"#,
+        )
+        .unwrap();
+        let new = parse(
+            r#"
+[apps.web]
+script = "web.js"
+
+[apps.worker]
+script = "worker.js"
+instances = 4
+
+[apps.mailer]
+script = "mailer.js"
+"#,
+        )
+        .unwrap();
+
+        let d = diff(&old, &new);
+        assert_eq!(d.added, vec!["mailer"]);
+        assert_eq!(d.removed, vec!["cron"]);
+        assert_eq!(d.changed, vec!["worker"]);
+
+        assert_eq!(
+            d.reconcile_plan(),
+            vec![
+                ReconcileAction::Stop("cron".into()),
+                ReconcileAction::Restart("worker".into()),
+                ReconcileAction::Start("mailer".into()),
+            ]
+        );
+
+        // Identical configs produce an empty plan.
+        let same = diff(&new, &new);
+        assert!(same.is_empty());
+        assert!(same.reconcile_plan().is_empty());
+    }
+
+    #[test]
+    fn instance_name_template() {
+        let toml_str = r#"
+[apps.api]
+script = "server.js"
+instances = 2
+instance_name_template = "{name}-{index}"
+"#;
+        let config = parse(toml_str).unwrap();
+        let api = config.get_app("api").unwrap();
+        assert_eq!(
+            api.instance_name_template.as_deref(),
+            Some("{name}-{index}")
+        );
+
+        let bad = r#"
+[apps.api]
+script = "server.js"
+instance_name_template = "{index}@{name}"
+"#;
+        let err = parse(bad).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("unsupported instance_name_template"));
+    }
+
+    #[test]
+    fn defaults_fill_unset_app_fields() {
+        let toml_str = r#"
+[defaults]
+interpreter = "node"
+kill_timeout = 10000
+env = { NODE_ENV = "production", LOG_LEVEL = "info" }
+
+[apps.api]
+script = "server.js"
+env = { LOG_LEVEL = "debug", PORT = "3000" }
+
+[apps.worker]
+script = "worker.py"
+interpreter = "python3"
+kill_timeout = 5000
+"#;
+        let config = parse(toml_str).unwrap();
+        let api = config.get_app("api").unwrap();
+        let worker = config.get_app("worker").unwrap();
+
+        assert_eq!(api.interpreter.as_deref(), Some("node"));
+        assert_eq!(worker.interpreter.as_deref(), Some("python3"));
+
+        // Explicitly setting the built-in value still beats [defaults]
+        assert_eq!(api.kill_timeout, 10000);
+        assert_eq!(worker.kill_timeout, 5000);
+
+        // env merges key by key, the app's own keys winning
+        assert_eq!(
+            api.env.get("NODE_ENV").map(String::as_str),
+            Some("production")
+        );
+        assert_eq!(api.env.get("LOG_LEVEL").map(String::as_str), Some("debug"));
+        assert_eq!(api.env.get("PORT").map(String::as_str), Some("3000"));
+        assert_eq!(worker.env.len(), 2);
+
+        let yaml = "defaults:\n  interpreter: node\napps:\n  api:\n    script: server.js\n";
+        let api = parse_yaml(yaml).unwrap().apps.remove("api").unwrap();
+        assert_eq!(api.interpreter.as_deref(), Some("node"));
+
+        let err = parse("[defaults]\nname = \"x\"\n[apps.api]\nscript = \"a.js\"\n").unwrap_err();
+        assert!(
+            err.to_string().contains("[defaults] cannot set name"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn format_keeps_defaults_table() {
+        let toml_str = r#"
+[defaults]
+interpreter = "node"
+env = { NODE_ENV = "production" }
+
+[apps.api]
+script = "server.js"
+env = { PORT = "3000" }
+
+[apps.worker]
+script = "worker.py"
+interpreter = "python3"
+"#;
+        let once = format(&parse(toml_str).unwrap()).unwrap();
+        assert!(once.contains("[defaults]"), "{once}");
+        assert_eq!(once.matches("interpreter").count(), 2, "{once}");
+        assert_eq!(once.matches("NODE_ENV").count(), 1, "{once}");
+        assert_eq!(format(&parse(&once).unwrap()).unwrap(), once);
+
+        // An app resetting a default to the built-in value can't be written
+        // relative to [defaults], so every app is written out in full
+        let reset = "[defaults]\nkill_timeout = 10000\n\n[apps.api]\nscript = \"a.js\"\nkill_timeout = 5000\n\n[apps.web]\nscript = \"w.js\"\n";
+        let expanded = format(&parse(reset).unwrap()).unwrap();
+        assert!(!expanded.contains("[defaults]"), "{expanded}");
+        let config = parse(&expanded).unwrap();
+        assert_eq!(config.get_app("api").unwrap().kill_timeout, 5000);
+        assert_eq!(config.get_app("web").unwrap().kill_timeout, 10000);
+    }
+
+    #[test]
+    fn format_losses_lists_unknown_keys() {
+        let toml_str = r#"
+[defaults]
+autorestart = true
+
+[apps.api]
+script = "server.js"
+name = "api"
+kill_timeout = 5000
+autorestar = false
+env_production = { PORT = 3000, MODE = "fast" }
+
+[apps.api.extra]
+note = "kept by hand"
+"#;
+        let losses = format_losses(toml_str).unwrap();
+        assert_eq!(
+            losses,
+            [
+                "apps.api.autorestar: unknown key would be dropped",
+                "apps.api.env_production.PORT: 3000 would be written as \"3000\"",
+                "apps.api.extra: unknown key would be dropped",
+            ]
+        );
+
+        // Defaults and back-filled names are left out without losing anything
+        assert!(
+            format_losses("[apps.web]\nscript = \"w.js\"\nautorestart = true\n")
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn validate_classify_rules() {
+        let config = parse(
+            r#"
+[[logs.rules]]
+pattern = "OOMKilled"
+level = "Fatal"
+
+[apps.api]
+script = "server.js"
+"#,
+        )
+        .unwrap();
+        let rules = &config.logs.unwrap().rules;
+        assert_eq!(rules[0].priority, 15);
+
+        let bad_regex = "[[logs.rules]]\npattern = \"(unclosed\"\nlevel = \"error\"\n";
+        let err = parse(bad_regex).unwrap_err();
+        assert!(matches!(err, ConfigError::Validation(_)), "{err}");
+        assert!(
+            err.to_string().contains("logs.rules[0]: invalid pattern"),
+            "{err}"
+        );
+
+        let bad_level = "[[logs.rules]]\npattern = \"x\"\nlevel = \"loud\"\n";
+        let err = parse(bad_level).unwrap_err();
+        assert!(err.to_string().contains("unknown level 'loud'"), "{err}");
+
+        // Same names as `velos logs --level`
+        for level in ["warning", "err", "3", "0"] {
+            let toml_str = format!("[[logs.rules]]\npattern = \"x\"\nlevel = \"{level}\"\n");
+            assert!(parse(&toml_str).is_ok(), "{level}");
+        }
+    }
+
+    #[test]
+    fn validate_default_window() {
+        let config = parse("[logs]\ndefault_window = \"24h\"\n").unwrap();
+        assert_eq!(config.logs.unwrap().default_window.as_deref(), Some("24h"));
+        for bad in ["24", "h", "1.5h", "-2d"] {
+            let err = parse(&format!("[logs]\ndefault_window = \"{bad}\"\n")).unwrap_err();
+            assert!(err.to_string().contains("default_window"), "{bad}: {err}");
+        }
+    }
+
+    #[test]
+    fn validate_level_colors() {
+        let config = parse("[logs.level_colors]\nerror = \"Bright-Red\"\nwarning = \"none\"\n");
+        assert!(config.is_ok(), "{config:?}");
+
+        let err = parse("[logs.level_colors]\nloud = \"red\"\n").unwrap_err();
+        assert!(err.to_string().contains("unknown level 'loud'"), "{err}");
+        let err = parse("[logs.level_colors]\nerror = \"crimson\"\n").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("unknown color 'crimson' for error"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn load_logs_ignores_apps() {
+        let dir = std::env::temp_dir().join(format!("velos_load_logs_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("velos.toml");
+        assert!(load_logs(&path).unwrap().is_none());
+
+        // An app with a missing env_file doesn't hide the [logs] table
+        std::fs::write(
+            &path,
+            "[logs]\nmax_line_length = 200\n\n[apps.api]\nscript = \"a.js\"\nenv_file = \"missing.env\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            load_logs(&path).unwrap().unwrap().max_line_length,
+            Some(200)
+        );
+
+        // A bad rule is reported rather than dropping the whole table
+        std::fs::write(
+            &path,
+            "[logs]\nmax_line_length = 200\n\n[[logs.rules]]\npattern = \"(\"\nlevel = \"error\"\n",
+        )
+        .unwrap();
+        let err = load_logs(&path).unwrap_err();
+        assert!(err.to_string().contains("logs.rules[0]"), "{err}");
+
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
@@ -0,0 +1,598 @@
+//! Convert a PM2 `ecosystem.config.js` (or `.json`) into a [`VelosConfig`],
+//! and back.
+//!
+//! Only the data part of the file is understood: the object literal that
+//! `module.exports` is set to, written with literal values. Bare keys,
+//! single quotes, comments and trailing commas are fine; `process.env.X`,
+//! `__dirname` and other expressions are not.
+
+use serde_json::{Map, Value};
+
+use crate::{parse, parse_memory_string, AppConfig, ConfigError, Instances, Result, VelosConfig};
+
+/// The converted config, plus a note for every PM2 setting that was dropped
+/// or approximated.
+#[derive(Debug, Clone)]
+pub struct Pm2Import {
+    pub config: VelosConfig,
+    pub warnings: Vec<String>,
+}
+
+/// PM2 options with no velos equivalent that are safe to drop silently.
+const IGNORED: &[&str] = &["exec_mode", "exec_interpreter", "vizion", "autostart"];
+
+/// Parse an ecosystem file's source and convert its `apps`.
+pub fn import(source: &str) -> Result<Pm2Import> {
+    let json = js_to_json(source);
+    let root: Value = serde_json::from_str(&json)
+        .map_err(|e| invalid(format!("cannot read the ecosystem file as data: {e}")))?;
+    let apps = match &root {
+        Value::Object(obj) => obj.get("apps").cloned().unwrap_or(Value::Null),
+        Value::Array(_) => root.clone(),
+        _ => Value::Null,
+    };
+    let Value::Array(apps) = apps else {
+        return Err(invalid("expected an `apps` array".into()));
+    };
+
+    let mut warnings = Vec::new();
+    let mut tables = toml::Table::new();
+    for (i, app) in apps.iter().enumerate() {
+        let Value::Object(app) = app else {
+            return Err(invalid(format!("apps[{i}] is not an object")));
+        };
+        let (key, table) =
+            convert_app(app, &mut warnings).map_err(|e| invalid(format!("apps[{i}]: {e}")))?;
+        if tables.contains_key(&key) {
+            return Err(invalid(format!("two apps are named '{key}'")));
+        }
+        tables.insert(key, toml::Value::Table(table));
+    }
+
+    let mut root = toml::Table::new();
+    root.insert("apps".into(), toml::Value::Table(tables));
+    let config = parse(&toml::to_string(&root)?)?;
+    Ok(Pm2Import { config, warnings })
+}
+
+fn invalid(msg: String) -> ConfigError {
+    ConfigError::Validation(format!("pm2: {msg}"))
+}
+
+/// One PM2 app → its velos table key and `[apps.<key>]` table.
+fn convert_app(
+    app: &Map<String, Value>,
+    warnings: &mut Vec<String>,
+) -> std::result::Result<(String, toml::Table), String> {
+    let script = string(app, "script")?.ok_or("missing script")?;
+    let key = match string(app, "name")? {
+        Some(name) => name,
+        None => std::path::Path::new(&script)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("app")
+            .to_string(),
+    };
+    let mut warn = |msg: String| warnings.push(format!("{key}: {msg}"));
+
+    let mut t = toml::Table::new();
+    t.insert("script".into(), script.clone().into());
+    for (field, value) in app {
+        match field.as_str() {
+            "name" | "script" => {}
+            "cwd" | "cron_restart" => {
+                t.insert(field.clone(), as_string(field, value)?.into());
+            }
+            "namespace" => {
+                t.insert("group".into(), as_string(field, value)?.into());
+            }
+            "interpreter" => match as_string(field, value)?.as_str() {
+                "none" | "" => {}
+                interpreter => {
+                    t.insert("interpreter".into(), interpreter.into());
+                }
+            },
+            "args" => {
+                let args = match value {
+                    Value::String(s) => s.split_whitespace().map(String::from).collect(),
+                    _ => string_list(field, value)?,
+                };
+                t.insert("args".into(), args.into());
+            }
+            "instances" => {
+                let cpus = std::thread::available_parallelism().map_or(1, |n| n.get() as i64);
+                let n = match value {
+                    Value::String(s) if s.eq_ignore_ascii_case("max") => 0,
+                    Value::String(s) => s.trim().parse().map_err(|_| bad(field, value))?,
+                    _ => value.as_i64().ok_or_else(|| bad(field, value))?,
+                };
+                // PM2: 0 / "max" = one per CPU, -N = all CPUs but N
+                if n == 0 {
+                    t.insert("instances".into(), "max".into());
+                } else if n < 0 {
+                    let count = (cpus + n).max(1);
+                    warn(format!(
+                        "instances {value} fixed at {count} (this machine's CPU count)"
+                    ));
+                    t.insert("instances".into(), count.into());
+                } else {
+                    t.insert("instances".into(), n.into());
+                }
+            }
+            "autorestart" | "merge_logs" => {
+                t.insert(field.clone(), as_bool(field, value)?.into());
+            }
+            "combine_logs" => {
+                t.insert("merge_logs".into(), as_bool(field, value)?.into());
+            }
+            "max_restarts" | "min_uptime" | "kill_timeout" | "restart_delay" | "watch_delay" => {
+                t.insert(field.clone(), as_int(field, value)?.into());
+            }
+            "exp_backoff_restart_delay" => {
+                // PM2 gives the initial delay; velos backs off from restart_delay
+                t.insert(field.clone(), true.into());
+                if !app.contains_key("restart_delay") {
+                    t.insert("restart_delay".into(), as_int(field, value)?.into());
+                }
+            }
+            "max_memory_restart" => {
+                let mem = match value {
+                    Value::Number(n) => n.to_string(),
+                    _ => as_string(field, value)?,
+                };
+                parse_memory_string(&mem).map_err(|_| bad(field, value))?;
+                t.insert(field.clone(), mem.into());
+            }
+            "watch" => match value {
+                Value::Bool(on) => {
+                    t.insert("watch".into(), (*on).into());
+                }
+                _ => {
+                    t.insert("watch".into(), true.into());
+                    t.insert("watch_paths".into(), string_list(field, value)?.into());
+                }
+            },
+            "ignore_watch" => {
+                t.insert("watch_ignore".into(), string_list(field, value)?.into());
+            }
+            "out_file" => {
+                t.insert("log_file".into(), as_string(field, value)?.into());
+            }
+            "log_file" if !app.contains_key("out_file") => {
+                t.insert("log_file".into(), as_string(field, value)?.into());
+            }
+            "env" => {
+                t.insert("env".into(), env_table(field, value)?.into());
+            }
+            profile if profile.starts_with("env_") => {
+                t.insert(field.clone(), env_table(field, value)?.into());
+            }
+            ignored if IGNORED.contains(&ignored) => {}
+            other => warn(format!("dropped unsupported option '{other}'")),
+        }
+    }
+    Ok((key, t))
+}
+
+fn bad(field: &str, value: &Value) -> String {
+    format!("invalid {field}: {value}")
+}
+
+fn string(app: &Map<String, Value>, field: &str) -> std::result::Result<Option<String>, String> {
+    app.get(field).map(|v| as_string(field, v)).transpose()
+}
+
+fn as_string(field: &str, value: &Value) -> std::result::Result<String, String> {
+    value
+        .as_str()
+        .map(String::from)
+        .ok_or_else(|| bad(field, value))
+}
+
+fn as_bool(field: &str, value: &Value) -> std::result::Result<bool, String> {
+    value.as_bool().ok_or_else(|| bad(field, value))
+}
+
+fn as_int(field: &str, value: &Value) -> std::result::Result<i64, String> {
+    match value {
+        Value::String(s) => s.trim().parse().ok(),
+        _ => value.as_i64(),
+    }
+    .ok_or_else(|| bad(field, value))
+}
+
+/// A string or an array of strings.
+fn string_list(field: &str, value: &Value) -> std::result::Result<Vec<String>, String> {
+    match value {
+        Value::String(s) => Ok(vec![s.clone()]),
+        Value::Array(items) => items.iter().map(|v| as_string(field, v)).collect(),
+        _ => Err(bad(field, value)),
+    }
+}
+
+/// An env object; numbers and booleans become strings, as PM2 passes them.
+fn env_table(field: &str, value: &Value) -> std::result::Result<toml::Table, String> {
+    let Value::Object(vars) = value else {
+        return Err(bad(field, value));
+    };
+    vars.iter()
+        .map(|(k, v)| {
+            let v = match v {
+                Value::String(s) => s.clone(),
+                Value::Number(_) | Value::Bool(_) => v.to_string(),
+                _ => return Err(bad(&format!("{field}.{k}"), v)),
+            };
+            Ok((k.clone(), toml::Value::String(v)))
+        })
+        .collect()
+}
+
+/// An ecosystem file's `{"apps": [...]}` object, plus a note for every
+/// velos setting PM2 has no place for.
+#[derive(Debug, Clone)]
+pub struct Pm2Export {
+    pub ecosystem: Value,
+    pub warnings: Vec<String>,
+}
+
+/// Convert every app, sorted by key, into PM2's ecosystem format.
+pub fn export(config: &VelosConfig) -> Pm2Export {
+    let mut keys: Vec<_> = config.apps.keys().collect();
+    keys.sort();
+    let mut warnings = Vec::new();
+    let apps = keys
+        .into_iter()
+        .map(|key| Value::Object(export_app(key, &config.apps[key], &mut warnings)))
+        .collect();
+    Pm2Export {
+        ecosystem: serde_json::json!({ "apps": Value::Array(apps) }),
+        warnings,
+    }
+}
+
+/// One velos app → its PM2 app object. Restart settings are always written
+/// out, since PM2's defaults differ from velos's.
+fn export_app(key: &str, app: &AppConfig, warnings: &mut Vec<String>) -> Map<String, Value> {
+    let name = app.name.clone().unwrap_or_else(|| key.to_string());
+    let mut o = Map::new();
+    o.insert("name".into(), name.clone().into());
+    o.insert("script".into(), app.script.clone().into());
+    if let Some(ref cwd) = app.cwd {
+        o.insert("cwd".into(), cwd.clone().into());
+    }
+    if let Some(ref interpreter) = app.interpreter {
+        o.insert("interpreter".into(), interpreter.clone().into());
+    }
+    if !app.args.is_empty() {
+        o.insert("args".into(), app.args.clone().into());
+    }
+    o.insert(
+        "instances".into(),
+        match app.instances {
+            Instances::Count(n) => n.into(),
+            Instances::Max => "max".into(),
+        },
+    );
+    if let Some(ref group) = app.group {
+        o.insert("namespace".into(), group.clone().into());
+    }
+    o.insert("autorestart".into(), app.autorestart.into());
+    o.insert("max_restarts".into(), app.max_restarts.into());
+    o.insert("min_uptime".into(), app.min_uptime.into());
+    o.insert("kill_timeout".into(), app.kill_timeout.into());
+    if app.exp_backoff_restart_delay {
+        o.insert("exp_backoff_restart_delay".into(), app.restart_delay.into());
+    }
+    if app.restart_delay > 0 {
+        o.insert("restart_delay".into(), app.restart_delay.into());
+    }
+    if let Some(ref mem) = app.max_memory_restart {
+        o.insert("max_memory_restart".into(), pm2_memory(mem).into());
+    }
+    if let Some(ref cron) = app.cron_restart {
+        o.insert("cron_restart".into(), cron.clone().into());
+    }
+    if app.watch {
+        let watch = if app.watch_paths.is_empty() {
+            Value::Bool(true)
+        } else {
+            app.watch_paths.clone().into()
+        };
+        o.insert("watch".into(), watch);
+        o.insert("watch_delay".into(), app.watch_delay.into());
+    }
+    if !app.watch_ignore.is_empty() {
+        o.insert("ignore_watch".into(), app.watch_ignore.clone().into());
+    }
+    if let Some(ref file) = app.log_file {
+        o.insert("out_file".into(), file.clone().into());
+    }
+    if app.merge_logs {
+        o.insert("merge_logs".into(), true.into());
+    }
+    if !app.env.is_empty() {
+        o.insert("env".into(), env_object(&app.env));
+    }
+    for (profile, env) in &app.env_profiles {
+        o.insert(format!("env_{profile}"), env_object(env));
+    }
+
+    let dropped = [
+        ("success_exit_codes", app.success_exit_codes != [0]),
+        ("stop_on_success", app.stop_on_success),
+        ("max_cpu_restart", app.max_cpu_restart.is_some()),
+        ("log_rate_limit", app.log_rate_limit.is_some()),
+        ("log_max_size", app.log_max_size.is_some()),
+        (
+            "instance_name_template",
+            app.instance_name_template.is_some(),
+        ),
+        ("max_instances", app.max_instances.is_some()),
+        ("pre_start", app.pre_start.is_some()),
+        ("pre_stop", app.pre_stop.is_some()),
+        ("post_stop", app.post_stop.is_some()),
+        ("env_file", app.env_file.is_some()),
+    ];
+    for (field, set) in dropped {
+        if set {
+            warnings.push(format!("{name}: PM2 has no equivalent for {field}"));
+        }
+    }
+    o
+}
+
+/// PM2 takes a number with an optional K, M or G suffix; velos also allows
+/// B and lowercase suffixes.
+fn pm2_memory(mem: &str) -> String {
+    let Ok(bytes) = parse_memory_string(mem) else {
+        return mem.to_string();
+    };
+    for (suffix, unit) in [("G", 1u64 << 30), ("M", 1 << 20), ("K", 1 << 10)] {
+        if bytes > 0 && bytes % unit == 0 {
+            return format!("{}{suffix}", bytes / unit);
+        }
+    }
+    bytes.to_string()
+}
+
+/// An env map as a JSON object with sorted keys.
+fn env_object(env: &std::collections::HashMap<String, String>) -> Value {
+    Value::Object(
+        env.iter()
+            .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+            .collect(),
+    )
+}
+
+/// Rewrite the object literal in an ecosystem file as JSON: keep what is
+/// between the first `{` and the last `}`, drop comments, quote bare keys,
+/// turn '...' and `...` strings into "..." and drop trailing commas.
+fn js_to_json(source: &str) -> String {
+    let body = match (source.find('{'), source.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &source[start..=end],
+        _ => source,
+    };
+    let chars: Vec<char> = body.chars().collect();
+    let mut out = String::with_capacity(body.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+                continue;
+            }
+            '"' | '\'' | '`' => {
+                out.push('"');
+                i += 1;
+                while i < chars.len() && chars[i] != c {
+                    match chars[i] {
+                        '\\' if i + 1 < chars.len() => {
+                            // \' needs no escape inside "..."
+                            if chars[i + 1] != '\'' {
+                                out.push('\\');
+                            }
+                            out.push(chars[i + 1]);
+                            i += 2;
+                            continue;
+                        }
+                        '"' => out.push_str("\\\""),
+                        '\n' => out.push_str("\\n"),
+                        other => out.push(other),
+                    }
+                    i += 1;
+                }
+                out.push('"');
+                i += 1;
+                continue;
+            }
+            '}' | ']' => {
+                let trimmed = out.trim_end().len();
+                if out[..trimmed].ends_with(',') {
+                    out.truncate(trimmed - 1);
+                }
+                out.push(c);
+            }
+            c if c.is_alphabetic() || c == '_' || c == '$' => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$')
+                {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                let mut next = i;
+                while next < chars.len() && chars[next].is_whitespace() {
+                    next += 1;
+                }
+                if chars.get(next) == Some(&':') {
+                    out.push('"');
+                    out.push_str(&word);
+                    out.push('"');
+                } else {
+                    out.push_str(&word);
+                }
+                continue;
+            }
+            _ => out.push(c),
+        }
+        i += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ECOSYSTEM: &str = r#"
+// PM2 ecosystem file
+module.exports = {
+  apps: [
+    {
+      name: 'api',
+      script: './dist/server.js',
+      instances: 4,
+      exec_mode: 'cluster',
+      max_memory_restart: '300M',
+      cron_restart: '0 3 * * *',
+      watch: ['dist', "config"],
+      ignore_watch: 'node_modules',
+      namespace: 'backend',
+      args: '--port 3000',
+      env: { NODE_ENV: 'development', PORT: 3000 },
+      env_production: { NODE_ENV: "production" }, /* used with --env production */
+      log_date_format: 'YYYY-MM-DD',
+    },
+    {
+      script: "worker.py",
+      interpreter: "python3",
+      autorestart: false,
+      watch: true,
+    },
+  ],
+};
+"#;
+
+    #[test]
+    fn test_import_pm2_app() {
+        let import = import(ECOSYSTEM).unwrap();
+        let api = &import.config.apps["api"];
+        assert_eq!(api.script, "./dist/server.js");
+        assert_eq!(api.instances, Instances::Count(4));
+        assert_eq!(api.max_memory_restart.as_deref(), Some("300M"));
+        assert_eq!(parse_memory_string("300M").unwrap(), 300 * 1024 * 1024);
+        assert_eq!(api.cron_restart.as_deref(), Some("0 3 * * *"));
+        assert!(api.watch);
+        assert_eq!(api.watch_paths, ["dist", "config"]);
+        assert_eq!(api.watch_ignore, ["node_modules"]);
+        assert_eq!(api.group.as_deref(), Some("backend"));
+        assert_eq!(api.args, ["--port", "3000"]);
+        assert_eq!(api.env["PORT"], "3000");
+        assert_eq!(api.env_profiles["production"]["NODE_ENV"], "production");
+        assert!(api.autorestart);
+
+        // Unnamed apps take the script's file stem
+        let worker = &import.config.apps["worker"];
+        assert_eq!(worker.interpreter.as_deref(), Some("python3"));
+        assert_eq!(
+            (worker.instances, worker.autorestart),
+            (Instances::Count(1), false)
+        );
+        assert!(worker.watch && worker.watch_paths.is_empty());
+
+        assert_eq!(
+            import.warnings,
+            ["api: dropped unsupported option 'log_date_format'"]
+        );
+    }
+
+    #[test]
+    fn test_export_pm2_round_trip() {
+        let config = parse(
+            r#"
+[apps.api]
+script = "server.js"
+cwd = "/srv/api"
+interpreter = "node"
+args = ["--port", "3000"]
+instances = 2
+group = "backend"
+max_restarts = 5
+kill_timeout = 8000
+restart_delay = 200
+exp_backoff_restart_delay = true
+max_memory_restart = "150M"
+cron_restart = "0 3 * * *"
+watch = true
+watch_paths = ["src"]
+watch_ignore = ["node_modules"]
+log_file = "/var/log/api.log"
+
+[apps.api.env]
+PORT = "3000"
+
+[apps.api.env_production]
+NODE_ENV = "production"
+
+[apps.worker]
+script = "worker.py"
+autorestart = false
+max_memory_restart = "1048576b"
+stop_on_success = true
+"#,
+        )
+        .unwrap();
+
+        let export = export(&config);
+        let apps = export.ecosystem["apps"].as_array().unwrap();
+        assert_eq!(apps[0]["name"], "api");
+        assert_eq!(apps[0]["namespace"], "backend");
+        assert_eq!(apps[0]["exp_backoff_restart_delay"], 200);
+        assert_eq!(apps[1]["max_memory_restart"], "1M");
+        assert_eq!(
+            export.warnings,
+            ["worker: PM2 has no equivalent for stop_on_success"]
+        );
+
+        let text = format!("module.exports = {};\n", export.ecosystem);
+        let back = import(&text).unwrap();
+        assert!(back.warnings.is_empty(), "{:?}", back.warnings);
+        assert_eq!(back.config.apps["api"], config.apps["api"]);
+
+        // Everything PM2 can express survives; the memory string is normalized
+        let mut worker = config.apps["worker"].clone();
+        worker.max_memory_restart = Some("1M".into());
+        worker.stop_on_success = false;
+        assert_eq!(back.config.apps["worker"], worker);
+    }
+
+    #[test]
+    fn test_import_pm2_json_and_errors() {
+        let json =
+            r#"{"apps": [{"name": "web", "script": "app.js", "max_memory_restart": 1048576}]}"#;
+        let web = &import(json).unwrap().config.apps["web"];
+        assert_eq!(web.max_memory_restart.as_deref(), Some("1048576"));
+
+        let err = import(r#"{apps: [{name: "x", script: "a.js", max_memory_restart: "lots"}]}"#)
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("invalid max_memory_restart"),
+            "{err}"
+        );
+        let err = import(r#"{apps: [{name: "x"}]}"#).unwrap_err();
+        assert!(err.to_string().contains("missing script"), "{err}");
+        let err = import("{apps: [{script: 'a.js'}, {script: 'lib/a.js'}]}").unwrap_err();
+        assert!(err.to_string().contains("two apps are named 'a'"), "{err}");
+    }
+}
@@ -1,8 +1,11 @@
+use std::path::PathBuf;
+
 /// Velos error types
 #[derive(Debug, thiserror::Error)]
 pub enum VelosError {
-    #[error("daemon is not running")]
-    DaemonNotRunning,
+    /// Nothing is listening on the socket that was tried.
+    #[error("daemon is not running (tried: {})", .0.display())]
+    DaemonNotRunning(PathBuf),
 
     #[error("connection failed: {0}")]
     ConnectionFailed(String),
@@ -13,6 +16,15 @@ pub enum VelosError {
     #[error("process not found: {0}")]
     ProcessNotFound(String),
 
+    #[error("script not found: {0}")]
+    ScriptNotFound(String),
+
+    #[error("process name already in use: {0}")]
+    NameInUse(String),
+
+    #[error("failed to spawn process: {0}")]
+    SpawnFailed(String),
+
     #[error("protocol error: {0}")]
     ProtocolError(String),
 
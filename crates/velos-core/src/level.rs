@@ -0,0 +1,38 @@
+//! Log level names, shared by the log engine and config validation.
+
+/// Level words loggers and users write, with their wire level
+/// (`LogEntry::level`: 0 debug, 1 info, 2 warn, 3 error, 4 fatal).
+pub const NAMES: &[(&str, u8)] = &[
+    ("trace", 0),
+    ("debug", 0),
+    ("info", 1),
+    ("notice", 1),
+    ("warn", 2),
+    ("warning", 2),
+    ("error", 3),
+    ("err", 3),
+    ("fatal", 4),
+    ("critical", 4),
+    ("crit", 4),
+    ("panic", 4),
+];
+
+/// Wire level for a level name as typed by a user or logger, any case:
+/// one of [`NAMES`] ("warn"/"warning", "error"/"err", ...).
+///
+/// Syslog severities 0-7 map to the nearest level: 0-2 (emerg, alert,
+/// crit) fatal, 3 error, 4 warn, 5-6 (notice, info) info, 7 debug.
+pub fn from_name(name: &str) -> Option<u8> {
+    let name = name.trim().to_lowercase();
+    if let Some(&(_, level)) = NAMES.iter().find(|(n, _)| *n == name) {
+        return Some(level);
+    }
+    match name.as_str() {
+        "7" => Some(0),
+        "5" | "6" => Some(1),
+        "4" => Some(2),
+        "3" => Some(3),
+        "0" | "1" | "2" => Some(4),
+        _ => None,
+    }
+}
@@ -1,6 +1,8 @@
 pub mod error;
+pub mod level;
 pub mod process;
 pub mod protocol;
+pub mod redact;
 
 pub use error::VelosError;
 pub use process::{ProcessConfig, ProcessStatus};
@@ -28,6 +28,18 @@ pub struct ProcessConfig {
     /// Restart process when memory exceeds this limit (bytes, None = unlimited)
     #[serde(default)]
     pub max_memory_restart: Option<u64>,
+    /// Exit codes treated as a clean exit rather than a crash (default [0])
+    #[serde(default = "default_success_exit_codes")]
+    pub success_exit_codes: Vec<i32>,
+    /// Don't autorestart after a clean exit (default false)
+    #[serde(default)]
+    pub stop_on_success: bool,
+    /// Restart when CPU% stays above this limit (None = unlimited)
+    #[serde(default)]
+    pub max_cpu_restart: Option<f32>,
+    /// Seconds CPU must stay above `max_cpu_restart` (default 60)
+    #[serde(default = "default_cpu_restart_window_secs")]
+    pub cpu_restart_window_secs: u32,
 }
 
 fn default_kill_timeout() -> u32 {
@@ -46,6 +58,14 @@ fn default_min_uptime_ms() -> u64 {
     1000
 }
 
+fn default_success_exit_codes() -> Vec<i32> {
+    vec![0]
+}
+
+fn default_cpu_restart_window_secs() -> u32 {
+    60
+}
+
 impl Default for ProcessConfig {
     fn default() -> Self {
         Self {
@@ -60,10 +80,41 @@ impl Default for ProcessConfig {
             restart_delay_ms: 0,
             exp_backoff_restart_delay: false,
             max_memory_restart: None,
+            success_exit_codes: default_success_exit_codes(),
+            stop_on_success: false,
+            max_cpu_restart: None,
+            cpu_restart_window_secs: default_cpu_restart_window_secs(),
         }
     }
 }
 
+/// Default cluster instance naming: `api:0`, `api:1`, ...
+pub const DEFAULT_INSTANCE_NAME_TEMPLATE: &str = "{name}:{index}";
+
+/// Instance name templates understood by the daemon and by name resolution.
+/// `{name}` is the base process name, `{index}` the 0-based instance index.
+pub const INSTANCE_NAME_TEMPLATES: &[&str] =
+    &["{name}:{index}", "{name}-{index}", "{name}_{index}"];
+
+/// Render a cluster instance name from a template.
+pub fn instance_name(template: &str, name: &str, index: u32) -> String {
+    template
+        .replace("{name}", name)
+        .replace("{index}", &index.to_string())
+}
+
+/// If `candidate` is an instance of `base` under `template`, return its index.
+pub fn match_instance(template: &str, base: &str, candidate: &str) -> Option<u32> {
+    let (prefix, suffix) = template.split_once("{index}")?;
+    let prefix = prefix.replace("{name}", base);
+    let suffix = suffix.replace("{name}", base);
+    let digits = candidate.strip_prefix(&prefix)?.strip_suffix(&suffix)?;
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
 /// Process lifecycle status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ProcessStatus {
@@ -85,3 +136,34 @@ impl std::fmt::Display for ProcessStatus {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instance_name_from_template() {
+        assert_eq!(
+            instance_name(DEFAULT_INSTANCE_NAME_TEMPLATE, "api", 2),
+            "api:2"
+        );
+        assert_eq!(instance_name("{name}-{index}", "api", 0), "api-0");
+    }
+
+    #[test]
+    fn match_instance_respects_template() {
+        assert_eq!(match_instance("{name}:{index}", "api", "api:3"), Some(3));
+        assert_eq!(match_instance("{name}-{index}", "api", "api-0"), Some(0));
+        assert_eq!(match_instance("{name}_{index}", "api", "api_12"), Some(12));
+
+        // Delimiter must match the template.
+        assert_eq!(match_instance("{name}:{index}", "api", "api-0"), None);
+        assert_eq!(match_instance("{name}-{index}", "api", "api:0"), None);
+
+        // Not an instance: different base or non-numeric suffix.
+        assert_eq!(match_instance("{name}-{index}", "api", "api-gateway"), None);
+        assert_eq!(match_instance("{name}:{index}", "api", "apix:1"), None);
+        assert_eq!(match_instance("{name}:{index}", "api", "api:"), None);
+        assert_eq!(match_instance("{name}:{index}", "api", "api"), None);
+    }
+}
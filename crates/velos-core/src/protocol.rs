@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::Serialize;
 
 // ============================================================
@@ -6,7 +8,12 @@ use serde::Serialize;
 
 pub const MAGIC: [u8; 2] = [0x56, 0x10];
 pub const VERSION: u8 = 0x01;
+/// Version 2: LOG_READ entries end with their `seq`, and PROCESS_RESET is
+/// handled. Only used once HELLO has settled on it.
+pub const VERSION_2: u8 = 0x02;
 pub const HEADER_SIZE: usize = 7;
+/// Protocol versions this client can speak, in ascending order.
+pub const SUPPORTED_VERSIONS: &[u8] = &[VERSION, VERSION_2];
 
 // ============================================================
 // Binary reader/writer (matches Zig protocol helpers)
@@ -31,6 +38,10 @@ impl BinaryWriter {
         self.buf.push(val);
     }
 
+    pub fn write_u16(&mut self, val: u16) {
+        self.buf.extend_from_slice(&val.to_le_bytes());
+    }
+
     pub fn write_u32(&mut self, val: u32) {
         self.buf.extend_from_slice(&val.to_le_bytes());
     }
@@ -131,11 +142,15 @@ impl<'a> BinaryReader<'a> {
 // ============================================================
 
 pub fn encode_header(payload_len: u32) -> [u8; HEADER_SIZE] {
+    encode_header_with_version(payload_len, VERSION)
+}
+
+pub fn encode_header_with_version(payload_len: u32, version: u8) -> [u8; HEADER_SIZE] {
     let len_bytes = payload_len.to_le_bytes();
     [
         MAGIC[0],
         MAGIC[1],
-        VERSION,
+        version,
         len_bytes[0],
         len_bytes[1],
         len_bytes[2],
@@ -150,7 +165,7 @@ pub fn decode_header(buf: &[u8; HEADER_SIZE]) -> Result<u32, crate::VelosError>
             buf[0], buf[1]
         )));
     }
-    if buf[2] != VERSION {
+    if !SUPPORTED_VERSIONS.contains(&buf[2]) {
         return Err(crate::VelosError::ProtocolError(format!(
             "unsupported protocol version: {}",
             buf[2]
@@ -173,6 +188,9 @@ pub enum CommandCode {
     ProcessList = 0x05,
     ProcessInfo = 0x06,
     ProcessScale = 0x07,
+    ProcessReset = 0x08,
+    ProcessHistory = 0x0C,
+    EventStream = 0x0D,
     LogRead = 0x10,
     LogStream = 0x11,
     MetricsGet = 0x20,
@@ -180,6 +198,8 @@ pub enum CommandCode {
     StateLoad = 0x31,
     Ping = 0x40,
     Shutdown = 0x41,
+    DaemonInfo = 0x42,
+    Hello = 0x43,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -201,6 +221,28 @@ impl ResponseStatus {
     }
 }
 
+/// Structured failure reason carried by an error response as
+/// `code(u8) + message`. Codes stay below 0x20 so they can't be confused
+/// with the first byte of a plain-text error message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ErrorCode {
+    ScriptNotFound = 0x01,
+    NameInUse = 0x02,
+    SpawnFailed = 0x03,
+}
+
+impl ErrorCode {
+    pub fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0x01 => Some(Self::ScriptNotFound),
+            0x02 => Some(Self::NameInUse),
+            0x03 => Some(Self::SpawnFailed),
+            _ => None,
+        }
+    }
+}
+
 // ============================================================
 // Request / Response wire types
 // ============================================================
@@ -214,8 +256,12 @@ pub struct Request {
 
 impl Request {
     pub fn encode(&self) -> Result<Vec<u8>, crate::VelosError> {
+        self.encode_with_version(VERSION)
+    }
+
+    pub fn encode_with_version(&self, version: u8) -> Result<Vec<u8>, crate::VelosError> {
         let body_len = 4 + 1 + self.payload.len();
-        let header = encode_header(body_len as u32);
+        let header = encode_header_with_version(body_len as u32, version);
         let mut buf = Vec::with_capacity(HEADER_SIZE + body_len);
         buf.extend_from_slice(&header);
         buf.extend_from_slice(&self.id.to_le_bytes());
@@ -251,8 +297,30 @@ impl Response {
         })
     }
 
+    /// Structured code at the front of an error payload, if any.
+    pub fn error_code(&self) -> Option<ErrorCode> {
+        self.payload.first().copied().and_then(ErrorCode::from_u8)
+    }
+
+    /// Error text without the leading code byte.
     pub fn error_message(&self) -> String {
-        String::from_utf8_lossy(&self.payload).to_string()
+        let msg = match self.error_code() {
+            Some(_) => &self.payload[1..],
+            None => &self.payload[..],
+        };
+        String::from_utf8_lossy(msg).to_string()
+    }
+
+    /// Map an error response to the matching `VelosError`; uncoded errors
+    /// become `ProtocolError`.
+    pub fn to_error(&self) -> crate::VelosError {
+        let msg = self.error_message();
+        match self.error_code() {
+            Some(ErrorCode::ScriptNotFound) => crate::VelosError::ScriptNotFound(msg),
+            Some(ErrorCode::NameInUse) => crate::VelosError::NameInUse(msg),
+            Some(ErrorCode::SpawnFailed) => crate::VelosError::SpawnFailed(msg),
+            None => crate::VelosError::ProtocolError(msg),
+        }
     }
 }
 
@@ -284,6 +352,28 @@ pub struct StartPayload {
     pub shutdown_with_message: bool,
     pub instances: u32,
     pub env_vars: String,
+    /// Cluster instance name template; empty = daemon default (`{name}:{index}`).
+    pub instance_name_template: String,
+    /// Absolute stdout log path; empty = `<log_dir>/<name>-out.log`.
+    pub out_log: String,
+    /// Absolute stderr log path; empty = `<log_dir>/<name>-err.log`.
+    pub err_log: String,
+    /// Write stderr lines to the stdout log.
+    pub merge_logs: bool,
+    /// Arguments passed to the script after its path (`argv[1..]`).
+    pub args: Vec<String>,
+    /// Logical group for `@group` targeting; empty = ungrouped.
+    pub group: String,
+    /// Max log lines per second; the daemon drops the excess. 0 = unlimited.
+    pub log_rate_limit: u32,
+    /// Exit codes that count as a clean exit; anything else is a crash.
+    pub success_exit_codes: Vec<i32>,
+    /// Don't autorestart after a clean exit.
+    pub stop_on_success: bool,
+    /// Restart when CPU% stays above this for `cpu_restart_window_secs`.
+    /// 0 = unlimited. Sent as tenths of a percent (u16).
+    pub max_cpu_restart: f32,
+    pub cpu_restart_window_secs: u32,
 }
 
 impl StartPayload {
@@ -310,10 +400,64 @@ impl StartPayload {
         w.write_u8(if self.shutdown_with_message { 1 } else { 0 });
         w.write_u32(self.instances);
         w.write_string(&self.env_vars);
+        w.write_string(&self.instance_name_template);
+        w.write_string(&self.out_log);
+        w.write_string(&self.err_log);
+        w.write_u8(if self.merge_logs { 1 } else { 0 });
+        w.write_u32(self.args.len() as u32);
+        for arg in &self.args {
+            w.write_string(arg);
+        }
+        w.write_string(&self.group);
+        w.write_u32(self.log_rate_limit);
+        w.write_u8(if self.stop_on_success { 1 } else { 0 });
+        w.write_u32(self.success_exit_codes.len() as u32);
+        for code in &self.success_exit_codes {
+            w.write_i32(*code);
+        }
+        w.write_u16(cpu_tenths(self.max_cpu_restart));
+        w.write_u32(self.cpu_restart_window_secs);
         w.buf
     }
 }
 
+/// CPU percent in the daemon's tenths-of-a-percent units (25.3 -> 253).
+fn cpu_tenths(percent: f32) -> u16 {
+    (percent * 10.0).round().clamp(0.0, u16::MAX as f32) as u16
+}
+
+// --- Hello (version negotiation) ---
+
+/// Payload: count(u8) + versions(u8 * count)
+pub struct HelloPayload {
+    pub versions: Vec<u8>,
+}
+
+impl HelloPayload {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut w = BinaryWriter::new();
+        w.write_u8(self.versions.len() as u8);
+        for v in &self.versions {
+            w.write_u8(*v);
+        }
+        w.buf
+    }
+}
+
+/// Response: negotiated version(u8)
+pub struct HelloResult {
+    pub version: u8,
+}
+
+impl HelloResult {
+    pub fn decode(data: &[u8]) -> Result<Self, crate::VelosError> {
+        let mut r = BinaryReader::new(data);
+        Ok(Self {
+            version: r.read_u8()?,
+        })
+    }
+}
+
 // --- Scale ---
 
 pub struct ScalePayload {
@@ -359,18 +503,29 @@ impl StartResult {
 
 // --- Stop ---
 
+/// Signal the process's whole group (it runs as a session leader, so this
+/// is every descendant that didn't start its own session), not just its PID.
+pub const STOP_FLAG_KILL_TREE: u8 = 0x01;
+
 pub struct StopPayload {
     pub process_id: u32,
     pub signal: u8,
     pub timeout_ms: u32,
+    /// `STOP_FLAG_*` bits.
+    pub flags: u8,
 }
 
 impl StopPayload {
+    /// The flags byte trails the payload and is only sent when a bit is set,
+    /// so a plain stop is byte-for-byte what older daemons expect.
     pub fn encode(&self) -> Vec<u8> {
         let mut w = BinaryWriter::new();
         w.write_u32(self.process_id);
         w.write_u8(self.signal);
         w.write_u32(self.timeout_ms);
+        if self.flags != 0 {
+            w.write_u8(self.flags);
+        }
         w.buf
     }
 }
@@ -401,6 +556,8 @@ pub struct ProcessInfo {
     pub uptime_ms: u64,
     pub restart_count: u32,
     pub cpu_percent: f32, // e.g. 25.3
+    /// Logical group (`velos start --group`), None when ungrouped.
+    pub group: Option<String>,
 }
 
 impl ProcessInfo {
@@ -415,32 +572,81 @@ impl ProcessInfo {
     }
 }
 
+/// A PROCESS_LIST response.
+#[derive(Debug, Clone)]
+pub struct ProcessList {
+    pub procs: Vec<ProcessInfo>,
+    /// Why the payload ended early, when decoded leniently and it did.
+    /// `procs` then holds only the records before the cut.
+    pub truncated: Option<String>,
+}
+
 pub fn decode_process_list(data: &[u8]) -> Result<Vec<ProcessInfo>, crate::VelosError> {
+    decode_process_list_with(data, false).map(|list| list.procs)
+}
+
+/// Decode a PROCESS_LIST payload. With `lenient`, a payload cut off partway
+/// (say the daemon died mid-reply) keeps the processes decoded so far and
+/// reports the cut in [`ProcessList::truncated`] instead of failing.
+pub fn decode_process_list_with(
+    data: &[u8],
+    lenient: bool,
+) -> Result<ProcessList, crate::VelosError> {
     let mut r = BinaryReader::new(data);
     let count = r.read_u32()? as usize;
     let mut procs = Vec::with_capacity(count);
+    let mut truncated = None;
     for _ in 0..count {
-        let id = r.read_u32()?;
-        let name = r.read_string()?;
-        let pid = r.read_u32()?;
-        let status = r.read_u8()?;
-        let memory_bytes = r.read_u64()?;
-        let uptime_ms = r.read_u64()?;
-        let restart_count = r.read_u32()?;
-        // cpu_percent: u16 * 10, backward compatible (0 if not present)
-        let cpu_raw = if r.remaining() >= 2 { r.read_u16()? } else { 0 };
-        procs.push(ProcessInfo {
-            id,
-            name,
-            pid,
-            status,
-            memory_bytes,
-            uptime_ms,
-            restart_count,
-            cpu_percent: cpu_raw as f32 / 10.0,
-        });
+        match decode_process_info(&mut r) {
+            Ok(p) => procs.push(p),
+            Err(e) if lenient => {
+                truncated = Some(format!(
+                    "process list cut off after {} of {count} processes ({e})",
+                    procs.len()
+                ));
+                break;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    // Groups follow the records, one string per process in the same order.
+    // Older daemons stop after the records.
+    if truncated.is_none() && r.remaining() >= 4 {
+        for p in &mut procs {
+            match r.read_string() {
+                Ok(group) => p.group = (!group.is_empty()).then_some(group),
+                Err(e) if lenient => {
+                    truncated = Some(format!("process list cut off in the groups ({e})"));
+                    break;
+                }
+                Err(e) => return Err(e),
+            }
+        }
     }
-    Ok(procs)
+    Ok(ProcessList { procs, truncated })
+}
+
+fn decode_process_info(r: &mut BinaryReader) -> Result<ProcessInfo, crate::VelosError> {
+    let id = r.read_u32()?;
+    let name = r.read_string()?;
+    let pid = r.read_u32()?;
+    let status = r.read_u8()?;
+    let memory_bytes = r.read_u64()?;
+    let uptime_ms = r.read_u64()?;
+    let restart_count = r.read_u32()?;
+    // cpu_percent: u16 * 10, backward compatible (0 if not present)
+    let cpu_raw = if r.remaining() >= 2 { r.read_u16()? } else { 0 };
+    Ok(ProcessInfo {
+        id,
+        name,
+        pid,
+        status,
+        memory_bytes,
+        uptime_ms,
+        restart_count,
+        cpu_percent: cpu_raw as f32 / 10.0,
+        group: None,
+    })
 }
 
 // --- Restart ---
@@ -457,6 +663,21 @@ impl RestartPayload {
     }
 }
 
+// --- Reset ---
+
+/// Zero a process's restart and crash counters.
+pub struct ResetPayload {
+    pub process_id: u32,
+}
+
+impl ResetPayload {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut w = BinaryWriter::new();
+        w.write_u32(self.process_id);
+        w.buf
+    }
+}
+
 // --- Info ---
 
 pub struct InfoPayload {
@@ -471,7 +692,7 @@ impl InfoPayload {
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct ProcessDetail {
     pub id: u32,
     pub name: String,
@@ -496,6 +717,68 @@ pub struct ProcessDetail {
     pub cron_restart: String,
     pub wait_ready: bool,
     pub shutdown_with_message: bool,
+    pub last_exit_reason: ExitReason,
+    /// Max log lines per second (0 = unlimited).
+    pub log_rate_limit: u32,
+    /// Log lines dropped by the rate limit since the process started.
+    pub suppressed_lines: u64,
+    /// stderr is written to the stdout log (`merge_logs`).
+    pub merge_logs: bool,
+    /// CPU% limit for restarts (0 = unlimited) and how long it must be
+    /// exceeded.
+    pub max_cpu_restart: f32,
+    pub cpu_restart_window_secs: u32,
+    /// Log file paths given at start; empty = `<log_dir>/<name>-out.log`
+    /// and `<log_dir>/<name>-err.log`.
+    pub out_log: String,
+    pub err_log: String,
+    /// Environment the process was started with. Not serialized: it holds
+    /// secrets, so callers print it through [`crate::redact::redact_env`].
+    #[serde(skip)]
+    pub env: HashMap<String, String>,
+}
+
+/// Why a process last exited, as tracked by the daemon.
+/// Wire byte: 0=none 1=crashed 2=oom 3=manual 4=signal 5=cpu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExitReason {
+    #[default]
+    None,
+    /// Exited on its own with a non-zero code.
+    Crashed,
+    /// Killed by the daemon for exceeding `max_memory_restart`.
+    Oom,
+    /// Stopped or restarted on request.
+    Manual,
+    /// Terminated by a signal the daemon did not send.
+    Signal,
+    /// Killed by the daemon for exceeding `max_cpu_restart`.
+    Cpu,
+}
+
+impl ExitReason {
+    pub fn from_u8(v: u8) -> Self {
+        match v {
+            1 => Self::Crashed,
+            2 => Self::Oom,
+            3 => Self::Manual,
+            4 => Self::Signal,
+            5 => Self::Cpu,
+            _ => Self::None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Crashed => "crashed",
+            Self::Oom => "oom",
+            Self::Manual => "manual",
+            Self::Signal => "signal",
+            Self::Cpu => "cpu",
+        }
+    }
 }
 
 impl ProcessDetail {
@@ -516,9 +799,14 @@ impl ProcessDetail {
 /// + script(string) + cwd(string) + interpreter(string)
 /// + kill_timeout(u32) + autorestart(u8) + max_restarts(i32)
 /// + min_uptime_ms(u64) + restart_delay_ms(u32) + exp_backoff(u8)
+/// + max_memory_restart(u64) + watch(u8) + cron_restart(string) + wait_ready(u8)
+/// + shutdown_with_message(u8), then optional trailing fields: last_exit_reason(u8)
+/// + log_rate_limit(u32) + suppressed(u64) + merge_logs(u8)
+/// + max_cpu_restart(u16, tenths of a percent) + cpu_restart_window_secs(u32)
+/// + out_log(string) + err_log(string) + env_vars(string, KEY=VALUE lines)
 pub fn decode_process_detail(data: &[u8]) -> Result<ProcessDetail, crate::VelosError> {
     let mut r = BinaryReader::new(data);
-    Ok(ProcessDetail {
+    let mut detail = ProcessDetail {
         id: r.read_u32()?,
         name: r.read_string()?,
         pid: r.read_u32()?,
@@ -542,6 +830,203 @@ pub fn decode_process_detail(data: &[u8]) -> Result<ProcessDetail, crate::VelosE
         cron_restart: r.read_string()?,
         wait_ready: r.read_u8()? != 0,
         shutdown_with_message: r.read_u8()? != 0,
+        last_exit_reason: ExitReason::None,
+        log_rate_limit: 0,
+        suppressed_lines: 0,
+        merge_logs: false,
+        max_cpu_restart: 0.0,
+        cpu_restart_window_secs: 0,
+        out_log: String::new(),
+        err_log: String::new(),
+        env: HashMap::new(),
+    };
+    // last_exit_reason: backward compatible (none if not present)
+    if r.remaining() >= 1 {
+        detail.last_exit_reason = ExitReason::from_u8(r.read_u8()?);
+    }
+    if r.remaining() >= 12 {
+        detail.log_rate_limit = r.read_u32()?;
+        detail.suppressed_lines = r.read_u64()?;
+    }
+    if r.remaining() >= 1 {
+        detail.merge_logs = r.read_u8()? != 0;
+    }
+    if r.remaining() >= 6 {
+        detail.max_cpu_restart = r.read_u16()? as f32 / 10.0;
+        detail.cpu_restart_window_secs = r.read_u32()?;
+    }
+    if r.remaining() >= 8 {
+        detail.out_log = r.read_string()?;
+        detail.err_log = r.read_string()?;
+    }
+    if r.remaining() >= 4 {
+        detail.env = r
+            .read_string()?
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+    }
+    Ok(detail)
+}
+
+// --- History ---
+
+pub struct HistoryPayload {
+    pub process_id: u32,
+}
+
+impl HistoryPayload {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut w = BinaryWriter::new();
+        w.write_u32(self.process_id);
+        w.buf
+    }
+}
+
+/// A lifecycle event from the daemon's bounded per-process history.
+#[derive(Debug, Clone, Serialize)]
+pub struct LifecycleEvent {
+    pub timestamp_ms: u64,
+    /// 0=restart 1=crash 2=oom 3=cron 4=watch 5=manual 6=start 7=stop 8=cpu
+    pub kind: u8,
+    /// Exit code for crashes; negative signal number if killed by a signal.
+    pub exit_code: i32,
+}
+
+impl LifecycleEvent {
+    pub fn kind_str(&self) -> &'static str {
+        match self.kind {
+            0 => "restart",
+            1 => "crash",
+            2 => "oom",
+            3 => "cron",
+            4 => "watch",
+            5 => "manual",
+            6 => "start",
+            7 => "stop",
+            8 => "cpu",
+            _ => "unknown",
+        }
+    }
+
+    /// The daemon's decision in words, e.g. "restarting due to max_memory_restart".
+    pub fn describe(&self) -> String {
+        match self.kind {
+            0 => "restarting after a clean exit".into(),
+            1 if self.exit_code < 0 => format!("crashed (signal {})", -self.exit_code),
+            1 => format!("crashed (exit code {})", self.exit_code),
+            2 => "restarting due to max_memory_restart".into(),
+            3 => "restarting due to cron_restart".into(),
+            4 => "restarting due to a watched file change".into(),
+            5 => "restart requested".into(),
+            6 => "started".into(),
+            7 => "stop requested".into(),
+            8 => "restarting due to max_cpu_restart".into(),
+            _ => format!("unknown event {}", self.kind),
+        }
+    }
+}
+
+/// Decode process history matching Zig handleProcessHistory encoding:
+/// count(u32) + count * [timestamp_ms(u64) + kind(u8) + exit_code(i32)], oldest first.
+pub fn decode_process_history(data: &[u8]) -> Result<Vec<LifecycleEvent>, crate::VelosError> {
+    let mut r = BinaryReader::new(data);
+    let count = r.read_u32()? as usize;
+    let mut events = Vec::with_capacity(count);
+    for _ in 0..count {
+        events.push(LifecycleEvent {
+            timestamp_ms: r.read_u64()?,
+            kind: r.read_u8()?,
+            exit_code: r.read_i32()?,
+        });
+    }
+    Ok(events)
+}
+
+// --- Event stream ---
+
+/// Request lifecycle events across all processes newer than `since_ms`.
+/// Polling with the newest timestamp seen so far follows the feed.
+pub struct EventStreamPayload {
+    pub since_ms: u64,
+}
+
+impl EventStreamPayload {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut w = BinaryWriter::new();
+        w.write_u64(self.since_ms);
+        w.buf
+    }
+}
+
+/// A lifecycle event together with the process it happened to.
+#[derive(Debug, Clone)]
+pub struct DaemonEvent {
+    pub process_id: u32,
+    pub name: String,
+    pub event: LifecycleEvent,
+}
+
+/// Decode an event stream frame matching Zig handleEventStream encoding:
+/// count(u32) + count * [timestamp_ms(u64) + process_id(u32) + name(string) +
+/// kind(u8) + exit_code(i32)]. Returned oldest first; the daemon sends each
+/// process's events in order but does not interleave processes.
+pub fn decode_event_stream(data: &[u8]) -> Result<Vec<DaemonEvent>, crate::VelosError> {
+    let mut r = BinaryReader::new(data);
+    let count = r.read_u32()? as usize;
+    let mut events = Vec::with_capacity(count);
+    for _ in 0..count {
+        let timestamp_ms = r.read_u64()?;
+        let process_id = r.read_u32()?;
+        let name = r.read_string()?;
+        events.push(DaemonEvent {
+            process_id,
+            name,
+            event: LifecycleEvent {
+                timestamp_ms,
+                kind: r.read_u8()?,
+                exit_code: r.read_i32()?,
+            },
+        });
+    }
+    events.sort_by_key(|e| e.event.timestamp_ms);
+    Ok(events)
+}
+
+// --- Daemon Info ---
+
+/// The settings a running daemon started with.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DaemonInfo {
+    pub socket_path: String,
+    /// Empty if the daemon runs without persistence.
+    pub state_dir: String,
+    /// Empty if the daemon wasn't told its version.
+    pub version: String,
+    /// When the daemon started listening (ms since the epoch).
+    pub started_ms: u64,
+    pub pid: u32,
+    /// 0 = unlimited.
+    pub max_processes: u32,
+    /// Concurrent IPC connections the daemon accepts.
+    pub max_clients: u32,
+}
+
+/// Decode a DAEMON_INFO reply matching Zig handleDaemonInfo encoding:
+/// socket_path(string) + state_dir(string) + version(string) +
+/// started_ms(u64) + pid(u32) + max_processes(u32) + max_clients(u32).
+/// Trailing bytes from newer daemons are ignored.
+pub fn decode_daemon_info(data: &[u8]) -> Result<DaemonInfo, crate::VelosError> {
+    let mut r = BinaryReader::new(data);
+    Ok(DaemonInfo {
+        socket_path: r.read_string()?,
+        state_dir: r.read_string()?,
+        version: r.read_string()?,
+        started_ms: r.read_u64()?,
+        pid: r.read_u32()?,
+        max_processes: r.read_u32()?,
+        max_clients: r.read_u32()?,
     })
 }
 
@@ -586,9 +1071,24 @@ pub struct LogEntry {
     pub level: u8,
     pub stream: u8,
     pub message: String,
+    /// Per-process sequence number assigned by the daemon, increasing by one
+    /// per line and kept across process restarts. 0 = unknown (read from
+    /// disk, or from a daemon that speaks only protocol version 1).
+    pub seq: u64,
 }
 
-pub fn decode_log_entries(data: &[u8]) -> Result<Vec<LogEntry>, crate::VelosError> {
+/// A LOG_READ response.
+#[derive(Debug, Clone)]
+pub struct LogRead {
+    pub entries: Vec<LogEntry>,
+    /// Lines the daemon dropped under `log_rate_limit` since the process
+    /// started. 0 from daemons that predate rate limiting.
+    pub suppressed: u64,
+}
+
+/// Decode a LOG_READ response sent under protocol `version`: entries carry
+/// `seq` from [`VERSION_2`] on.
+pub fn decode_log_read(data: &[u8], version: u8) -> Result<LogRead, crate::VelosError> {
     let mut r = BinaryReader::new(data);
     let count = r.read_u32()? as usize;
     let mut entries = Vec::with_capacity(count);
@@ -598,9 +1098,22 @@ pub fn decode_log_entries(data: &[u8]) -> Result<Vec<LogEntry>, crate::VelosErro
             level: r.read_u8()?,
             stream: r.read_u8()?,
             message: r.read_string()?,
+            seq: if version >= VERSION_2 {
+                r.read_u64()?
+            } else {
+                0
+            },
         });
     }
-    Ok(entries)
+    let suppressed = if r.remaining() >= 8 { r.read_u64()? } else { 0 };
+    Ok(LogRead {
+        entries,
+        suppressed,
+    })
+}
+
+pub fn decode_log_entries(data: &[u8], version: u8) -> Result<Vec<LogEntry>, crate::VelosError> {
+    decode_log_read(data, version).map(|read| read.entries)
 }
 
 // ============================================================
@@ -655,6 +1168,42 @@ mod tests {
         assert_eq!(&resp.payload, b"pong");
     }
 
+    fn error_response(payload: &[u8]) -> Response {
+        let mut body = Vec::new();
+        body.extend_from_slice(&7u32.to_le_bytes());
+        body.push(ResponseStatus::Error as u8);
+        body.extend_from_slice(payload);
+        Response::from_body(&body).unwrap()
+    }
+
+    #[test]
+    fn test_error_code_decode() {
+        let resp = error_response(b"\x01/srv/app/missing.js");
+        assert_eq!(resp.error_code(), Some(ErrorCode::ScriptNotFound));
+        assert_eq!(resp.error_message(), "/srv/app/missing.js");
+        assert!(matches!(
+            resp.to_error(),
+            crate::VelosError::ScriptNotFound(ref m) if m == "/srv/app/missing.js"
+        ));
+
+        assert!(matches!(
+            error_response(b"\x02api").to_error(),
+            crate::VelosError::NameInUse(ref m) if m == "api"
+        ));
+        assert!(matches!(
+            error_response(b"\x03ProcessFdQuotaExceeded").to_error(),
+            crate::VelosError::SpawnFailed(ref m) if m == "ProcessFdQuotaExceeded"
+        ));
+
+        // Plain-text errors from older daemons are left alone
+        let resp = error_response(b"ProcessNotFound");
+        assert_eq!(resp.error_code(), None);
+        assert!(matches!(
+            resp.to_error(),
+            crate::VelosError::ProtocolError(ref m) if m == "ProcessNotFound"
+        ));
+    }
+
     #[test]
     fn test_binary_writer_reader_roundtrip() {
         let mut w = BinaryWriter::new();
@@ -670,6 +1219,25 @@ mod tests {
         assert_eq!(r.read_u64().unwrap(), 9999999);
     }
 
+    #[test]
+    fn test_hello_roundtrip() {
+        let bytes = HelloPayload {
+            versions: vec![1, 2],
+        }
+        .encode();
+        assert_eq!(bytes, vec![2, 1, 2]);
+        assert_eq!(HelloResult::decode(&[1]).unwrap().version, 1);
+
+        let req = Request {
+            id: 1,
+            command: CommandCode::Hello,
+            payload: bytes,
+        };
+        let encoded = req.encode().unwrap();
+        assert_eq!(encoded[2], VERSION);
+        assert_eq!(encoded[HEADER_SIZE + 4], 0x43);
+    }
+
     #[test]
     fn test_start_payload_encode() {
         let payload = StartPayload {
@@ -694,6 +1262,17 @@ mod tests {
             shutdown_with_message: false,
             instances: 1,
             env_vars: "FOO=bar\nBAZ=qux".into(),
+            instance_name_template: "{name}-{index}".into(),
+            out_log: "/var/log/app.out".into(),
+            err_log: String::new(),
+            merge_logs: true,
+            args: vec!["--port".into(), "3000".into(), "two words".into()],
+            group: "backend".into(),
+            log_rate_limit: 500,
+            success_exit_codes: vec![0, 2],
+            stop_on_success: true,
+            max_cpu_restart: 90.5,
+            cpu_restart_window_secs: 30,
         };
         let bytes = payload.encode();
 
@@ -719,6 +1298,200 @@ mod tests {
         assert_eq!(r.read_u8().unwrap(), 0); // shutdown_with_message
         assert_eq!(r.read_u32().unwrap(), 1); // instances
         assert_eq!(r.read_string().unwrap(), "FOO=bar\nBAZ=qux"); // env_vars
+        assert_eq!(r.read_string().unwrap(), "{name}-{index}"); // instance_name_template
+        assert_eq!(r.read_string().unwrap(), "/var/log/app.out"); // out_log
+        assert_eq!(r.read_string().unwrap(), ""); // err_log
+        assert_eq!(r.read_u8().unwrap(), 1); // merge_logs
+        assert_eq!(r.read_u32().unwrap(), 3); // args count
+        assert_eq!(r.read_string().unwrap(), "--port");
+        assert_eq!(r.read_string().unwrap(), "3000");
+        assert_eq!(r.read_string().unwrap(), "two words");
+        assert_eq!(r.read_string().unwrap(), "backend"); // group
+        assert_eq!(r.read_u32().unwrap(), 500); // log_rate_limit
+        assert_eq!(r.read_u8().unwrap(), 1); // stop_on_success
+        assert_eq!(r.read_u32().unwrap(), 2); // success_exit_codes count
+        assert_eq!(r.read_i32().unwrap(), 0);
+        assert_eq!(r.read_i32().unwrap(), 2);
+        assert_eq!(r.read_u16().unwrap(), 905); // max_cpu_restart, tenths of a percent
+        assert_eq!(r.read_u32().unwrap(), 30); // cpu_restart_window_secs
+        assert!(r.read_u8().is_err()); // nothing after cpu_restart_window_secs
+    }
+
+    #[test]
+    fn test_stop_payload_kill_tree_flag() {
+        let plain = StopPayload {
+            process_id: 7,
+            signal: 15,
+            timeout_ms: 5000,
+            flags: 0,
+        };
+        let bytes = plain.encode();
+        assert_eq!(bytes.len(), 9); // unchanged layout for older daemons
+        let mut r = BinaryReader::new(&bytes);
+        assert_eq!(r.read_u32().unwrap(), 7);
+        assert_eq!(r.read_u8().unwrap(), 15);
+        assert_eq!(r.read_u32().unwrap(), 5000);
+        assert!(r.read_u8().is_err());
+
+        let tree = StopPayload {
+            flags: STOP_FLAG_KILL_TREE,
+            ..plain
+        };
+        let bytes = tree.encode();
+        assert_eq!(bytes.len(), 10);
+        assert_eq!(bytes[..9], plain.encode()[..]);
+        assert_eq!(bytes[9] & STOP_FLAG_KILL_TREE, STOP_FLAG_KILL_TREE);
+    }
+
+    #[test]
+    fn test_log_entries_decode_seq() {
+        let mut w = BinaryWriter::new();
+        w.write_u32(2); // count
+        for (ts, msg, seq) in [(1000u64, "first", 41u64), (2000, "second", 42)] {
+            w.write_u64(ts);
+            w.write_u8(1); // level
+            w.write_u8(0); // stream
+            w.write_string(msg);
+            w.write_u64(seq);
+        }
+
+        let entries = decode_log_entries(&w.buf, VERSION_2).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].message, "first");
+        assert_eq!(entries[0].seq, 41);
+        assert_eq!(entries[1].timestamp_ms, 2000);
+        assert_eq!(entries[1].seq, 42);
+
+        // An entry cut off before its seq is a protocol error
+        let truncated = &w.buf[..w.buf.len() - 4];
+        assert!(decode_log_entries(truncated, VERSION_2).is_err());
+
+        // Version 1 daemons send entries without seq
+        let mut w = BinaryWriter::new();
+        w.write_u32(2); // count
+        for msg in ["first", "second"] {
+            w.write_u64(1000);
+            w.write_u8(1); // level
+            w.write_u8(0); // stream
+            w.write_string(msg);
+        }
+        let entries = decode_log_entries(&w.buf, VERSION).unwrap();
+        assert_eq!(entries[1].message, "second");
+        assert_eq!(entries[1].seq, 0);
+    }
+
+    #[test]
+    fn test_log_read_decode_suppressed() {
+        let mut w = BinaryWriter::new();
+        w.write_u32(1); // count
+        w.write_u64(1000);
+        w.write_u8(2); // level
+        w.write_u8(0); // stream
+        w.write_string("(250 lines suppressed)");
+        w.write_u64(7); // seq
+
+        // Older daemons end after the entries
+        assert_eq!(decode_log_read(&w.buf, VERSION_2).unwrap().suppressed, 0);
+
+        w.write_u64(250); // suppressed
+        let read = decode_log_read(&w.buf, VERSION_2).unwrap();
+        assert_eq!(read.suppressed, 250);
+        assert_eq!(read.entries.len(), 1);
+        assert_eq!(read.entries[0].seq, 7);
+    }
+
+    #[test]
+    fn test_process_history_decode() {
+        let mut w = BinaryWriter::new();
+        w.write_u32(3); // count
+        w.write_u64(1000);
+        w.write_u8(1); // crash
+        w.write_i32(137);
+        w.write_u64(2000);
+        w.write_u8(2); // oom
+        w.write_i32(0);
+        w.write_u64(3000);
+        w.write_u8(5); // manual
+        w.write_i32(0);
+
+        let events = decode_process_history(&w.buf).unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].timestamp_ms, 1000);
+        assert_eq!(events[0].kind_str(), "crash");
+        assert_eq!(events[0].exit_code, 137);
+        assert_eq!(events[1].kind_str(), "oom");
+        assert_eq!(events[2].timestamp_ms, 3000);
+        assert_eq!(events[2].kind_str(), "manual");
+
+        // Empty history
+        let empty = decode_process_history(&0u32.to_le_bytes()).unwrap();
+        assert!(empty.is_empty());
+
+        // Truncated payload
+        assert!(decode_process_history(&w.buf[..20]).is_err());
+    }
+
+    #[test]
+    fn test_event_stream_decode() {
+        let mut w = BinaryWriter::new();
+        w.write_u32(2); // count
+        w.write_u64(2000);
+        w.write_u32(3);
+        w.write_string("worker");
+        w.write_u8(2); // oom
+        w.write_i32(0);
+        w.write_u64(1000);
+        w.write_u32(0);
+        w.write_string("api");
+        w.write_u8(1); // crash
+        w.write_i32(-9);
+
+        let events = decode_event_stream(&w.buf).unwrap();
+        assert_eq!(events.len(), 2);
+        // Sorted oldest first across processes
+        assert_eq!(events[0].name, "api");
+        assert_eq!(events[0].process_id, 0);
+        assert_eq!(events[0].event.timestamp_ms, 1000);
+        assert_eq!(events[0].event.describe(), "crashed (signal 9)");
+        assert_eq!(events[1].name, "worker");
+        assert_eq!(events[1].event.kind_str(), "oom");
+
+        assert!(decode_event_stream(&0u32.to_le_bytes()).unwrap().is_empty());
+        assert!(decode_event_stream(&w.buf[..30]).is_err());
+        assert_eq!(
+            EventStreamPayload { since_ms: 1000 }.encode(),
+            1000u64.to_le_bytes()
+        );
+    }
+
+    #[test]
+    fn test_daemon_info_decode() {
+        let mut w = BinaryWriter::new();
+        w.write_string("/home/u/.velos/velos.sock");
+        w.write_string("/home/u/.velos");
+        w.write_string("0.1.14");
+        w.write_u64(1_700_000_000_000);
+        w.write_u32(4242);
+        w.write_u32(0);
+        w.write_u32(64);
+        let mut bytes = w.buf;
+
+        let expected = DaemonInfo {
+            socket_path: "/home/u/.velos/velos.sock".into(),
+            state_dir: "/home/u/.velos".into(),
+            version: "0.1.14".into(),
+            started_ms: 1_700_000_000_000,
+            pid: 4242,
+            max_processes: 0,
+            max_clients: 64,
+        };
+        assert_eq!(decode_daemon_info(&bytes).unwrap(), expected);
+
+        // Fields a newer daemon appends are skipped
+        bytes.extend_from_slice(&[1, 2, 3]);
+        assert_eq!(decode_daemon_info(&bytes).unwrap(), expected);
+
+        assert!(decode_daemon_info(&bytes[..20]).is_err());
     }
 
     #[test]
@@ -748,6 +1521,7 @@ mod tests {
         w.write_string("0 0 * * *"); // cron_restart
         w.write_u8(1); // wait_ready
         w.write_u8(0); // shutdown_with_message
+        w.write_u8(2); // last_exit_reason = oom
 
         let detail = decode_process_detail(&w.buf).unwrap();
         assert_eq!(detail.id, 1);
@@ -773,6 +1547,98 @@ mod tests {
         assert_eq!(detail.cron_restart, "0 0 * * *");
         assert!(detail.wait_ready);
         assert!(!detail.shutdown_with_message);
+        assert_eq!(detail.last_exit_reason, ExitReason::Oom);
+    }
+
+    #[test]
+    fn test_process_detail_exit_reason() {
+        let encode = |reason: Option<u8>| {
+            let mut w = BinaryWriter::new();
+            w.write_u32(1);
+            w.write_string("myapp");
+            w.write_u32(0);
+            w.write_u8(2); // errored
+            w.write_u64(0);
+            w.write_u64(0);
+            w.write_u32(1);
+            w.write_u32(1);
+            w.write_u64(0);
+            w.write_string("app.js");
+            w.write_string("/tmp");
+            w.write_string("");
+            w.write_u32(5000);
+            w.write_u8(1);
+            w.write_i32(15);
+            w.write_u64(1000);
+            w.write_u32(0);
+            w.write_u8(0);
+            w.write_u64(0);
+            w.write_u8(0);
+            w.write_string("");
+            w.write_u8(0);
+            w.write_u8(0);
+            if let Some(b) = reason {
+                w.write_u8(b);
+            }
+            w.buf
+        };
+
+        // Older daemons don't send the byte
+        let old = decode_process_detail(&encode(None)).unwrap();
+        assert_eq!(old.last_exit_reason, ExitReason::None);
+
+        for (byte, reason, s) in [
+            (1, ExitReason::Crashed, "crashed"),
+            (2, ExitReason::Oom, "oom"),
+            (3, ExitReason::Manual, "manual"),
+            (4, ExitReason::Signal, "signal"),
+            (5, ExitReason::Cpu, "cpu"),
+            (99, ExitReason::None, "none"),
+        ] {
+            let detail = decode_process_detail(&encode(Some(byte))).unwrap();
+            assert_eq!(detail.last_exit_reason, reason);
+            assert_eq!(detail.last_exit_reason.as_str(), s);
+        }
+
+        // Newer daemons append log_rate_limit, suppressed_lines and merge_logs
+        let mut bytes = encode(Some(0));
+        assert!(!decode_process_detail(&bytes).unwrap().merge_logs);
+        bytes.extend_from_slice(&100u32.to_le_bytes());
+        bytes.extend_from_slice(&7u64.to_le_bytes());
+        bytes.push(1);
+        let detail = decode_process_detail(&bytes).unwrap();
+        assert_eq!((detail.log_rate_limit, detail.suppressed_lines), (100, 7));
+        assert!(detail.merge_logs);
+        assert_eq!(detail.max_cpu_restart, 0.0);
+
+        // ... then the CPU limit
+        bytes.extend_from_slice(&855u16.to_le_bytes());
+        bytes.extend_from_slice(&120u32.to_le_bytes());
+        let detail = decode_process_detail(&bytes).unwrap();
+        assert_eq!(
+            (detail.max_cpu_restart, detail.cpu_restart_window_secs),
+            (85.5, 120)
+        );
+        assert_eq!(detail.out_log, "");
+
+        // ... then the log paths
+        let mut w = BinaryWriter::new();
+        w.write_string("/var/log/api.out");
+        w.write_string("");
+        bytes.extend_from_slice(&w.buf);
+        let detail = decode_process_detail(&bytes).unwrap();
+        assert_eq!(detail.out_log, "/var/log/api.out");
+        assert_eq!(detail.err_log, "");
+        assert!(detail.env.is_empty());
+
+        // ... then the environment
+        let mut w = BinaryWriter::new();
+        w.write_string("PORT=3000\nAPI_TOKEN=a=b");
+        bytes.extend_from_slice(&w.buf);
+        let detail = decode_process_detail(&bytes).unwrap();
+        assert_eq!(detail.env.len(), 2);
+        assert_eq!(detail.env["PORT"], "3000");
+        assert_eq!(detail.env["API_TOKEN"], "a=b");
     }
 
     #[test]
@@ -793,5 +1659,60 @@ mod tests {
         assert_eq!(procs[0].name, "myapp");
         assert_eq!(procs[0].pid, 1234);
         assert_eq!(procs[0].status_str(), "running");
+        assert_eq!(procs[0].group, None);
+    }
+
+    #[test]
+    fn test_process_list_decode_groups() {
+        let mut w = BinaryWriter::new();
+        w.write_u32(2); // count
+        for (id, name) in [(1, "api"), (2, "worker")] {
+            w.write_u32(id);
+            w.write_string(name);
+            w.write_u32(100 + id); // pid
+            w.write_u8(1); // status
+            w.write_u64(0); // memory
+            w.write_u64(0); // uptime
+            w.write_u32(0); // restarts
+            w.buf.extend_from_slice(&0u16.to_le_bytes()); // cpu
+        }
+        w.write_string("backend");
+        w.write_string("");
+
+        let procs = decode_process_list(&w.buf).unwrap();
+        assert_eq!(procs[0].group.as_deref(), Some("backend"));
+        assert_eq!(procs[1].group, None);
+    }
+
+    #[test]
+    fn test_process_list_decode_truncated() {
+        let mut w = BinaryWriter::new();
+        w.write_u32(3); // count, but only two records follow in full
+        for (id, name) in [(1, "api"), (2, "worker"), (3, "cron")] {
+            w.write_u32(id);
+            w.write_string(name);
+            w.write_u32(100 + id); // pid
+            w.write_u8(1); // status
+            w.write_u64(0); // memory
+            w.write_u64(0); // uptime
+            w.write_u32(0); // restarts
+            w.buf.extend_from_slice(&0u16.to_le_bytes()); // cpu
+        }
+        // Cut the third record off in its memory field
+        let cut = w.buf.len() - 2 - 4 - 8 - 4;
+        let data = &w.buf[..cut];
+
+        assert!(decode_process_list(data).is_err());
+        assert!(decode_process_list_with(data, false).is_err());
+
+        let list = decode_process_list_with(data, true).unwrap();
+        let names: Vec<_> = list.procs.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["api", "worker"]);
+        let warning = list.truncated.unwrap();
+        assert!(warning.contains("after 2 of 3 processes"), "{warning}");
+
+        // A complete payload decodes the same either way
+        let full = decode_process_list_with(&w.buf, true).unwrap();
+        assert_eq!((full.procs.len(), full.truncated), (3, None));
     }
 }
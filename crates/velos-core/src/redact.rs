@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+/// Replacement shown instead of a secret value.
+pub const REDACTED: &str = "****";
+
+/// Key patterns treated as secrets by default. `*` matches any run of
+/// characters; matching is case-insensitive.
+pub const DEFAULT_SECRET_PATTERNS: &[&str] =
+    &["*SECRET*", "*PASSWORD*", "*TOKEN*", "*KEY*", "DATABASE_URL"];
+
+/// True if `key` matches any of `patterns`.
+pub fn is_secret_key<S: AsRef<str>>(key: &str, patterns: &[S]) -> bool {
+    let key = key.to_ascii_uppercase();
+    patterns
+        .iter()
+        .any(|p| glob_match(&p.as_ref().to_ascii_uppercase(), &key))
+}
+
+/// Mask the values of secret-looking keys using the default patterns.
+pub fn redact_env(env: &HashMap<String, String>) -> HashMap<String, String> {
+    redact_env_with(env, DEFAULT_SECRET_PATTERNS)
+}
+
+/// Mask the values of keys matching `patterns`; other entries are copied as-is.
+pub fn redact_env_with<S: AsRef<str>>(
+    env: &HashMap<String, String>,
+    patterns: &[S],
+) -> HashMap<String, String> {
+    env.iter()
+        .map(|(k, v)| {
+            let v = if is_secret_key(k, patterns) {
+                REDACTED.to_string()
+            } else {
+                v.clone()
+            };
+            (k.clone(), v)
+        })
+        .collect()
+}
+
+/// Minimal glob: `*` matches any (possibly empty) run of characters.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if text.len() < first.len() + last.len() || !text.starts_with(first) || !text.ends_with(last) {
+        return false;
+    }
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_secret_key_default_patterns() {
+        for key in [
+            "API_KEY",
+            "STRIPE_SECRET_KEY",
+            "DB_PASSWORD",
+            "github_token",
+            "DATABASE_URL",
+        ] {
+            assert!(is_secret_key(key, DEFAULT_SECRET_PATTERNS), "{key}");
+        }
+        for key in ["NODE_ENV", "PORT", "DATABASE_URL_REPLICA_COUNT"] {
+            // DATABASE_URL is an exact pattern, not a prefix
+            assert!(!is_secret_key(key, DEFAULT_SECRET_PATTERNS), "{key}");
+        }
+    }
+
+    #[test]
+    fn test_redact_env_default_patterns() {
+        let env: HashMap<String, String> = [
+            ("API_KEY", "abc123"),
+            ("STRIPE_SECRET_KEY", "sk_live_x"),
+            ("DB_PASSWORD", "hunter2"),
+            ("github_token", "ghp_x"),
+            ("DATABASE_URL", "postgres://u:p@h/db"),
+            ("NODE_ENV", "production"),
+            ("PORT", "3000"),
+            ("DATABASE_URL_REPLICA_COUNT", "2"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let redacted = redact_env(&env);
+        assert_eq!(redacted["API_KEY"], REDACTED);
+        assert_eq!(redacted["STRIPE_SECRET_KEY"], REDACTED);
+        assert_eq!(redacted["DB_PASSWORD"], REDACTED);
+        assert_eq!(redacted["github_token"], REDACTED);
+        assert_eq!(redacted["DATABASE_URL"], REDACTED);
+        assert_eq!(redacted["NODE_ENV"], "production");
+        assert_eq!(redacted["PORT"], "3000");
+        assert_eq!(redacted["DATABASE_URL_REPLICA_COUNT"], "2");
+        assert_eq!(redacted.len(), env.len());
+
+        let custom = redact_env_with(&env, &["PORT"]);
+        assert_eq!(custom["PORT"], REDACTED);
+        assert_eq!(custom["API_KEY"], "abc123");
+    }
+
+    #[test]
+    fn test_is_secret_key_custom_patterns() {
+        assert!(is_secret_key("SESSION_ID", &["SESSION_*"]));
+        assert!(!is_secret_key("API_KEY", &["SESSION_*"]));
+    }
+}
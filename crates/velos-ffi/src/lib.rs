@@ -6,6 +6,7 @@ extern "C" {
     fn velos_daemon_run() -> c_int;
     fn velos_daemon_shutdown() -> c_int;
     fn velos_set_notify_binary(path: *const c_char);
+    fn velos_set_version(version: *const c_char);
 }
 
 pub fn ping() -> String {
@@ -44,6 +45,12 @@ pub fn set_notify_binary(path: &str) {
     unsafe { velos_set_notify_binary(c.as_ptr()) };
 }
 
+/// Set the version the daemon reports in ping replies. Call after `daemon_init`.
+pub fn set_version(version: &str) {
+    let c = CString::new(version).unwrap();
+    unsafe { velos_set_version(c.as_ptr()) };
+}
+
 pub fn daemon_shutdown() -> Result<(), i32> {
     let ret = unsafe { velos_daemon_shutdown() };
     if ret == 0 {
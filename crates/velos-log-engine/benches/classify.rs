@@ -0,0 +1,47 @@
+//! Sequential vs parallel classification of a large batch.
+//!
+//! cargo bench -p velos-log-engine --bench classify
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use velos_core::LogEntry;
+use velos_log_engine::classifier::Classifier;
+
+const MESSAGES: [&str; 6] = [
+    "GET /api/users 200 12ms",
+    "ERROR failed to connect to db: connection refused",
+    "warning: retrying request 3/5",
+    r#"{"level":"info","msg":"job done","took":42}"#,
+    "DEBUG cache miss for key user:1234",
+    "worker 7 picked up job 8812",
+];
+
+fn corpus(lines: usize) -> Vec<LogEntry> {
+    (0..lines)
+        .map(|i| LogEntry {
+            timestamp_ms: 1_700_000_000_000 + i as u64,
+            level: 1,
+            stream: (i % 3 == 0) as u8,
+            message: MESSAGES[i % MESSAGES.len()].to_string(),
+            seq: i as u64,
+        })
+        .collect()
+}
+
+fn classify(c: &mut Criterion) {
+    let classifier = Classifier::with_defaults();
+    let mut group = c.benchmark_group("classify_batch");
+    for lines in [1_000, 50_000, 200_000] {
+        let entries = corpus(lines);
+        group.throughput(Throughput::Elements(lines as u64));
+        group.bench_with_input(BenchmarkId::new("sequential", lines), &entries, |b, e| {
+            b.iter(|| classifier.classify_batch(e))
+        });
+        group.bench_with_input(BenchmarkId::new("parallel", lines), &entries, |b, e| {
+            b.iter(|| classifier.classify_batch_parallel(e))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, classify);
+criterion_main!(benches);
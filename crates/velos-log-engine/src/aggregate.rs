@@ -0,0 +1,180 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::Value;
+
+use crate::anomaly::bucket_start;
+use crate::ProcessedEntry;
+
+/// Bucket for lines that are not JSON or lack the field.
+pub const ABSENT: &str = "(absent)";
+
+/// Parse the JSON object in a log line, allowing a non-JSON prefix such as a
+/// timestamp (`12:00:00 {"status_code":500}`).
+pub fn parse_json_fields(message: &str) -> Option<serde_json::Map<String, Value>> {
+    let start = message.find('{')?;
+    match serde_json::from_str(message[start..].trim_end()) {
+        Ok(Value::Object(map)) => Some(map),
+        _ => None,
+    }
+}
+
+/// Look up `field` in a JSON line; dots descend into nested objects
+/// (`req.method`). Strings come back unquoted, other values as JSON text.
+pub fn field_value(message: &str, field: &str) -> Option<String> {
+    let map = parse_json_fields(message)?;
+    let mut parts = field.split('.');
+    let mut value = map.get(parts.next()?)?;
+    for part in parts {
+        value = value.get(part)?;
+    }
+    Some(match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    })
+}
+
+/// Count entries by the value of `field`, most common first (ties by value).
+/// Lines without the field land in the [`ABSENT`] bucket.
+pub fn aggregate_by_field(entries: &[ProcessedEntry], field: &str) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for e in entries {
+        let value = field_value(&e.message, field).unwrap_or_else(|| ABSENT.to_string());
+        *counts.entry(value).or_default() += 1;
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// Number of entries at each level, keyed by level name.
+pub fn level_counts(entries: &[ProcessedEntry]) -> BTreeMap<&'static str, usize> {
+    let mut counts = BTreeMap::new();
+    for e in entries {
+        *counts.entry(e.level.as_str()).or_default() += 1;
+    }
+    counts
+}
+
+/// Per-level counts for one fixed-width slice of time.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct TimelineBucket {
+    pub bucket_start_ms: u64,
+    pub counts: BTreeMap<&'static str, usize>,
+}
+
+/// Bucket entries into `bucket_ms`-wide intervals, oldest first. Quiet
+/// intervals between the first and last entry appear with empty counts so
+/// the series plots without gaps.
+pub fn level_timeline(entries: &[ProcessedEntry], bucket_ms: u64) -> Vec<TimelineBucket> {
+    let bucket_ms = bucket_ms.max(1);
+    let mut by_bucket: BTreeMap<u64, BTreeMap<&'static str, usize>> = BTreeMap::new();
+    for e in entries {
+        let counts = by_bucket
+            .entry(bucket_start(e.timestamp_ms, bucket_ms))
+            .or_default();
+        *counts.entry(e.level.as_str()).or_default() += 1;
+    }
+    let (Some(&first), Some(&last)) = (by_bucket.keys().next(), by_bucket.keys().next_back())
+    else {
+        return Vec::new();
+    };
+    (first..=last)
+        .step_by(bucket_ms as usize)
+        .map(|bucket_start_ms| TimelineBucket {
+            bucket_start_ms,
+            counts: by_bucket.remove(&bucket_start_ms).unwrap_or_default(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LogLevel;
+
+    fn entry(message: &str) -> ProcessedEntry {
+        at(0, LogLevel::Info, message)
+    }
+
+    fn at(timestamp_ms: u64, level: LogLevel, message: &str) -> ProcessedEntry {
+        ProcessedEntry {
+            timestamp_ms,
+            level,
+            stream: 0,
+            message: message.into(),
+            seq: 0,
+        }
+    }
+
+    #[test]
+    fn test_aggregate_by_status_code() {
+        let entries = [
+            entry(r#"{"status_code":200,"path":"/"}"#),
+            entry(r#"{"status_code":500,"path":"/api"}"#),
+            entry(r#"12:00:01 {"status_code":200,"path":"/health"}"#),
+            entry(r#"{"status_code":"404"}"#),
+            entry(r#"{"path":"/no-status"}"#),
+            entry("plain text line"),
+            entry(r#"{"status_code":200}"#),
+        ];
+        let counts = aggregate_by_field(&entries, "status_code");
+        assert_eq!(
+            counts,
+            [
+                ("200".to_string(), 3),
+                (ABSENT.to_string(), 2),
+                ("404".to_string(), 1),
+                ("500".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_field_value_nested() {
+        let line = r#"{"req":{"method":"GET","ok":true}}"#;
+        assert_eq!(field_value(line, "req.method").as_deref(), Some("GET"));
+        assert_eq!(field_value(line, "req.ok").as_deref(), Some("true"));
+        assert_eq!(field_value(line, "req.missing"), None);
+        assert_eq!(field_value("[1,2]", "req"), None);
+    }
+
+    #[test]
+    fn test_level_timeline_three_minutes() {
+        let base = 1_700_000_040_000; // on a minute boundary
+        let entries = [
+            at(base + 1_000, LogLevel::Info, "a"),
+            at(base + 59_999, LogLevel::Error, "b"),
+            at(base + 60_000, LogLevel::Info, "c"),
+            at(base + 75_000, LogLevel::Info, "d"),
+            at(base + 90_000, LogLevel::Warn, "e"),
+            at(base + 150_000, LogLevel::Error, "f"),
+        ];
+        let timeline = level_timeline(&entries, 60_000);
+        let expected = [
+            (base, vec![("error", 1), ("info", 1)]),
+            (base + 60_000, vec![("info", 2), ("warn", 1)]),
+            (base + 120_000, vec![("error", 1)]),
+        ];
+        assert_eq!(timeline.len(), 3);
+        for (bucket, (start, counts)) in timeline.iter().zip(expected) {
+            assert_eq!(bucket.bucket_start_ms, start);
+            assert_eq!(bucket.counts, counts.into_iter().collect());
+        }
+
+        assert_eq!(
+            level_counts(&entries),
+            [("error", 2), ("info", 3), ("warn", 1)]
+                .into_iter()
+                .collect()
+        );
+    }
+
+    #[test]
+    fn test_level_timeline_fills_quiet_buckets() {
+        let entries = [at(0, LogLevel::Info, "a"), at(180_000, LogLevel::Info, "b")];
+        let timeline = level_timeline(&entries, 60_000);
+        assert_eq!(timeline.len(), 4);
+        assert!(timeline[1].counts.is_empty() && timeline[2].counts.is_empty());
+        assert!(level_timeline(&[], 60_000).is_empty());
+    }
+}
@@ -16,6 +16,26 @@ impl AnomalySeverity {
     }
 }
 
+/// How [`AnomalyDetector`] decides a value is out of line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum AnomalyMethod {
+    /// Standard deviations above the mean. Assumes roughly normal data.
+    Sigma,
+    /// Interquartile ranges above the third quartile (Q3 + k*IQR). Spikes
+    /// already in the window barely move the quartiles, so it holds up on
+    /// bursty, right-skewed metrics such as log volume.
+    Iqr,
+}
+
+impl AnomalyMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sigma => "sigma",
+            Self::Iqr => "iqr",
+        }
+    }
+}
+
 /// A detected anomaly in log metrics.
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct Anomaly {
@@ -23,9 +43,14 @@ pub struct Anomaly {
     pub current_value: f64,
     pub mean: f64,
     pub std_dev: f64,
+    /// Distance above normal in the method's unit: standard deviations
+    /// above the mean for `Sigma`, IQRs above Q3 for `Iqr`.
     pub sigma: f64,
     pub timestamp_ms: u64,
     pub severity: AnomalySeverity,
+    pub method: AnomalyMethod,
+    /// Width of the detector's buckets: values are counts per this many seconds.
+    pub bucket_secs: u64,
 }
 
 /// Generic sliding window for time-series metrics.
@@ -75,6 +100,20 @@ impl SlidingWindow {
             / (self.values.len() - 1) as f64;
         variance.sqrt()
     }
+
+    /// The `p` quantile (0.0-1.0) of the window, interpolating linearly
+    /// between the two nearest values. 0.0 when empty.
+    pub fn quantile(&self, p: f64) -> f64 {
+        if self.values.is_empty() {
+            return 0.0;
+        }
+        let mut sorted: Vec<f64> = self.values.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        let rank = p.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+        let lower = rank.floor() as usize;
+        let upper = rank.ceil() as usize;
+        sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+    }
 }
 
 /// Anomaly detector using sliding windows for error_rate and log_volume.
@@ -82,32 +121,49 @@ pub struct AnomalyDetector {
     pub error_rate: SlidingWindow,
     pub log_volume: SlidingWindow,
     window_size: usize,
+    bucket_secs: u64,
+    method: AnomalyMethod,
+    /// Thresholds in the method's unit (σ, or k for Q3 + k*IQR).
     sigma_warning: f64,
     sigma_critical: f64,
     min_data_points: usize,
 }
 
 impl AnomalyDetector {
-    pub fn new(window_size: usize, sigma_warning: f64, sigma_critical: f64) -> Self {
+    pub fn new(
+        window_size: usize,
+        method: AnomalyMethod,
+        sigma_warning: f64,
+        sigma_critical: f64,
+    ) -> Self {
         Self {
             error_rate: SlidingWindow::new(window_size),
             log_volume: SlidingWindow::new(window_size),
             window_size,
+            bucket_secs: MINUTE_MS / 1000,
+            method,
             sigma_warning,
             sigma_critical,
             min_data_points: 10,
         }
     }
 
-    /// Default: 60-minute window, 2σ warning, 3σ critical.
+    /// Default: 60 one-minute buckets, 2σ warning, 3σ critical.
     pub fn with_defaults() -> Self {
-        Self::new(60, 2.0, 3.0)
+        Self::new(60, AnomalyMethod::Sigma, 2.0, 3.0)
     }
 
-    /// Record a data point (call once per minute).
-    pub fn record(&mut self, errors_per_minute: f64, lines_per_minute: f64) {
-        self.error_rate.push(errors_per_minute);
-        self.log_volume.push(lines_per_minute);
+    /// Buckets `bucket_secs` wide instead of a minute. The window still
+    /// holds `window_size` of them.
+    pub fn with_bucket_secs(mut self, bucket_secs: u64) -> Self {
+        self.bucket_secs = bucket_secs.max(1);
+        self
+    }
+
+    /// Record a data point (call once per bucket).
+    pub fn record(&mut self, errors_per_bucket: f64, lines_per_bucket: f64) {
+        self.error_rate.push(errors_per_bucket);
+        self.log_volume.push(lines_per_bucket);
     }
 
     /// Check for anomalies against current values.
@@ -141,36 +197,38 @@ impl AnomalyDetector {
         let mean = window.mean();
         let std_dev = window.std_dev();
 
-        if std_dev < f64::EPSILON {
+        let (center, spread) = match self.method {
+            AnomalyMethod::Sigma => (mean, std_dev),
+            AnomalyMethod::Iqr => {
+                let q3 = window.quantile(0.75);
+                (q3, q3 - window.quantile(0.25))
+            }
+        };
+        if spread < f64::EPSILON {
             // No variance — can't detect anomaly
             return None;
         }
 
-        let sigma = (current - mean) / std_dev;
-
-        if sigma >= self.sigma_critical {
-            Some(Anomaly {
-                metric: name.to_string(),
-                current_value: current,
-                mean,
-                std_dev,
-                sigma,
-                timestamp_ms: now_ms,
-                severity: AnomalySeverity::Critical,
-            })
+        let sigma = (current - center) / spread;
+
+        let severity = if sigma >= self.sigma_critical {
+            AnomalySeverity::Critical
         } else if sigma >= self.sigma_warning {
-            Some(Anomaly {
-                metric: name.to_string(),
-                current_value: current,
-                mean,
-                std_dev,
-                sigma,
-                timestamp_ms: now_ms,
-                severity: AnomalySeverity::Warning,
-            })
+            AnomalySeverity::Warning
         } else {
-            None
-        }
+            return None;
+        };
+        Some(Anomaly {
+            metric: name.to_string(),
+            current_value: current,
+            mean,
+            std_dev,
+            sigma,
+            timestamp_ms: now_ms,
+            severity,
+            method: self.method,
+            bucket_secs: self.bucket_secs,
+        })
     }
 
     /// Whether enough data has been accumulated.
@@ -181,10 +239,27 @@ impl AnomalyDetector {
     pub fn window_size(&self) -> usize {
         self.window_size
     }
+
+    pub fn bucket_secs(&self) -> u64 {
+        self.bucket_secs
+    }
+}
+
+/// Default width of the buckets fed to the detector.
+pub const MINUTE_MS: u64 = 60_000;
+
+/// Start of the `width_ms`-wide bucket `timestamp_ms` falls in. Buckets are
+/// aligned to the epoch, so the same instant always lands in the same one.
+pub fn bucket_start(timestamp_ms: u64, width_ms: u64) -> u64 {
+    timestamp_ms - timestamp_ms % width_ms.max(1)
+}
+
+fn in_bucket(e: &crate::ProcessedEntry, bucket_start_ms: u64, bucket_end_ms: u64) -> bool {
+    e.timestamp_ms >= bucket_start_ms && e.timestamp_ms < bucket_end_ms
 }
 
 /// Compute error_rate and log_volume from a batch of entries within a time bucket.
-pub fn compute_minute_metrics(
+pub fn compute_bucket_metrics(
     entries: &[crate::ProcessedEntry],
     bucket_start_ms: u64,
     bucket_end_ms: u64,
@@ -193,7 +268,7 @@ pub fn compute_minute_metrics(
     let mut total = 0u32;
 
     for e in entries {
-        if e.timestamp_ms >= bucket_start_ms && e.timestamp_ms < bucket_end_ms {
+        if in_bucket(e, bucket_start_ms, bucket_end_ms) {
             total += 1;
             if matches!(e.level, crate::LogLevel::Error | crate::LogLevel::Fatal) {
                 errors += 1;
@@ -204,6 +279,146 @@ pub fn compute_minute_metrics(
     (errors as f64, total as f64)
 }
 
+/// Run the detector over one log read: bucket the entries into
+/// `anomaly_bucket_secs`-wide buckets (empty ones count as zero), warm it
+/// with every bucket but the newest, then check the newest. Only the last
+/// `anomaly_window` buckets before it are used. Returns nothing until there
+/// are enough warm-up buckets.
+pub fn detect_from_entries(
+    entries: &[crate::ProcessedEntry],
+    cfg: &velos_config::LogEngineConfig,
+) -> Vec<Anomaly> {
+    let Some(newest) = entries.iter().map(|e| e.timestamp_ms).max() else {
+        return Vec::new();
+    };
+    let oldest = entries
+        .iter()
+        .map(|e| e.timestamp_ms)
+        .min()
+        .unwrap_or(newest);
+    let window = cfg.anomaly_window.max(1) as usize;
+    let mut detector = AnomalyDetector::new(
+        window,
+        AnomalyMethod::Sigma,
+        cfg.anomaly_sigma_warn,
+        cfg.anomaly_sigma_crit,
+    )
+    .with_bucket_secs(cfg.anomaly_bucket_secs);
+    let width = detector.bucket_secs() * 1000;
+
+    let last = bucket_start(newest, width);
+    let first = bucket_start(oldest, width).max(last.saturating_sub(window as u64 * width));
+    let mut start = first;
+    while start < last {
+        let (errors, total) = compute_bucket_metrics(entries, start, start + width);
+        detector.record(errors, total);
+        start += width;
+    }
+    if !detector.has_enough_data() {
+        return Vec::new();
+    }
+
+    let (errors, total) = compute_bucket_metrics(entries, last, last + width);
+    detector.check(errors, total, newest)
+}
+
+/// An anomaly together with sample lines from the bucket it fired in.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AnomalyExplanation {
+    pub anomaly: Anomaly,
+    pub bucket_start_ms: u64,
+    pub bucket_end_ms: u64,
+    /// All lines that fell in the bucket (the sample below may be shorter).
+    pub bucket_lines: usize,
+    pub lines: Vec<crate::ProcessedEntry>,
+}
+
+/// For each anomaly, pull up to `max_lines` entries from its bucket.
+/// Error-rate anomalies show error/fatal lines; volume anomalies show any.
+pub fn explain_anomalies(
+    entries: &[crate::ProcessedEntry],
+    anomalies: &[Anomaly],
+    max_lines: usize,
+) -> Vec<AnomalyExplanation> {
+    anomalies
+        .iter()
+        .map(|a| {
+            let width = a.bucket_secs.max(1) * 1000;
+            let bucket_start_ms = bucket_start(a.timestamp_ms, width);
+            let bucket_end_ms = bucket_start_ms + width;
+            let (errors, total) = compute_bucket_metrics(entries, bucket_start_ms, bucket_end_ms);
+            let errors_only = a.metric == "error_rate";
+            let bucket_lines = if errors_only { errors } else { total } as usize;
+            let lines = entries
+                .iter()
+                .filter(|e| in_bucket(e, bucket_start_ms, bucket_end_ms))
+                .filter(|e| !errors_only || e.level >= crate::LogLevel::Error)
+                .take(max_lines)
+                .cloned()
+                .collect();
+            AnomalyExplanation {
+                anomaly: a.clone(),
+                bucket_start_ms,
+                bucket_end_ms,
+                bucket_lines,
+                lines,
+            }
+        })
+        .collect()
+}
+
+/// Error counts for two adjacent windows of the same width, for a quick
+/// "is it getting worse?" check without the full detector.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct WindowComparison {
+    pub window_ms: u64,
+    /// Start of the older window; the newer one starts `window_ms` later.
+    pub previous_start_ms: u64,
+    pub previous_errors: u64,
+    pub previous_total: u64,
+    pub current_errors: u64,
+    pub current_total: u64,
+    /// `current_errors / previous_errors`, None when the older window had none.
+    pub ratio: Option<f64>,
+}
+
+impl WindowComparison {
+    pub fn delta(&self) -> i64 {
+        self.current_errors as i64 - self.previous_errors as i64
+    }
+
+    /// "↑" when errors went up, "↓" when down, "=" when unchanged.
+    pub fn indicator(&self) -> &'static str {
+        match self.delta() {
+            d if d > 0 => "↑",
+            d if d < 0 => "↓",
+            _ => "=",
+        }
+    }
+}
+
+/// Compare errors in `[end - window, end)` against the window before it.
+pub fn compare_windows(
+    entries: &[crate::ProcessedEntry],
+    end_ms: u64,
+    window_ms: u64,
+) -> WindowComparison {
+    let current_start = end_ms.saturating_sub(window_ms);
+    let previous_start = current_start.saturating_sub(window_ms);
+    let (previous_errors, previous_total) =
+        compute_bucket_metrics(entries, previous_start, current_start);
+    let (current_errors, current_total) = compute_bucket_metrics(entries, current_start, end_ms);
+    WindowComparison {
+        window_ms,
+        previous_start_ms: previous_start,
+        previous_errors: previous_errors as u64,
+        previous_total: previous_total as u64,
+        current_errors: current_errors as u64,
+        current_total: current_total as u64,
+        ratio: (previous_errors > 0.0).then(|| current_errors / previous_errors),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -260,7 +475,7 @@ mod tests {
 
     #[test]
     fn test_anomaly_detection_spike() {
-        let mut detector = AnomalyDetector::new(60, 2.0, 3.0);
+        let mut detector = AnomalyDetector::new(60, AnomalyMethod::Sigma, 2.0, 3.0);
         // Feed stable data: 5 errors/min, slight variance
         for i in 0..30 {
             detector.record(5.0 + (i % 3) as f64 * 0.5, 100.0);
@@ -270,11 +485,50 @@ mod tests {
         assert!(!anomalies.is_empty());
         assert_eq!(anomalies[0].metric, "error_rate");
         assert!(anomalies[0].sigma >= 2.0);
+        assert_eq!(anomalies[0].method, AnomalyMethod::Sigma);
+    }
+
+    #[test]
+    fn test_sliding_window_quantile() {
+        let mut w = SlidingWindow::new(10);
+        assert_eq!(w.quantile(0.5), 0.0);
+        for v in [40.0, 10.0, 30.0, 20.0] {
+            w.push(v);
+        }
+        assert_eq!(w.quantile(0.0), 10.0);
+        assert_eq!(w.quantile(1.0), 40.0);
+        // Rank 1.5 sits halfway between 20 and 30
+        assert_eq!(w.quantile(0.5), 25.0);
+        assert_eq!(w.quantile(0.25), 17.5);
+        assert_eq!(w.quantile(0.75), 32.5);
+    }
+
+    #[test]
+    fn test_iqr_flags_heavy_spike_only() {
+        let mut detector = AnomalyDetector::new(60, AnomalyMethod::Iqr, 1.5, 3.0);
+        // Bursty volume: mostly 100-108 lines/min, with a burst every 10 minutes
+        for i in 0..40 {
+            let volume = if i % 10 == 9 {
+                160.0
+            } else {
+                100.0 + (i % 5) as f64 * 2.0
+            };
+            detector.record(1.0, volume);
+        }
+        // Modest variance inside the usual range is not flagged
+        assert!(detector.check(1.0, 110.0, 1000).is_empty());
+
+        // A heavy spike is critical, and says which method caught it
+        let anomalies = detector.check(1.0, 400.0, 1000);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].metric, "log_volume");
+        assert_eq!(anomalies[0].severity, AnomalySeverity::Critical);
+        assert_eq!(anomalies[0].method, AnomalyMethod::Iqr);
     }
 
     #[test]
     fn test_anomaly_severity_levels() {
-        let mut detector = AnomalyDetector::new(60, 2.0, 3.0);
+        let mut detector = AnomalyDetector::new(60, AnomalyMethod::Sigma, 2.0, 3.0);
         for _ in 0..30 {
             detector.record(5.0, 100.0);
         }
@@ -288,29 +542,221 @@ mod tests {
     }
 
     #[test]
-    fn test_compute_minute_metrics() {
+    fn test_detect_from_entries() {
+        let entry = |ts: u64, level: crate::LogLevel| crate::ProcessedEntry {
+            timestamp_ms: ts,
+            level,
+            stream: 0,
+            message: String::new(),
+            seq: 0,
+        };
+        let cfg = velos_config::LogEngineConfig::default();
+        // 20 quiet minutes: 10 lines each, one error every other minute
+        let mut entries = Vec::new();
+        for minute in 0..20u64 {
+            for i in 0..10 {
+                let level = if i == 0 && minute % 2 == 0 {
+                    crate::LogLevel::Error
+                } else {
+                    crate::LogLevel::Info
+                };
+                entries.push(entry(minute * MINUTE_MS + i * 1000, level));
+            }
+        }
+        assert!(detect_from_entries(&entries, &cfg).is_empty());
+
+        // Then a minute full of errors
+        for i in 0..30 {
+            entries.push(entry(20 * MINUTE_MS + i * 1000, crate::LogLevel::Error));
+        }
+        let anomalies = detect_from_entries(&entries, &cfg);
+        let error_rate = anomalies.iter().find(|a| a.metric == "error_rate").unwrap();
+        assert_eq!(error_rate.severity, AnomalySeverity::Critical);
+        assert_eq!(error_rate.current_value, 30.0);
+        assert_eq!(
+            bucket_start(error_rate.timestamp_ms, MINUTE_MS),
+            20 * MINUTE_MS
+        );
+
+        // Too few minutes before the spike to judge it
+        let short: Vec<_> = entries
+            .iter()
+            .filter(|e| e.timestamp_ms >= 15 * MINUTE_MS)
+            .cloned()
+            .collect();
+        assert!(detect_from_entries(&short, &cfg).is_empty());
+        assert!(detect_from_entries(&[], &cfg).is_empty());
+    }
+
+    #[test]
+    fn test_ten_second_buckets() {
+        let entry = |ts: u64, level: crate::LogLevel| crate::ProcessedEntry {
+            timestamp_ms: ts,
+            level,
+            stream: 0,
+            message: String::new(),
+            seq: 0,
+        };
+        use crate::LogLevel::{Error, Info};
+        // One batch across three 10s buckets: [0, 10s), [10s, 20s), [20s, 30s)
+        let entries = vec![
+            entry(1_000, Info),
+            entry(9_999, Error),
+            entry(10_000, Error),
+            entry(12_000, Error),
+            entry(15_000, Info),
+            entry(19_000, Info),
+            entry(25_000, Info),
+        ];
+        let counts: Vec<(f64, f64)> = (0..3)
+            .map(|i| compute_bucket_metrics(&entries, i * 10_000, (i + 1) * 10_000))
+            .collect();
+        assert_eq!(counts, [(1.0, 2.0), (2.0, 4.0), (0.0, 1.0)]);
+
+        // Five minutes of 10s buckets, then a burst of errors in the last one
+        let mut burst = Vec::new();
+        for bucket in 0..30u64 {
+            burst.push(entry(bucket * 10_000, Info));
+            burst.push(entry(
+                bucket * 10_000 + 1,
+                if bucket % 2 == 0 { Error } else { Info },
+            ));
+        }
+        for i in 0..20 {
+            burst.push(entry(300_000 + i * 100, Error));
+        }
+        let mut cfg = velos_config::LogEngineConfig {
+            anomaly_bucket_secs: 10,
+            ..Default::default()
+        };
+        let anomalies = detect_from_entries(&burst, &cfg);
+        let error_rate = anomalies.iter().find(|a| a.metric == "error_rate").unwrap();
+        assert_eq!(error_rate.current_value, 20.0);
+        assert_eq!(error_rate.bucket_secs, 10);
+        let ex = &explain_anomalies(&burst, std::slice::from_ref(error_rate), 5)[0];
+        assert_eq!((ex.bucket_start_ms, ex.bucket_end_ms), (300_000, 310_000));
+        assert_eq!(ex.bucket_lines, 20);
+
+        // The same five minutes are too few one-minute buckets to judge
+        cfg.anomaly_bucket_secs = 60;
+        assert!(detect_from_entries(&burst, &cfg).is_empty());
+    }
+
+    #[test]
+    fn test_compute_bucket_metrics() {
         let entries = vec![
             crate::ProcessedEntry {
                 timestamp_ms: 1000,
                 level: crate::LogLevel::Info,
                 stream: 0,
                 message: "ok".into(),
+                seq: 0,
             },
             crate::ProcessedEntry {
                 timestamp_ms: 2000,
                 level: crate::LogLevel::Error,
                 stream: 0,
                 message: "fail".into(),
+                seq: 0,
             },
             crate::ProcessedEntry {
                 timestamp_ms: 3000,
                 level: crate::LogLevel::Info,
                 stream: 0,
                 message: "ok2".into(),
+                seq: 0,
             },
         ];
-        let (errors, total) = compute_minute_metrics(&entries, 0, 60000);
+        let (errors, total) = compute_bucket_metrics(&entries, 0, 60000);
         assert!((errors - 1.0).abs() < f64::EPSILON);
         assert!((total - 3.0).abs() < f64::EPSILON);
     }
+
+    #[test]
+    fn test_explain_anomalies_uses_bucket() {
+        let entry = |ts: u64, level: crate::LogLevel, msg: &str| crate::ProcessedEntry {
+            timestamp_ms: ts,
+            level,
+            stream: 0,
+            message: msg.into(),
+            seq: 0,
+        };
+        let entries = vec![
+            entry(59_000, crate::LogLevel::Error, "before"),
+            entry(60_000, crate::LogLevel::Error, "spike 1"),
+            entry(61_000, crate::LogLevel::Info, "noise"),
+            entry(90_000, crate::LogLevel::Fatal, "spike 2"),
+            entry(119_999, crate::LogLevel::Error, "spike 3"),
+            entry(120_000, crate::LogLevel::Error, "after"),
+        ];
+        let anomaly = Anomaly {
+            metric: "error_rate".into(),
+            current_value: 3.0,
+            mean: 0.5,
+            std_dev: 0.5,
+            sigma: 5.0,
+            timestamp_ms: 75_000,
+            severity: AnomalySeverity::Critical,
+            method: AnomalyMethod::Sigma,
+            bucket_secs: 60,
+        };
+
+        let explained = explain_anomalies(&entries, std::slice::from_ref(&anomaly), 10);
+        assert_eq!(explained.len(), 1);
+        let ex = &explained[0];
+        assert_eq!((ex.bucket_start_ms, ex.bucket_end_ms), (60_000, 120_000));
+        let msgs: Vec<&str> = ex.lines.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(msgs, vec!["spike 1", "spike 2", "spike 3"]);
+        assert_eq!(ex.bucket_lines, 3);
+
+        // Capped sample, volume anomalies include non-error lines
+        let volume = Anomaly {
+            metric: "log_volume".into(),
+            ..anomaly
+        };
+        let ex = &explain_anomalies(&entries, &[volume], 2)[0];
+        let msgs: Vec<&str> = ex.lines.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(msgs, vec!["spike 1", "noise"]);
+        assert_eq!(ex.bucket_lines, 4);
+    }
+
+    #[test]
+    fn test_compare_windows() {
+        let entry = |ts: u64, level: crate::LogLevel| crate::ProcessedEntry {
+            timestamp_ms: ts,
+            level,
+            stream: 0,
+            message: String::new(),
+            seq: 0,
+        };
+        use crate::LogLevel::{Error, Fatal, Info};
+        // 5-minute windows ending at 10:00: [9:50, 9:55) and [9:55, 10:00)
+        let end = 600_000;
+        let entries = vec![
+            entry(250_000, Error), // before both windows
+            entry(300_000, Error),
+            entry(400_000, Info),
+            entry(449_999, Fatal),
+            entry(450_000, Error),
+            entry(500_000, Error),
+            entry(550_000, Info),
+            entry(599_999, Fatal),
+            entry(600_000, Error), // at the end bound, not counted
+        ];
+        let cmp = compare_windows(&entries, end, 150_000);
+        assert_eq!(cmp.previous_start_ms, 300_000);
+        assert_eq!((cmp.previous_errors, cmp.previous_total), (2, 3));
+        assert_eq!((cmp.current_errors, cmp.current_total), (3, 4));
+        assert_eq!(cmp.delta(), 1);
+        assert_eq!(cmp.ratio, Some(1.5));
+        assert_eq!(cmp.indicator(), "↑");
+
+        let calmer = compare_windows(&entries[..5], 450_000, 150_000);
+        assert_eq!((calmer.previous_errors, calmer.current_errors), (1, 2));
+        let quiet = compare_windows(&entries, 900_000, 150_000);
+        assert_eq!((quiet.previous_errors, quiet.current_errors), (1, 0));
+        assert_eq!(quiet.indicator(), "↓");
+        let none = compare_windows(&[], end, 150_000);
+        assert_eq!((none.ratio, none.indicator()), (None, "="));
+    }
 }
@@ -1,3 +1,6 @@
+use std::sync::LazyLock;
+
+use rayon::prelude::*;
 use regex::Regex;
 use velos_core::LogEntry;
 
@@ -10,9 +13,68 @@ pub struct ClassificationRule {
     pub priority: u8,
 }
 
+/// Batches smaller than this are classified on the calling thread: below it,
+/// handing chunks to the thread pool costs more than it saves.
+pub const PARALLEL_THRESHOLD: usize = 10_000;
+
+/// Lines per chunk handed to one worker by `classify_batch_parallel`.
+const PARALLEL_CHUNK: usize = 2_048;
+
+/// Priority of preset rules: above every default rule, so a preset decides
+/// first for the lines it knows.
+const PRESET_PRIORITY: u8 = 20;
+
+/// Highest priority in the default ruleset. Rules ranked above it (presets,
+/// `[[logs.rules]]` at their default 15) still go before a line's own level
+/// prefix; the default rules only see lines without one.
+const GENERAL_PRIORITY: u8 = 10;
+
+/// A level token opening the line, after an optional timestamp:
+/// `[DEBUG] x`, `ERROR: y`, `2024-01-01 12:00:00 INFO started`,
+/// `[12:00:00] <warn> z`. Bracketed or colon-terminated tokens match in any
+/// case; bare ones only in capitals, so a sentence starting with "Error"
+/// or "Info" is left to the rules.
+static LEADING_LEVEL: LazyLock<Regex> = LazyLock::new(|| {
+    let timestamp = r"\d{4}[-/]\d{2}[-/]\d{2}[T ]\d{2}:\d{2}:\d{2}(?:[.,]\d+)?(?:Z|[+-]\d{2}:?\d{2})?|\d{2}:\d{2}:\d{2}(?:[.,]\d+)?";
+    let mut names: Vec<&str> = velos_core::level::NAMES.iter().map(|(n, _)| *n).collect();
+    names.sort_by_key(|n| std::cmp::Reverse(n.len()));
+    let token = names.join("|");
+    let upper = token.to_uppercase();
+    Regex::new(&format!(
+        r"^\s*(?:\[?(?:{timestamp})\]?\s+)?(?:[\[<](?i:({token}))[\]>]|(?i:({token})):|({upper})(?:\s|\||$))"
+    ))
+    .unwrap()
+});
+
+/// "error"/"failure" as a noun modifier ("error handling", "error page"):
+/// describes a feature, not a failure. Blanked out before the default
+/// rules run, so "user error handling enabled" stays Info.
+static NOUN_MODIFIER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\b(?:error|err|exception|failure)\s+(?:handling|handlers?|pages?|boundar(?:y|ies)|reporting|reporters?|tracking|trackers?|budgets?)\b",
+    )
+    .unwrap()
+});
+
+/// The level a line states for itself in its leading token, if any.
+fn leading_level(message: &str) -> Option<LogLevel> {
+    let caps = LEADING_LEVEL.captures(message)?;
+    let token = (1..=3).find_map(|i| caps.get(i))?.as_str();
+    LogLevel::parse(token)
+}
+
 /// Auto-classifies raw log entries by detecting log level from message content.
 pub struct Classifier {
     rules: Vec<ClassificationRule>,
+    /// Fields read as the level of a JSON line, first present one wins.
+    json_level_keys: Vec<String>,
+    /// Whether a leading level token (`[ERROR]`, `WARN:`) is trusted over
+    /// the default rules. Off for [`empty`](Self::empty).
+    leading_levels: bool,
+}
+
+fn default_json_level_keys() -> Vec<String> {
+    vec!["level".to_string()]
 }
 
 impl Classifier {
@@ -40,12 +102,116 @@ impl Classifier {
                 priority: 4,
             },
         ];
-        Self { rules }
+        Self {
+            rules,
+            json_level_keys: default_json_level_keys(),
+            leading_levels: true,
+        }
+    }
+
+    /// Create a classifier tuned for a log format, one of
+    /// [`velos_config::CLASSIFIER_PRESETS`]. The preset's rules are checked
+    /// first, in order; lines they don't recognize fall through to the
+    /// default ruleset.
+    pub fn preset(name: &str) -> Option<Self> {
+        let rules: &[(&str, LogLevel)] = match name {
+            // Error log severities, then access log status codes (the
+            // number right after the quoted request line).
+            "nginx" => &[
+                (r"\[(emerg|alert|crit)\]", LogLevel::Fatal),
+                (r"\[error\]", LogLevel::Error),
+                (r"\[warn\]", LogLevel::Warn),
+                (r"\[(notice|info)\]", LogLevel::Info),
+                (r"\[debug\]", LogLevel::Debug),
+                (r#"" 5\d\d "#, LogLevel::Error),
+                (r#"" 4\d\d "#, LogLevel::Warn),
+                (r#"" [123]\d\d "#, LogLevel::Info),
+            ],
+            // Request completion status wins over the Ruby Logger prefix
+            // (`E, [2024-01-01T12:00:00 #42] ERROR -- : ...`).
+            "rails" => &[
+                (r"\bCompleted 5\d\d\b", LogLevel::Error),
+                (r"\bCompleted 4\d\d\b", LogLevel::Warn),
+                (r"\bCompleted [123]\d\d\b", LogLevel::Info),
+                (r"^F, \[", LogLevel::Fatal),
+                (r"^E, \[", LogLevel::Error),
+                (r"^W, \[", LogLevel::Warn),
+                (r"^I, \[", LogLevel::Info),
+                (r"^D, \[", LogLevel::Debug),
+            ],
+            // Level fields anywhere in the line (e.g. behind a timestamp
+            // prefix), by name or as pino/bunyan numbers.
+            "json" => &[
+                (
+                    r#""(level|severity)"\s*:\s*("(?i:fatal|panic|critical)"|60\b)"#,
+                    LogLevel::Fatal,
+                ),
+                (
+                    r#""(level|severity)"\s*:\s*("(?i:error|err)"|50\b)"#,
+                    LogLevel::Error,
+                ),
+                (
+                    r#""(level|severity)"\s*:\s*("(?i:warn|warning)"|40\b)"#,
+                    LogLevel::Warn,
+                ),
+                (
+                    r#""(level|severity)"\s*:\s*("(?i:info|notice)"|30\b)"#,
+                    LogLevel::Info,
+                ),
+                (
+                    r#""(level|severity)"\s*:\s*("(?i:debug|trace)"|[12]0\b)"#,
+                    LogLevel::Debug,
+                ),
+            ],
+            _ => return None,
+        };
+
+        let mut classifier = Self::with_defaults();
+        classifier
+            .rules
+            .extend(rules.iter().map(|(pattern, level)| ClassificationRule {
+                pattern: Regex::new(pattern).unwrap(),
+                level: *level,
+                priority: PRESET_PRIORITY,
+            }));
+        // Stable: preset rules keep their order among themselves
+        classifier
+            .rules
+            .sort_by_key(|r| std::cmp::Reverse(r.priority));
+        Some(classifier)
+    }
+
+    /// Create a classifier from the `[logs]` config: its `classifier_preset`
+    /// (or the default ruleset), its `json_level_keys`, then each
+    /// `[[logs.rules]]` entry. The config is expected to be validated, so a
+    /// rule with a bad pattern or level is skipped.
+    pub fn from_config(config: &velos_config::LogEngineConfig) -> Self {
+        let mut classifier = config
+            .classifier_preset
+            .as_deref()
+            .and_then(Self::preset)
+            .unwrap_or_else(Self::with_defaults);
+        classifier.set_json_level_keys(config.json_level_keys.clone());
+        classifier.add_config_rules(config);
+        classifier
+    }
+
+    /// Add the `[[logs.rules]]` entries from the config.
+    pub fn add_config_rules(&mut self, config: &velos_config::LogEngineConfig) {
+        for rule in &config.rules {
+            if let Some(level) = LogLevel::parse(&rule.level) {
+                self.add_rule(&rule.pattern, level, rule.priority);
+            }
+        }
     }
 
     /// Create an empty classifier (no rules, everything is Info).
     pub fn empty() -> Self {
-        Self { rules: Vec::new() }
+        Self {
+            rules: Vec::new(),
+            json_level_keys: default_json_level_keys(),
+            leading_levels: false,
+        }
     }
 
     /// Add a custom rule.
@@ -56,10 +222,16 @@ impl Classifier {
                 level,
                 priority,
             });
-            self.rules.sort_by(|a, b| b.priority.cmp(&a.priority));
+            self.rules.sort_by_key(|r| std::cmp::Reverse(r.priority));
         }
     }
 
+    /// Set the JSON fields holding a line's level (e.g. `["severity", "level"]`),
+    /// checked in order. Defaults to `["level"]`.
+    pub fn set_json_level_keys(&mut self, keys: Vec<String>) {
+        self.json_level_keys = keys;
+    }
+
     /// Classify a single log entry.
     pub fn classify(&self, entry: &LogEntry) -> LogLevel {
         // If the daemon already assigned a non-default level, trust it
@@ -67,31 +239,39 @@ impl Classifier {
             return LogLevel::from_u8(entry.level);
         }
 
-        // JSON-aware: try to parse as JSON and extract "level" field
+        // JSON-aware: try to parse as JSON and extract the level field
         if entry.message.starts_with('{') {
             if let Ok(val) = serde_json::from_str::<serde_json::Value>(&entry.message) {
-                if let Some(lvl) = val.get("level").and_then(|v| v.as_str()) {
-                    return match lvl.to_lowercase().as_str() {
-                        "fatal" | "panic" | "critical" => LogLevel::Fatal,
-                        "error" | "err" => LogLevel::Error,
-                        "warn" | "warning" => LogLevel::Warn,
-                        "debug" | "trace" => LogLevel::Debug,
-                        _ => LogLevel::Info,
-                    };
+                let lvl = self
+                    .json_level_keys
+                    .iter()
+                    .find_map(|key| val.get(key).and_then(|v| v.as_str()));
+                if let Some(lvl) = lvl {
+                    return LogLevel::parse(lvl).unwrap_or(LogLevel::Info);
                 }
             }
         }
 
-        // Apply rules (sorted by priority)
-        for rule in &self.rules {
-            if rule.pattern.is_match(&entry.message) {
-                // stderr floor: if on stderr, level is at least Warn
-                if entry.stream == 1 && (rule.level as u8) < (LogLevel::Warn as u8) {
-                    return LogLevel::Warn;
-                }
-                return rule.level;
+        // Apply rules (sorted by priority): presets and custom rules, then
+        // the line's own level prefix, then the default rules. The prefix
+        // is the logger's word, so it skips the stderr floor: plenty of
+        // loggers write every level to stderr.
+        let general = self
+            .rules
+            .partition_point(|r| r.priority > GENERAL_PRIORITY);
+        let (explicit, general) = self.rules.split_at(general);
+        if let Some(level) = Self::match_rules(explicit, &entry.message, entry.stream) {
+            return level;
+        }
+        if self.leading_levels {
+            if let Some(level) = leading_level(&entry.message) {
+                return level;
             }
         }
+        let message = NOUN_MODIFIER.replace_all(&entry.message, "");
+        if let Some(level) = Self::match_rules(general, &message, entry.stream) {
+            return level;
+        }
 
         // Default: stderr → Warn, stdout → Info
         if entry.stream == 1 {
@@ -101,6 +281,16 @@ impl Classifier {
         }
     }
 
+    /// The level of the first rule matching the line, if any.
+    fn match_rules(rules: &[ClassificationRule], message: &str, stream: u8) -> Option<LogLevel> {
+        let rule = rules.iter().find(|r| r.pattern.is_match(message))?;
+        // stderr floor: if on stderr, level is at least Warn
+        if stream == 1 && (rule.level as u8) < (LogLevel::Warn as u8) {
+            return Some(LogLevel::Warn);
+        }
+        Some(rule.level)
+    }
+
     /// Classify a batch of raw LogEntry into ProcessedEntry.
     pub fn classify_batch(&self, entries: &[LogEntry]) -> Vec<ProcessedEntry> {
         entries
@@ -108,6 +298,23 @@ impl Classifier {
             .map(|e| ProcessedEntry::from_raw(e, self.classify(e)))
             .collect()
     }
+
+    /// Same result as [`classify_batch`](Self::classify_batch), split into
+    /// chunks classified on the rayon pool once the batch reaches
+    /// [`PARALLEL_THRESHOLD`] lines. Order is preserved.
+    pub fn classify_batch_parallel(&self, entries: &[LogEntry]) -> Vec<ProcessedEntry> {
+        if entries.len() < PARALLEL_THRESHOLD {
+            return self.classify_batch(entries);
+        }
+        entries
+            .par_chunks(PARALLEL_CHUNK)
+            .flat_map_iter(|chunk| {
+                chunk
+                    .iter()
+                    .map(|e| ProcessedEntry::from_raw(e, self.classify(e)))
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -120,6 +327,7 @@ mod tests {
             level,
             stream,
             message: msg.to_string(),
+            seq: 0,
         }
     }
 
@@ -173,6 +381,21 @@ mod tests {
         assert_eq!(c.classify(&e), LogLevel::Error);
     }
 
+    #[test]
+    fn test_classify_json_level_keys() {
+        let mut c = Classifier::empty();
+        let e = make_entry(r#"{"severity":"error","msg":"retrying"}"#, 1, 0);
+        assert_eq!(c.classify(&e), LogLevel::Info);
+
+        c.set_json_level_keys(vec!["severity".into(), "level".into()]);
+        assert_eq!(c.classify(&e), LogLevel::Error);
+
+        // Keys are checked in order
+        let both = make_entry(r#"{"level":"info","severity":"warning"}"#, 1, 0);
+        assert_eq!(c.classify(&both), LogLevel::Warn);
+        assert_eq!(Classifier::with_defaults().classify(&both), LogLevel::Info);
+    }
+
     #[test]
     fn test_classify_respects_existing_level() {
         let c = Classifier::with_defaults();
@@ -181,6 +404,100 @@ mod tests {
         assert_eq!(c.classify(&e), LogLevel::Error);
     }
 
+    #[test]
+    fn test_nginx_preset_status_codes() {
+        let c = Classifier::preset("nginx").unwrap();
+        let line = |status: &str| {
+            make_entry(
+                &format!(
+                    r#"10.0.0.1 - - [12/Mar/2024:10:00:00 +0000] "GET /api/errors HTTP/1.1" {status} 512 "-" "curl/8.0""#
+                ),
+                1,
+                0,
+            )
+        };
+        assert_eq!(c.classify(&line("500")), LogLevel::Error);
+        assert_eq!(c.classify(&line("503")), LogLevel::Error);
+        assert_eq!(c.classify(&line("404")), LogLevel::Warn);
+        // "/api/errors" in the path must not make a 200 an error
+        assert_eq!(c.classify(&line("200")), LogLevel::Info);
+
+        let err_log = make_entry("2024/03/12 10:00:00 [crit] 42#0: *1 connect() failed", 1, 0);
+        assert_eq!(c.classify(&err_log), LogLevel::Fatal);
+    }
+
+    #[test]
+    fn test_rails_and_json_presets() {
+        let rails = Classifier::preset("rails").unwrap();
+        let completed = make_entry(
+            "I, [2024-03-12T10:00:00.123 #42]  INFO -- : Completed 500 Internal Server Error in 12ms",
+            1,
+            0,
+        );
+        assert_eq!(rails.classify(&completed), LogLevel::Error);
+        let ok = make_entry(
+            "I, [2024-03-12T10:00:00.123 #42]  INFO -- : Completed 200 OK in 3ms",
+            1,
+            0,
+        );
+        assert_eq!(rails.classify(&ok), LogLevel::Info);
+        let warn = make_entry(
+            "W, [2024-03-12T10:00:00.123 #42]  WARN -- : slow query",
+            1,
+            0,
+        );
+        assert_eq!(rails.classify(&warn), LogLevel::Warn);
+
+        let json = Classifier::preset("json").unwrap();
+        let pino = make_entry(r#"12:00:00 {"level":50,"msg":"db down"}"#, 1, 0);
+        assert_eq!(json.classify(&pino), LogLevel::Error);
+        let sev = make_entry(r#"{"severity":"WARNING","msg":"retrying"}"#, 1, 0);
+        assert_eq!(json.classify(&sev), LogLevel::Warn);
+
+        for name in velos_config::CLASSIFIER_PRESETS {
+            assert!(Classifier::preset(name).is_some(), "preset {name}");
+        }
+        assert!(Classifier::preset("apache").is_none());
+    }
+
+    #[test]
+    fn test_leading_level_token() {
+        let c = Classifier::with_defaults();
+        let level = |msg: &str, stream: u8| c.classify(&make_entry(msg, 1, stream));
+
+        assert_eq!(level("[DEBUG] x", 0), LogLevel::Debug);
+        assert_eq!(level("ERROR: y", 0), LogLevel::Error);
+        assert_eq!(level("WARNING - low disk", 0), LogLevel::Warn);
+        assert_eq!(level("[12:00:01] <crit> raid degraded", 0), LogLevel::Fatal);
+        // The prefix beats the default rules and the stderr floor
+        assert_eq!(level("INFO: retry failed, trying again", 0), LogLevel::Info);
+        assert_eq!(level("DEBUG:root:connection error", 1), LogLevel::Debug);
+
+        // "error" as a noun behind an INFO prefix is not an error
+        let noun = "2024-01-01 12:00:00 INFO user error handling enabled";
+        assert_eq!(level(noun, 0), LogLevel::Info);
+        assert_eq!(
+            level("2024-01-01T12:00:00.123Z ERROR boom", 0),
+            LogLevel::Error
+        );
+        // A sentence starting with a level word is left to the rules
+        assert_eq!(level("Info about the error", 0), LogLevel::Error);
+        // "error" as a noun modifier is not an error, prefix or not
+        assert_eq!(level("user error handling enabled", 0), LogLevel::Info);
+        assert_eq!(level("custom error page loaded", 0), LogLevel::Info);
+        assert_eq!(level("error page render failed", 0), LogLevel::Error);
+        assert_eq!(level("request error: timeout", 0), LogLevel::Error);
+
+        // Preset rules still go first
+        let rails = Classifier::preset("rails").unwrap();
+        let e = make_entry("INFO Completed 500 Internal Server Error", 1, 0);
+        assert_eq!(rails.classify(&e), LogLevel::Error);
+        assert_eq!(
+            Classifier::empty().classify(&make_entry("ERROR: y", 1, 0)),
+            LogLevel::Info
+        );
+    }
+
     #[test]
     fn test_custom_rule() {
         let mut c = Classifier::with_defaults();
@@ -188,4 +505,81 @@ mod tests {
         let e = make_entry("SEGFAULT at 0x0000", 1, 0);
         assert_eq!(c.classify(&e), LogLevel::Fatal);
     }
+
+    #[test]
+    fn test_parallel_batch_matches_sequential() {
+        let messages = [
+            "GET /health 200",
+            "ERROR connection refused",
+            "warning: disk at 91%",
+            r#"{"level":"debug","msg":"tick"}"#,
+            "thread 'main' panicked at src/main.rs:3",
+            "DEBUG cache miss",
+        ];
+        let entries: Vec<LogEntry> = (0..PARALLEL_THRESHOLD * 3 + 17)
+            .map(|i| {
+                let mut e = make_entry(messages[i % messages.len()], 1, (i % 2) as u8);
+                e.seq = i as u64;
+                e
+            })
+            .collect();
+
+        let c = Classifier::with_defaults();
+        let sequential = c.classify_batch(&entries);
+        let parallel = c.classify_batch_parallel(&entries);
+        assert_eq!(parallel.len(), sequential.len());
+        for (p, s) in parallel.iter().zip(&sequential) {
+            assert_eq!((p.seq, p.level, &p.message), (s.seq, s.level, &s.message));
+        }
+
+        // Small batches take the sequential path
+        let small = &entries[..100];
+        assert_eq!(c.classify_batch_parallel(small).len(), 100);
+    }
+
+    #[test]
+    fn test_from_config_rules() {
+        let config = velos_config::parse(
+            r#"
+[logs]
+json_level_keys = ["severity"]
+
+[[logs.rules]]
+pattern = "OOMKilled"
+level = "fatal"
+
+[[logs.rules]]
+pattern = "(?i)retrying"
+level = "info"
+priority = 9
+
+[apps.api]
+script = "server.js"
+"#,
+        )
+        .unwrap();
+        let c = Classifier::from_config(config.logs.as_ref().unwrap());
+
+        assert_eq!(
+            c.classify(&make_entry("pod OOMKilled", 1, 0)),
+            LogLevel::Fatal
+        );
+        // Priority 9 sits between the default error (8) and fatal (10) rules
+        let e = make_entry("request failed, retrying", 1, 0);
+        assert_eq!(c.classify(&e), LogLevel::Info);
+        let e = make_entry("panic while retrying", 1, 0);
+        assert_eq!(c.classify(&e), LogLevel::Fatal);
+        let e = make_entry(r#"{"severity":"error","msg":"x"}"#, 1, 0);
+        assert_eq!(c.classify(&e), LogLevel::Error);
+
+        let preset = velos_config::LogEngineConfig {
+            classifier_preset: Some("nginx".into()),
+            ..Default::default()
+        };
+        let e = make_entry("2024/01/01 [crit] worker died", 1, 0);
+        assert_eq!(
+            Classifier::from_config(&preset).classify(&e),
+            LogLevel::Fatal
+        );
+    }
 }
@@ -3,16 +3,28 @@ use std::collections::HashMap;
 use crate::{LogLevel, ProcessedEntry};
 
 /// Result of deduplication: a template with occurrence count.
+///
+/// This is also the `velos logs --dedupe --json` schema, which tooling
+/// relies on: exactly these fields, in this order, with `level` as the
+/// lowercase name ("error"). Add fields only at the end.
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct DedupResult {
+    /// Message with variable parts replaced (`<IP>`, `<N>`, ...).
     pub template: String,
     pub count: u64,
     pub first_seen_ms: u64,
     pub last_seen_ms: u64,
+    /// Highest level seen among the grouped lines.
+    #[serde(serialize_with = "serialize_level")]
     pub level: LogLevel,
+    /// The first raw message of the group.
     pub sample: String,
 }
 
+fn serialize_level<S: serde::Serializer>(level: &LogLevel, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(level.as_str())
+}
+
 /// Normalizes log messages by replacing variable parts with placeholders.
 pub fn normalize(message: &str) -> String {
     use regex::Regex;
@@ -34,6 +46,32 @@ pub fn normalize(message: &str) -> String {
     s.into_owned()
 }
 
+/// [`normalize`], then also mask the tokens that vary per user or request:
+/// quoted strings as `<STR>`, email addresses as `<EMAIL>` and absolute or
+/// `./`-relative file paths as `<PATH>`. Bare words are left alone, since
+/// one line can't tell a name apart from its fixed text;
+/// [`PatternDetector::with_aggressive`](crate::pattern::PatternDetector::with_aggressive)
+/// merges those across lines instead.
+pub fn normalize_aggressive(message: &str) -> String {
+    use regex::Regex;
+    use std::sync::LazyLock;
+
+    static RE_STR: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#""[^"]*"|'[^']*'|`[^`]*`"#).unwrap());
+    // `<` and `>` so addresses [`normalize`] already masked digits in match
+    static RE_EMAIL: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"[\w.+<>-]+@[\w<>-]+(?:\.[\w<>-]+)+").unwrap());
+    // Only at a token start, so URLs ("https://host/x") and ratios stay as-is
+    static RE_PATH: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(^|[\s=(\[])(?:~|\.{1,2})?/[^\s,;:)\]]+").unwrap());
+
+    let s = normalize(message);
+    let s = RE_STR.replace_all(&s, "<STR>");
+    let s = RE_EMAIL.replace_all(&s, "<EMAIL>");
+    let s = RE_PATH.replace_all(&s, "${1}<PATH>");
+    s.into_owned()
+}
+
 fn hash_string(s: &str) -> u64 {
     use std::hash::{Hash, Hasher};
     let mut hasher = std::collections::hash_map::DefaultHasher::new();
@@ -42,9 +80,15 @@ fn hash_string(s: &str) -> u64 {
 }
 
 /// Deduplication engine with sliding time window.
+///
+/// Feed it batches over time with [`push_batch`](Self::push_batch) and read
+/// the groups with [`snapshot`](Self::snapshot); counts carry over between
+/// batches until a group goes quiet for longer than the window.
 pub struct DedupEngine {
     entries: HashMap<u64, DedupEntry>,
     window_ms: u64,
+    /// Newest timestamp pushed so far: "now" for eviction.
+    newest_ms: u64,
 }
 
 struct DedupEntry {
@@ -61,6 +105,7 @@ impl DedupEngine {
         Self {
             entries: HashMap::new(),
             window_ms: window_secs * 1000,
+            newest_ms: 0,
         }
     }
 
@@ -69,40 +114,61 @@ impl DedupEngine {
         Self::new(60)
     }
 
-    /// Process entries and return deduplicated results.
+    /// Deduplicate one standalone batch: forget earlier state, then group
+    /// `entries`. Nothing in the batch is evicted, however old.
     pub fn deduplicate(&mut self, entries: &[ProcessedEntry]) -> Vec<DedupResult> {
         self.entries.clear();
+        self.newest_ms = 0;
+        for entry in entries {
+            self.push(entry);
+        }
+        self.snapshot()
+    }
 
+    /// Add the next chunk of a stream, keeping counts from earlier calls.
+    /// Afterwards, groups last seen more than the window before the newest
+    /// timestamp so far are dropped.
+    pub fn push_batch(&mut self, entries: &[ProcessedEntry]) {
         for entry in entries {
-            let normalized = normalize(&entry.message);
-            let hash = hash_string(&normalized);
-
-            if let Some(existing) = self.entries.get_mut(&hash) {
-                // Check if within time window
-                if entry.timestamp_ms.saturating_sub(existing.last_seen_ms) <= self.window_ms {
-                    existing.count += 1;
-                    existing.last_seen_ms = entry.timestamp_ms;
-                    // Keep the highest severity level
-                    if (entry.level as u8) > (existing.level as u8) {
-                        existing.level = entry.level;
-                    }
-                    continue;
+            self.push(entry);
+        }
+        let cutoff = self.newest_ms.saturating_sub(self.window_ms);
+        self.entries.retain(|_, e| e.last_seen_ms >= cutoff);
+    }
+
+    fn push(&mut self, entry: &ProcessedEntry) {
+        self.newest_ms = self.newest_ms.max(entry.timestamp_ms);
+        let normalized = normalize(&entry.message);
+        let hash = hash_string(&normalized);
+
+        if let Some(existing) = self.entries.get_mut(&hash) {
+            // Check if within time window
+            if entry.timestamp_ms.saturating_sub(existing.last_seen_ms) <= self.window_ms {
+                existing.count += 1;
+                existing.last_seen_ms = existing.last_seen_ms.max(entry.timestamp_ms);
+                // Keep the highest severity level
+                if (entry.level as u8) > (existing.level as u8) {
+                    existing.level = entry.level;
                 }
+                return;
             }
-
-            self.entries.insert(
-                hash,
-                DedupEntry {
-                    template: normalized,
-                    count: 1,
-                    first_seen_ms: entry.timestamp_ms,
-                    last_seen_ms: entry.timestamp_ms,
-                    level: entry.level,
-                    sample: entry.message.clone(),
-                },
-            );
         }
 
+        self.entries.insert(
+            hash,
+            DedupEntry {
+                template: normalized,
+                count: 1,
+                first_seen_ms: entry.timestamp_ms,
+                last_seen_ms: entry.timestamp_ms,
+                level: entry.level,
+                sample: entry.message.clone(),
+            },
+        );
+    }
+
+    /// The current groups, most frequent first.
+    pub fn snapshot(&self) -> Vec<DedupResult> {
         let mut results: Vec<DedupResult> = self
             .entries
             .values()
@@ -116,8 +182,14 @@ impl DedupEngine {
             })
             .collect();
 
-        // Sort by count descending
-        results.sort_by(|a, b| b.count.cmp(&a.count));
+        // Count descending; ties by first appearance, then template, so the
+        // order does not depend on hash map iteration
+        results.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then(a.first_seen_ms.cmp(&b.first_seen_ms))
+                .then_with(|| a.template.cmp(&b.template))
+        });
         results
     }
 }
@@ -169,6 +241,34 @@ mod tests {
         assert_eq!(normalize("Segfault at 0xDEADBEEF"), "Segfault at <HEX>");
     }
 
+    #[test]
+    fn test_dedup_json_schema() {
+        let entry = |ts, level, message: &str| ProcessedEntry {
+            timestamp_ms: ts,
+            level,
+            stream: 0,
+            message: message.into(),
+            seq: 0,
+        };
+        let mut engine = DedupEngine::with_defaults();
+        let results = engine.deduplicate(&[
+            entry(1000, LogLevel::Info, "Server started"),
+            entry(2000, LogLevel::Warn, "Retry 12 of 50"),
+            entry(3000, LogLevel::Error, "Retry 13 of 50"),
+            entry(4000, LogLevel::Info, "Cache warmed"),
+        ]);
+
+        let json = serde_json::to_string(&results).unwrap();
+        assert_eq!(
+            json,
+            concat!(
+                r#"[{"template":"Retry <N> of <N>","count":2,"first_seen_ms":2000,"last_seen_ms":3000,"level":"error","sample":"Retry 12 of 50"},"#,
+                r#"{"template":"Server started","count":1,"first_seen_ms":1000,"last_seen_ms":1000,"level":"info","sample":"Server started"},"#,
+                r#"{"template":"Cache warmed","count":1,"first_seen_ms":4000,"last_seen_ms":4000,"level":"info","sample":"Cache warmed"}]"#,
+            )
+        );
+    }
+
     #[test]
     fn test_dedup_groups_similar() {
         let mut engine = DedupEngine::with_defaults();
@@ -178,18 +278,21 @@ mod tests {
                 level: LogLevel::Error,
                 stream: 0,
                 message: "Connection to 192.168.1.1:5432 failed".into(),
+                seq: 0,
             },
             ProcessedEntry {
                 timestamp_ms: 2000,
                 level: LogLevel::Error,
                 stream: 0,
                 message: "Connection to 10.0.0.5:5432 failed".into(),
+                seq: 0,
             },
             ProcessedEntry {
                 timestamp_ms: 3000,
                 level: LogLevel::Error,
                 stream: 0,
                 message: "Connection to 172.16.0.1:5432 failed".into(),
+                seq: 0,
             },
         ];
         let results = engine.deduplicate(&entries);
@@ -207,15 +310,53 @@ mod tests {
                 level: LogLevel::Error,
                 stream: 0,
                 message: "Connection failed".into(),
+                seq: 0,
             },
             ProcessedEntry {
                 timestamp_ms: 2000,
                 level: LogLevel::Info,
                 stream: 0,
                 message: "Server started".into(),
+                seq: 0,
             },
         ];
         let results = engine.deduplicate(&entries);
         assert_eq!(results.len(), 2);
     }
+
+    #[test]
+    fn test_push_batch_accumulates_and_evicts() {
+        let entry = |ts, message: &str| ProcessedEntry {
+            timestamp_ms: ts,
+            level: LogLevel::Warn,
+            stream: 0,
+            message: message.into(),
+            seq: 0,
+        };
+        let counts = |engine: &DedupEngine| {
+            engine
+                .snapshot()
+                .into_iter()
+                .map(|r| (r.template, r.count))
+                .collect::<Vec<_>>()
+        };
+        let mut engine = DedupEngine::new(60);
+
+        engine.push_batch(&[entry(1_000, "Retry 11 of 50"), entry(2_000, "Cache warmed")]);
+        engine.push_batch(&[entry(30_000, "Retry 12 of 50")]);
+        assert_eq!(
+            counts(&engine),
+            [("Retry <N> of <N>".into(), 2), ("Cache warmed".into(), 1)]
+        );
+
+        // 70s in: "Cache warmed" was last seen over a minute ago
+        engine.push_batch(&[entry(70_000, "Retry 13 of 50")]);
+        assert_eq!(counts(&engine), [("Retry <N> of <N>".into(), 3)]);
+        let retry = &engine.snapshot()[0];
+        assert_eq!((retry.first_seen_ms, retry.last_seen_ms), (1_000, 70_000));
+
+        // A standalone batch starts over and keeps everything in it
+        let results = engine.deduplicate(&[entry(0, "boot"), entry(500_000, "shutdown")]);
+        assert_eq!(results.len(), 2);
+    }
 }
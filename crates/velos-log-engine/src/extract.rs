@@ -0,0 +1,123 @@
+use regex::Regex;
+
+use crate::ProcessedEntry;
+
+/// Pulls the named capture groups of a regex out of each log line, one
+/// column per group, for `velos logs --regex-extract`.
+#[derive(Debug, Clone)]
+pub struct FieldExtractor {
+    regex: Regex,
+    columns: Vec<String>,
+}
+
+/// One line's worth of extraction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Extracted<'a> {
+    /// Captured values, in [`FieldExtractor::columns`] order. An optional
+    /// group that didn't take part in the match is an empty string.
+    Fields(Vec<String>),
+    /// The line didn't match; kept only when asked for.
+    Unmatched(&'a str),
+}
+
+impl FieldExtractor {
+    /// `None` if the regex has no named groups, since there would be no
+    /// columns to show.
+    pub fn new(regex: Regex) -> Option<Self> {
+        let columns: Vec<String> = regex.capture_names().flatten().map(String::from).collect();
+        if columns.is_empty() {
+            return None;
+        }
+        Some(Self { regex, columns })
+    }
+
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Named captures from the first match in `message`.
+    pub fn extract(&self, message: &str) -> Option<Vec<String>> {
+        let caps = self.regex.captures(message)?;
+        Some(
+            self.columns
+                .iter()
+                .map(|name| caps.name(name).map_or("", |m| m.as_str()).to_string())
+                .collect(),
+        )
+    }
+
+    /// Extract every entry in order. Lines that don't match are dropped, or
+    /// kept as [`Extracted::Unmatched`] with `keep_unmatched`.
+    pub fn extract_all<'a>(
+        &self,
+        entries: &'a [ProcessedEntry],
+        keep_unmatched: bool,
+    ) -> Vec<Extracted<'a>> {
+        entries
+            .iter()
+            .filter_map(|e| match self.extract(&e.message) {
+                Some(fields) => Some(Extracted::Fields(fields)),
+                None if keep_unmatched => Some(Extracted::Unmatched(&e.message)),
+                None => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LogLevel;
+
+    fn entry(message: &str) -> ProcessedEntry {
+        ProcessedEntry {
+            timestamp_ms: 0,
+            level: LogLevel::Info,
+            stream: 0,
+            message: message.into(),
+            seq: 0,
+        }
+    }
+
+    #[test]
+    fn test_extract_named_groups() {
+        let regex = Regex::new(r"(?P<method>\w+) (?P<path>/\S+) (?P<status>\d+)").unwrap();
+        let extractor = FieldExtractor::new(regex).unwrap();
+        assert_eq!(extractor.columns(), ["method", "path", "status"]);
+
+        let entries = [
+            entry("GET /api/users 200 12ms"),
+            entry("worker started"),
+            entry("10:00:01 POST /login 401"),
+        ];
+        let row = |m: &str, p: &str, s: &str| Extracted::Fields(vec![m.into(), p.into(), s.into()]);
+
+        assert_eq!(
+            extractor.extract_all(&entries, false),
+            [
+                row("GET", "/api/users", "200"),
+                row("POST", "/login", "401")
+            ]
+        );
+        assert_eq!(
+            extractor.extract_all(&entries, true),
+            [
+                row("GET", "/api/users", "200"),
+                Extracted::Unmatched("worker started"),
+                row("POST", "/login", "401"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_optional_group_and_no_names() {
+        let regex = Regex::new(r"user=(?P<user>\w+)(?: id=(?P<id>\d+))?").unwrap();
+        let extractor = FieldExtractor::new(regex).unwrap();
+        assert_eq!(
+            extractor.extract("login user=ann"),
+            Some(vec!["ann".to_string(), String::new()])
+        );
+
+        assert!(FieldExtractor::new(Regex::new(r"(\w+) (\d+)").unwrap()).is_none());
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{LogLevel, ProcessedEntry};
+
+/// Keep roughly `percent`% of entries below `Error`, spread evenly through the
+/// batch (every-Nth, so output is reproducible). Error and fatal entries are
+/// always kept so rare failures survive sampling.
+pub fn sample(entries: &[ProcessedEntry], percent: f64) -> Vec<ProcessedEntry> {
+    let percent = percent.clamp(0.0, 100.0);
+    let mut seen: u64 = 0;
+    entries
+        .iter()
+        .filter(|e| {
+            if e.level >= LogLevel::Error {
+                return true;
+            }
+            // Keep an entry whenever the running quota crosses a whole number.
+            let before = (seen as f64 * percent / 100.0).floor();
+            seen += 1;
+            let after = (seen as f64 * percent / 100.0).floor();
+            after > before
+        })
+        .cloned()
+        .collect()
+}
+
+/// Which end of a batch to keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Window {
+    /// The earliest N entries.
+    Head(usize),
+    /// The latest N entries.
+    Tail(usize),
+}
+
+/// Keep the first or last N entries of a batch (assumed oldest first).
+pub fn select_window(mut entries: Vec<ProcessedEntry>, window: Window) -> Vec<ProcessedEntry> {
+    match window {
+        Window::Head(n) => {
+            entries.truncate(n);
+            entries
+        }
+        Window::Tail(n) => {
+            let skip = entries.len().saturating_sub(n);
+            entries.split_off(skip)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(level: LogLevel, ts: u64) -> ProcessedEntry {
+        ProcessedEntry {
+            timestamp_ms: ts,
+            level,
+            stream: 0,
+            message: format!("msg {ts}"),
+            seq: 0,
+        }
+    }
+
+    #[test]
+    fn sample_keeps_errors_and_downsamples_info() {
+        let mut entries: Vec<ProcessedEntry> = (0..100).map(|i| entry(LogLevel::Info, i)).collect();
+        entries.push(entry(LogLevel::Error, 100));
+        entries.push(entry(LogLevel::Fatal, 101));
+
+        let sampled = sample(&entries, 10.0);
+        let info = sampled.iter().filter(|e| e.level == LogLevel::Info).count();
+        assert_eq!(info, 10);
+        assert!(sampled.iter().any(|e| e.level == LogLevel::Error));
+        assert!(sampled.iter().any(|e| e.level == LogLevel::Fatal));
+
+        // Deterministic: same input, same output.
+        let again = sample(&entries, 10.0);
+        let ts: Vec<u64> = sampled.iter().map(|e| e.timestamp_ms).collect();
+        let ts_again: Vec<u64> = again.iter().map(|e| e.timestamp_ms).collect();
+        assert_eq!(ts, ts_again);
+
+        assert_eq!(sample(&entries, 100.0).len(), entries.len());
+        assert_eq!(sample(&entries, 0.0).len(), 2);
+    }
+
+    #[test]
+    fn head_and_tail_select_opposite_ends() {
+        let entries: Vec<ProcessedEntry> = (0..10).map(|i| entry(LogLevel::Info, i)).collect();
+
+        let head = select_window(entries.clone(), Window::Head(3));
+        let tail = select_window(entries.clone(), Window::Tail(3));
+        let ts = |v: &[ProcessedEntry]| v.iter().map(|e| e.timestamp_ms).collect::<Vec<_>>();
+        assert_eq!(ts(&head), vec![0, 1, 2]);
+        assert_eq!(ts(&tail), vec![7, 8, 9]);
+
+        // Asking for more than the batch returns it unchanged either way.
+        assert_eq!(select_window(entries.clone(), Window::Head(50)).len(), 10);
+        assert_eq!(select_window(entries, Window::Tail(50)).len(), 10);
+    }
+}
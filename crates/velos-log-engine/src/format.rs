@@ -1,7 +1,7 @@
 use crate::ProcessedEntry;
 
 /// Format a log entry as structured JSON Line.
-/// Output: `{"ts":1707734400000,"lvl":"info","pid":0,"msg":"Server started","src":"stdout"}`
+/// Output: `{"ts":1707734400000,"lvl":"info","pid":0,"msg":"Server started","src":"stdout","seq":12}`
 pub fn format_structured(entry: &ProcessedEntry, pid: u32) -> String {
     let src = if entry.stream == 1 {
         "stderr"
@@ -14,10 +14,21 @@ pub fn format_structured(entry: &ProcessedEntry, pid: u32) -> String {
         "pid": pid,
         "msg": entry.message,
         "src": src,
+        "seq": entry.seq,
     })
     .to_string()
 }
 
+/// Format entries as newline-delimited JSON (one `format_structured` object per line).
+pub fn format_ndjson(entries: &[ProcessedEntry], pid: u32) -> String {
+    let mut out = String::new();
+    for e in entries {
+        out.push_str(&format_structured(e, pid));
+        out.push('\n');
+    }
+    out
+}
+
 /// Format a log entry as plain text.
 /// Output: `[out|10:05:03] Server started on port 3000`
 pub fn format_plain(entry: &ProcessedEntry) -> String {
@@ -26,6 +37,17 @@ pub fn format_plain(entry: &ProcessedEntry) -> String {
     format!("[{}|{}] {}", stream_tag, time, entry.message)
 }
 
+/// Like [`format_plain`] without the stream tag, for processes whose stdout
+/// and stderr are merged.
+/// Output: `[10:05:03] Server started on port 3000`
+pub fn format_merged(entry: &ProcessedEntry) -> String {
+    format!(
+        "[{}] {}",
+        format_timestamp(entry.timestamp_ms),
+        entry.message
+    )
+}
+
 /// Format a log entry as plain text with level indicator.
 /// Output: `[INFO|out|10:05:03] Server started on port 3000`
 pub fn format_plain_with_level(entry: &ProcessedEntry) -> String {
@@ -35,6 +57,68 @@ pub fn format_plain_with_level(entry: &ProcessedEntry) -> String {
     format!("[{}|{}|{}] {}", level, stream_tag, time, entry.message)
 }
 
+/// Like [`format_plain_with_level`] without the stream tag, for processes
+/// whose stdout and stderr are merged.
+/// Output: `[INFO|10:05:03] Server started on port 3000`
+pub fn format_merged_with_level(entry: &ProcessedEntry) -> String {
+    let time = format_timestamp(entry.timestamp_ms);
+    let level = entry.level.as_str().to_uppercase();
+    format!("[{}|{}] {}", level, time, entry.message)
+}
+
+/// Trim rendered lines to a total byte budget (each line counts its trailing
+/// newline). Returns the lines that fit and how many were dropped.
+pub fn truncate_to_bytes(mut lines: Vec<String>, max_bytes: u64) -> (Vec<String>, usize) {
+    let mut used: u64 = 0;
+    let mut keep = lines.len();
+    for (i, line) in lines.iter().enumerate() {
+        used += line.len() as u64 + 1;
+        if used > max_bytes {
+            keep = i;
+            break;
+        }
+    }
+    let dropped = lines.len() - keep;
+    lines.truncate(keep);
+    (lines, dropped)
+}
+
+/// Cut `message` to `max_chars` characters, noting how many were dropped:
+/// `"data:AAAA…(+9120 chars)"`. Shorter messages are left alone.
+pub fn truncate_message(message: &mut String, max_chars: usize) {
+    if let Some((cut, _)) = message.char_indices().nth(max_chars) {
+        let dropped = message[cut..].chars().count();
+        message.truncate(cut);
+        message.push_str(&format!("\u{2026}(+{dropped} chars)"));
+    }
+}
+
+/// An entry as `--json` writes it, but with the fields of a JSON message
+/// merged in at the top level (`--flatten-parsed`) instead of nested in the
+/// `message` string. A parsed key that clashes with an entry field gets a
+/// `parsed_` prefix. Other messages are left as they are, and so is
+/// `message` when text precedes the JSON.
+pub fn flatten_parsed(entry: &ProcessedEntry) -> serde_json::Value {
+    let mut out = serde_json::to_value(entry).unwrap_or_default();
+    let Some(fields) = crate::aggregate::parse_json_fields(&entry.message) else {
+        return out;
+    };
+    let Some(obj) = out.as_object_mut() else {
+        return out;
+    };
+    if entry.message.trim_start().starts_with('{') {
+        obj.remove("message");
+    }
+    for (key, value) in fields {
+        let mut key = key;
+        while obj.contains_key(&key) {
+            key = format!("parsed_{key}");
+        }
+        obj.insert(key, value);
+    }
+    out
+}
+
 /// Short timestamp for dedup output (HH:MM:SS).
 pub fn format_timestamp_short(ms: u64) -> String {
     format_timestamp(ms)
@@ -59,6 +143,7 @@ mod tests {
             level,
             stream,
             message: msg.to_string(),
+            seq: 0,
         }
     }
 
@@ -73,6 +158,47 @@ mod tests {
         assert_eq!(v["src"], "stdout");
     }
 
+    #[test]
+    fn test_truncate_message() {
+        let mut short = "GET /health 200".to_string();
+        truncate_message(&mut short, 15);
+        assert_eq!(short, "GET /health 200");
+
+        let mut blob = format!("data:{}", "A".repeat(9000));
+        truncate_message(&mut blob, 8);
+        assert_eq!(blob, "data:AAA\u{2026}(+8997 chars)");
+
+        // Counted in chars, cut on a char boundary
+        let mut utf8 = "привет мир".to_string();
+        truncate_message(&mut utf8, 6);
+        assert_eq!(utf8, "привет\u{2026}(+4 chars)");
+    }
+
+    #[test]
+    fn test_flatten_parsed() {
+        let e = make_processed(r#"{"req_id":"x","latency":5}"#, LogLevel::Info, 0, 1000);
+        let v = flatten_parsed(&e);
+        assert_eq!(v["req_id"], "x");
+        assert_eq!(v["latency"], 5);
+        assert_eq!(v["timestamp_ms"], 1000);
+        assert!(v.get("message").is_none());
+
+        // Clashing keys keep the entry's value; the parsed one is prefixed
+        let e = make_processed(r#"{"level":"debug","seq":7}"#, LogLevel::Warn, 0, 0);
+        let v = flatten_parsed(&e);
+        assert_eq!(v["level"], serde_json::to_value(LogLevel::Warn).unwrap());
+        assert_eq!(v["parsed_level"], "debug");
+        assert_eq!(v["parsed_seq"], 7);
+
+        // Text before the JSON stays in "message"; plain lines are untouched
+        let e = make_processed(r#"12:00:00 {"status":500}"#, LogLevel::Error, 1, 0);
+        let v = flatten_parsed(&e);
+        assert_eq!(v["status"], 500);
+        assert_eq!(v["message"], r#"12:00:00 {"status":500}"#);
+        let e = make_processed("plain line", LogLevel::Info, 0, 0);
+        assert_eq!(flatten_parsed(&e), serde_json::to_value(&e).unwrap());
+    }
+
     #[test]
     fn test_format_plain() {
         // 10:05:03 = 36303 seconds = 36303000 ms
@@ -94,4 +220,42 @@ mod tests {
         let s = format_plain_with_level(&e);
         assert!(s.starts_with("[WARN|out|"));
     }
+
+    #[test]
+    fn test_truncate_to_bytes() {
+        let lines: Vec<String> = (0..100).map(|i| format!("line {i:04}")).collect();
+        // Each line is 9 bytes + newline = 10 bytes.
+        let (kept, dropped) = truncate_to_bytes(lines.clone(), 35);
+        assert_eq!(kept, vec!["line 0000", "line 0001", "line 0002"]);
+        assert_eq!(dropped, 97);
+
+        // Cap larger than the batch keeps everything.
+        let (kept, dropped) = truncate_to_bytes(lines, 10_000);
+        assert_eq!(kept.len(), 100);
+        assert_eq!(dropped, 0);
+    }
+
+    #[test]
+    fn test_format_ndjson() {
+        let entries = vec![
+            make_processed("started", LogLevel::Info, 0, 1000),
+            make_processed(
+                "line with \"quotes\"\nand newline",
+                LogLevel::Error,
+                1,
+                2000,
+            ),
+            make_processed("done", LogLevel::Warn, 0, 3000),
+        ];
+        let out = format_ndjson(&entries, 42);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        for (line, entry) in lines.iter().zip(&entries) {
+            let v: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(v["msg"], entry.message.as_str());
+            assert_eq!(v["ts"], entry.timestamp_ms);
+            assert_eq!(v["pid"], 42);
+        }
+        assert_eq!(format_ndjson(&[], 1), "");
+    }
 }
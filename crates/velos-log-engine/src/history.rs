@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Pattern templates seen before for one process, with when each was first
+/// seen. Persisted as a small JSON file so "is this pattern new?" can be
+/// answered across runs.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PatternHistory {
+    /// template → first_seen_ms
+    patterns: HashMap<String, u64>,
+}
+
+impl PatternHistory {
+    /// History file for a process: ~/.velos/patterns/<name>.json
+    pub fn default_path(process_name: &str) -> PathBuf {
+        let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
+        let file = format!("{}.json", process_name.replace(['/', '\\'], "_"));
+        PathBuf::from(home)
+            .join(".velos")
+            .join("patterns")
+            .join(file)
+    }
+
+    /// Load a history file. A missing or unreadable file starts an empty
+    /// history: losing it only means patterns are reported as new once more.
+    pub fn load(path: &Path) -> Self {
+        std::fs::read(path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_vec(self).map_err(io::Error::other)?;
+        std::fs::write(path, data)
+    }
+
+    pub fn contains(&self, template: &str) -> bool {
+        self.patterns.contains_key(template)
+    }
+
+    pub fn first_seen_ms(&self, template: &str) -> Option<u64> {
+        self.patterns.get(template).copied()
+    }
+
+    /// Remember `template`, keeping the earliest first-seen time.
+    pub fn record(&mut self, template: &str, seen_ms: u64) {
+        self.patterns
+            .entry(template.to_string())
+            .and_modify(|t| *t = (*t).min(seen_ms))
+            .or_insert(seen_ms);
+    }
+
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_roundtrip() {
+        let path = std::env::temp_dir()
+            .join(format!("velos_history_{}", std::process::id()))
+            .join("api.json");
+        let _ = std::fs::remove_file(&path);
+        assert!(PatternHistory::load(&path).is_empty());
+
+        let mut history = PatternHistory::default();
+        history.record("Connection to <IP> failed", 2000);
+        history.record("Connection to <IP> failed", 1000);
+        history.record("Timeout after <NUM>ms", 5000);
+        history.save(&path).unwrap();
+
+        let loaded = PatternHistory::load(&path);
+        assert_eq!(loaded, history);
+        assert_eq!(
+            loaded.first_seen_ms("Connection to <IP> failed"),
+            Some(1000)
+        );
+        assert_eq!(loaded.len(), 2);
+
+        // Corrupt file: start over rather than fail
+        std::fs::write(&path, b"{not json").unwrap();
+        assert!(PatternHistory::load(&path).is_empty());
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
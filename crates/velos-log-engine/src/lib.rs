@@ -1,14 +1,19 @@
+pub mod aggregate;
 pub mod anomaly;
 pub mod classifier;
 pub mod dedup;
+pub mod extract;
+pub mod filter;
 pub mod format;
+pub mod history;
 pub mod pattern;
+pub mod sink;
 pub mod summary;
 
 use velos_core::LogEntry;
 
 /// Log level classification.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize)]
 pub enum LogLevel {
     Debug = 0,
     Info = 1,
@@ -28,6 +33,36 @@ impl LogLevel {
         }
     }
 
+    /// Parse a level name as typed by a user: "warn"/"warning", "error"/"err",
+    /// or a syslog severity 0-7 (see [`velos_core::level::from_name`]).
+    pub fn parse(name: &str) -> Option<Self> {
+        velos_core::level::from_name(name).map(Self::from_u8)
+    }
+
+    /// Parse a comma-separated level filter ("error,warn", "3,4"). `None`
+    /// means no filtering: the list contains `all`. Unknown names are skipped.
+    pub fn parse_list(spec: &str) -> Option<Vec<Self>> {
+        let names: Vec<&str> = spec.split(',').map(str::trim).collect();
+        if names.iter().any(|n| n.eq_ignore_ascii_case("all")) {
+            return None;
+        }
+        Some(names.into_iter().filter_map(Self::parse).collect())
+    }
+
+    /// This level and every more severe one (`Warn` → warn, error, fatal).
+    pub fn and_above(self) -> Vec<Self> {
+        [
+            Self::Debug,
+            Self::Info,
+            Self::Warn,
+            Self::Error,
+            Self::Fatal,
+        ]
+        .into_iter()
+        .filter(|l| *l >= self)
+        .collect()
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::Debug => "debug",
@@ -46,6 +81,7 @@ pub struct ProcessedEntry {
     pub level: LogLevel,
     pub stream: u8,
     pub message: String,
+    pub seq: u64,
 }
 
 impl ProcessedEntry {
@@ -55,10 +91,27 @@ impl ProcessedEntry {
             level,
             stream: entry.stream,
             message: entry.message.clone(),
+            seq: entry.seq,
         }
     }
 }
 
+/// Whether `entries` are in non-decreasing timestamp order.
+pub fn is_time_ordered(entries: &[ProcessedEntry]) -> bool {
+    entries
+        .windows(2)
+        .all(|w| w[0].timestamp_ms <= w[1].timestamp_ms)
+}
+
+/// Order entries by timestamp. The daemon returns lines in arrival order,
+/// which is not guaranteed to be time order; the sort is stable, so lines
+/// sharing a timestamp keep that order.
+pub fn sort_entries_stable(entries: &mut [ProcessedEntry]) {
+    if !is_time_ordered(entries) {
+        entries.sort_by_key(|e| e.timestamp_ms);
+    }
+}
+
 /// Pipeline stage trait.
 pub trait LogProcessor {
     fn process(&mut self, entries: &[ProcessedEntry]) -> Vec<ProcessedEntry>;
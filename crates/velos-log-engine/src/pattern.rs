@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use crate::history::PatternHistory;
 use crate::{LogLevel, ProcessedEntry};
 
 /// Detected trend direction.
@@ -18,6 +19,21 @@ impl Trend {
             Self::Declining => "declining",
         }
     }
+
+    /// Parse "rising", "stable" or "declining".
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "rising" => Some(Self::Rising),
+            "stable" => Some(Self::Stable),
+            "declining" => Some(Self::Declining),
+            _ => None,
+        }
+    }
+}
+
+/// Keep only the patterns moving in `trend`, preserving their order.
+pub fn filter_by_trend(patterns: Vec<DetectedPattern>, trend: Trend) -> Vec<DetectedPattern> {
+    patterns.into_iter().filter(|p| p.trend == trend).collect()
 }
 
 /// A detected recurring log pattern.
@@ -29,14 +45,26 @@ pub struct DetectedPattern {
     pub first_seen_ms: u64,
     pub last_seen_ms: u64,
     pub trend: Trend,
+    /// Not in the pattern history before this detection run.
+    pub is_new: bool,
 }
 
 /// Pattern detector: identifies recurring log message patterns.
 pub struct PatternDetector {
     min_frequency: u32,
     time_window_ms: u64,
+    /// Template with [`normalize_aggressive`](crate::dedup::normalize_aggressive)
+    /// and merge templates one bare word apart (see [`merge_bare_tokens`]).
+    aggressive: bool,
 }
 
+/// Wildcard for the one word a merged template varies in.
+const VARIABLE_TOKEN: &str = "<*>";
+
+/// Words a template must keep besides the varying one to be merged, so
+/// "disk quota exceeded" and "disk quota ok" stay apart.
+const MIN_SHARED_TOKENS: usize = 3;
+
 struct PatternBucket {
     template: String,
     count: u32,
@@ -53,6 +81,7 @@ impl PatternDetector {
         Self {
             min_frequency,
             time_window_ms: time_window_secs * 1000,
+            aggressive: false,
         }
     }
 
@@ -61,13 +90,37 @@ impl PatternDetector {
         Self::new(5, 300)
     }
 
+    /// Defaults, but quoted strings, emails and file paths are masked too,
+    /// and templates differing in one bare word are merged, so
+    /// "user alice logged in" and "user bob logged in" share the pattern
+    /// "user <*> logged in".
+    pub fn with_aggressive() -> Self {
+        Self {
+            aggressive: true,
+            ..Self::with_defaults()
+        }
+    }
+
     /// Detect patterns from a batch of entries.
     /// Returns patterns sorted by frequency descending.
+    ///
+    /// The window ends at the newest entry, so an out-of-order batch is
+    /// sorted (on a copy) before anything is counted.
     pub fn detect(&self, entries: &[ProcessedEntry]) -> Vec<DetectedPattern> {
         if entries.is_empty() {
             return Vec::new();
         }
 
+        let sorted;
+        let entries = if crate::is_time_ordered(entries) {
+            entries
+        } else {
+            let mut copy = entries.to_vec();
+            crate::sort_entries_stable(&mut copy);
+            sorted = copy;
+            &sorted
+        };
+
         let now_ms = entries.last().map(|e| e.timestamp_ms).unwrap_or(0);
         let window_start = now_ms.saturating_sub(self.time_window_ms);
         let midpoint = window_start + self.time_window_ms / 2;
@@ -79,7 +132,11 @@ impl PatternDetector {
                 continue;
             }
 
-            let normalized = crate::dedup::normalize(&entry.message);
+            let normalized = if self.aggressive {
+                crate::dedup::normalize_aggressive(&entry.message)
+            } else {
+                crate::dedup::normalize(&entry.message)
+            };
             let hash = {
                 use std::hash::{Hash, Hasher};
                 let mut h = std::collections::hash_map::DefaultHasher::new();
@@ -110,8 +167,13 @@ impl PatternDetector {
             }
         }
 
+        let mut buckets: Vec<PatternBucket> = buckets.into_values().collect();
+        if self.aggressive {
+            buckets = merge_bare_tokens(buckets);
+        }
+
         let mut patterns: Vec<DetectedPattern> = buckets
-            .into_values()
+            .into_iter()
             .filter(|b| b.count >= self.min_frequency)
             .map(|b| {
                 let trend = detect_trend(b.first_half_count, b.second_half_count);
@@ -122,11 +184,27 @@ impl PatternDetector {
                     first_seen_ms: b.first_seen_ms,
                     last_seen_ms: b.last_seen_ms,
                     trend,
+                    is_new: false,
                 }
             })
             .collect();
 
-        patterns.sort_by(|a, b| b.frequency.cmp(&a.frequency));
+        patterns.sort_by_key(|p| std::cmp::Reverse(p.frequency));
+        patterns
+    }
+
+    /// Detect patterns, flag the ones `history` has never seen as `is_new`,
+    /// then record all of them in `history`.
+    pub fn detect_with_history(
+        &self,
+        entries: &[ProcessedEntry],
+        history: &mut PatternHistory,
+    ) -> Vec<DetectedPattern> {
+        let mut patterns = self.detect(entries);
+        for p in &mut patterns {
+            p.is_new = !history.contains(&p.template);
+            history.record(&p.template, p.first_seen_ms);
+        }
         patterns
     }
 
@@ -138,6 +216,64 @@ impl PatternDetector {
     }
 }
 
+/// Merge buckets whose templates have the same number of words and differ
+/// in exactly one, which becomes [`VARIABLE_TOKEN`]. A merged template
+/// varies in that one position only; a line differing elsewhere starts or
+/// joins another bucket.
+fn merge_bare_tokens(mut buckets: Vec<PatternBucket>) -> Vec<PatternBucket> {
+    // Oldest first, so the result doesn't depend on hash order
+    buckets.sort_by(|a, b| (a.first_seen_ms, &a.template).cmp(&(b.first_seen_ms, &b.template)));
+
+    let mut merged: Vec<PatternBucket> = Vec::new();
+    // The shape each merged bucket is committed to once it absorbed another
+    let mut committed: Vec<Option<String>> = Vec::new();
+    let mut by_shape: HashMap<String, usize> = HashMap::new();
+
+    for bucket in buckets {
+        let tokens: Vec<&str> = bucket.template.split(' ').collect();
+        let shapes: Vec<String> = if tokens.len() > MIN_SHARED_TOKENS {
+            (0..tokens.len())
+                .map(|i| {
+                    let mut shape = tokens.clone();
+                    shape[i] = VARIABLE_TOKEN;
+                    shape.join(" ")
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let target = shapes.iter().find_map(|shape| {
+            let &idx = by_shape.get(shape)?;
+            match &committed[idx] {
+                Some(c) if c != shape => None,
+                _ => Some((idx, shape)),
+            }
+        });
+        if let Some((idx, shape)) = target {
+            let into = &mut merged[idx];
+            into.template = shape.clone();
+            into.count += bucket.count;
+            into.first_seen_ms = into.first_seen_ms.min(bucket.first_seen_ms);
+            into.last_seen_ms = into.last_seen_ms.max(bucket.last_seen_ms);
+            if (bucket.level as u8) > (into.level as u8) {
+                into.level = bucket.level;
+            }
+            into.first_half_count += bucket.first_half_count;
+            into.second_half_count += bucket.second_half_count;
+            committed[idx] = Some(shape.clone());
+            continue;
+        }
+
+        for shape in shapes {
+            by_shape.entry(shape).or_insert(merged.len());
+        }
+        merged.push(bucket);
+        committed.push(None);
+    }
+    merged
+}
+
 fn detect_trend(first_half: u32, second_half: u32) -> Trend {
     if first_half == 0 && second_half == 0 {
         return Trend::Stable;
@@ -166,9 +302,45 @@ mod tests {
             level: LogLevel::Error,
             stream: 0,
             message: msg.to_string(),
+            seq: 0,
         }
     }
 
+    #[test]
+    fn test_detect_unsorted_batch_matches_sorted() {
+        // 20 minutes of the same error; the window only covers the last 5.
+        let mut sorted: Vec<ProcessedEntry> = (0..20)
+            .map(|i| make_entry(&format!("Timeout after {i}ms"), i * 60_000))
+            .collect();
+        let mut unsorted = sorted.clone();
+        unsorted.swap(0, 19);
+        assert!(!crate::is_time_ordered(&unsorted));
+
+        let detector = PatternDetector::new(3, 300);
+        let expected = detector.detect(&sorted);
+        assert_eq!(expected.len(), 1);
+        assert_eq!(expected[0].frequency, 6);
+        assert_eq!(expected[0].first_seen_ms, 14 * 60_000);
+        assert_eq!(expected[0].last_seen_ms, 19 * 60_000);
+
+        let got = detector.detect(&unsorted);
+        assert_eq!(got[0].frequency, expected[0].frequency);
+        assert_eq!(got[0].first_seen_ms, expected[0].first_seen_ms);
+        assert_eq!(got[0].last_seen_ms, expected[0].last_seen_ms);
+        assert_eq!(got[0].trend, expected[0].trend);
+
+        crate::sort_entries_stable(&mut unsorted);
+        assert!(crate::is_time_ordered(&unsorted));
+        assert_eq!(detector.detect(&unsorted)[0].first_seen_ms, 14 * 60_000);
+
+        // Stable: lines sharing a timestamp keep their arrival order
+        sorted.push(make_entry("b", 0));
+        sorted.push(make_entry("a", 0));
+        crate::sort_entries_stable(&mut sorted);
+        assert_eq!(sorted[1].message, "b");
+        assert_eq!(sorted[2].message, "a");
+    }
+
     #[test]
     fn test_detect_patterns() {
         let detector = PatternDetector::new(3, 60);
@@ -186,6 +358,53 @@ mod tests {
         assert!(patterns[0].template.contains("<IP>"));
     }
 
+    #[test]
+    fn test_aggressive_collapses_varied_names() {
+        let mut messages = Vec::new();
+        for name in ["alice", "bob", "carol", "dave", "erin"] {
+            messages.push(format!("user {name} logged in"));
+            messages.push(format!("key '{name}' loaded from /home/{name}/.ssh/id_rsa"));
+            messages.push(format!("mail to {name}@example.com bounced"));
+        }
+        for _ in 0..5 {
+            messages.push("user alice logged out".to_string());
+            messages.push("disk quota exceeded".to_string());
+            messages.push("disk quota ok".to_string());
+            messages.push("GET https://example.com/api/v1 took 3/5 retries".to_string());
+        }
+        let entries: Vec<ProcessedEntry> = messages
+            .iter()
+            .enumerate()
+            .map(|(i, m)| make_entry(m, 1000 + i as u64 * 100))
+            .collect();
+
+        let plain: Vec<String> = PatternDetector::with_defaults()
+            .detect(&entries)
+            .into_iter()
+            .map(|p| p.template)
+            .collect();
+        assert_eq!(plain.len(), 4, "{plain:?}");
+
+        let mut templates: Vec<(String, u32)> = PatternDetector::with_aggressive()
+            .detect(&entries)
+            .into_iter()
+            .map(|p| (p.template, p.frequency))
+            .collect();
+        templates.sort();
+        let expected = [
+            ("GET https://example.com/api/v1 took 3/5 retries", 5),
+            ("disk quota exceeded", 5),
+            ("disk quota ok", 5),
+            ("key <STR> loaded from <PATH>", 5),
+            ("mail to <EMAIL> bounced", 5),
+            ("user <*> logged in", 5),
+            ("user alice logged out", 5),
+        ];
+        let expected: Vec<(String, u32)> =
+            expected.iter().map(|(t, n)| (t.to_string(), *n)).collect();
+        assert_eq!(templates, expected);
+    }
+
     #[test]
     fn test_min_frequency_filter() {
         let detector = PatternDetector::new(5, 60);
@@ -221,6 +440,64 @@ mod tests {
         assert_eq!(patterns[0].trend, Trend::Declining);
     }
 
+    #[test]
+    fn test_filter_by_trend_keeps_rising() {
+        let detector = PatternDetector::new(2, 100);
+        let mut entries: Vec<ProcessedEntry> = Vec::new();
+        for i in 0..10 {
+            entries.push(make_entry("new error", 80000 + i * 1000));
+            entries.push(make_entry("old error", 10000 + i * 1000));
+        }
+        for i in 0..20 {
+            entries.push(make_entry("steady error", i * 5000));
+        }
+
+        let patterns = detector.detect(&entries);
+        let trends: Vec<Trend> = patterns.iter().map(|p| p.trend).collect();
+        assert!(trends.contains(&Trend::Declining) && trends.contains(&Trend::Stable));
+
+        let rising = filter_by_trend(patterns, Trend::Rising);
+        assert_eq!(rising.len(), 1);
+        assert_eq!(rising[0].template, "new error");
+
+        assert_eq!(Trend::parse("Rising"), Some(Trend::Rising));
+        assert_eq!(Trend::parse("up"), None);
+    }
+
+    #[test]
+    fn test_detect_with_history_flags_new() {
+        let detector = PatternDetector::new(2, 60);
+        let mut history = PatternHistory::default();
+        history.record("Connection to <IP> failed", 500);
+
+        let mut entries = Vec::new();
+        for i in 0..3 {
+            entries.push(make_entry(
+                &format!("Connection to 10.0.0.{i}:5432 failed"),
+                1000 + i * 100,
+            ));
+            entries.push(make_entry("disk quota exceeded", 1050 + i * 100));
+        }
+
+        let patterns = detector.detect_with_history(&entries, &mut history);
+        assert_eq!(patterns.len(), 2);
+        let new: Vec<&str> = patterns
+            .iter()
+            .filter(|p| p.is_new)
+            .map(|p| p.template.as_str())
+            .collect();
+        assert_eq!(new, vec!["disk quota exceeded"]);
+
+        // Recorded now: a second run reports nothing new, and the seeded
+        // first-seen time is kept
+        let again = detector.detect_with_history(&entries, &mut history);
+        assert!(again.iter().all(|p| !p.is_new));
+        assert_eq!(
+            history.first_seen_ms("Connection to <IP> failed"),
+            Some(500)
+        );
+    }
+
     #[test]
     fn test_top_n() {
         let detector = PatternDetector::new(1, 60);
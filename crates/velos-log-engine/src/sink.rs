@@ -0,0 +1,268 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use velos_config::{parse_memory_string, ConfigError};
+
+use crate::{LogLevel, ProcessedEntry};
+
+/// Default size cap, matching the daemon's `log_max_size`.
+pub const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+/// Default number of rotated files, matching the daemon's `log_retain_count`.
+pub const DEFAULT_RETAIN_COUNT: u32 = 30;
+
+/// Append-only line writer with size-based rotation.
+///
+/// Uses the same layout as the daemon's log writer: the live file is `path`,
+/// rotated files are `path.1` (newest) through `path.N` (oldest).
+///
+/// Only for files the CLI owns (`logs --output-dir`). The daemon keeps its
+/// own log files open, so renaming them from here would leave it writing
+/// into `path.1`; `velos flush` truncates those in place instead.
+pub struct RotatingFileSink {
+    path: PathBuf,
+    max_bytes: u64,
+    retain_count: u32,
+    file: Option<File>,
+    size: u64,
+}
+
+impl RotatingFileSink {
+    /// `max_bytes == 0` disables rotation.
+    pub fn new(path: impl Into<PathBuf>, max_bytes: u64, retain_count: u32) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes,
+            retain_count,
+            file: None,
+            size: 0,
+        }
+    }
+
+    /// Build from app config values (`log_max_size` like "10M", `log_retain_count`).
+    pub fn from_config(
+        path: impl Into<PathBuf>,
+        log_max_size: Option<&str>,
+        log_retain_count: u32,
+    ) -> Result<Self, ConfigError> {
+        let max_bytes = match log_max_size {
+            Some(s) => parse_memory_string(s)?,
+            None => DEFAULT_MAX_BYTES,
+        };
+        Ok(Self::new(path, max_bytes, log_retain_count))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append one line (a trailing newline is added), rotating first if the
+    /// line would push the live file past the cap.
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.file.is_none() {
+            self.open()?;
+        }
+        if self.max_bytes > 0 && self.size > 0 && self.size + len > self.max_bytes {
+            self.rotate()?;
+        }
+        let file = self.file.as_mut().expect("sink file opened above");
+        file.write_all(line.as_bytes())?;
+        file.write_all(b"\n")?;
+        self.size += len;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self.file.as_mut() {
+            Some(f) => f.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Force a rotation: live → .1, .1 → .2, ... dropping anything past the
+    /// retention count. With `retain_count == 0` the live file is truncated.
+    pub fn rotate(&mut self) -> io::Result<()> {
+        self.file = None;
+        if self.retain_count > 0 {
+            self.prune(self.retain_count)?;
+            for i in (1..self.retain_count).rev() {
+                let from = self.rotated_path(i);
+                if from.exists() {
+                    std::fs::rename(&from, self.rotated_path(i + 1))?;
+                }
+            }
+            if self.path.exists() {
+                std::fs::rename(&self.path, self.rotated_path(1))?;
+            }
+        }
+        self.file = Some(
+            OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(true)
+                .open(&self.path)?,
+        );
+        self.size = 0;
+        Ok(())
+    }
+
+    fn open(&mut self) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = file.metadata()?.len();
+        self.file = Some(file);
+        Ok(())
+    }
+
+    /// Remove rotated files numbered `from` and above (oldest first to go).
+    fn prune(&self, from: u32) -> io::Result<()> {
+        let mut i = from;
+        loop {
+            let p = self.rotated_path(i);
+            if !p.exists() {
+                return Ok(());
+            }
+            std::fs::remove_file(p)?;
+            i += 1;
+        }
+    }
+
+    fn rotated_path(&self, n: u32) -> PathBuf {
+        let mut s = self.path.clone().into_os_string();
+        s.push(format!(".{n}"));
+        PathBuf::from(s)
+    }
+}
+
+/// Append `entries` to one file per level under `dir` (`error.log`,
+/// `warn.log`, ...), creating it first. Levels with no entries get no file.
+/// Each file rotates like a daemon log at the default size cap, so repeated
+/// exports into the same directory stay bounded.
+///
+/// Returns the files written with their line counts, most severe first.
+pub fn write_by_level(
+    dir: &Path,
+    entries: &[ProcessedEntry],
+    render: impl Fn(&ProcessedEntry) -> String,
+) -> io::Result<Vec<(PathBuf, usize)>> {
+    std::fs::create_dir_all(dir)?;
+    let mut written = Vec::new();
+    for level in LogLevel::Debug.and_above().into_iter().rev() {
+        let mut lines = entries.iter().filter(|e| e.level == level).peekable();
+        if lines.peek().is_none() {
+            continue;
+        }
+        let path = dir.join(format!("{}.log", level.as_str()));
+        let mut sink = RotatingFileSink::new(&path, DEFAULT_MAX_BYTES, DEFAULT_RETAIN_COUNT);
+        let mut count = 0;
+        for e in lines {
+            sink.write_line(&render(e))?;
+            count += 1;
+        }
+        sink.flush()?;
+        written.push((path, count));
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("velos_sink_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn rotates_at_cap() {
+        let dir = temp_dir("cap");
+        let path = dir.join("app-out.log");
+        // Each line is 10 bytes with its newline; cap fits exactly 3.
+        let mut sink = RotatingFileSink::new(&path, 30, 5);
+        for i in 0..3 {
+            sink.write_line(&format!("line {i:04}")).unwrap();
+        }
+        sink.flush().unwrap();
+        assert!(!dir.join("app-out.log.1").exists());
+
+        sink.write_line("line 0003").unwrap();
+        sink.flush().unwrap();
+        let rotated = std::fs::read_to_string(dir.join("app-out.log.1")).unwrap();
+        assert_eq!(rotated.lines().count(), 3);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "line 0003\n");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn retention_prunes_oldest() {
+        let dir = temp_dir("retain");
+        let path = dir.join("app-out.log");
+        let mut sink = RotatingFileSink::new(&path, 10, 2);
+        // One 10-byte line per file: every write after the first rotates.
+        for i in 0..5 {
+            sink.write_line(&format!("line {i:04}")).unwrap();
+        }
+        sink.flush().unwrap();
+
+        let read = |p: PathBuf| std::fs::read_to_string(p).unwrap();
+        assert_eq!(read(path.clone()), "line 0004\n");
+        assert_eq!(read(dir.join("app-out.log.1")), "line 0003\n");
+        assert_eq!(read(dir.join("app-out.log.2")), "line 0002\n");
+        assert!(!dir.join("app-out.log.3").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn writes_one_file_per_level() {
+        let dir = temp_dir("by_level").join("archive");
+        let entry = |level: LogLevel, message: &str| ProcessedEntry {
+            timestamp_ms: 0,
+            level,
+            stream: 0,
+            message: message.into(),
+            seq: 0,
+        };
+        let entries = [
+            entry(LogLevel::Info, "started"),
+            entry(LogLevel::Error, "db down"),
+            entry(LogLevel::Warn, "slow query"),
+            entry(LogLevel::Error, "db still down"),
+            entry(LogLevel::Info, "retrying"),
+            entry(LogLevel::Info, "ok"),
+        ];
+
+        let written = write_by_level(&dir, &entries, |e| e.message.clone()).unwrap();
+        let files: Vec<_> = written
+            .iter()
+            .map(|(p, n)| (p.file_name().unwrap().to_str().unwrap(), *n))
+            .collect();
+        assert_eq!(files, [("error.log", 2), ("warn.log", 1), ("info.log", 3)]);
+
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(read("error.log"), "db down\ndb still down\n");
+        assert_eq!(read("info.log").lines().count(), 3);
+        assert!(!dir.join("debug.log").exists() && !dir.join("fatal.log").exists());
+
+        let _ = std::fs::remove_dir_all(dir.parent().unwrap());
+    }
+
+    #[test]
+    fn from_config_parses_size() {
+        let sink = RotatingFileSink::from_config("/tmp/x.log", Some("1K"), 3).unwrap();
+        assert_eq!(sink.max_bytes, 1024);
+        assert!(RotatingFileSink::from_config("/tmp/x.log", Some("big"), 3).is_err());
+        let sink = RotatingFileSink::from_config("/tmp/x.log", None, 3).unwrap();
+        assert_eq!(sink.max_bytes, DEFAULT_MAX_BYTES);
+    }
+}
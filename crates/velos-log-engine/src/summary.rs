@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 
-use crate::anomaly::Anomaly;
+use velos_core::protocol::ExitReason;
+
+use crate::anomaly::{Anomaly, AnomalyMethod};
 use crate::pattern::DetectedPattern;
 use crate::{LogLevel, ProcessedEntry};
 
@@ -17,6 +19,17 @@ pub struct LogSummary {
     pub last_error: Option<String>,
     pub last_error_ms: Option<u64>,
     pub health_score: u8,
+    /// Error-level pattern templates not seen in previous runs.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub new_error_patterns: Vec<String>,
+    /// Lines the daemon dropped under `log_rate_limit`. Not known from the
+    /// entries alone, so callers fill it in from the process detail.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub suppressed_lines: u64,
+}
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
 }
 
 /// Compact pattern info for summary output.
@@ -25,6 +38,8 @@ pub struct PatternSummary {
     pub template: String,
     pub count: u32,
     pub trend: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_new: bool,
 }
 
 impl From<&DetectedPattern> for PatternSummary {
@@ -33,6 +48,7 @@ impl From<&DetectedPattern> for PatternSummary {
             template: p.template.clone(),
             count: p.frequency,
             trend: p.trend.as_str().to_string(),
+            is_new: p.is_new,
         }
     }
 }
@@ -44,6 +60,7 @@ pub fn generate_summary(
     patterns: &[DetectedPattern],
     anomalies: &[Anomaly],
     restart_count: u32,
+    last_exit_reason: ExitReason,
 ) -> LogSummary {
     let total_lines = entries.len() as u64;
 
@@ -76,12 +93,18 @@ pub fn generate_summary(
     }
 
     let top_patterns: Vec<PatternSummary> = patterns.iter().take(5).map(|p| p.into()).collect();
+    let new_error_patterns: Vec<String> = patterns
+        .iter()
+        .filter(|p| p.is_new && p.level >= LogLevel::Error)
+        .map(|p| p.template.clone())
+        .collect();
 
     let health_score = compute_health_score(
         by_level.get("error").copied().unwrap_or(0) + by_level.get("fatal").copied().unwrap_or(0),
         anomalies.len() as u64,
         restart_count as u64,
-    );
+    )
+    .saturating_sub(exit_reason_penalty(last_exit_reason));
 
     LogSummary {
         process_name: process_name.to_string(),
@@ -94,6 +117,8 @@ pub fn generate_summary(
         last_error,
         last_error_ms,
         health_score,
+        new_error_patterns,
+        suppressed_lines: 0,
     }
 }
 
@@ -103,6 +128,16 @@ fn compute_health_score(error_count: u64, anomaly_count: u64, restart_count: u64
     100u8.saturating_sub(penalty.min(100) as u8)
 }
 
+/// Extra penalty for how the process last went down. A restart requested by
+/// the operator is free; being killed for a resource limit is the most telling.
+fn exit_reason_penalty(reason: ExitReason) -> u8 {
+    match reason {
+        ExitReason::None | ExitReason::Manual => 0,
+        ExitReason::Crashed | ExitReason::Signal => 10,
+        ExitReason::Oom | ExitReason::Cpu => 20,
+    }
+}
+
 /// Format summary for terminal display.
 pub fn format_summary(s: &LogSummary) -> String {
     let mut out = String::new();
@@ -120,20 +155,34 @@ pub fn format_summary(s: &LogSummary) -> String {
         "Lines: {} | Errors: {} | Warnings: {}\n",
         s.total_lines, errors, warnings
     ));
+    if s.suppressed_lines > 0 {
+        out.push_str(&format!(
+            "Suppressed: {} line(s) dropped by log_rate_limit\n",
+            s.suppressed_lines
+        ));
+    }
 
     if !s.top_patterns.is_empty() {
         out.push_str("Top patterns:\n");
         for (i, p) in s.top_patterns.iter().enumerate() {
             out.push_str(&format!(
-                "  {}. \"{}\" (x{}, trend: {})\n",
+                "  {}. \"{}\" (x{}, trend: {}){}\n",
                 i + 1,
                 truncate(&p.template, 60),
                 p.count,
-                p.trend
+                p.trend,
+                if p.is_new { " [new]" } else { "" }
             ));
         }
     }
 
+    for t in &s.new_error_patterns {
+        out.push_str(&format!(
+            "New error pattern detected: \"{}\"\n",
+            truncate(t, 60)
+        ));
+    }
+
     if let Some(ref err) = s.last_error {
         let ago = if let Some(ts) = s.last_error_ms {
             let diff = s.period_end_ms.saturating_sub(ts);
@@ -150,19 +199,148 @@ pub fn format_summary(s: &LogSummary) -> String {
 
     for a in &s.anomalies {
         out.push_str(&format!(
-            "Anomaly: {} {:.1}\u{03c3} above normal ({:.1}/min vs avg {:.1}/min)\n",
-            a.metric, a.sigma, a.current_value, a.mean
+            "Anomaly: {} {} above normal ({:.1}{per} vs avg {:.1}{per})\n",
+            a.metric,
+            anomaly_distance(a),
+            a.current_value,
+            a.mean,
+            per = per_bucket(a.bucket_secs)
         ));
     }
 
     out
 }
 
-fn truncate(s: &str, max: usize) -> String {
-    if s.len() <= max {
-        s.to_string()
+/// Format summary as a Markdown block for issues, chat and dashboards.
+pub fn format_summary_markdown(s: &LogSummary) -> String {
+    let mut out = format!("## Log summary: {}\n\n", s.process_name);
+
+    let errors = s.by_level.get("error").copied().unwrap_or(0)
+        + s.by_level.get("fatal").copied().unwrap_or(0);
+    let warnings = s.by_level.get("warn").copied().unwrap_or(0);
+    out.push_str(&format!(
+        "- Health: **{}/100**\n- Period: {}\n- Lines: {} ({} errors, {} warnings)\n",
+        s.health_score,
+        format_period(s.period_start_ms, s.period_end_ms),
+        s.total_lines,
+        errors,
+        warnings
+    ));
+    if s.suppressed_lines > 0 {
+        out.push_str(&format!(
+            "- Suppressed: {} line(s) dropped by log_rate_limit\n",
+            s.suppressed_lines
+        ));
+    }
+    if let Some(ref err) = s.last_error {
+        out.push_str(&format!("- Last error: `{}`\n", markdown_cell(err)));
+    }
+
+    out.push_str("\n### Top patterns\n\n");
+    if s.top_patterns.is_empty() {
+        out.push_str("None.\n");
     } else {
-        format!("{}...", &s[..max - 3])
+        out.push_str("| Pattern | Count | Trend |\n| --- | ---: | --- |\n");
+        for p in &s.top_patterns {
+            out.push_str(&format!(
+                "| `{}` | {} | {}{} |\n",
+                markdown_cell(&p.template),
+                p.count,
+                p.trend,
+                if p.is_new { " (new)" } else { "" }
+            ));
+        }
+    }
+
+    out.push_str("\n### Anomalies\n\n");
+    if s.anomalies.is_empty() {
+        out.push_str("None.\n");
+    }
+    for a in &s.anomalies {
+        out.push_str(&format!(
+            "- **{}** {}: {} above normal ({:.1}{per} vs avg {:.1}{per})\n",
+            a.metric,
+            a.severity.as_str(),
+            anomaly_distance(a),
+            a.current_value,
+            a.mean,
+            per = per_bucket(a.bucket_secs)
+        ));
+    }
+    out
+}
+
+/// Format summary as one line of compact JSON with the `--ai` key
+/// abbreviations (see commands/mod.rs in velos-cli).
+pub fn format_summary_ai(s: &LogSummary) -> String {
+    let patterns: Vec<_> = s
+        .top_patterns
+        .iter()
+        .map(|p| serde_json::json!({ "tp": p.template, "f": p.count, "tr": p.trend }))
+        .collect();
+    let anomalies: Vec<_> = s
+        .anomalies
+        .iter()
+        .map(|a| {
+            serde_json::json!({
+                "mt": a.metric,
+                "v": a.current_value,
+                "sv": a.severity.as_str(),
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "n": s.process_name,
+        "h": s.health_score,
+        "t": s.period_end_ms,
+        "g": s.total_lines,
+        "lv": s.by_level,
+        "pt": patterns,
+        "an": anomalies,
+        "er": s.last_error,
+    })
+    .to_string()
+}
+
+/// "3.2σ", or "4.0×IQR" for IQR-detected anomalies.
+fn anomaly_distance(a: &Anomaly) -> String {
+    match a.method {
+        AnomalyMethod::Sigma => format!("{:.1}\u{03c3}", a.sigma),
+        AnomalyMethod::Iqr => format!("{:.1}\u{00d7}IQR", a.sigma),
+    }
+}
+
+/// Rate unit for an anomaly's counts: "/min" for the default one-minute
+/// buckets, "/10s" for others.
+fn per_bucket(bucket_secs: u64) -> String {
+    if bucket_secs == 60 {
+        "/min".to_string()
+    } else {
+        format!("/{bucket_secs}s")
+    }
+}
+
+/// Keep a value on one table row: pipes escaped, newlines flattened, and
+/// no backticks to break the code span it sits in.
+fn markdown_cell(s: &str) -> String {
+    truncate(s, 80)
+        .replace('|', "\\|")
+        .replace('`', "'")
+        .replace('\n', " ")
+}
+
+/// Cut `s` to at most `max` characters, ending in "..." when cut. Counts
+/// characters, not bytes, so a multi-byte character is never split.
+fn truncate(s: &str, max: usize) -> String {
+    match s.char_indices().nth(max) {
+        None => s.to_string(),
+        Some(_) => {
+            let cut = s
+                .char_indices()
+                .nth(max.saturating_sub(3))
+                .map_or(s.len(), |(i, _)| i);
+            format!("{}...", &s[..cut])
+        }
     }
 }
 
@@ -218,27 +396,47 @@ mod tests {
                 level: LogLevel::Info,
                 stream: 0,
                 message: "ok".into(),
+                seq: 0,
             },
             ProcessedEntry {
                 timestamp_ms: 2000,
                 level: LogLevel::Error,
                 stream: 0,
                 message: "db failed".into(),
+                seq: 0,
             },
             ProcessedEntry {
                 timestamp_ms: 3000,
                 level: LogLevel::Info,
                 stream: 0,
                 message: "recovered".into(),
+                seq: 0,
             },
         ];
-        let summary = generate_summary("test-app", &entries, &[], &[], 0);
+        let summary = generate_summary("test-app", &entries, &[], &[], 0, ExitReason::None);
         assert_eq!(summary.process_name, "test-app");
         assert_eq!(summary.total_lines, 3);
         assert_eq!(summary.last_error.as_deref(), Some("db failed"));
         assert_eq!(summary.health_score, 95); // 1 error * 5 = 5 penalty
     }
 
+    #[test]
+    fn test_health_score_oom_vs_restart() {
+        let entries = vec![ProcessedEntry {
+            timestamp_ms: 1000,
+            level: LogLevel::Info,
+            stream: 0,
+            message: "ok".into(),
+            seq: 0,
+        }];
+        let manual = generate_summary("app", &entries, &[], &[], 1, ExitReason::Manual);
+        let crashed = generate_summary("app", &entries, &[], &[], 1, ExitReason::Crashed);
+        let oom = generate_summary("app", &entries, &[], &[], 1, ExitReason::Oom);
+        assert_eq!(manual.health_score, 97); // 1 restart * 3
+        assert_eq!(crashed.health_score, 87);
+        assert_eq!(oom.health_score, 77);
+    }
+
     #[test]
     fn test_format_summary() {
         let summary = LogSummary {
@@ -254,10 +452,84 @@ mod tests {
             last_error: Some("connection refused".into()),
             last_error_ms: Some(3500000),
             health_score: 50,
+            new_error_patterns: vec!["disk quota exceeded".into()],
+            suppressed_lines: 1200,
         };
         let output = format_summary(&summary);
         assert!(output.contains("Health: 50/100"));
+        assert!(output.contains("Suppressed: 1200 line(s)"));
+        assert!(output.contains("New error pattern detected: \"disk quota exceeded\""));
         assert!(output.contains("Errors: 10"));
         assert!(output.contains("connection refused"));
     }
+
+    fn sample_summary() -> LogSummary {
+        LogSummary {
+            process_name: "api".into(),
+            period_start_ms: 0,
+            period_end_ms: 600_000,
+            total_lines: 40,
+            by_level: [("info".into(), 30), ("error".into(), 10)]
+                .into_iter()
+                .collect(),
+            top_patterns: vec![PatternSummary {
+                template: "GET /a|b failed after <N>ms".into(),
+                count: 10,
+                trend: "rising".into(),
+                is_new: true,
+            }],
+            anomalies: vec![Anomaly {
+                metric: "error_rate".into(),
+                current_value: 9.0,
+                mean: 0.5,
+                std_dev: 1.0,
+                sigma: 8.5,
+                timestamp_ms: 600_000,
+                severity: crate::anomaly::AnomalySeverity::Critical,
+                method: AnomalyMethod::Sigma,
+                bucket_secs: 60,
+            }],
+            last_error: Some("timeout".into()),
+            last_error_ms: Some(590_000),
+            health_score: 40,
+            new_error_patterns: vec![],
+            suppressed_lines: 0,
+        }
+    }
+
+    #[test]
+    fn test_format_summary_markdown() {
+        let md = format_summary_markdown(&sample_summary());
+        assert!(md.starts_with("## Log summary: api\n"));
+        assert!(md.contains("- Health: **40/100**"));
+        assert!(md.contains("| Pattern | Count | Trend |\n| --- | ---: | --- |\n"));
+        assert!(md.contains("| `GET /a\\|b failed after <N>ms` | 10 | rising (new) |"));
+        assert!(md.contains("- **error_rate** critical: 8.5\u{03c3} above normal"));
+
+        let mut quiet = sample_summary();
+        quiet.top_patterns.clear();
+        quiet.anomalies.clear();
+        let md = format_summary_markdown(&quiet);
+        assert!(!md.contains("| Pattern |"));
+        assert!(md.contains("### Anomalies\n\nNone.\n"));
+
+        // Long non-ASCII templates are cut on a character boundary
+        let cell = markdown_cell(&"ошибка ".repeat(20));
+        assert_eq!(cell.chars().count(), 80);
+        assert!(cell.ends_with("..."));
+        assert_eq!(truncate("héllo", 5), "héllo");
+    }
+
+    #[test]
+    fn test_format_summary_ai() {
+        let ai: serde_json::Value =
+            serde_json::from_str(&format_summary_ai(&sample_summary())).unwrap();
+        assert_eq!(ai["n"], "api");
+        assert_eq!(ai["h"], 40);
+        assert_eq!(ai["g"], 40);
+        assert_eq!(ai["lv"]["error"], 10);
+        assert_eq!(ai["pt"][0]["f"], 10);
+        assert_eq!(ai["an"][0]["sv"], "critical");
+        assert_eq!(ai["er"], "timeout");
+    }
 }
@@ -85,7 +85,7 @@ pub fn all_tools() -> Vec<ToolDefinition> {
                 "properties": {
                     "name_or_id": { "type": "string", "description": "Process name or numeric ID" },
                     "lines": { "type": "integer", "description": "Number of lines (default: 50)", "default": 50 },
-                    "level": { "type": "string", "description": "Filter by level: debug,info,warn,error,fatal (comma-separated)" }
+                    "level": { "type": "string", "description": "Filter by level: debug,info,warn,error,fatal or syslog 0-7 (comma-separated); 'all' for no filter" }
                 },
                 "required": ["name_or_id"]
             }),
@@ -100,7 +100,7 @@ pub fn all_tools() -> Vec<ToolDefinition> {
                     "pattern": { "type": "string", "description": "Regex pattern to search for" },
                     "since": { "type": "string", "description": "Start time (e.g. '1h', '30m', '2d')" },
                     "until": { "type": "string", "description": "End time" },
-                    "level": { "type": "string", "description": "Filter by level (comma-separated)" }
+                    "level": { "type": "string", "description": "Filter by level (comma-separated; syslog 0-7 and 'all' accepted)" }
                 },
                 "required": ["name_or_id", "pattern"]
             }),
@@ -1,6 +1,10 @@
 use serde_json::Value;
+use velos_client::resolve::{resolve_many, resolve_one};
 use velos_core::protocol::StartPayload;
 use velos_core::VelosError;
+use velos_log_engine::classifier::Classifier;
+use velos_log_engine::history::PatternHistory;
+use velos_log_engine::LogLevel;
 
 /// Execute an MCP tool by name.
 pub async fn execute(tool_name: &str, arguments: Value) -> Result<String, VelosError> {
@@ -40,19 +44,21 @@ async fn connect() -> Result<velos_client::VelosClient, VelosError> {
     velos_client::VelosClient::connect().await
 }
 
-async fn resolve_id(
-    client: &mut velos_client::VelosClient,
-    name_or_id: &str,
-) -> Result<u32, VelosError> {
-    if let Ok(id) = name_or_id.parse::<u32>() {
-        return Ok(id);
+/// The `[logs]` table of ./velos.toml. A broken table is reported on stderr
+/// and the built-in settings used instead.
+fn logs_config() -> Option<velos_config::LogEngineConfig> {
+    match velos_config::load_logs(std::path::Path::new("velos.toml")) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("[velos] Warning: ignoring [logs] in velos.toml: {e}");
+            None
+        }
     }
-    let procs = client.list().await?;
-    procs
-        .iter()
-        .find(|p| p.name == name_or_id)
-        .map(|p| p.id)
-        .ok_or_else(|| VelosError::ProcessNotFound(name_or_id.to_string()))
+}
+
+/// The `[logs]` classifier settings, or the built-in rules without them.
+fn classifier(config: Option<&velos_config::LogEngineConfig>) -> Classifier {
+    config.map_or_else(Classifier::with_defaults, Classifier::from_config)
 }
 
 // --- Process tools ---
@@ -117,6 +123,17 @@ async fn process_start(args: Value) -> Result<String, VelosError> {
         shutdown_with_message: false,
         instances: 1,
         env_vars: String::new(),
+        instance_name_template: String::new(),
+        out_log: String::new(),
+        err_log: String::new(),
+        merge_logs: false,
+        args: Vec::new(),
+        group: String::new(),
+        log_rate_limit: 0,
+        success_exit_codes: vec![0],
+        stop_on_success: false,
+        max_cpu_restart: 0.0,
+        cpu_restart_window_secs: 60,
     };
 
     let mut client = connect().await?;
@@ -133,8 +150,9 @@ async fn process_stop(args: Value) -> Result<String, VelosError> {
     let name_or_id = get_string(&args, "name_or_id")
         .ok_or_else(|| VelosError::ProtocolError("missing 'name_or_id'".into()))?;
     let mut client = connect().await?;
-    let id = resolve_id(&mut client, &name_or_id).await?;
-    client.stop(id).await?;
+    for id in resolve_many(&mut client, &name_or_id).await? {
+        client.stop(id).await?;
+    }
     Ok(
         serde_json::json!({"success": true, "message": format!("stopped {name_or_id}")})
             .to_string(),
@@ -145,8 +163,9 @@ async fn process_restart(args: Value) -> Result<String, VelosError> {
     let name_or_id = get_string(&args, "name_or_id")
         .ok_or_else(|| VelosError::ProtocolError("missing 'name_or_id'".into()))?;
     let mut client = connect().await?;
-    let id = resolve_id(&mut client, &name_or_id).await?;
-    client.restart(id).await?;
+    for id in resolve_many(&mut client, &name_or_id).await? {
+        client.restart(id).await?;
+    }
     Ok(
         serde_json::json!({"success": true, "message": format!("restarted {name_or_id}")})
             .to_string(),
@@ -157,8 +176,9 @@ async fn process_delete(args: Value) -> Result<String, VelosError> {
     let name_or_id = get_string(&args, "name_or_id")
         .ok_or_else(|| VelosError::ProtocolError("missing 'name_or_id'".into()))?;
     let mut client = connect().await?;
-    let id = resolve_id(&mut client, &name_or_id).await?;
-    client.delete(id).await?;
+    for id in resolve_many(&mut client, &name_or_id).await? {
+        client.delete(id).await?;
+    }
     Ok(
         serde_json::json!({"success": true, "message": format!("deleted {name_or_id}")})
             .to_string(),
@@ -169,7 +189,7 @@ async fn process_info(args: Value) -> Result<String, VelosError> {
     let name_or_id = get_string(&args, "name_or_id")
         .ok_or_else(|| VelosError::ProtocolError("missing 'name_or_id'".into()))?;
     let mut client = connect().await?;
-    let id = resolve_id(&mut client, &name_or_id).await?;
+    let id = resolve_one(&mut client, &name_or_id).await?;
     let info = client.info(id).await?;
     Ok(serde_json::json!({
         "id": info.id,
@@ -197,14 +217,13 @@ async fn log_read(args: Value) -> Result<String, VelosError> {
     let level_filter = get_string(&args, "level");
 
     let mut client = connect().await?;
-    let id = resolve_id(&mut client, &name_or_id).await?;
+    let id = resolve_one(&mut client, &name_or_id).await?;
     let entries = client.logs(id, lines).await?;
 
-    let classifier = velos_log_engine::classifier::Classifier::with_defaults();
+    let classifier = classifier(logs_config().as_ref());
     let mut processed = classifier.classify_batch(&entries);
 
-    if let Some(ref levels) = level_filter {
-        let allowed = parse_levels(levels);
+    if let Some(allowed) = level_filter.as_deref().and_then(LogLevel::parse_list) {
         processed.retain(|e| allowed.contains(&e.level));
     }
 
@@ -229,14 +248,13 @@ async fn log_search(args: Value) -> Result<String, VelosError> {
     let level_filter = get_string(&args, "level");
 
     let mut client = connect().await?;
-    let id = resolve_id(&mut client, &name_or_id).await?;
+    let id = resolve_one(&mut client, &name_or_id).await?;
     let entries = client.logs(id, 500).await?;
 
-    let classifier = velos_log_engine::classifier::Classifier::with_defaults();
+    let classifier = classifier(logs_config().as_ref());
     let mut processed = classifier.classify_batch(&entries);
 
-    if let Some(ref levels) = level_filter {
-        let allowed = parse_levels(levels);
+    if let Some(allowed) = level_filter.as_deref().and_then(LogLevel::parse_list) {
         processed.retain(|e| allowed.contains(&e.level));
     }
 
@@ -263,17 +281,33 @@ async fn log_summary(args: Value) -> Result<String, VelosError> {
     let lines = get_u32(&args, "lines").unwrap_or(200);
 
     let mut client = connect().await?;
-    let id = resolve_id(&mut client, &name_or_id).await?;
+    let id = resolve_one(&mut client, &name_or_id).await?;
     let entries = client.logs(id, lines).await?;
 
-    let classifier = velos_log_engine::classifier::Classifier::with_defaults();
+    let config = logs_config();
+    let classifier = classifier(config.as_ref());
     let processed = classifier.classify_batch(&entries);
 
-    let detector = velos_log_engine::pattern::PatternDetector::with_defaults();
-    let patterns = detector.detect(&processed);
+    let detail = client.info(id).await?;
+    let config = config.unwrap_or_default();
+    let anomalies = velos_log_engine::anomaly::detect_from_entries(&processed, &config);
 
-    let summary =
-        velos_log_engine::summary::generate_summary(&name_or_id, &processed, &patterns, &[], 0);
+    // Flag patterns this process has not produced before
+    let detector = velos_log_engine::pattern::PatternDetector::with_defaults();
+    let history_path = PatternHistory::default_path(&detail.name);
+    let mut history = PatternHistory::load(&history_path);
+    let patterns = detector.detect_with_history(&processed, &mut history);
+    let _ = history.save(&history_path);
+
+    let mut summary = velos_log_engine::summary::generate_summary(
+        &name_or_id,
+        &processed,
+        &patterns,
+        &anomalies,
+        detail.restart_count,
+        detail.last_exit_reason,
+    );
+    summary.suppressed_lines = detail.suppressed_lines;
 
     serde_json::to_string_pretty(&summary).map_err(|e| VelosError::ProtocolError(e.to_string()))
 }
@@ -324,7 +358,7 @@ async fn metrics_snapshot(args: Value) -> Result<String, VelosError> {
     let mut client = connect().await?;
 
     if let Some(ref nid) = name_or_id {
-        let id = resolve_id(&mut client, nid).await?;
+        let id = resolve_one(&mut client, nid).await?;
         let info = client.info(id).await?;
         Ok(serde_json::json!({
             "name": info.name,
@@ -356,7 +390,7 @@ async fn config_get(args: Value) -> Result<String, VelosError> {
     let name_or_id = get_string(&args, "name_or_id")
         .ok_or_else(|| VelosError::ProtocolError("missing 'name_or_id'".into()))?;
     let mut client = connect().await?;
-    let id = resolve_id(&mut client, &name_or_id).await?;
+    let id = resolve_one(&mut client, &name_or_id).await?;
     let info = client.info(id).await?;
     Ok(serde_json::json!({
         "name": info.name,
@@ -383,19 +417,3 @@ async fn config_set(_args: Value) -> Result<String, VelosError> {
         "config_set not yet implemented (requires daemon support)".into(),
     ))
 }
-
-// --- Utility ---
-
-fn parse_levels(levels_str: &str) -> Vec<velos_log_engine::LogLevel> {
-    levels_str
-        .split(',')
-        .filter_map(|l| match l.trim().to_lowercase().as_str() {
-            "debug" => Some(velos_log_engine::LogLevel::Debug),
-            "info" => Some(velos_log_engine::LogLevel::Info),
-            "warn" | "warning" => Some(velos_log_engine::LogLevel::Warn),
-            "error" | "err" => Some(velos_log_engine::LogLevel::Error),
-            "fatal" => Some(velos_log_engine::LogLevel::Fatal),
-            _ => None,
-        })
-        .collect()
-}
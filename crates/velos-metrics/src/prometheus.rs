@@ -7,10 +7,68 @@ use axum::{extract::State, response::IntoResponse, routing::get, Router};
 use tokio::sync::RwLock;
 use velos_client::VelosClient;
 use velos_core::protocol::ProcessInfo;
+use velos_core::VelosError;
+
+/// Longest the poller waits between attempts while the daemon is down
+/// (unless `poll_interval` itself is longer).
+const MAX_POLL_BACKOFF: Duration = Duration::from_secs(60);
 
 /// Cached process list, refreshed periodically.
 struct MetricsState {
     processes: Vec<ProcessInfo>,
+    /// Whether the last poll reached the daemon (`velos_daemon_up`).
+    daemon_up: bool,
+}
+
+impl MetricsState {
+    /// Apply one poll's result. A failed poll drops the process list rather
+    /// than serving stale values. Returns a line to log when the daemon goes
+    /// down or comes back, so an outage is reported once, not every poll.
+    fn record(&mut self, result: Result<Vec<ProcessInfo>, VelosError>) -> Option<String> {
+        let was_up = self.daemon_up;
+        match result {
+            Ok(procs) => {
+                self.processes = procs;
+                self.daemon_up = true;
+                (!was_up).then(|| "daemon reachable again".to_string())
+            }
+            Err(e) => {
+                self.processes.clear();
+                self.daemon_up = false;
+                was_up.then(|| format!("daemon unreachable ({e}), backing off"))
+            }
+        }
+    }
+}
+
+/// Circuit breaker for the poller: every consecutive failure doubles the
+/// wait before the next attempt, up to `max`; a success resets it.
+#[derive(Debug)]
+struct PollBackoff {
+    interval: Duration,
+    max: Duration,
+    failures: u32,
+}
+
+impl PollBackoff {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            max: MAX_POLL_BACKOFF.max(interval),
+            failures: 0,
+        }
+    }
+
+    /// Record a poll's outcome and return how long to wait before the next.
+    fn next_delay(&mut self, ok: bool) -> Duration {
+        if ok {
+            self.failures = 0;
+            return self.interval;
+        }
+        self.failures = self.failures.saturating_add(1);
+        let factor = 1u32 << self.failures.min(16);
+        self.interval.saturating_mul(factor).min(self.max)
+    }
 }
 
 /// Start the Prometheus metrics HTTP server.
@@ -20,26 +78,24 @@ struct MetricsState {
 pub async fn serve(port: u16, poll_interval: Duration) -> Result<(), velos_core::VelosError> {
     let state = Arc::new(RwLock::new(MetricsState {
         processes: Vec::new(),
+        // Assume up so a daemon that is down at startup gets logged
+        daemon_up: true,
     }));
 
     // Background poller
     let poller_state = Arc::clone(&state);
     tokio::spawn(async move {
+        let mut backoff = PollBackoff::new(poll_interval);
         loop {
-            match VelosClient::connect().await {
-                Ok(mut client) => match client.list().await {
-                    Ok(procs) => {
-                        poller_state.write().await.processes = procs;
-                    }
-                    Err(e) => {
-                        eprintln!("[velos-metrics] poll error: {e}");
-                    }
-                },
-                Err(e) => {
-                    eprintln!("[velos-metrics] connect error: {e}");
-                }
+            let result = match VelosClient::connect().await {
+                Ok(mut client) => client.list().await,
+                Err(e) => Err(e),
+            };
+            let delay = backoff.next_delay(result.is_ok());
+            if let Some(line) = poller_state.write().await.record(result) {
+                eprintln!("[velos-metrics] {line}");
             }
-            tokio::time::sleep(poll_interval).await;
+            tokio::time::sleep(delay).await;
         }
     });
 
@@ -58,9 +114,17 @@ pub async fn serve(port: u16, poll_interval: Duration) -> Result<(), velos_core:
         .map_err(|e| velos_core::VelosError::ProtocolError(format!("server error: {e}")))
 }
 
+/// Fetch the process list once and render it exactly as `/metrics` would,
+/// for `velos metrics --once`.
+pub async fn scrape_once() -> Result<String, velos_core::VelosError> {
+    let mut client = VelosClient::connect().await?;
+    let procs = client.list().await?;
+    Ok(format_metrics(&procs, true))
+}
+
 async fn metrics_handler(State(state): State<Arc<RwLock<MetricsState>>>) -> impl IntoResponse {
     let snap = state.read().await;
-    let body = format_metrics(&snap.processes);
+    let body = format_metrics(&snap.processes, snap.daemon_up);
     (
         [(
             axum::http::header::CONTENT_TYPE,
@@ -70,7 +134,7 @@ async fn metrics_handler(State(state): State<Arc<RwLock<MetricsState>>>) -> impl
     )
 }
 
-fn format_metrics(processes: &[ProcessInfo]) -> String {
+fn format_metrics(processes: &[ProcessInfo], daemon_up: bool) -> String {
     let mut out = String::with_capacity(4096);
 
     // --- per-process metrics ---
@@ -170,6 +234,14 @@ fn format_metrics(processes: &[ProcessInfo]) -> String {
 
     // --- daemon-level metrics ---
 
+    write_help_type(
+        &mut out,
+        "velos_daemon_up",
+        "Whether the last poll reached the daemon (1=up, 0=down)",
+        "gauge",
+    );
+    writeln!(out, "velos_daemon_up {}", u8::from(daemon_up)).ok();
+
     write_help_type(
         &mut out,
         "velos_daemon_processes_total",
@@ -199,8 +271,9 @@ mod tests {
 
     #[test]
     fn test_format_metrics_empty() {
-        let out = format_metrics(&[]);
+        let out = format_metrics(&[], true);
         assert!(out.contains("velos_daemon_processes_total 0"));
+        assert!(out.contains("velos_daemon_up 1"));
     }
 
     #[test]
@@ -214,8 +287,9 @@ mod tests {
             uptime_ms: 86_400_000,
             restart_count: 3,
             cpu_percent: 12.5,
+            group: None,
         }];
-        let out = format_metrics(&procs);
+        let out = format_metrics(&procs, true);
         assert!(out.contains("velos_process_memory_bytes{name=\"api\",instance=\"0\"} 47185920"));
         assert!(out.contains("velos_process_uptime_seconds{name=\"api\",instance=\"0\"} 86400.000"));
         assert!(out.contains("velos_process_restart_total{name=\"api\",instance=\"0\"} 3"));
@@ -223,6 +297,90 @@ mod tests {
         assert!(out.contains("velos_daemon_processes_total 1"));
     }
 
+    #[tokio::test]
+    async fn test_once_matches_handler() {
+        let procs = vec![ProcessInfo {
+            id: 2,
+            name: "worker\"1".to_string(),
+            pid: 99,
+            status: 2,
+            memory_bytes: 1024,
+            uptime_ms: 1500,
+            restart_count: 7,
+            cpu_percent: 0.5,
+            group: Some("jobs".into()),
+        }];
+        let state = Arc::new(RwLock::new(MetricsState {
+            processes: procs.clone(),
+            daemon_up: true,
+        }));
+        let response = metrics_handler(State(state)).await.into_response();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        // scrape_once renders the fetched list with the same formatter
+        assert_eq!(body, format_metrics(&procs, true).as_bytes());
+    }
+
+    #[test]
+    fn test_poll_backoff_progression() {
+        let mut backoff = PollBackoff::new(Duration::from_secs(5));
+        assert_eq!(backoff.next_delay(true), Duration::from_secs(5));
+
+        let secs: Vec<u64> = (0..6)
+            .map(|_| backoff.next_delay(false).as_secs())
+            .collect();
+        assert_eq!(secs, [10, 20, 40, 60, 60, 60]);
+
+        // Recovery resets straight back to the normal interval
+        assert_eq!(backoff.next_delay(true), Duration::from_secs(5));
+        assert_eq!(backoff.next_delay(false), Duration::from_secs(10));
+
+        // A poll interval above the cap is never shortened
+        let mut slow = PollBackoff::new(Duration::from_secs(120));
+        assert_eq!(slow.next_delay(false), Duration::from_secs(120));
+    }
+
+    #[test]
+    fn test_daemon_up_metric_follows_polls() {
+        let proc = ProcessInfo {
+            id: 0,
+            name: "api".to_string(),
+            pid: 1234,
+            status: 1,
+            memory_bytes: 0,
+            uptime_ms: 0,
+            restart_count: 0,
+            cpu_percent: 0.0,
+            group: None,
+        };
+        let mut state = MetricsState {
+            processes: Vec::new(),
+            daemon_up: true,
+        };
+        let down = || Err(VelosError::DaemonNotRunning("/tmp/velos.sock".into()));
+        let render = |s: &MetricsState| format_metrics(&s.processes, s.daemon_up);
+
+        assert_eq!(state.record(Ok(vec![proc.clone()])), None);
+        assert!(render(&state).contains("velos_daemon_up 1"));
+        assert!(render(&state).contains("velos_daemon_processes_total 1"));
+
+        // Down: logged once, and the stale list is not served
+        assert!(state
+            .record(down())
+            .unwrap()
+            .starts_with("daemon unreachable"));
+        assert_eq!(state.record(down()), None);
+        assert!(render(&state).contains("velos_daemon_up 0"));
+        assert!(render(&state).contains("velos_daemon_processes_total 0"));
+
+        assert_eq!(
+            state.record(Ok(vec![proc])).as_deref(),
+            Some("daemon reachable again")
+        );
+        assert!(render(&state).contains("velos_daemon_up 1"));
+    }
+
     #[test]
     fn test_escape_label() {
         assert_eq!(escape("hello\"world"), "hello\\\"world");
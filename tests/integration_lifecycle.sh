@@ -7,6 +7,7 @@
 # Phase 5: AI CLI (--ai flag), MCP server (stdio JSON-RPC, tools)
 # Phase 6: cluster mode, scaling, metrics endpoint, REST API
 # Phase 7: shell completions, --version, socket permissions, startup, error messages
+# Phase 8: protocol handshake and CLI extensions
 set -euo pipefail
 
 SCRIPT_DIR="$(cd "$(dirname "$0")" && pwd)"
@@ -32,7 +33,7 @@ trap cleanup EXIT
 pass() { TOTAL=$((TOTAL + 1)); PASSED=$((PASSED + 1)); echo "  PASS: $1"; }
 fail() { TOTAL=$((TOTAL + 1)); FAILED=$((FAILED + 1)); echo "  FAIL: $1"; [ -n "${2:-}" ] && echo "        $2"; }
 
-echo "=== Velos Integration Test: Full Lifecycle (Phase 2 + 3 + 4 + 5 + 6 + 7) ==="
+echo "=== Velos Integration Test: Full Lifecycle (Phase 2 + 3 + 4 + 5 + 6 + 7 + 8) ==="
 echo "Test dir: $TEST_DIR"
 echo ""
 
@@ -101,6 +102,16 @@ if __name__ == '__main__':
         else:
             print(json.dumps({'ok': False}))
 
+    elif cmd == 'hello':
+        versions = [int(v) for v in sys.argv[3].split(',')] if len(sys.argv) > 3 else [1]
+        payload = struct.pack('B', len(versions)) + bytes(versions)
+        r = send_recv(sock, 1, 0x43, payload)
+        if r and r['status'] == 0 and len(r['payload']) >= 1:
+            print(json.dumps({'ok': True, 'version': r['payload'][0]}))
+        else:
+            msg = r['payload'].decode() if r else 'no response'
+            print(json.dumps({'ok': False, 'error': msg}))
+
     elif cmd == 'start':
         name, script, cwd = sys.argv[3], sys.argv[4], sys.argv[5]
         autorestart = int(sys.argv[6]) if len(sys.argv) > 6 else 0
@@ -1430,10 +1441,10 @@ sleep 0.3
 echo "40. Cluster via CLI (velos start -i 2)"
 RESULT=$(eval $VELOS_CLI start "$TEST_DIR/hello.sh" --name cli-cluster -i 2 --json 2>/dev/null || echo "{}")
 sleep 0.5
-LIST_RESULT=$(eval $VELOS_CLI list --json 2>/dev/null || echo "[]")
+LIST_RESULT=$(eval $VELOS_CLI list --json 2>/dev/null || echo "{}")
 if echo "$LIST_RESULT" | python3 -c "
 import sys,json
-data=json.load(sys.stdin)
+data=json.load(sys.stdin)['processes']
 cluster = [p for p in data if p.get('name','').startswith('cli-cluster:')]
 assert len(cluster) == 2, f'expected 2 CLI cluster instances, got {len(cluster)}'
 " 2>/dev/null; then
@@ -1670,6 +1681,451 @@ else
 fi
 echo ""
 
+# ==============================================================
+# PHASE 8: Protocol & CLI extensions
+# ==============================================================
+
+echo "===== PHASE 8: Protocol & CLI extensions ====="
+echo ""
+
+# --- 51. Protocol version handshake ---
+echo "51. Protocol version handshake"
+HELLO=$(python3 "$TEST_DIR/ipc_client.py" "$SOCKET" hello 1,2)
+HELLO_V1=$(python3 "$TEST_DIR/ipc_client.py" "$SOCKET" hello 1)
+HELLO_NONE=$(python3 "$TEST_DIR/ipc_client.py" "$SOCKET" hello 9)
+if echo "$HELLO" | python3 -c "import sys,json; d=json.load(sys.stdin); assert d['ok'] and d['version']==2" 2>/dev/null \
+    && echo "$HELLO_V1" | python3 -c "import sys,json; d=json.load(sys.stdin); assert d['ok'] and d['version']==1" 2>/dev/null \
+    && echo "$HELLO_NONE" | python3 -c "import sys,json; d=json.load(sys.stdin); assert not d['ok']" 2>/dev/null; then
+    pass "hello negotiated version 2, fell back to 1, rejected unknown-only version"
+else
+    fail "hello handshake" "$HELLO / $HELLO_V1 / $HELLO_NONE"
+fi
+echo ""
+
+# --- 52. Structured start errors ---
+echo "52. Structured start errors"
+MISSING=$($IPC start "no-script" "$TEST_DIR/does-not-exist.sh" "$TEST_DIR")
+FIRST=$($IPC start "dup-name" "$TEST_DIR/hello.sh" "$TEST_DIR")
+DUP=$($IPC start "dup-name" "$TEST_DIR/hello.sh" "$TEST_DIR")
+if echo "$MISSING" | python3 -c "import sys,json; d=json.load(sys.stdin); assert not d['ok'] and d['error'].startswith('\x01')" 2>/dev/null \
+    && echo "$DUP" | python3 -c "import sys,json; d=json.load(sys.stdin); assert not d['ok'] and d['error']=='\x02dup-name'" 2>/dev/null; then
+    pass "missing script and duplicate name return error codes 1 and 2"
+else
+    fail "structured start errors" "$MISSING / $DUP"
+fi
+DUP_ID=$(echo "$FIRST" | python3 -c "import sys,json; print(json.load(sys.stdin).get('process_id',''))" 2>/dev/null)
+[ -n "$DUP_ID" ] && $IPC delete "$DUP_ID" >/dev/null 2>&1 || true
+echo ""
+
+# --- 53. Manual stop is not a crash ---
+echo "53. Manual stop is not a crash"
+RESULT=$($IPC start "stop-quiet" "$TEST_DIR/hello.sh" "$TEST_DIR")
+QUIET_ID=$(echo "$RESULT" | python3 -c "import sys,json; print(json.load(sys.stdin).get('process_id',''))" 2>/dev/null)
+sleep 0.5
+HOME="$TEST_DIR" "$VELOS" stop stop-quiet >/dev/null 2>&1 || true
+sleep 1  # let the daemon reap the SIGTERMed child
+HIST=$(HOME="$TEST_DIR" "$VELOS" info stop-quiet --json --history 2>/dev/null || echo "{}")
+if echo "$HIST" | python3 -c "
+import sys,json
+d=json.load(sys.stdin)
+kinds=[e['kind'] for e in d['history']]
+assert 'stop' in kinds, kinds
+assert 'crash' not in kinds, kinds
+assert d['status']==0, d['status']
+assert d['last_exit_reason']=='manual', d['last_exit_reason']
+" 2>/dev/null; then
+    pass "stop leaves status stopped and no crash in info --history"
+else
+    fail "manual stop recorded as a crash" "$HIST"
+fi
+[ -n "$QUIET_ID" ] && $IPC delete "$QUIET_ID" >/dev/null 2>&1 || true
+echo ""
+
+# --- 54. Stop sends no crash notification ---
+echo "54. Stop sends no crash notification"
+# A second daemon under its own HOME, so the crash records its notify-crash
+# runs write can be counted without touching the real ~/.velos
+NOTIFY_HOME="$TEST_DIR/notify_home"
+NOTIFY_SOCK="$NOTIFY_HOME/.velos/velos.sock"
+mkdir -p "$NOTIFY_HOME/.velos"
+HOME="$NOTIFY_HOME" "$VELOS" daemon --socket "$NOTIFY_SOCK" --state-dir "$NOTIFY_HOME/.velos" >/dev/null 2>&1 &
+NOTIFY_DAEMON=$!
+for i in $(seq 1 20); do [ -S "$NOTIFY_SOCK" ] && break; sleep 0.1; done
+NIPC="python3 $TEST_DIR/ipc_client.py $NOTIFY_SOCK"
+$NIPC start "notify-stop" "$TEST_DIR/hello.sh" "$TEST_DIR" >/dev/null
+sleep 0.5
+HOME="$NOTIFY_HOME" "$VELOS" stop notify-stop >/dev/null 2>&1 || true
+# Control: a real crash in the same daemon does notify
+$NIPC start "notify-crash" "$TEST_DIR/crasher.sh" "$TEST_DIR" >/dev/null
+sleep 2
+NOTIFIED=$(cat "$NOTIFY_HOME/.velos/crashes/"*.json 2>/dev/null | python3 -c "
+import sys,json,re
+names=re.findall(r'\"process_name\":\s*\"([^\"]+)\"', sys.stdin.read())
+print(json.dumps(sorted(set(names))))
+" 2>/dev/null || echo "[]")
+kill "$NOTIFY_DAEMON" 2>/dev/null || true
+wait "$NOTIFY_DAEMON" 2>/dev/null || true
+if echo "$NOTIFIED" | python3 -c "
+import sys,json
+names=json.load(sys.stdin)
+assert 'notify-crash' in names, names
+assert 'notify-stop' not in names, names
+" 2>/dev/null; then
+    pass "crash notified, manual stop not"
+else
+    fail "crash notifications" "$NOTIFIED"
+fi
+echo ""
+
+# --- 55. Event stream: stop is only a stop ---
+echo "55. Event stream: stop is only a stop"
+$IPC start "stop-events" "$TEST_DIR/hello.sh" "$TEST_DIR" >/dev/null
+sleep 0.5
+HOME="$TEST_DIR" "$VELOS" stop stop-events >/dev/null 2>&1 || true
+sleep 1
+EVENTS=$(HOME="$TEST_DIR" "$VELOS" events stop-events --json 2>/dev/null || echo "")
+if echo "$EVENTS" | python3 -c "
+import sys,json
+kinds=[json.loads(l)['kind'] for l in sys.stdin if l.strip()]
+assert kinds==['start','stop'], kinds
+" 2>/dev/null; then
+    pass "velos events shows start then stop, no crash"
+else
+    fail "events after stop" "$EVENTS"
+fi
+EV_ID=$(HOME="$TEST_DIR" "$VELOS" info stop-events --json 2>/dev/null | python3 -c "import sys,json; print(json.load(sys.stdin)['id'])" 2>/dev/null || echo "")
+[ -n "$EV_ID" ] && $IPC delete "$EV_ID" >/dev/null 2>&1 || true
+echo ""
+
+# --- 56. info --json masks secret env values ---
+echo "56. info --json masks secret env values"
+HOME="$TEST_DIR" "$VELOS" start "$TEST_DIR/hello.sh" --name env-secrets --cwd "$TEST_DIR" \
+    -e GITHUB_TOKEN=ghp_test -e SESSION_SECRET=s3cret -e PASSWORD=hunter2 -e APP_PORT=3000 >/dev/null 2>&1 || true
+sleep 0.5
+ENV_INFO=$(HOME="$TEST_DIR" "$VELOS" info env-secrets --json 2>/dev/null || echo "{}")
+ENV_SHOWN=$(HOME="$TEST_DIR" "$VELOS" info env-secrets --json --show-secrets 2>/dev/null || echo "{}")
+if echo "$ENV_INFO" | python3 -c "
+import sys,json
+env=json.load(sys.stdin)['env']
+for k in ('GITHUB_TOKEN','SESSION_SECRET','PASSWORD'):
+    assert env[k]=='****', (k, env[k])
+assert env['APP_PORT']=='3000', env['APP_PORT']
+" 2>/dev/null && echo "$ENV_SHOWN" | python3 -c "
+import sys,json
+assert json.load(sys.stdin)['env']['GITHUB_TOKEN']=='ghp_test'
+" 2>/dev/null; then
+    pass "info --json masks *_TOKEN/*_SECRET/PASSWORD, --show-secrets prints them"
+else
+    fail "info --json env redaction" "$ENV_INFO"
+fi
+HOME="$TEST_DIR" "$VELOS" delete env-secrets >/dev/null 2>&1 || true
+echo ""
+
+# --- 57. start --config twice skips running apps ---
+echo "57. start --config twice skips running apps"
+cat > "$TEST_DIR/twice.toml" <<EOF
+[apps.cfg-a]
+script = "$TEST_DIR/hello.sh"
+
+[apps.cfg-b]
+script = "$TEST_DIR/hello.sh"
+EOF
+HOME="$TEST_DIR" "$VELOS" start --config "$TEST_DIR/twice.toml" >/dev/null 2>&1 || true
+sleep 0.5
+HOME="$TEST_DIR" "$VELOS" delete cfg-b >/dev/null 2>&1 || true
+TWICE_OUT=$(HOME="$TEST_DIR" "$VELOS" start --config "$TEST_DIR/twice.toml" 2>&1)
+TWICE_RC=$?
+sleep 0.5
+TWICE_LIST=$(HOME="$TEST_DIR" "$VELOS" list --json 2>/dev/null || echo "{}")
+if [ "$TWICE_RC" -eq 0 ] && echo "$TWICE_OUT" | grep -q "'cfg-a' is already running" \
+    && echo "$TWICE_OUT" | grep -q "Started 'cfg-b'" \
+    && echo "$TWICE_LIST" | python3 -c "
+import sys,json
+names=[p['name'] for p in json.load(sys.stdin)['processes']]
+assert names.count('cfg-a')==1 and names.count('cfg-b')==1, names
+" 2>/dev/null; then
+    pass "second start --config reports cfg-a already running and starts cfg-b"
+else
+    fail "start --config twice" "rc=$TWICE_RC $TWICE_OUT"
+fi
+HOME="$TEST_DIR" "$VELOS" delete cfg-a >/dev/null 2>&1 || true
+HOME="$TEST_DIR" "$VELOS" delete cfg-b >/dev/null 2>&1 || true
+echo ""
+
+# --- 58. max_memory_restart kills the old process ---
+echo "58. max_memory_restart kills the old process"
+cat > "$TEST_DIR/memhog.sh" <<'EOF'
+#!/bin/sh
+exec python3 -c "import time; b = bytearray(64 * 1024 * 1024); time.sleep(60)"
+EOF
+chmod +x "$TEST_DIR/memhog.sh"
+HOME="$TEST_DIR" "$VELOS" start "$TEST_DIR/memhog.sh" --name memhog --max-memory 20M >/dev/null 2>&1 || true
+sleep 1
+OLD_PID=$(HOME="$TEST_DIR" "$VELOS" info memhog --json 2>/dev/null | python3 -c "import sys,json; print(json.load(sys.stdin)['pid'])" 2>/dev/null || echo 0)
+sleep 4
+MEM_INFO=$(HOME="$TEST_DIR" "$VELOS" info memhog --json --history 2>/dev/null || echo "{}")
+if [ "$OLD_PID" -gt 0 ] && ! kill -0 "$OLD_PID" 2>/dev/null \
+    && echo "$MEM_INFO" | python3 -c "
+import sys,json
+d=json.load(sys.stdin)
+kinds=[e['kind'] for e in d['history']]
+assert 'oom' in kinds and 'crash' not in kinds, kinds
+assert d['pid'] != $OLD_PID, d['pid']
+" 2>/dev/null; then
+    pass "old pid $OLD_PID is gone after the oom restart"
+else
+    fail "max_memory_restart kills the old process" "old=$OLD_PID $MEM_INFO"
+fi
+HOME="$TEST_DIR" "$VELOS" delete memhog >/dev/null 2>&1 || true
+echo ""
+
+# --- 59. stop --kill-tree stops child processes ---
+echo "59. stop --kill-tree stops child processes"
+cat > "$TEST_DIR/spawner.sh" <<'EOF'
+#!/bin/sh
+sleep 300 &
+echo $! > "$1"
+wait
+EOF
+chmod +x "$TEST_DIR/spawner.sh"
+# A reaped child is gone; an unreaped one lingers as a zombie, which is dead too
+alive() { ps -o stat= -p "$1" 2>/dev/null | grep -q '^[^Z]'; }
+HOME="$TEST_DIR" "$VELOS" start "$TEST_DIR/spawner.sh" --name tree-plain -- "$TEST_DIR/tree-plain.pid" >/dev/null 2>&1 || true
+HOME="$TEST_DIR" "$VELOS" start "$TEST_DIR/spawner.sh" --name tree-kill -- "$TEST_DIR/tree-kill.pid" >/dev/null 2>&1 || true
+sleep 1
+PLAIN_CHILD=$(cat "$TEST_DIR/tree-plain.pid" 2>/dev/null || echo 0)
+KILL_CHILD=$(cat "$TEST_DIR/tree-kill.pid" 2>/dev/null || echo 0)
+HOME="$TEST_DIR" "$VELOS" stop tree-plain >/dev/null 2>&1 || true
+HOME="$TEST_DIR" "$VELOS" stop tree-kill --kill-tree >/dev/null 2>&1 || true
+sleep 1
+if [ "$PLAIN_CHILD" -gt 0 ] && [ "$KILL_CHILD" -gt 0 ] \
+    && alive "$PLAIN_CHILD" && ! alive "$KILL_CHILD"; then
+    pass "--kill-tree stopped the child, plain stop left it running"
+else
+    fail "stop --kill-tree" "plain child $PLAIN_CHILD, kill-tree child $KILL_CHILD"
+fi
+[ "$PLAIN_CHILD" -gt 0 ] && kill "$PLAIN_CHILD" 2>/dev/null || true
+HOME="$TEST_DIR" "$VELOS" delete tree-plain >/dev/null 2>&1 || true
+HOME="$TEST_DIR" "$VELOS" delete tree-kill >/dev/null 2>&1 || true
+echo ""
+
+# --- 60. start --out-log/--err-log/--merge-logs ---
+echo "60. start --out-log/--err-log/--merge-logs"
+cat > "$TEST_DIR/twostreams.sh" <<'EOF'
+#!/bin/sh
+echo "to stdout"
+echo "to stderr" >&2
+sleep 60
+EOF
+chmod +x "$TEST_DIR/twostreams.sh"
+mkdir -p "$TEST_DIR/custom-logs"
+HOME="$TEST_DIR" "$VELOS" start "$TEST_DIR/twostreams.sh" --name split-logs \
+    --out-log "$TEST_DIR/custom-logs/split.out" --err-log "$TEST_DIR/custom-logs/split.err" >/dev/null 2>&1 || true
+HOME="$TEST_DIR" "$VELOS" start "$TEST_DIR/twostreams.sh" --name merged-logs \
+    --out-log "$TEST_DIR/custom-logs/merged.log" --merge-logs >/dev/null 2>&1 || true
+BAD_DIR=$(HOME="$TEST_DIR" "$VELOS" start "$TEST_DIR/twostreams.sh" --name bad-logs \
+    --out-log "$TEST_DIR/no-such-dir/out.log" 2>&1)
+sleep 1
+if grep -q "to stdout" "$TEST_DIR/custom-logs/split.out" 2>/dev/null \
+    && grep -q "to stderr" "$TEST_DIR/custom-logs/split.err" 2>/dev/null \
+    && ! grep -q "to stderr" "$TEST_DIR/custom-logs/split.out" 2>/dev/null \
+    && grep -q "to stdout" "$TEST_DIR/custom-logs/merged.log" 2>/dev/null \
+    && grep -q "to stderr" "$TEST_DIR/custom-logs/merged.log" 2>/dev/null \
+    && echo "$BAD_DIR" | grep -q "does not exist"; then
+    pass "logs go to the given files, --merge-logs puts stderr in the out log"
+else
+    fail "start --out-log/--err-log" "$BAD_DIR"
+fi
+HOME="$TEST_DIR" "$VELOS" delete split-logs >/dev/null 2>&1 || true
+HOME="$TEST_DIR" "$VELOS" delete merged-logs >/dev/null 2>&1 || true
+HOME="$TEST_DIR" "$VELOS" delete bad-logs >/dev/null 2>&1 || true
+echo ""
+
+# --- 61. velos group list/restart/stop ---
+echo "61. velos group list/restart/stop"
+HOME="$TEST_DIR" "$VELOS" start "$TEST_DIR/hello.sh" --name grp-api --group grp-backend >/dev/null 2>&1 || true
+HOME="$TEST_DIR" "$VELOS" start "$TEST_DIR/hello.sh" --name grp-worker --group grp-backend >/dev/null 2>&1 || true
+HOME="$TEST_DIR" "$VELOS" start "$TEST_DIR/hello.sh" --name grp-web --group grp-frontend >/dev/null 2>&1 || true
+sleep 0.5
+GROUPS_JSON=$(HOME="$TEST_DIR" "$VELOS" group list --json 2>/dev/null || echo "[]")
+WEB_PID_BEFORE=$(HOME="$TEST_DIR" "$VELOS" info grp-web --json 2>/dev/null | python3 -c "import sys,json; print(json.load(sys.stdin)['pid'])" 2>/dev/null || echo 0)
+HOME="$TEST_DIR" "$VELOS" group restart grp-frontend >/dev/null 2>&1 || true
+HOME="$TEST_DIR" "$VELOS" group stop grp-backend >/dev/null 2>&1 || true
+sleep 0.5
+GROUP_LIST=$(HOME="$TEST_DIR" "$VELOS" list --json 2>/dev/null || echo "{}")
+if echo "$GROUPS_JSON" | python3 -c "
+import sys,json
+groups={g['group']: g['members'] for g in json.load(sys.stdin)}
+assert groups.get('grp-backend')==2 and groups.get('grp-frontend')==1, groups
+" 2>/dev/null && echo "$GROUP_LIST" | python3 -c "
+import sys,json
+procs={p['name']: p for p in json.load(sys.stdin)['processes']}
+assert procs['grp-api']['status']==0 and procs['grp-worker']['status']==0, procs
+web=procs['grp-web']
+assert web['status']==1 and web['pid']!=$WEB_PID_BEFORE, web
+" 2>/dev/null; then
+    pass "group list counts members, restart and stop hit only their group"
+else
+    fail "velos group" "$GROUPS_JSON / $GROUP_LIST"
+fi
+for grp_name in grp-api grp-worker grp-web; do
+    HOME="$TEST_DIR" "$VELOS" delete "$grp_name" >/dev/null 2>&1 || true
+done
+echo ""
+
+# --- 62. log_rate_limit drops excess lines ---
+echo "62. log_rate_limit drops excess lines"
+cat > "$TEST_DIR/flood.sh" <<'EOF'
+#!/bin/sh
+i=0
+while [ $i -lt 2000 ]; do echo "flood line $i"; i=$((i + 1)); done
+sleep 60
+EOF
+chmod +x "$TEST_DIR/flood.sh"
+cat > "$TEST_DIR/flood.toml" <<EOF
+[apps.flood]
+script = "$TEST_DIR/flood.sh"
+log_rate_limit = 10
+EOF
+HOME="$TEST_DIR" "$VELOS" start --config "$TEST_DIR/flood.toml" >/dev/null 2>&1 || true
+sleep 1.5
+FLOOD_INFO=$(HOME="$TEST_DIR" "$VELOS" info flood --json 2>/dev/null || echo "{}")
+FLOOD_LOGS=$(HOME="$TEST_DIR" "$VELOS" logs flood --lines 500 --json 2>/dev/null || echo "[]")
+if echo "$FLOOD_INFO" | python3 -c "
+import sys,json
+d=json.load(sys.stdin)
+assert d['log_rate_limit']==10 and d['suppressed_lines']>0, d
+" 2>/dev/null && echo "$FLOOD_LOGS" | python3 -c "
+import sys,json
+msgs=[e['message'] for e in json.load(sys.stdin)]
+assert any('lines suppressed' in m for m in msgs), msgs[-5:]
+assert len([m for m in msgs if m.startswith('flood line')]) < 2000, len(msgs)
+" 2>/dev/null; then
+    pass "excess lines are dropped, counted in info and marked in the log"
+else
+    fail "log_rate_limit" "$FLOOD_INFO"
+fi
+HOME="$TEST_DIR" "$VELOS" delete flood >/dev/null 2>&1 || true
+echo ""
+
+# --- 63. logs --include-internal ---
+echo "63. logs --include-internal"
+HOME="$TEST_DIR" "$VELOS" start "$TEST_DIR/hello.sh" --name internal-logs >/dev/null 2>&1 || true
+sleep 0.5
+HOME="$TEST_DIR" "$VELOS" restart internal-logs >/dev/null 2>&1 || true
+sleep 0.5
+PLAIN_LOGS=$(HOME="$TEST_DIR" "$VELOS" logs internal-logs --json 2>/dev/null || echo "[]")
+INTERNAL_LOGS=$(HOME="$TEST_DIR" "$VELOS" logs internal-logs --include-internal --json 2>/dev/null || echo "[]")
+if echo "$PLAIN_LOGS" | python3 -c "
+import sys,json
+assert not any(e['message'].startswith('[velos]') for e in json.load(sys.stdin))
+" 2>/dev/null && echo "$INTERNAL_LOGS" | python3 -c "
+import sys,json
+data=json.load(sys.stdin)
+msgs=[e['message'] for e in data]
+assert '[velos] started' in msgs and '[velos] restart requested' in msgs, msgs
+assert 'hello from velos' in msgs, msgs
+ts=[e['timestamp_ms'] for e in data]
+assert ts==sorted(ts), ts
+" 2>/dev/null; then
+    pass "daemon decisions are interleaved with process output by time"
+else
+    fail "logs --include-internal" "$INTERNAL_LOGS"
+fi
+HOME="$TEST_DIR" "$VELOS" delete internal-logs >/dev/null 2>&1 || true
+echo ""
+
+# --- 64. status reports the daemon's runtime config ---
+echo "64. status reports the daemon's runtime config"
+STATUS_JSON=$(HOME="$TEST_DIR" "$VELOS" status --json 2>/dev/null || echo "{}")
+if echo "$STATUS_JSON" | python3 -c "
+import sys,json,time
+d=json.load(sys.stdin)
+assert d['daemon_running'] is True, d
+assert d['pid']==$DAEMON_PID, d['pid']
+assert d['state_dir'].rstrip('/')=='$TEST_DIR'.rstrip('/'), d['state_dir']
+assert 0 < d['started_ms'] <= time.time()*1000, d['started_ms']
+assert isinstance(d['max_processes'], int), d['max_processes']
+" 2>/dev/null; then
+    pass "status --json carries pid, state dir, start time and process limit"
+else
+    fail "status daemon info" "$STATUS_JSON"
+fi
+echo ""
+
+# --- 65. success_exit_codes and stop_on_success ---
+echo "65. success_exit_codes and stop_on_success"
+cat > "$TEST_DIR/exit2.sh" <<'EOF'
+#!/bin/sh
+echo "nothing to do"
+exit 2
+EOF
+chmod +x "$TEST_DIR/exit2.sh"
+cat > "$TEST_DIR/exitcodes.toml" <<EOF
+[apps.batch-ok]
+script = "$TEST_DIR/exit2.sh"
+success_exit_codes = [0, 2]
+stop_on_success = true
+
+[apps.batch-fail]
+script = "$TEST_DIR/exit2.sh"
+autorestart = false
+EOF
+HOME="$TEST_DIR" "$VELOS" start --config "$TEST_DIR/exitcodes.toml" >/dev/null 2>&1 || true
+sleep 1.5
+OK_INFO=$(HOME="$TEST_DIR" "$VELOS" info batch-ok --json --history 2>/dev/null || echo "{}")
+FAIL_INFO=$(HOME="$TEST_DIR" "$VELOS" info batch-fail --json --history 2>/dev/null || echo "{}")
+if echo "$OK_INFO" | python3 -c "
+import sys,json
+d=json.load(sys.stdin)
+kinds=[e['kind'] for e in d['history']]
+assert d['status']==0 and d['restart_count']==0, d
+assert 'crash' not in kinds and 'restart' not in kinds, kinds
+" 2>/dev/null && echo "$FAIL_INFO" | python3 -c "
+import sys,json
+d=json.load(sys.stdin)
+assert 'crash' in [e['kind'] for e in d['history']], d['history']
+" 2>/dev/null; then
+    pass "exit 2 is clean with success_exit_codes = [0, 2] and not restarted; a crash otherwise"
+else
+    fail "success_exit_codes" "$OK_INFO / $FAIL_INFO"
+fi
+HOME="$TEST_DIR" "$VELOS" delete batch-ok >/dev/null 2>&1 || true
+HOME="$TEST_DIR" "$VELOS" delete batch-fail >/dev/null 2>&1 || true
+echo ""
+
+# --- 66. restart --reset-counters ---
+echo "66. restart --reset-counters"
+cat > "$TEST_DIR/flap-once.sh" <<'EOF'
+#!/bin/sh
+# Crash on the first run only, then stay up
+if [ ! -f "$1" ]; then touch "$1"; exit 1; fi
+sleep 60
+EOF
+chmod +x "$TEST_DIR/flap-once.sh"
+rm -f "$TEST_DIR/flap-once.marker"
+HOME="$TEST_DIR" "$VELOS" start "$TEST_DIR/flap-once.sh" --name flap-once -- "$TEST_DIR/flap-once.marker" >/dev/null 2>&1 || true
+sleep 2
+FLAP_BEFORE=$(HOME="$TEST_DIR" "$VELOS" info flap-once --json 2>/dev/null || echo "{}")
+HOME="$TEST_DIR" "$VELOS" restart flap-once --reset-counters >/dev/null 2>&1 || true
+sleep 0.5
+FLAP_AFTER=$(HOME="$TEST_DIR" "$VELOS" info flap-once --json 2>/dev/null || echo "{}")
+if echo "$FLAP_BEFORE" | python3 -c "
+import sys,json
+assert json.load(sys.stdin)['restart_count']>=1
+" 2>/dev/null && echo "$FLAP_AFTER" | python3 -c "
+import sys,json
+d=json.load(sys.stdin)
+assert d['status']==1, d['status']
+assert d['restart_count']==0 and d['consecutive_crashes']==0, d
+" 2>/dev/null; then
+    pass "restart --reset-counters restarts and zeroes the counters"
+else
+    fail "restart --reset-counters" "$FLAP_BEFORE / $FLAP_AFTER"
+fi
+HOME="$TEST_DIR" "$VELOS" delete flap-once >/dev/null 2>&1 || true
+echo ""
+
 # ==============================================================
 # CLEANUP
 # ==============================================================
@@ -1677,8 +2133,8 @@ echo ""
 echo "===== Shutdown ====="
 echo ""
 
-# --- 51. Shutdown daemon ---
-echo "51. Shutdown daemon"
+# --- 67. Shutdown daemon ---
+echo "67. Shutdown daemon"
 kill "$DAEMON_PID" 2>/dev/null
 wait "$DAEMON_PID" 2>/dev/null || true
 sleep 0.5  # allow filesystem to sync on Linux
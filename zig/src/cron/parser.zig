@@ -46,7 +46,11 @@ pub const CronExpr = struct {
         try parseField(fields[1], &result.hours, 0, 23);
         try parseField(fields[2], &result.days, 1, 31);
         try parseField(fields[3], &result.months, 1, 12);
-        try parseField(fields[4], &result.weekdays, 0, 6);
+        // Weekday 7 is Sunday too: parse 0-7, then fold 7 onto 0
+        var weekdays = [_]bool{false} ** 8;
+        try parseField(fields[4], &weekdays, 0, 7);
+        result.weekdays = weekdays[0..7].*;
+        result.weekdays[0] = result.weekdays[0] or weekdays[7];
 
         return result;
     }
@@ -250,6 +254,20 @@ test "parse specific day and month" {
     try std.testing.expect(!expr.matches(0, 0, 25, 11, 3));
 }
 
+test "parse weekday 7 as Sunday" {
+    const sunday = try CronExpr.parse("0 3 * * 7");
+    try std.testing.expect(sunday.matches(0, 3, 15, 6, 0));
+    try std.testing.expect(!sunday.matches(0, 3, 15, 6, 6));
+
+    const weekend = try CronExpr.parse("0 3 * * 6-7");
+    try std.testing.expect(weekend.matches(0, 3, 15, 6, 6));
+    try std.testing.expect(weekend.matches(0, 3, 15, 6, 0));
+    try std.testing.expect(!weekend.matches(0, 3, 15, 6, 1));
+
+    const result = CronExpr.parse("0 3 * * 8");
+    try std.testing.expectError(CronExpr.ParseError.ValueOutOfRange, result);
+}
+
 test "parse error - too few fields" {
     const result = CronExpr.parse("30 2 *");
     try std.testing.expectError(CronExpr.ParseError.InvalidFormat, result);
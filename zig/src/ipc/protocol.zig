@@ -6,7 +6,9 @@ const std = @import("std");
 
 pub const MAGIC_0: u8 = 0x56; // ASCII 'V'
 pub const MAGIC_1: u8 = 0x10;
-pub const PROTOCOL_VERSION: u8 = 0x01;
+pub const PROTOCOL_VERSION: u8 = 0x02; // highest version spoken, settled per connection by HELLO
+pub const MIN_PROTOCOL_VERSION: u8 = 0x01; // clients that never say HELLO
+pub const VERSION_LOG_SEQ: u8 = 0x02; // LOG_READ entries end with seq(u64)
 pub const HEADER_SIZE: usize = 7;
 pub const MAX_PAYLOAD_SIZE: u32 = 4 * 1024 * 1024; // 4 MB max
 
@@ -18,11 +20,16 @@ pub const Command = enum(u8) {
     process_list = 0x05,
     process_info = 0x06,
     process_scale = 0x07,
+    process_reset = 0x08,
+    process_history = 0x0C,
+    event_stream = 0x0D,
     log_read = 0x10,
     state_save = 0x30,
     state_load = 0x31,
     ping = 0x40,
     shutdown = 0x41,
+    daemon_info = 0x42,
+    hello = 0x43,
     _,
 };
 
@@ -32,6 +39,15 @@ pub const Status = enum(u8) {
     _,
 };
 
+/// Structured failure reason sent as the first byte of an error payload,
+/// followed by the message. Kept below 0x20 so clients can tell it apart
+/// from a plain-text error.
+pub const ErrorCode = enum(u8) {
+    script_not_found = 0x01,
+    name_in_use = 0x02,
+    spawn_failed = 0x03,
+};
+
 pub const Request = struct {
     id: u32,
     command: Command,
@@ -45,17 +61,17 @@ pub const Response = struct {
 };
 
 /// Encode a 7-byte header into buf. Returns the header slice.
-pub fn encodeHeader(buf: *[HEADER_SIZE]u8, payload_len: u32) void {
+pub fn encodeHeader(buf: *[HEADER_SIZE]u8, payload_len: u32, version: u8) void {
     buf[0] = MAGIC_0;
     buf[1] = MAGIC_1;
-    buf[2] = PROTOCOL_VERSION;
+    buf[2] = version;
     std.mem.writeInt(u32, buf[3..7], payload_len, .little);
 }
 
 /// Validate a 7-byte header. Returns payload length or error.
 pub fn decodeHeader(buf: *const [HEADER_SIZE]u8) !u32 {
     if (buf[0] != MAGIC_0 or buf[1] != MAGIC_1) return error.InvalidMagic;
-    if (buf[2] != PROTOCOL_VERSION) return error.UnsupportedVersion;
+    if (buf[2] < MIN_PROTOCOL_VERSION or buf[2] > PROTOCOL_VERSION) return error.UnsupportedVersion;
     const len = std.mem.readInt(u32, buf[3..7], .little);
     if (len > MAX_PAYLOAD_SIZE) return error.PayloadTooLarge;
     return len;
@@ -112,6 +128,12 @@ pub fn decodeResponse(data: []const u8) !Response {
 // === Payload encoding helpers ===
 // Simple binary format: LE integers, length-prefixed strings (u32 len + bytes)
 
+pub fn readU16(buf: []const u8, offset: usize) struct { val: u16, next: usize } {
+    if (offset + 2 > buf.len) return .{ .val = 0, .next = offset };
+    const val = std.mem.readInt(u16, buf[offset..][0..2], .little);
+    return .{ .val = val, .next = offset + 2 };
+}
+
 pub fn writeU32(buf: []u8, offset: usize, val: u32) usize {
     std.mem.writeInt(u32, buf[offset..][0..4], val, .little);
     return offset + 4;
@@ -158,6 +180,19 @@ pub fn readU8(buf: []const u8, offset: usize) struct { val: u8, next: usize } {
     return .{ .val = buf[offset], .next = offset + 1 };
 }
 
+/// Settle a HELLO: `payload` is count(u8) + versions(u8 * count), the
+/// versions a client speaks. Picks the highest one within `min`..`max`.
+pub fn negotiateVersion(payload: []const u8, min: u8, max: u8) error{NoCommonVersion}!u8 {
+    const count_r = readU8(payload, 0);
+    var best: ?u8 = null;
+    var i: usize = 0;
+    while (i < count_r.val and count_r.next + i < payload.len) : (i += 1) {
+        const v = payload[count_r.next + i];
+        if (v >= min and v <= max and (best == null or v > best.?)) best = v;
+    }
+    return best orelse error.NoCommonVersion;
+}
+
 pub fn writeI32(buf: []u8, offset: usize, val: i32) usize {
     std.mem.writeInt(i32, buf[offset..][0..4], val, .little);
     return offset + 4;
@@ -170,18 +205,37 @@ pub fn readI32(buf: []const u8, offset: usize) struct { val: i32, next: usize }
 }
 
 /// Build a full wire message: header + encoded request/response
-pub fn buildMessage(allocator: std.mem.Allocator, payload: []const u8) ![]u8 {
+pub fn buildMessage(allocator: std.mem.Allocator, payload: []const u8, version: u8) ![]u8 {
     const msg = try allocator.alloc(u8, HEADER_SIZE + payload.len);
-    encodeHeader(msg[0..HEADER_SIZE], @intCast(payload.len));
+    encodeHeader(msg[0..HEADER_SIZE], @intCast(payload.len), version);
     @memcpy(msg[HEADER_SIZE..], payload);
     return msg;
 }
 
 test "header encode/decode roundtrip" {
     var hdr: [HEADER_SIZE]u8 = undefined;
-    encodeHeader(&hdr, 42);
+    encodeHeader(&hdr, 42, PROTOCOL_VERSION);
     const len = try decodeHeader(&hdr);
     try std.testing.expectEqual(@as(u32, 42), len);
+
+    encodeHeader(&hdr, 42, MIN_PROTOCOL_VERSION);
+    try std.testing.expectEqual(@as(u32, 42), try decodeHeader(&hdr));
+    encodeHeader(&hdr, 42, PROTOCOL_VERSION + 1);
+    try std.testing.expectError(error.UnsupportedVersion, decodeHeader(&hdr));
+}
+
+test "negotiateVersion picks the highest common version" {
+    // Client range reaching above ours: our highest
+    try std.testing.expectEqual(@as(u8, 2), try negotiateVersion(&[_]u8{ 3, 1, 2, 3 }, 1, 2));
+    // Client range below ours: its highest we still speak
+    try std.testing.expectEqual(@as(u8, 2), try negotiateVersion(&[_]u8{ 2, 1, 2 }, 2, 4));
+    try std.testing.expectEqual(@as(u8, 1), try negotiateVersion(&[_]u8{ 1, 1 }, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION));
+    // No overlap, or nothing offered
+    try std.testing.expectError(error.NoCommonVersion, negotiateVersion(&[_]u8{ 2, 3, 4 }, 1, 2));
+    try std.testing.expectError(error.NoCommonVersion, negotiateVersion(&[_]u8{ 1, 1 }, 2, 4));
+    try std.testing.expectError(error.NoCommonVersion, negotiateVersion(&[_]u8{0}, 1, 2));
+    // A count past the end of the payload reads only what is there
+    try std.testing.expectEqual(@as(u8, 1), try negotiateVersion(&[_]u8{ 5, 1 }, 1, 2));
 }
 
 test "request encode/decode roundtrip" {
@@ -6,8 +6,12 @@ const Supervisor = @import("../process/supervisor.zig").Supervisor;
 const ProcessConfig = @import("../process/supervisor.zig").ProcessConfig;
 const ProcessInfo = @import("../process/supervisor.zig").ProcessInfo;
 const ProcessStatus = @import("../process/supervisor.zig").ProcessStatus;
+const formatInstanceName = @import("../process/supervisor.zig").formatInstanceName;
+const ExitCodeSet = @import("../process/supervisor.zig").ExitCodeSet;
+const defaultSuccessCodes = @import("../process/supervisor.zig").defaultSuccessCodes;
 const LogCollector = @import("../log/collector.zig").LogCollector;
 const Persistence = @import("../state/persistence.zig").Persistence;
+const freeConfig = @import("../state/persistence.zig").freeConfig;
 
 pub const IpcServer = struct {
     const Self = @This();
@@ -17,6 +21,7 @@ pub const IpcServer = struct {
     const ClientState = struct {
         fd: posix.fd_t,
         recv_buf: std.ArrayList(u8),
+        version: u8 = protocol.MIN_PROTOCOL_VERSION, // settled by HELLO
     };
 
     listen_fd: posix.fd_t,
@@ -28,6 +33,8 @@ pub const IpcServer = struct {
     persistence: ?*Persistence,
     allocator: std.mem.Allocator,
     shutdown_requested: bool,
+    daemon_version: []const u8, // reported in the ping reply; empty = unknown
+    started_ms: u64, // when the daemon started listening, for the uptime in ping replies
 
     pub fn init(
         allocator: std.mem.Allocator,
@@ -64,6 +71,8 @@ pub const IpcServer = struct {
             .persistence = null,
             .allocator = allocator,
             .shutdown_requested = false,
+            .daemon_version = "",
+            .started_ms = @intCast(std.time.milliTimestamp()),
         };
     }
 
@@ -159,26 +168,78 @@ pub const IpcServer = struct {
     fn handleRequest(self: *Self, client_fd: posix.fd_t, request: protocol.Request) !void {
         switch (request.command) {
             .ping => try self.handlePing(client_fd, request.id),
+            .hello => try self.handleHello(client_fd, request),
             .process_start => try self.handleProcessStart(client_fd, request),
             .process_stop => try self.handleProcessStop(client_fd, request),
             .process_restart => try self.handleProcessRestart(client_fd, request),
+            .process_reset => try self.handleProcessReset(client_fd, request),
             .process_delete => try self.handleProcessDelete(client_fd, request),
             .process_list => try self.handleProcessList(client_fd, request.id),
             .process_info => try self.handleProcessInfo(client_fd, request),
             .process_scale => try self.handleProcessScale(client_fd, request),
+            .process_history => try self.handleProcessHistory(client_fd, request),
+            .event_stream => try self.handleEventStream(client_fd, request),
             .log_read => try self.handleLogRead(client_fd, request),
             .state_save => try self.handleStateSave(client_fd, request.id),
             .state_load => try self.handleStateLoad(client_fd, request.id),
             .shutdown => try self.handleShutdown(client_fd, request.id),
+            .daemon_info => try self.handleDaemonInfo(client_fd, request.id),
             _ => try self.sendError(client_fd, request.id, "unknown command"),
         }
     }
 
     fn handlePing(self: *Self, client_fd: posix.fd_t, req_id: u32) !void {
-        const payload = "pong";
+        // "pong" or "pong <version> <uptime_ms>"; older clients print it as-is
+        if (self.daemon_version.len == 0) {
+            try self.sendResponse(client_fd, req_id, .ok, "pong");
+            return;
+        }
+        const now: u64 = @intCast(std.time.milliTimestamp());
+        var buf: [96]u8 = undefined;
+        const payload = std.fmt.bufPrint(&buf, "pong {s} {d}", .{ self.daemon_version, now -| self.started_ms }) catch "pong";
         try self.sendResponse(client_fd, req_id, .ok, payload);
     }
 
+    fn handleDaemonInfo(self: *Self, client_fd: posix.fd_t, req_id: u32) !void {
+        // Response: socket_path(string) + state_dir(string) + version(string)
+        //   + started_ms(u64) + pid(u32) + max_processes(u32, 0 = unlimited)
+        //   + max_clients(u32). New fields are appended; clients ignore extra bytes.
+        var buf: std.ArrayList(u8) = .{};
+        defer buf.deinit(self.allocator);
+        var tmp: [8]u8 = undefined;
+
+        const state_dir = if (self.persistence) |p| p.state_dir else "";
+        for ([_][]const u8{ self.socket_path, state_dir, self.daemon_version }) |s| {
+            std.mem.writeInt(u32, tmp[0..4], @intCast(s.len), .little);
+            try buf.appendSlice(self.allocator, tmp[0..4]);
+            try buf.appendSlice(self.allocator, s);
+        }
+        std.mem.writeInt(u64, tmp[0..8], self.started_ms, .little);
+        try buf.appendSlice(self.allocator, tmp[0..8]);
+        std.mem.writeInt(u32, tmp[0..4], @intCast(std.c.getpid()), .little);
+        try buf.appendSlice(self.allocator, tmp[0..4]);
+        // The supervisor has no process cap
+        std.mem.writeInt(u32, tmp[0..4], 0, .little);
+        try buf.appendSlice(self.allocator, tmp[0..4]);
+        std.mem.writeInt(u32, tmp[0..4], MAX_CLIENTS, .little);
+        try buf.appendSlice(self.allocator, tmp[0..4]);
+
+        try self.sendResponse(client_fd, req_id, .ok, buf.items);
+    }
+
+    fn handleHello(self: *Self, client_fd: posix.fd_t, request: protocol.Request) !void {
+        // Payload: count(u8) + versions(u8 * count)
+        // Response: negotiated version(u8) — the highest version both sides support.
+        const version = protocol.negotiateVersion(request.payload, protocol.MIN_PROTOCOL_VERSION, protocol.PROTOCOL_VERSION) catch {
+            try self.sendError(client_fd, request.id, "no common protocol version");
+            return;
+        };
+        // The reply already goes out under the new version
+        if (self.clients.get(client_fd)) |client| client.version = version;
+        const resp = [1]u8{version};
+        try self.sendResponse(client_fd, request.id, .ok, &resp);
+    }
+
     fn handleProcessStart(self: *Self, client_fd: posix.fd_t, request: protocol.Request) !void {
         // Parse config from payload:
         // name(string) + script(string) + cwd(string) + interpreter(string, empty=null)
@@ -241,6 +302,66 @@ pub const IpcServer = struct {
         // env_vars (newline-separated KEY=VALUE pairs)
         const has_extended4 = off < data.len;
         const env_vars_r = protocol.readString(data, off);
+        off = env_vars_r.next;
+
+        // instance_name_template (string, empty = "{name}:{index}")
+        const has_extended5 = off < data.len;
+        const name_tmpl_r = protocol.readString(data, off);
+        off = name_tmpl_r.next;
+
+        // out_log(string) + err_log(string) + merge_logs(u8); empty path = default location
+        const has_extended6 = off < data.len;
+        const out_log_r = protocol.readString(data, off);
+        off = out_log_r.next;
+        const err_log_r = protocol.readString(data, off);
+        off = err_log_r.next;
+        const merge_logs_r = protocol.readU8(data, off);
+        off = merge_logs_r.next;
+
+        // args: count(u32) + [string]...; kept wire-encoded, expanded into argv at spawn
+        const has_extended7 = off < data.len;
+        const args_start = off;
+        const args_count_r = protocol.readU32(data, off);
+        off = args_count_r.next;
+        var arg_i: u32 = 0;
+        while (has_extended7 and arg_i < args_count_r.val and off < data.len) : (arg_i += 1) {
+            off = protocol.readString(data, off).next;
+        }
+        const args_end = @min(off, data.len);
+
+        // group (string, empty = ungrouped)
+        const has_extended8 = off < data.len;
+        const group_r = protocol.readString(data, off);
+        off = group_r.next;
+
+        // log_rate_limit (u32 lines/sec, 0 = unlimited)
+        const has_extended9 = off < data.len;
+        const rate_limit_r = protocol.readU32(data, off);
+        off = rate_limit_r.next;
+
+        // stop_on_success (u8) + success_exit_codes: count(u32) + [i32]...; absent = {0}
+        const has_extended10 = off < data.len;
+        const stop_on_success_r = protocol.readU8(data, off);
+        off = stop_on_success_r.next;
+        const codes_count_r = protocol.readU32(data, off);
+        off = codes_count_r.next;
+        var success_codes = defaultSuccessCodes();
+        if (has_extended10 and codes_count_r.val > 0) {
+            success_codes = ExitCodeSet.initEmpty();
+            var code_i: u32 = 0;
+            while (code_i < codes_count_r.val and off + 4 <= data.len) : (code_i += 1) {
+                const code_r = protocol.readI32(data, off);
+                off = code_r.next;
+                if (code_r.val >= 0 and code_r.val <= 255) success_codes.set(@intCast(code_r.val));
+            }
+        }
+
+        // max_cpu_restart (u16, CPU% * 10, 0 = unlimited) + cpu_restart_window_secs (u32)
+        const has_extended11 = off + 6 <= data.len;
+        const max_cpu_r = protocol.readU16(data, off);
+        off = max_cpu_r.next;
+        const cpu_window_r = protocol.readU32(data, off);
+        off = cpu_window_r.next;
 
         const config = ProcessConfig{
             .name = name_r.val,
@@ -264,14 +385,34 @@ pub const IpcServer = struct {
             .shutdown_with_message = if (has_extended2) shutdown_msg_r.val != 0 else false,
             .instances = instances,
             .env_vars = if (has_extended4 and env_vars_r.val.len > 0) env_vars_r.val else null,
+            .instance_name_template = if (has_extended5 and name_tmpl_r.val.len > 0) name_tmpl_r.val else null,
+            .out_log = if (has_extended6 and out_log_r.val.len > 0) out_log_r.val else null,
+            .err_log = if (has_extended6 and err_log_r.val.len > 0) err_log_r.val else null,
+            .merge_logs = if (has_extended6) merge_logs_r.val != 0 else false,
+            .args = if (has_extended7 and args_count_r.val > 0) data[args_start..args_end] else null,
+            .group = if (has_extended8 and group_r.val.len > 0) group_r.val else null,
+            .log_rate_limit = if (has_extended9) rate_limit_r.val else 0,
+            .success_exit_codes = success_codes,
+            .stop_on_success = if (has_extended10) stop_on_success_r.val != 0 else false,
+            .max_cpu_restart = if (has_extended11) max_cpu_r.val else 0,
+            .cpu_restart_window_ms = if (has_extended11 and cpu_window_r.val > 0) @as(u64, cpu_window_r.val) * 1000 else 60_000,
         };
 
+        if (!scriptExists(config.script, config.cwd)) {
+            try self.sendCodedError(client_fd, request.id, .script_not_found, config.script);
+            return;
+        }
+        if (self.supervisor.findByName(name_r.val) != null) {
+            try self.sendCodedError(client_fd, request.id, .name_in_use, name_r.val);
+            return;
+        }
+
         if (instances > 1) {
             // Cluster mode: start N instances
             var first_id: u32 = 0;
             var i: u32 = 0;
             while (i < instances) : (i += 1) {
-                const inst_name = std.fmt.allocPrint(self.allocator, "{s}:{d}", .{ name_r.val, i }) catch {
+                const inst_name = formatInstanceName(self.allocator, config.instance_name_template, name_r.val, i) catch {
                     try self.sendError(client_fd, request.id, "OutOfMemory");
                     return;
                 };
@@ -281,9 +422,14 @@ pub const IpcServer = struct {
                 inst_config.name = inst_name;
                 inst_config.instance_id = i;
 
+                if (i == 0 and self.supervisor.findByName(inst_name) != null) {
+                    try self.sendCodedError(client_fd, request.id, .name_in_use, inst_name);
+                    return;
+                }
+
                 const result = self.supervisor.startProcess(inst_config) catch |err| {
                     if (i == 0) {
-                        try self.sendError(client_fd, request.id, @errorName(err));
+                        try self.sendCodedError(client_fd, request.id, .spawn_failed, @errorName(err));
                         return;
                     }
                     continue;
@@ -301,7 +447,7 @@ pub const IpcServer = struct {
         } else {
             // Fork mode: single instance (existing behavior)
             const result = self.supervisor.startProcess(config) catch |err| {
-                try self.sendError(client_fd, request.id, @errorName(err));
+                try self.sendCodedError(client_fd, request.id, .spawn_failed, @errorName(err));
                 return;
             };
 
@@ -318,7 +464,9 @@ pub const IpcServer = struct {
     }
 
     fn handleProcessStop(self: *Self, client_fd: posix.fd_t, request: protocol.Request) !void {
-        // Payload: process_id(u32) + signal(u8) + timeout_ms(u32)
+        // Payload: process_id(u32) + signal(u8) + timeout_ms(u32) [+ flags(u8)]
+        // flags bit 0x01 = kill tree (signal the whole process group). Older
+        // clients omit the byte, which means no flags.
         const data = request.payload;
         var off: usize = 0;
 
@@ -327,8 +475,11 @@ pub const IpcServer = struct {
         const sig_r = protocol.readU8(data, off);
         off = sig_r.next;
         const timeout_r = protocol.readU32(data, off);
+        off = timeout_r.next;
+        const flags: u8 = if (off < data.len) protocol.readU8(data, off).val else 0;
+        const kill_tree = (flags & 0x01) != 0;
 
-        self.supervisor.stopProcess(id_r.val, sig_r.val, timeout_r.val) catch |err| {
+        self.supervisor.stopProcess(id_r.val, sig_r.val, timeout_r.val, kill_tree) catch |err| {
             try self.sendError(client_fd, request.id, @errorName(err));
             return;
         };
@@ -398,6 +549,16 @@ pub const IpcServer = struct {
             try buf.appendSlice(self.allocator, tmp[0..2]);
         }
 
+        // groups: one string per process, same order (after the records so
+        // older clients that stop at `count` records are unaffected)
+        for (procs) |proc| {
+            const group = proc.config.group orelse "";
+            var len_buf: [4]u8 = undefined;
+            std.mem.writeInt(u32, &len_buf, @intCast(group.len), .little);
+            try buf.appendSlice(self.allocator, &len_buf);
+            try buf.appendSlice(self.allocator, group);
+        }
+
         try self.sendResponse(client_fd, req_id, .ok, buf.items);
     }
 
@@ -416,7 +577,9 @@ pub const IpcServer = struct {
         };
         defer self.log_collector.freeEntries(entries);
 
-        // Encode: count(u32) + [timestamp(u64) + level(u8) + stream(u8) + message(string)]...
+        // Encode: count(u32) + [timestamp(u64) + level(u8) + stream(u8) + message(string) + seq(u64)]... + suppressed(u64)
+        // seq only for clients that settled on VERSION_LOG_SEQ or later
+        const with_seq = self.clientVersion(client_fd) >= protocol.VERSION_LOG_SEQ;
         var buf: std.ArrayList(u8) = .{};
         defer buf.deinit(self.allocator);
 
@@ -439,8 +602,18 @@ pub const IpcServer = struct {
             std.mem.writeInt(u32, tmp[0..4], @intCast(entry.message.len), .little);
             try buf.appendSlice(self.allocator, tmp[0..4]);
             try buf.appendSlice(self.allocator, entry.message);
+
+            // seq
+            if (with_seq) {
+                std.mem.writeInt(u64, tmp[0..8], entry.seq, .little);
+                try buf.appendSlice(self.allocator, tmp[0..8]);
+            }
         }
 
+        // suppressed: lines dropped by log_rate_limit since the process started
+        std.mem.writeInt(u64, tmp[0..8], self.log_collector.suppressedCount(id_r.val), .little);
+        try buf.appendSlice(self.allocator, tmp[0..8]);
+
         try self.sendResponse(client_fd, request.id, .ok, buf.items);
     }
 
@@ -468,6 +641,93 @@ pub const IpcServer = struct {
         try self.sendResponse(client_fd, request.id, .ok, &[_]u8{});
     }
 
+    fn handleProcessReset(self: *Self, client_fd: posix.fd_t, request: protocol.Request) !void {
+        // Payload: process_id(u32)
+        const data = request.payload;
+        const id_r = protocol.readU32(data, 0);
+
+        self.supervisor.resetCounters(id_r.val) catch |err| {
+            try self.sendError(client_fd, request.id, @errorName(err));
+            return;
+        };
+        try self.sendResponse(client_fd, request.id, .ok, &[_]u8{});
+    }
+
+    fn handleProcessHistory(self: *Self, client_fd: posix.fd_t, request: protocol.Request) !void {
+        // Payload: process_id(u32)
+        // Response: count(u32) + count * [timestamp_ms(u64) + kind(u8) + exit_code(i32)],
+        // oldest first. kind: 0=restart 1=crash 2=oom 3=cron 4=watch 5=manual 6=start 7=stop
+        const data = request.payload;
+        const id_r = protocol.readU32(data, 0);
+
+        const proc = self.supervisor.getProcess(id_r.val) orelse {
+            try self.sendError(client_fd, request.id, "ProcessNotFound");
+            return;
+        };
+
+        var buf: std.ArrayList(u8) = .{};
+        defer buf.deinit(self.allocator);
+
+        var tmp: [8]u8 = undefined;
+
+        std.mem.writeInt(u32, tmp[0..4], proc.history_len, .little);
+        try buf.appendSlice(self.allocator, tmp[0..4]);
+
+        var i: u32 = 0;
+        while (i < proc.history_len) : (i += 1) {
+            const ev = proc.historyAt(i);
+            std.mem.writeInt(u64, tmp[0..8], ev.timestamp_ms, .little);
+            try buf.appendSlice(self.allocator, tmp[0..8]);
+            try buf.append(self.allocator, @intFromEnum(ev.kind));
+            std.mem.writeInt(i32, tmp[0..4], ev.exit_code, .little);
+            try buf.appendSlice(self.allocator, tmp[0..4]);
+        }
+
+        try self.sendResponse(client_fd, request.id, .ok, buf.items);
+    }
+
+    fn handleEventStream(self: *Self, client_fd: posix.fd_t, request: protocol.Request) !void {
+        // Payload: since_ms(u64), optional (absent = 0 = everything still held)
+        // Response: count(u32) + count * [timestamp_ms(u64) + process_id(u32) + name(string)
+        //   + kind(u8) + exit_code(i32)] for every process's history events newer than
+        // since_ms. Unordered across processes; clients sort by timestamp and poll
+        // again with the newest timestamp they have seen to follow the feed.
+        const data = request.payload;
+        const since_ms: u64 = if (data.len >= 8) protocol.readU64(data, 0).val else 0;
+
+        var buf: std.ArrayList(u8) = .{};
+        defer buf.deinit(self.allocator);
+
+        try buf.appendNTimes(self.allocator, 0, 4);
+        var count: u32 = 0;
+        var tmp: [8]u8 = undefined;
+
+        var it = self.supervisor.processes.valueIterator();
+        while (it.next()) |proc_ptr| {
+            const proc = proc_ptr.*;
+            var i: u32 = 0;
+            while (i < proc.history_len) : (i += 1) {
+                const ev = proc.historyAt(i);
+                if (ev.timestamp_ms <= since_ms) continue;
+
+                std.mem.writeInt(u64, tmp[0..8], ev.timestamp_ms, .little);
+                try buf.appendSlice(self.allocator, tmp[0..8]);
+                std.mem.writeInt(u32, tmp[0..4], proc.id, .little);
+                try buf.appendSlice(self.allocator, tmp[0..4]);
+                std.mem.writeInt(u32, tmp[0..4], @intCast(proc.name.len), .little);
+                try buf.appendSlice(self.allocator, tmp[0..4]);
+                try buf.appendSlice(self.allocator, proc.name);
+                try buf.append(self.allocator, @intFromEnum(ev.kind));
+                std.mem.writeInt(i32, tmp[0..4], ev.exit_code, .little);
+                try buf.appendSlice(self.allocator, tmp[0..4]);
+                count += 1;
+            }
+        }
+        std.mem.writeInt(u32, buf.items[0..4], count, .little);
+
+        try self.sendResponse(client_fd, request.id, .ok, buf.items);
+    }
+
     fn handleProcessInfo(self: *Self, client_fd: posix.fd_t, request: protocol.Request) !void {
         // Payload: process_id(u32)
         const data = request.payload;
@@ -568,6 +828,33 @@ pub const IpcServer = struct {
         try buf.append(self.allocator, if (proc.config.wait_ready) @as(u8, 1) else 0);
         // shutdown_with_message
         try buf.append(self.allocator, if (proc.config.shutdown_with_message) @as(u8, 1) else 0);
+        // last_exit_reason (0=none 1=crashed 2=oom 3=manual 4=signal)
+        try buf.append(self.allocator, @intFromEnum(proc.last_exit_reason));
+        // log_rate_limit (lines/sec, 0 = unlimited)
+        std.mem.writeInt(u32, tmp[0..4], proc.config.log_rate_limit, .little);
+        try buf.appendSlice(self.allocator, tmp[0..4]);
+        // suppressed_lines (dropped by the rate limit)
+        std.mem.writeInt(u64, tmp[0..8], self.log_collector.suppressedCount(proc.id), .little);
+        try buf.appendSlice(self.allocator, tmp[0..8]);
+        // merge_logs
+        try buf.append(self.allocator, if (proc.config.merge_logs) @as(u8, 1) else 0);
+        // max_cpu_restart (CPU% * 10) + cpu_restart_window_secs
+        std.mem.writeInt(u16, tmp[0..2], proc.config.max_cpu_restart, .little);
+        try buf.appendSlice(self.allocator, tmp[0..2]);
+        std.mem.writeInt(u32, tmp[0..4], @intCast(proc.config.cpu_restart_window_ms / 1000), .little);
+        try buf.appendSlice(self.allocator, tmp[0..4]);
+        // out_log + err_log (empty = <log_dir>/<name>-out.log / -err.log)
+        for ([_]?[]const u8{ proc.config.out_log, proc.config.err_log }) |path| {
+            const log_path = path orelse "";
+            std.mem.writeInt(u32, tmp[0..4], @intCast(log_path.len), .little);
+            try buf.appendSlice(self.allocator, tmp[0..4]);
+            if (log_path.len > 0) try buf.appendSlice(self.allocator, log_path);
+        }
+        // env_vars (newline-separated KEY=VALUE pairs, as given at start)
+        const env_vars = proc.config.env_vars orelse "";
+        std.mem.writeInt(u32, tmp[0..4], @intCast(env_vars.len), .little);
+        try buf.appendSlice(self.allocator, tmp[0..4]);
+        if (env_vars.len > 0) try buf.appendSlice(self.allocator, env_vars);
 
         try self.sendResponse(client_fd, request.id, .ok, buf.items);
     }
@@ -638,16 +925,7 @@ pub const IpcServer = struct {
             return;
         };
         defer {
-            for (configs) |cfg| {
-                self.allocator.free(cfg.name);
-                self.allocator.free(cfg.script);
-                self.allocator.free(cfg.cwd);
-                if (cfg.interpreter) |interp| self.allocator.free(interp);
-                if (cfg.watch_paths) |wp| self.allocator.free(wp);
-                if (cfg.watch_ignore) |wi| self.allocator.free(wi);
-                if (cfg.cron_restart) |cr| self.allocator.free(cr);
-                if (cfg.env_vars) |ev| self.allocator.free(ev);
-            }
+            for (configs) |cfg| freeConfig(self.allocator, cfg);
             self.allocator.free(configs);
         }
 
@@ -679,16 +957,31 @@ pub const IpcServer = struct {
         const resp_data = try protocol.encodeResponse(self.allocator, resp);
         defer self.allocator.free(resp_data);
 
-        const msg = try protocol.buildMessage(self.allocator, resp_data);
+        const msg = try protocol.buildMessage(self.allocator, resp_data, self.clientVersion(client_fd));
         defer self.allocator.free(msg);
 
         _ = posix.write(client_fd, msg) catch {};
     }
 
+    /// Protocol version settled with this client (the base version until HELLO).
+    fn clientVersion(self: *Self, client_fd: posix.fd_t) u8 {
+        const client = self.clients.get(client_fd) orelse return protocol.MIN_PROTOCOL_VERSION;
+        return client.version;
+    }
+
     fn sendError(self: *Self, client_fd: posix.fd_t, req_id: u32, err_msg: []const u8) !void {
         try self.sendResponse(client_fd, req_id, .err, err_msg);
     }
 
+    /// Error response carrying a structured code: code(u8) + message.
+    fn sendCodedError(self: *Self, client_fd: posix.fd_t, req_id: u32, code: protocol.ErrorCode, err_msg: []const u8) !void {
+        const payload = try self.allocator.alloc(u8, 1 + err_msg.len);
+        defer self.allocator.free(payload);
+        payload[0] = @intFromEnum(code);
+        @memcpy(payload[1..], err_msg);
+        try self.sendResponse(client_fd, req_id, .err, payload);
+    }
+
     /// Remove and clean up a client connection
     pub fn removeClient(self: *Self, fd: posix.fd_t) void {
         self.event_loop.removeFd(fd);
@@ -711,7 +1004,69 @@ pub const IpcServer = struct {
         self.persistence = p;
     }
 
+    pub fn setDaemonVersion(self: *Self, version: []const u8) void {
+        self.daemon_version = version;
+    }
+
     pub fn isShutdownRequested(self: *Self) bool {
         return self.shutdown_requested;
     }
 };
+
+/// Whether `script` can be found. Names with a '/' are paths, absolute or
+/// relative to `cwd`. Bare names (e.g. "app.js", "npm", "python3.11") may
+/// be a file in `cwd` handed to an interpreter, or a command on PATH.
+fn scriptExists(script: []const u8, cwd: []const u8) bool {
+    return scriptExistsIn(script, cwd, std.posix.getenv("PATH") orelse "");
+}
+
+fn scriptExistsIn(script: []const u8, cwd: []const u8, path_env: []const u8) bool {
+    if (std.fs.path.isAbsolute(script)) {
+        std.fs.accessAbsolute(script, .{}) catch return false;
+        return true;
+    }
+    if (existsIn(cwd, script)) return true;
+    if (std.mem.indexOfScalar(u8, script, '/') != null) return false;
+
+    var it = std.mem.tokenizeScalar(u8, path_env, ':');
+    while (it.next()) |path_dir| {
+        if (existsIn(path_dir, script)) return true;
+    }
+    return false;
+}
+
+fn existsIn(dir_path: []const u8, name: []const u8) bool {
+    // dir_path may be relative to the daemon's own cwd (the API defaults cwd to ".")
+    var dir = std.fs.cwd().openDir(dir_path, .{}) catch return false;
+    defer dir.close();
+    dir.access(name, .{}) catch return false;
+    return true;
+}
+
+test "scriptExists with a relative cwd" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    try tmp.dir.writeFile(.{ .sub_path = "app.js", .data = "" });
+
+    // testing.tmpDir lives under .zig-cache/tmp in the current directory
+    var buf: [std.fs.max_path_bytes]u8 = undefined;
+    const cwd = try std.fmt.bufPrint(&buf, ".zig-cache/tmp/{s}", .{tmp.sub_path});
+    try std.testing.expect(scriptExists("app.js", cwd));
+    try std.testing.expect(scriptExists("./app.js", cwd));
+    try std.testing.expect(!scriptExists("missing.js", cwd));
+    try std.testing.expect(!scriptExists("app.js", "."));
+    try std.testing.expect(scriptExists("sh", "."));
+}
+
+test "scriptExists resolves dotted bare names through PATH" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    try tmp.dir.writeFile(.{ .sub_path = "python3.11", .data = "" });
+
+    var buf: [std.fs.max_path_bytes]u8 = undefined;
+    const bin = try std.fmt.bufPrint(&buf, ".zig-cache/tmp/{s}", .{tmp.sub_path});
+    try std.testing.expect(scriptExistsIn("python3.11", ".", bin));
+    try std.testing.expect(!scriptExistsIn("python3.12", ".", bin));
+    // Only bare names are looked up on PATH
+    try std.testing.expect(!scriptExistsIn("./python3.11", ".", bin));
+}
@@ -83,6 +83,7 @@ var g_socket_path: ?[]u8 = null;
 var g_running: bool = false;
 var g_initialized: bool = false;
 var g_notify_binary: ?[]u8 = null;
+var g_version: ?[]u8 = null;
 
 // ============================================================
 // C ABI exports
@@ -107,6 +108,20 @@ export fn velos_set_notify_binary(path_c: ?[*:0]const u8) void {
     }
 }
 
+/// Set the version string the daemon reports in ping replies
+/// (the embedding binary's package version).
+export fn velos_set_version(version_c: ?[*:0]const u8) void {
+    if (g_version) |old| g_allocator.free(old);
+    g_version = if (version_c) |v|
+        g_allocator.dupe(u8, std.mem.span(v)) catch null
+    else
+        null;
+
+    if (g_ipc_server) |*server| {
+        server.setDaemonVersion(g_version orelse "");
+    }
+}
+
 /// Initialize the daemon. Sets up directories, event loop, IPC server, supervisor.
 export fn velos_daemon_init(
     socket_path_c: ?[*:0]const u8,
@@ -311,7 +326,7 @@ export fn velos_process_stop(process_id: u32, signal: c_int, timeout_ms: u32) c_
     const sig: u8 = if (signal == 0) signals_mod.SIGTERM else @intCast(signal);
     const timeout = if (timeout_ms == 0) @as(u32, 5000) else timeout_ms;
 
-    g_supervisor.?.stopProcess(process_id, sig, timeout) catch return -2;
+    g_supervisor.?.stopProcess(process_id, sig, timeout, false) catch return -2;
     return 0;
 }
 
@@ -476,12 +491,7 @@ export fn velos_state_load() c_int {
 
     const configs = p.loadState() catch return -2;
     defer {
-        for (configs) |cfg| {
-            g_allocator.free(cfg.name);
-            g_allocator.free(cfg.script);
-            g_allocator.free(cfg.cwd);
-            if (cfg.interpreter) |interp| g_allocator.free(interp);
-        }
+        for (configs) |cfg| persistence_mod.freeConfig(g_allocator, cfg);
         g_allocator.free(configs);
     }
 
@@ -512,4 +522,6 @@ comptime {
     _ = watcher_mod;
     _ = cron_mod;
     _ = ipc_channel_mod;
+    _ = server_mod;
+    _ = persistence_mod;
 }
@@ -18,6 +18,14 @@ pub const LogCollector = struct {
         log_max_size: u64 = 10 * 1024 * 1024,
         log_retain_count: u32 = 30,
         last_error_notify_ms: u64 = 0, // debounce for error notifications
+        out_path: ?[]const u8 = null, // owned; null = <log_dir>/<name>-out.log
+        err_path: ?[]const u8 = null, // owned; null = <log_dir>/<name>-err.log
+        merge_logs: bool = false, // stderr lines go to the stdout log
+        rate_limit: u32 = 0, // max lines per second, 0 = unlimited
+        rate_window_start_ms: u64 = 0,
+        rate_window_lines: u32 = 0,
+        rate_window_dropped: u64 = 0, // dropped in the current window, reported by a marker line
+        suppressed_total: u64 = 0, // dropped since the process was added
     };
 
     processes: std.AutoHashMap(u32, *ProcessLog), // process_id -> log state
@@ -52,6 +60,8 @@ pub const LogCollector = struct {
             const proc_log = proc_log_ptr.*;
             proc_log.ring.deinit();
             self.allocator.free(proc_log.name);
+            if (proc_log.out_path) |op| self.allocator.free(op);
+            if (proc_log.err_path) |ep| self.allocator.free(ep);
             self.allocator.destroy(proc_log);
         }
         self.processes.deinit();
@@ -101,6 +111,8 @@ pub const LogCollector = struct {
 
         proc_log.ring.deinit();
         self.allocator.free(proc_log.name);
+        if (proc_log.out_path) |op| self.allocator.free(op);
+        if (proc_log.err_path) |ep| self.allocator.free(ep);
         self.allocator.destroy(proc_log);
         _ = self.processes.remove(process_id);
     }
@@ -203,17 +215,28 @@ pub const LogCollector = struct {
         const timestamp_ms = getTimestampMs();
         const level: u8 = if (stream == 1) 3 else 1; // stderr = error, stdout = info
 
-        // Write to ring buffer
-        try proc_log.ring.push(timestamp_ms, level, stream, line);
+        // Rate limit: fixed one-second windows. Lines over the limit are
+        // dropped; the first line of the next window is preceded by a marker.
+        if (proc_log.rate_limit > 0) {
+            if (timestamp_ms -| proc_log.rate_window_start_ms >= 1000) {
+                if (proc_log.rate_window_dropped > 0) {
+                    var marker_buf: [64]u8 = undefined;
+                    const marker = std.fmt.bufPrint(&marker_buf, "({d} lines suppressed)", .{proc_log.rate_window_dropped}) catch "(lines suppressed)";
+                    self.storeLine(proc_log, timestamp_ms, 2, 0, marker) catch {};
+                }
+                proc_log.rate_window_start_ms = timestamp_ms;
+                proc_log.rate_window_lines = 0;
+                proc_log.rate_window_dropped = 0;
+            }
+            if (proc_log.rate_window_lines >= proc_log.rate_limit) {
+                proc_log.rate_window_dropped += 1;
+                proc_log.suppressed_total += 1;
+                return;
+            }
+            proc_log.rate_window_lines += 1;
+        }
 
-        // Write to file with rotation
-        self.writer.writeLineWithRotation(
-            proc_log.name,
-            stream,
-            line,
-            proc_log.log_max_size,
-            proc_log.log_retain_count,
-        ) catch {};
+        try self.storeLine(proc_log, timestamp_ms, level, stream, line);
 
         // Detect error patterns in stderr lines
         if (stream == 1 and self.error_callback != null) {
@@ -226,6 +249,32 @@ pub const LogCollector = struct {
         }
     }
 
+    /// Write one line to the ring buffer and the process's log file.
+    fn storeLine(self: *Self, proc_log: *ProcessLog, timestamp_ms: u64, level: u8, stream: u8, line: []const u8) !void {
+        // Write to ring buffer
+        try proc_log.ring.push(timestamp_ms, level, stream, line);
+
+        // Write to file with rotation (custom path and stream merging if configured)
+        const file_stream: u8 = if (proc_log.merge_logs) 0 else stream;
+        const custom_path = if (file_stream == 0) proc_log.out_path else proc_log.err_path;
+        if (custom_path) |path| {
+            self.writer.writeLineToPath(
+                path,
+                line,
+                proc_log.log_max_size,
+                proc_log.log_retain_count,
+            ) catch {};
+        } else {
+            self.writer.writeLineWithRotation(
+                proc_log.name,
+                file_stream,
+                line,
+                proc_log.log_max_size,
+                proc_log.log_retain_count,
+            ) catch {};
+        }
+    }
+
     /// Check if a stderr line matches common error patterns.
     fn isErrorPattern(line: []const u8) bool {
         // Python
@@ -294,6 +343,28 @@ pub const LogCollector = struct {
         proc_log.log_retain_count = retain_count;
     }
 
+    /// Set custom log file paths (absolute, copied) and stream merging for a process
+    pub fn setLogPaths(self: *Self, process_id: u32, out_path: ?[]const u8, err_path: ?[]const u8, merge_logs: bool) void {
+        const proc_log = self.processes.get(process_id) orelse return;
+        if (proc_log.out_path) |op| self.allocator.free(op);
+        if (proc_log.err_path) |ep| self.allocator.free(ep);
+        proc_log.out_path = if (out_path) |op| self.allocator.dupe(u8, op) catch null else null;
+        proc_log.err_path = if (err_path) |ep| self.allocator.dupe(u8, ep) catch null else null;
+        proc_log.merge_logs = merge_logs;
+    }
+
+    /// Set the log rate limit (lines per second, 0 = unlimited) for a process
+    pub fn setRateLimit(self: *Self, process_id: u32, lines_per_sec: u32) void {
+        const proc_log = self.processes.get(process_id) orelse return;
+        proc_log.rate_limit = lines_per_sec;
+    }
+
+    /// Lines dropped by the rate limit since the process was added
+    pub fn suppressedCount(self: *Self, process_id: u32) u64 {
+        const proc_log = self.processes.get(process_id) orelse return 0;
+        return proc_log.suppressed_total;
+    }
+
     /// Get the fd -> process mapping (for event loop registration)
     pub fn getFdInfo(self: *Self, fd: posix.fd_t) ?FdInfo {
         return self.fd_to_process.get(fd);
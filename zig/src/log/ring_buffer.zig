@@ -10,12 +10,14 @@ pub const RingBuffer = struct {
         level: u8, // 0=debug, 1=info, 2=warn, 3=error
         stream: u8, // 0=stdout, 1=stderr
         message: []const u8, // owned copy
+        seq: u64, // 1-based, increases by one per pushed line
     };
 
     entries: []?Entry,
     capacity: u32,
     head: u32, // next write position
     count: u32, // current number of entries
+    next_seq: u64, // sequence number for the next pushed line
     allocator: std.mem.Allocator,
     mutex: std.Thread.Mutex,
 
@@ -27,6 +29,7 @@ pub const RingBuffer = struct {
             .capacity = capacity,
             .head = 0,
             .count = 0,
+            .next_seq = 1,
             .allocator = allocator,
             .mutex = .{},
         };
@@ -59,7 +62,9 @@ pub const RingBuffer = struct {
             .level = level,
             .stream = stream,
             .message = msg_copy,
+            .seq = self.next_seq,
         };
+        self.next_seq += 1;
 
         self.head = (self.head + 1) % self.capacity;
         if (self.count < self.capacity) {
@@ -91,6 +96,7 @@ pub const RingBuffer = struct {
                 .level = entry.level,
                 .stream = entry.stream,
                 .message = entry.message, // shared reference, valid as long as ring buffer lives
+                .seq = entry.seq,
             };
         }
 
@@ -133,4 +139,7 @@ test "ring buffer wraps around" {
     try std.testing.expectEqualStrings("b", entries[0].message);
     try std.testing.expectEqualStrings("c", entries[1].message);
     try std.testing.expectEqualStrings("d", entries[2].message);
+    // Sequence numbers keep counting past overwritten entries
+    try std.testing.expectEqual(@as(u64, 2), entries[0].seq);
+    try std.testing.expectEqual(@as(u64, 4), entries[2].seq);
 }
@@ -47,9 +47,35 @@ pub const LogWriter = struct {
         const path = try std.fmt.allocPrint(self.allocator, "{s}/{s}{s}", .{ self.log_dir, name, suffix });
         defer self.allocator.free(path);
 
-        // Check if rotation is needed
         const size_key_suffix = if (stream == 0) "-out" else "-err";
         const size_key = try std.fmt.allocPrint(self.allocator, "{s}{s}", .{ name, size_key_suffix });
+        try self.appendWithRotation(path, size_key, line, max_size, retain_count);
+    }
+
+    /// Write line to an explicit absolute path (custom out_log/err_log).
+    /// Size tracking is keyed by the path itself.
+    pub fn writeLineToPath(
+        self: *Self,
+        path: []const u8,
+        line: []const u8,
+        max_size: u64,
+        retain_count: u32,
+    ) !void {
+        const size_key = try self.allocator.dupe(u8, path);
+        try self.appendWithRotation(path, size_key, line, max_size, retain_count);
+    }
+
+    /// Append to `path`, rotating first if it has reached max_size.
+    /// Takes ownership of `size_key`.
+    fn appendWithRotation(
+        self: *Self,
+        path: []const u8,
+        size_key: []const u8,
+        line: []const u8,
+        max_size: u64,
+        retain_count: u32,
+    ) !void {
+        errdefer self.allocator.free(size_key);
 
         var current_size = self.file_sizes.get(size_key) orelse blk: {
             // Get current file size if it exists
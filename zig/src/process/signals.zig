@@ -54,3 +54,8 @@ pub fn sendSignal(pid: posix.pid_t, sig: u8) !void {
         return error.SignalFailed;
     }
 }
+
+/// Send a signal to every process in the group led by `pgid`
+pub fn sendSignalToGroup(pgid: posix.pid_t, sig: u8) !void {
+    return sendSignal(-pgid, sig);
+}
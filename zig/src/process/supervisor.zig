@@ -6,6 +6,7 @@ const LogCollector = @import("../log/collector.zig").LogCollector;
 const Watcher = @import("../watch/watcher.zig").Watcher;
 const CronExpr = @import("../cron/parser.zig").CronExpr;
 const IpcChannel = @import("ipc_channel.zig").IpcChannel;
+const protocol = @import("../ipc/protocol.zig");
 
 pub const ProcessStatus = enum(u8) {
     stopped = 0,
@@ -39,8 +40,60 @@ pub const ProcessConfig = struct {
     instances: u32 = 1, // cluster mode: number of instances
     instance_id: u32 = 0, // this instance's 0-based ID
     env_vars: ?[]const u8 = null, // newline-separated KEY=VALUE pairs
+    instance_name_template: ?[]const u8 = null, // null = "{name}:{index}"
+    out_log: ?[]const u8 = null, // absolute stdout log path, null = <log_dir>/<name>-out.log
+    err_log: ?[]const u8 = null, // absolute stderr log path, null = <log_dir>/<name>-err.log
+    merge_logs: bool = false, // write stderr lines to the stdout log
+    args: ?[]const u8 = null, // script arguments, wire-encoded: count(u32) + [string]...
+    group: ?[]const u8 = null, // logical group for @group targeting, null = ungrouped
+    log_rate_limit: u32 = 0, // max log lines per second, 0 = unlimited (excess is dropped)
+    success_exit_codes: ExitCodeSet = defaultSuccessCodes(), // exit codes that are a clean exit, not a crash
+    stop_on_success: bool = false, // no autorestart after a clean exit
+    max_cpu_restart: u16 = 0, // CPU% * 10 (like cpu_percent), 0 = unlimited
+    cpu_restart_window_ms: u64 = 60_000, // how long CPU must stay above max_cpu_restart
 };
 
+/// One bit per exit status (0-255).
+pub const ExitCodeSet = std.StaticBitSet(256);
+
+pub fn defaultSuccessCodes() ExitCodeSet {
+    var set = ExitCodeSet.initEmpty();
+    set.set(0);
+    return set;
+}
+
+/// Lifecycle event kinds recorded in a process's history (wire: u8).
+pub const LifecycleKind = enum(u8) {
+    restart = 0, // autorestart after a clean exit
+    crash = 1, // non-zero exit or killed by a signal
+    oom = 2, // restarted for exceeding max_memory_restart
+    cron = 3, // cron_restart schedule fired
+    watch = 4, // file watcher detected a change
+    manual = 5, // restart requested over IPC
+    start = 6, // started over IPC (or resurrected)
+    stop = 7, // stop requested over IPC
+    cpu = 8, // restarted for exceeding max_cpu_restart
+};
+
+/// Why a process last exited (wire: u8, trailing byte of PROCESS_INFO).
+pub const ExitReason = enum(u8) {
+    none = 0,
+    crashed = 1, // exited on its own with a non-zero code
+    oom = 2, // killed for exceeding max_memory_restart
+    manual = 3, // stopped or restarted over IPC
+    signal = 4, // terminated by a signal the daemon did not send
+    cpu = 5, // killed for exceeding max_cpu_restart
+};
+
+pub const LifecycleEvent = struct {
+    timestamp_ms: u64 = 0,
+    kind: LifecycleKind = .restart,
+    exit_code: i32 = 0, // exit code for crashes, negative signal number if signaled
+};
+
+/// Number of lifecycle events kept per process (oldest are overwritten).
+pub const HISTORY_CAPACITY: usize = 32;
+
 pub const ProcessInfo = struct {
     id: u32,
     name: []const u8, // owned
@@ -48,17 +101,41 @@ pub const ProcessInfo = struct {
     status: ProcessStatus,
     memory_bytes: u64,
     cpu_percent: u16 = 0, // CPU% * 10 (e.g. 253 = 25.3%)
+    cpu_over_since_ms: u64 = 0, // when cpu_percent first went above max_cpu_restart, 0 = not above
     uptime_ms: u64,
     restart_count: u32,
     start_time_ms: u64,
     config: ProcessConfig, // stored config (strings owned)
     consecutive_crashes: u32 = 0,
     last_restart_ms: u64 = 0,
+    last_exit_reason: ExitReason = .none,
+    exit_expected: bool = false, // set by stopProcess so the reap isn't counted as a crash
+    kill_tree: bool = false, // stop signals go to the whole process group, not just pid
     instance_id: u32 = 0, // cluster instance ID
     last_crash_notify_ms: u64 = 0, // debounce for crash notifications
     // For CPU% delta calculation
     prev_cpu_time_ns: u64 = 0,
     prev_wall_time_ns: u64 = 0,
+    // Bounded lifecycle history (ring buffer)
+    history: [HISTORY_CAPACITY]LifecycleEvent = [_]LifecycleEvent{.{}} ** HISTORY_CAPACITY,
+    history_len: u32 = 0,
+    history_next: u32 = 0,
+
+    pub fn recordEvent(self: *ProcessInfo, kind: LifecycleKind, exit_code: i32) void {
+        self.history[self.history_next] = .{
+            .timestamp_ms = @intCast(std.time.milliTimestamp()),
+            .kind = kind,
+            .exit_code = exit_code,
+        };
+        self.history_next = @intCast((self.history_next + 1) % HISTORY_CAPACITY);
+        if (self.history_len < HISTORY_CAPACITY) self.history_len += 1;
+    }
+
+    /// Get the i-th event, oldest first (i < history_len).
+    pub fn historyAt(self: *const ProcessInfo, i: u32) LifecycleEvent {
+        const start = (self.history_next + HISTORY_CAPACITY - self.history_len) % HISTORY_CAPACITY;
+        return self.history[(start + i) % HISTORY_CAPACITY];
+    }
 };
 
 pub const Supervisor = struct {
@@ -136,6 +213,11 @@ pub const Supervisor = struct {
             if (proc.config.watch_paths) |p| self.allocator.free(p);
             if (proc.config.watch_ignore) |p| self.allocator.free(p);
             if (proc.config.cron_restart) |c| self.allocator.free(c);
+            if (proc.config.args) |a| self.allocator.free(a);
+            if (proc.config.group) |g| self.allocator.free(g);
+            if (proc.config.instance_name_template) |t| self.allocator.free(t);
+            if (proc.config.out_log) |ol| self.allocator.free(ol);
+            if (proc.config.err_log) |el| self.allocator.free(el);
             self.allocator.destroy(proc);
         }
         self.processes.deinit();
@@ -198,6 +280,9 @@ pub const Supervisor = struct {
         const script_z = try self.allocator.dupeZ(u8, config.script);
         defer self.allocator.free(script_z);
         try argv_list.append(self.allocator, script_z);
+        var args_z: std.ArrayList([:0]u8) = .{};
+        defer freeScriptArgs(self.allocator, &args_z);
+        try appendScriptArgs(self.allocator, &argv_list, &args_z, config.args);
         try argv_list.append(self.allocator, null); // null terminator
 
         const cwd_z = try self.allocator.dupeZ(u8, config.cwd);
@@ -305,16 +390,30 @@ pub const Supervisor = struct {
                 .instances = config.instances,
                 .instance_id = config.instance_id,
                 .env_vars = if (config.env_vars) |ev| try self.allocator.dupe(u8, ev) else null,
+                .instance_name_template = if (config.instance_name_template) |t| try self.allocator.dupe(u8, t) else null,
+                .out_log = if (config.out_log) |ol| try self.allocator.dupe(u8, ol) else null,
+                .err_log = if (config.err_log) |el| try self.allocator.dupe(u8, el) else null,
+                .merge_logs = config.merge_logs,
+                .args = if (config.args) |a| try self.allocator.dupe(u8, a) else null,
+                .group = if (config.group) |g| try self.allocator.dupe(u8, g) else null,
+                .log_rate_limit = config.log_rate_limit,
+                .success_exit_codes = config.success_exit_codes,
+                .stop_on_success = config.stop_on_success,
+                .max_cpu_restart = config.max_cpu_restart,
+                .cpu_restart_window_ms = config.cpu_restart_window_ms,
             },
             .instance_id = config.instance_id,
         };
 
         try self.processes.put(id, proc);
         try self.pid_to_id.put(pid, id);
+        proc.recordEvent(.start, 0);
 
         // Register with log collector
         try self.log_collector.addProcess(id, config.name, stdout_pipe[0], stderr_pipe[0]);
         self.log_collector.setLogConfig(id, config.log_max_size, config.log_retain_count);
+        self.log_collector.setLogPaths(id, config.out_log, config.err_log, config.merge_logs);
+        self.log_collector.setRateLimit(id, config.log_rate_limit);
 
         // Store IPC channel and configure process status
         if (ipc_chan) |ch| {
@@ -337,7 +436,10 @@ pub const Supervisor = struct {
     }
 
     /// Initiate graceful stop: send SIGTERM and set kill deadline.
-    pub fn stopProcess(self: *Self, process_id: u32, sig: u8, timeout_ms: u32) !void {
+    /// With `kill_tree`, signals go to the process group (the child is a
+    /// session leader, so pgid == pid) and any descendants still alive when
+    /// the main process is reaped are SIGKILLed.
+    pub fn stopProcess(self: *Self, process_id: u32, sig: u8, timeout_ms: u32, kill_tree: bool) !void {
         const proc = self.processes.get(process_id) orelse return error.ProcessNotFound;
         if (proc.status != .running and proc.status != .starting) return;
 
@@ -348,8 +450,17 @@ pub const Supervisor = struct {
             }
         }
 
+        proc.last_exit_reason = .manual;
+        proc.exit_expected = true;
+        proc.kill_tree = kill_tree;
+        proc.recordEvent(.stop, 0);
+
         // Send signal
-        signals.sendSignal(proc.pid, sig) catch {};
+        if (kill_tree) {
+            signals.sendSignalToGroup(proc.pid, sig) catch {};
+        } else {
+            signals.sendSignal(proc.pid, sig) catch {};
+        }
 
         if (sig != signals.SIGKILL) {
             // Set SIGKILL deadline
@@ -400,6 +511,11 @@ pub const Supervisor = struct {
         if (proc.config.watch_paths) |p| self.allocator.free(p);
         if (proc.config.watch_ignore) |p| self.allocator.free(p);
         if (proc.config.cron_restart) |c| self.allocator.free(c);
+        if (proc.config.args) |a| self.allocator.free(a);
+        if (proc.config.group) |g| self.allocator.free(g);
+        if (proc.config.instance_name_template) |t| self.allocator.free(t);
+        if (proc.config.out_log) |ol| self.allocator.free(ol);
+        if (proc.config.err_log) |el| self.allocator.free(el);
         self.allocator.destroy(proc);
         _ = self.processes.remove(process_id);
     }
@@ -419,11 +535,31 @@ pub const Supervisor = struct {
             _ = self.pending_kills.remove(process_id);
             _ = self.pid_to_id.remove(reap.pid);
 
+            // Descendants that ignored the stop signal don't outlive the leader
+            if (proc.kill_tree) {
+                signals.sendSignalToGroup(reap.pid, signals.SIGKILL) catch {};
+                proc.kill_tree = false;
+            }
+
             const was_running = proc.status == .running;
-            const abnormal_exit = reap.signaled or reap.exit_code != 0;
+            const success_exit = !reap.signaled and proc.config.success_exit_codes.isSet(reap.exit_code);
+            // A stop we asked for (SIGTERM from stopProcess) is not a crash:
+            // no crash event, no errored status, no crash notification
+            const expected_exit = proc.exit_expected;
+            proc.exit_expected = false;
+            const abnormal_exit = !success_exit and !expected_exit;
+
+            if (!expected_exit) {
+                if (reap.signaled) {
+                    proc.last_exit_reason = .signal;
+                } else if (!success_exit) {
+                    proc.last_exit_reason = .crashed;
+                }
+            }
 
             if (abnormal_exit) {
                 proc.status = .errored;
+                proc.recordEvent(.crash, if (reap.signaled) -@as(i32, @intCast(reap.signal)) else @as(i32, reap.exit_code));
                 // Fire crash notification with cooldown (60s per process)
                 const now_ms = @as(u64, @intCast(std.time.milliTimestamp()));
                 if (now_ms - proc.last_crash_notify_ms >= 60_000) {
@@ -436,6 +572,7 @@ pub const Supervisor = struct {
 
             // Autorestart logic
             if (!was_running or !proc.config.autorestart) continue;
+            if (success_exit and proc.config.stop_on_success) continue;
 
             // Calculate uptime of the process that just died
             const uptime = now -| proc.start_time_ms;
@@ -465,6 +602,8 @@ pub const Supervisor = struct {
                 delay = @min(base << shift, 15000);
             }
 
+            if (!abnormal_exit) proc.recordEvent(.restart, 0);
+
             if (delay > 0) {
                 // Schedule delayed restart
                 self.pending_restarts.put(process_id, now + delay) catch {};
@@ -591,6 +730,9 @@ pub const Supervisor = struct {
         const script_z = try self.allocator.dupeZ(u8, proc.config.script);
         defer self.allocator.free(script_z);
         try argv_list.append(self.allocator, script_z);
+        var args_z: std.ArrayList([:0]u8) = .{};
+        defer freeScriptArgs(self.allocator, &args_z);
+        try appendScriptArgs(self.allocator, &argv_list, &args_z, proc.config.args);
         try argv_list.append(self.allocator, null);
 
         const cwd_z = try self.allocator.dupeZ(u8, proc.config.cwd);
@@ -730,7 +872,11 @@ pub const Supervisor = struct {
         for (to_kill.items) |process_id| {
             if (self.processes.get(process_id)) |proc| {
                 if (proc.status == .running or proc.status == .stopped) {
-                    signals.sendSignal(proc.pid, signals.SIGKILL) catch {};
+                    if (proc.kill_tree) {
+                        signals.sendSignalToGroup(proc.pid, signals.SIGKILL) catch {};
+                    } else {
+                        signals.sendSignal(proc.pid, signals.SIGKILL) catch {};
+                    }
                 }
             }
             _ = self.pending_kills.remove(process_id);
@@ -774,6 +920,15 @@ pub const Supervisor = struct {
         return self.processes.get(process_id);
     }
 
+    /// Look up a process by its exact name.
+    pub fn findByName(self: *Self, name: []const u8) ?*ProcessInfo {
+        var it = self.processes.valueIterator();
+        while (it.next()) |proc_ptr| {
+            if (std.mem.eql(u8, proc_ptr.*.config.name, name)) return proc_ptr.*;
+        }
+        return null;
+    }
+
     /// Stop all running processes (graceful shutdown)
     pub fn stopAll(self: *Self) void {
         var it = self.processes.valueIterator();
@@ -807,6 +962,8 @@ pub const Supervisor = struct {
         _ = self.pending_kills.remove(process_id);
 
         // Restart
+        proc.recordEvent(.manual, 0);
+        proc.last_exit_reason = .manual;
         try self.doRestart(process_id, proc);
 
         // Return the pipe fds (last two added)
@@ -819,6 +976,15 @@ pub const Supervisor = struct {
         return error.RestartFailed;
     }
 
+    /// Zero the restart count and crash streak and forget the last exit
+    /// reason (`velos restart --reset-counters`). Lifecycle history is kept.
+    pub fn resetCounters(self: *Self, process_id: u32) !void {
+        const proc = self.processes.get(process_id) orelse return error.ProcessNotFound;
+        proc.restart_count = 0;
+        proc.consecutive_crashes = 0;
+        proc.last_exit_reason = .none;
+    }
+
     /// Update resource usage (RSS memory + CPU%) for all running processes.
     /// Should be called periodically (every ~2 seconds).
     pub fn updateResourceUsage(self: *Self) void {
@@ -838,9 +1004,10 @@ pub const Supervisor = struct {
                 proc.memory_bytes = usage.rss;
                 // max_memory_restart check
                 if (proc.config.max_memory_restart > 0 and usage.rss > proc.config.max_memory_restart) {
-                    self.doRestart(proc.id, proc) catch {
-                        proc.status = .errored;
-                    };
+                    proc.recordEvent(.oom, 0);
+                    proc.last_exit_reason = .oom;
+                    self.replaceOverLimit(proc);
+                    continue;
                 }
             }
 
@@ -855,9 +1022,39 @@ pub const Supervisor = struct {
             }
             proc.prev_cpu_time_ns = usage.cpu_time_ns;
             proc.prev_wall_time_ns = wall_now_ns;
+
+            // max_cpu_restart check: only a sustained overrun counts
+            if (proc.config.max_cpu_restart > 0 and proc.cpu_percent > proc.config.max_cpu_restart) {
+                if (proc.cpu_over_since_ms == 0) {
+                    proc.cpu_over_since_ms = now;
+                } else if (now - proc.cpu_over_since_ms >= proc.config.cpu_restart_window_ms) {
+                    proc.recordEvent(.cpu, 0);
+                    proc.last_exit_reason = .cpu;
+                    self.replaceOverLimit(proc);
+                }
+            } else {
+                proc.cpu_over_since_ms = 0;
+            }
         }
     }
 
+    /// Kill a process over max_memory_restart or max_cpu_restart and start a
+    /// fresh one. Neither path waits for a graceful stop: a spinning process
+    /// may never get to its SIGTERM handler, and pending_kills is keyed by
+    /// process id, so a SIGKILL deadline would hit the replacement instead.
+    fn replaceOverLimit(self: *Self, proc: *ProcessInfo) void {
+        proc.cpu_over_since_ms = 0;
+        proc.cpu_percent = 0;
+        proc.prev_cpu_time_ns = 0;
+        proc.prev_wall_time_ns = 0;
+        // Forget the old pid first so its reap isn't handled as a crash
+        _ = self.pid_to_id.remove(proc.pid);
+        signals.sendSignal(proc.pid, signals.SIGKILL) catch {};
+        self.doRestart(proc.id, proc) catch {
+            proc.status = .errored;
+        };
+    }
+
     /// Detect interpreter for a script file based on shebang or extension.
     fn detectInterpreter(self: *Self, script: []const u8, cwd: []const u8) ?[*:0]const u8 {
         _ = self;
@@ -941,6 +1138,7 @@ pub const Supervisor = struct {
         for (to_restart.items) |process_id| {
             if (self.processes.get(process_id)) |proc| {
                 if (proc.status == .running) {
+                    proc.recordEvent(.watch, 0);
                     self.doRestart(process_id, proc) catch {
                         proc.status = .errored;
                     };
@@ -981,6 +1179,7 @@ pub const Supervisor = struct {
         for (to_restart.items) |process_id| {
             if (self.processes.get(process_id)) |proc| {
                 if (proc.status == .running) {
+                    proc.recordEvent(.cron, 0);
                     self.doRestart(process_id, proc) catch {
                         proc.status = .errored;
                     };
@@ -1028,7 +1227,7 @@ pub const Supervisor = struct {
         var it = self.processes.valueIterator();
         while (it.next()) |proc_ptr| {
             const proc = proc_ptr.*;
-            if (matchesBaseName(proc.name, base_name)) {
+            if (matchesBaseName(proc.name, base_name, proc.config.instance_name_template)) {
                 try instance_ids.append(self.allocator, .{ .proc_id = proc.id, .inst_id = proc.instance_id });
                 if (template_proc == null) template_proc = proc;
                 if (proc.instance_id > max_instance_id) max_instance_id = proc.instance_id;
@@ -1044,8 +1243,8 @@ pub const Supervisor = struct {
             const template = template_proc orelse return error.ProcessNotFound;
             var next_inst = max_instance_id + 1;
             // If scaling from fork mode (single instance, no :N suffix), rename it to :0
-            if (current == 1 and std.mem.indexOfScalar(u8, template.name, ':') == null) {
-                const new_name = try std.fmt.allocPrint(self.allocator, "{s}:0", .{base_name});
+            if (current == 1 and std.mem.eql(u8, template.name, base_name)) {
+                const new_name = try formatInstanceName(self.allocator, template.config.instance_name_template, base_name, 0);
                 self.allocator.free(template.name);
                 template.name = new_name;
                 const new_cfg_name = try self.allocator.dupe(u8, new_name);
@@ -1059,7 +1258,7 @@ pub const Supervisor = struct {
 
             var i: u32 = 0;
             while (i < target_count - current) : (i += 1) {
-                const inst_name = std.fmt.allocPrint(self.allocator, "{s}:{d}", .{ base_name, next_inst }) catch continue;
+                const inst_name = formatInstanceName(self.allocator, template.config.instance_name_template, base_name, next_inst) catch continue;
                 defer self.allocator.free(inst_name);
 
                 var cfg = template.config;
@@ -1078,7 +1277,7 @@ pub const Supervisor = struct {
             var it2 = self.processes.valueIterator();
             while (it2.next()) |proc_ptr| {
                 const proc = proc_ptr.*;
-                if (matchesBaseName(proc.name, base_name)) {
+                if (matchesBaseName(proc.name, base_name, proc.config.instance_name_template)) {
                     proc.config.instances = target_count;
                 }
             }
@@ -1093,7 +1292,7 @@ pub const Supervisor = struct {
                 var it2 = self.processes.valueIterator();
                 while (it2.next()) |proc_ptr| {
                     const proc = proc_ptr.*;
-                    if (matchesBaseName(proc.name, base_name) and
+                    if (matchesBaseName(proc.name, base_name, proc.config.instance_name_template) and
                         (proc.status == .running or proc.status == .starting))
                     {
                         if (!found or proc.instance_id > max_id) {
@@ -1106,7 +1305,7 @@ pub const Supervisor = struct {
 
                 if (!found) break;
 
-                self.stopProcess(max_proc_id, signals.SIGTERM, 5000) catch {};
+                self.stopProcess(max_proc_id, signals.SIGTERM, 5000, false) catch {};
                 stopped += 1;
                 to_stop -= 1;
             }
@@ -1115,7 +1314,7 @@ pub const Supervisor = struct {
             var it3 = self.processes.valueIterator();
             while (it3.next()) |proc_ptr| {
                 const proc = proc_ptr.*;
-                if (matchesBaseName(proc.name, base_name)) {
+                if (matchesBaseName(proc.name, base_name, proc.config.instance_name_template)) {
                     proc.config.instances = target_count;
                 }
             }
@@ -1142,19 +1341,39 @@ extern "c" fn localtime(timer: *const c_long) ?*const CTm;
 extern "c" fn setenv(name: [*:0]const u8, value: [*:0]const u8, overwrite: c_int) c_int;
 
 /// Check if a process name matches a base name (exact or "base:N" pattern).
-fn matchesBaseName(name: []const u8, base_name: []const u8) bool {
+fn matchesBaseName(name: []const u8, base_name: []const u8, template: ?[]const u8) bool {
     if (std.mem.eql(u8, name, base_name)) return true;
-    if (name.len > base_name.len + 1 and
+    const delim = instanceDelimiter(template);
+    if (name.len > base_name.len + delim.len and
         std.mem.startsWith(u8, name, base_name) and
-        name[base_name.len] == ':')
+        std.mem.startsWith(u8, name[base_name.len..], delim))
     {
-        const suffix = name[base_name.len + 1 ..];
+        const suffix = name[base_name.len + delim.len ..];
         _ = std.fmt.parseInt(u32, suffix, 10) catch return false;
         return true;
     }
     return false;
 }
 
+/// Delimiter between base name and index for a "{name}<delim>{index}" template.
+fn instanceDelimiter(template: ?[]const u8) []const u8 {
+    const t = template orelse return ":";
+    const prefix = "{name}";
+    const suffix = "{index}";
+    if (t.len > prefix.len + suffix.len and
+        std.mem.startsWith(u8, t, prefix) and
+        std.mem.endsWith(u8, t, suffix))
+    {
+        return t[prefix.len .. t.len - suffix.len];
+    }
+    return ":";
+}
+
+/// Build a cluster instance name ("api:0", "api-0", ...). Caller owns the result.
+pub fn formatInstanceName(allocator: std.mem.Allocator, template: ?[]const u8, base_name: []const u8, index: u32) ![]u8 {
+    return std.fmt.allocPrint(allocator, "{s}{s}{d}", .{ base_name, instanceDelimiter(template), index });
+}
+
 /// Set VELOS_INSTANCE_ID and NODE_APP_INSTANCE env vars in child process
 fn setInstanceEnv(instance_id: u32) void {
     var id_buf: [20]u8 = undefined;
@@ -1174,6 +1393,33 @@ fn setIpcEnv(child_fd: posix.fd_t) void {
     _ = setenv("VELOS_IPC_FD", @ptrCast(&fd_val), 1);
 }
 
+/// Append script arguments (wire-encoded count(u32) + [string]...) to argv.
+/// Each argument is copied NUL-terminated into `owned`, which the caller
+/// releases with freeScriptArgs once the child has been forked.
+fn appendScriptArgs(
+    allocator: std.mem.Allocator,
+    argv_list: *std.ArrayList(?[*:0]const u8),
+    owned: *std.ArrayList([:0]u8),
+    args: ?[]const u8,
+) !void {
+    const data = args orelse return;
+    const count_r = protocol.readU32(data, 0);
+    var off = count_r.next;
+    var i: u32 = 0;
+    while (i < count_r.val and off < data.len) : (i += 1) {
+        const arg_r = protocol.readString(data, off);
+        off = arg_r.next;
+        const arg_z = try allocator.dupeZ(u8, arg_r.val);
+        try owned.append(allocator, arg_z);
+        try argv_list.append(allocator, arg_z);
+    }
+}
+
+fn freeScriptArgs(allocator: std.mem.Allocator, owned: *std.ArrayList([:0]u8)) void {
+    for (owned.items) |a| allocator.free(a);
+    owned.deinit(allocator);
+}
+
 /// Apply user environment variables (newline-separated KEY=VALUE pairs) before exec.
 /// Called in child process only — uses stack buffers since we're about to exec.
 fn applyEnvVars(env_vars: ?[]const u8) void {
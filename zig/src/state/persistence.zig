@@ -2,6 +2,14 @@ const std = @import("std");
 const posix = std.posix;
 const ProcessInfo = @import("../process/supervisor.zig").ProcessInfo;
 const ProcessConfig = @import("../process/supervisor.zig").ProcessConfig;
+const ExitCodeSet = @import("../process/supervisor.zig").ExitCodeSet;
+
+/// First u32 of a versioned state file. Legacy files start with the entry
+/// count instead, which never gets this large.
+pub const STATE_MAGIC: u32 = 0x534C_4556; // "VELS"
+
+/// Current state file version. Version 2 adds the extension block.
+pub const STATE_VERSION: u32 = 2;
 
 /// Manages the ~/.velos/ runtime directory, PID file, and state persistence.
 pub const Persistence = struct {
@@ -90,10 +98,13 @@ pub const Persistence = struct {
         return try std.fmt.allocPrint(self.allocator, "{s}/logs", .{self.state_dir});
     }
 
-    /// Save process configs to state.json using a simple binary format.
-    /// Format: count(u32) + [name_len(u32) + name + script_len(u32) + script + cwd_len(u32) + cwd
+    /// Save process configs to state.bin using a simple binary format.
+    /// Format: magic(u32) + version(u32) + count(u32)
+    ///         + [name_len(u32) + name + script_len(u32) + script + cwd_len(u32) + cwd
     ///         + interp_len(u32) + interp + kill_timeout(u32) + autorestart(u8)
-    ///         + max_restarts(i32) + min_uptime_ms(u64) + restart_delay_ms(u32) + exp_backoff(u8)]...
+    ///         + max_restarts(i32) + min_uptime_ms(u64) + restart_delay_ms(u32) + exp_backoff(u8)
+    ///         + ... + env_vars + ext_len(u32) + ext]...
+    /// See `encodeExtension` for the extension block.
     pub fn saveState(self: *Self, procs: []*ProcessInfo) !void {
         const path = try std.fmt.allocPrint(self.allocator, "{s}/state.bin", .{self.state_dir});
         defer self.allocator.free(path);
@@ -101,8 +112,12 @@ pub const Persistence = struct {
         const file = try std.fs.createFileAbsolute(path, .{});
         defer file.close();
 
-        // Write count
+        // Write header and count
         var tmp: [8]u8 = undefined;
+        std.mem.writeInt(u32, tmp[0..4], STATE_MAGIC, .little);
+        try file.writeAll(tmp[0..4]);
+        std.mem.writeInt(u32, tmp[0..4], STATE_VERSION, .little);
+        try file.writeAll(tmp[0..4]);
         std.mem.writeInt(u32, tmp[0..4], @intCast(procs.len), .little);
         try file.writeAll(tmp[0..4]);
 
@@ -209,9 +224,42 @@ pub const Persistence = struct {
             std.mem.writeInt(u32, tmp[0..4], @intCast(ev.len), .little);
             try file.writeAll(tmp[0..4]);
             if (ev.len > 0) try file.writeAll(ev);
+
+            // extension block, length-prefixed so older readers can skip it
+            var ext: std.ArrayList(u8) = .{};
+            defer ext.deinit(self.allocator);
+            try self.encodeExtension(&ext, cfg);
+            std.mem.writeInt(u32, tmp[0..4], @intCast(ext.items.len), .little);
+            try file.writeAll(tmp[0..4]);
+            try file.writeAll(ext.items);
         }
     }
 
+    /// Version 2 fields: args + instance_name_template + merge_logs(u8) + out_log + err_log
+    /// + group + log_rate_limit(u32) + success_exit_codes(32-byte bitmap) + stop_on_success(u8)
+    /// + max_cpu_restart(u16) + cpu_restart_window_ms(u64). Strings are len(u32) + bytes,
+    /// empty for null. New fields go at the end.
+    fn encodeExtension(self: *Self, ext: *std.ArrayList(u8), cfg: ProcessConfig) !void {
+        const a = self.allocator;
+        try appendString(a, ext, cfg.args);
+        try appendString(a, ext, cfg.instance_name_template);
+        try ext.append(a, if (cfg.merge_logs) 1 else 0);
+        try appendString(a, ext, cfg.out_log);
+        try appendString(a, ext, cfg.err_log);
+        try appendString(a, ext, cfg.group);
+        try appendInt(a, ext, u32, cfg.log_rate_limit);
+
+        var codes = [_]u8{0} ** 32;
+        for (0..256) |code| {
+            if (cfg.success_exit_codes.isSet(code)) codes[code / 8] |= @as(u8, 1) << @intCast(code % 8);
+        }
+        try ext.appendSlice(a, &codes);
+
+        try ext.append(a, if (cfg.stop_on_success) 1 else 0);
+        try appendInt(a, ext, u16, cfg.max_cpu_restart);
+        try appendInt(a, ext, u64, cfg.cpu_restart_window_ms);
+    }
+
     /// Load process configs from state.bin. Caller must free each config's strings and the slice.
     pub fn loadState(self: *Self) ![]ProcessConfig {
         const path = try std.fmt.allocPrint(self.allocator, "{s}/state.bin", .{self.state_dir});
@@ -231,22 +279,28 @@ pub const Persistence = struct {
         const read_len = try file.readAll(data);
         if (read_len < 4) return try self.allocator.alloc(ProcessConfig, 0);
 
-        var off: usize = 0;
-        const count = std.mem.readInt(u32, data[0..4], .little);
-        off = 4;
+        var off: usize = 4;
+        var count = std.mem.readInt(u32, data[0..4], .little);
+        var version: u32 = 1;
+        if (count == STATE_MAGIC) {
+            if (read_len < 12) return try self.allocator.alloc(ProcessConfig, 0);
+            version = std.mem.readInt(u32, data[4..8], .little);
+            count = std.mem.readInt(u32, data[8..12], .little);
+            off = 12;
+        }
 
         var configs: std.ArrayList(ProcessConfig) = .{};
 
         var i: u32 = 0;
         while (i < count) : (i += 1) {
-            const cfg = self.readOneConfig(data, &off) catch break;
+            const cfg = self.readOneConfig(data, &off, version) catch break;
             configs.append(self.allocator, cfg) catch break;
         }
 
         return configs.toOwnedSlice(self.allocator);
     }
 
-    fn readOneConfig(self: *Self, data: []const u8, off: *usize) !ProcessConfig {
+    fn readOneConfig(self: *Self, data: []const u8, off: *usize, version: u32) !ProcessConfig {
         const name = try self.readBinString(data, off);
         const script = try self.readBinString(data, off);
         const cwd = try self.readBinString(data, off);
@@ -370,7 +424,7 @@ pub const Persistence = struct {
             } else ev_str;
         }
 
-        return ProcessConfig{
+        var cfg = ProcessConfig{
             .name = name,
             .script = script,
             .cwd = cwd,
@@ -394,6 +448,66 @@ pub const Persistence = struct {
             .instance_id = instance_id,
             .env_vars = env_vars,
         };
+        if (version >= 2) try self.readExtension(data, off, &cfg);
+        return cfg;
+    }
+
+    /// Read the extension block (see `encodeExtension`) into `cfg`. Fields a
+    /// newer daemon appended are skipped; a shorter block leaves defaults.
+    fn readExtension(self: *Self, data: []const u8, off: *usize, cfg: *ProcessConfig) !void {
+        if (off.* + 4 > data.len) return error.TruncatedState;
+        const len = std.mem.readInt(u32, data[off.*..][0..4], .little);
+        off.* += 4;
+        if (off.* + len > data.len) return error.TruncatedState;
+        const ext = data[off.*..][0..len];
+        off.* += len;
+
+        var pos: usize = 0;
+        cfg.args = try self.readOptionalString(ext, &pos);
+        cfg.instance_name_template = try self.readOptionalString(ext, &pos);
+        if (pos < ext.len) {
+            cfg.merge_logs = ext[pos] != 0;
+            pos += 1;
+        }
+        cfg.out_log = try self.readOptionalString(ext, &pos);
+        cfg.err_log = try self.readOptionalString(ext, &pos);
+        cfg.group = try self.readOptionalString(ext, &pos);
+        if (pos + 4 <= ext.len) {
+            cfg.log_rate_limit = std.mem.readInt(u32, ext[pos..][0..4], .little);
+            pos += 4;
+        }
+        if (pos + 32 <= ext.len) {
+            cfg.success_exit_codes = ExitCodeSet.initEmpty();
+            for (0..256) |code| {
+                if ((ext[pos + code / 8] & (@as(u8, 1) << @intCast(code % 8))) != 0) {
+                    cfg.success_exit_codes.set(code);
+                }
+            }
+            pos += 32;
+        }
+        if (pos < ext.len) {
+            cfg.stop_on_success = ext[pos] != 0;
+            pos += 1;
+        }
+        if (pos + 2 <= ext.len) {
+            cfg.max_cpu_restart = std.mem.readInt(u16, ext[pos..][0..2], .little);
+            pos += 2;
+        }
+        if (pos + 8 <= ext.len) {
+            cfg.cpu_restart_window_ms = std.mem.readInt(u64, ext[pos..][0..8], .little);
+            pos += 8;
+        }
+    }
+
+    /// A length-prefixed string if bytes remain, null when absent or empty.
+    fn readOptionalString(self: *Self, data: []const u8, off: *usize) !?[]const u8 {
+        if (off.* + 4 > data.len) return null;
+        const str = try self.readBinString(data, off);
+        if (str.len == 0) {
+            self.allocator.free(str);
+            return null;
+        }
+        return str;
     }
 
     fn readBinString(self: *Self, data: []const u8, off: *usize) ![]u8 {
@@ -406,3 +520,139 @@ pub const Persistence = struct {
         return str;
     }
 };
+
+/// Free the strings of a config returned by `loadState`.
+pub fn freeConfig(allocator: std.mem.Allocator, cfg: ProcessConfig) void {
+    allocator.free(cfg.name);
+    allocator.free(cfg.script);
+    allocator.free(cfg.cwd);
+    const optional = [_]?[]const u8{
+        cfg.interpreter,
+        cfg.watch_paths,
+        cfg.watch_ignore,
+        cfg.cron_restart,
+        cfg.env_vars,
+        cfg.instance_name_template,
+        cfg.out_log,
+        cfg.err_log,
+        cfg.args,
+        cfg.group,
+    };
+    for (optional) |s| {
+        if (s) |str| allocator.free(str);
+    }
+}
+
+fn appendInt(a: std.mem.Allocator, buf: *std.ArrayList(u8), comptime T: type, value: T) !void {
+    var b: [@sizeOf(T)]u8 = undefined;
+    std.mem.writeInt(T, &b, value, .little);
+    try buf.appendSlice(a, &b);
+}
+
+fn appendString(a: std.mem.Allocator, buf: *std.ArrayList(u8), s: ?[]const u8) !void {
+    const str = s orelse "";
+    try appendInt(a, buf, u32, @intCast(str.len));
+    try buf.appendSlice(a, str);
+}
+
+test "saveState and loadState round-trip the extension fields" {
+    const allocator = std.testing.allocator;
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    const dir = try tmp.dir.realpathAlloc(allocator, ".");
+    defer allocator.free(dir);
+    var p = Persistence.init(allocator, dir);
+
+    var codes = ExitCodeSet.initEmpty();
+    codes.set(0);
+    codes.set(143);
+    var proc = ProcessInfo{
+        .id = 1,
+        .name = "api",
+        .pid = 0,
+        .status = .stopped,
+        .memory_bytes = 0,
+        .uptime_ms = 0,
+        .restart_count = 0,
+        .start_time_ms = 0,
+        .config = .{
+            .name = "api",
+            .script = "server.js",
+            .cwd = "/srv/api",
+            .interpreter = null,
+            .env_vars = "PORT=3000",
+            .instance_name_template = "{name}-{index}",
+            .out_log = "/var/log/api.out",
+            .err_log = "/var/log/api.err",
+            .merge_logs = true,
+            .args = "\x01\x00\x00\x00\x06\x00\x00\x00--port",
+            .group = "backend",
+            .log_rate_limit = 500,
+            .success_exit_codes = codes,
+            .stop_on_success = true,
+            .max_cpu_restart = 900,
+            .cpu_restart_window_ms = 30_000,
+        },
+    };
+    var procs = [_]*ProcessInfo{&proc};
+    try p.saveState(&procs);
+
+    const configs = try p.loadState();
+    defer {
+        for (configs) |cfg| freeConfig(allocator, cfg);
+        allocator.free(configs);
+    }
+    try std.testing.expectEqual(@as(usize, 1), configs.len);
+    const cfg = configs[0];
+    try std.testing.expectEqualStrings("server.js", cfg.script);
+    try std.testing.expectEqualStrings("PORT=3000", cfg.env_vars.?);
+    try std.testing.expectEqualStrings(proc.config.args.?, cfg.args.?);
+    try std.testing.expectEqualStrings("{name}-{index}", cfg.instance_name_template.?);
+    try std.testing.expect(cfg.merge_logs);
+    try std.testing.expectEqualStrings("/var/log/api.out", cfg.out_log.?);
+    try std.testing.expectEqualStrings("/var/log/api.err", cfg.err_log.?);
+    try std.testing.expectEqualStrings("backend", cfg.group.?);
+    try std.testing.expectEqual(@as(u32, 500), cfg.log_rate_limit);
+    try std.testing.expect(cfg.success_exit_codes.isSet(0));
+    try std.testing.expect(cfg.success_exit_codes.isSet(143));
+    try std.testing.expect(!cfg.success_exit_codes.isSet(1));
+    try std.testing.expect(cfg.stop_on_success);
+    try std.testing.expectEqual(@as(u16, 900), cfg.max_cpu_restart);
+    try std.testing.expectEqual(@as(u64, 30_000), cfg.cpu_restart_window_ms);
+}
+
+test "loadState reads a legacy file without a header" {
+    const allocator = std.testing.allocator;
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    const dir = try tmp.dir.realpathAlloc(allocator, ".");
+    defer allocator.free(dir);
+    var p = Persistence.init(allocator, dir);
+
+    // count + name, script, cwd, interpreter + the fixed legacy fields
+    var buf: std.ArrayList(u8) = .{};
+    defer buf.deinit(allocator);
+    try appendInt(allocator, &buf, u32, 1);
+    try appendString(allocator, &buf, "worker");
+    try appendString(allocator, &buf, "worker.py");
+    try appendString(allocator, &buf, "/srv/worker");
+    try appendString(allocator, &buf, null);
+    try appendInt(allocator, &buf, u32, 5000);
+    try buf.append(allocator, 1);
+    try appendInt(allocator, &buf, i32, 15);
+    try appendInt(allocator, &buf, u64, 1000);
+    try appendInt(allocator, &buf, u32, 0);
+    try buf.append(allocator, 0);
+    try tmp.dir.writeFile(.{ .sub_path = "state.bin", .data = buf.items });
+
+    const configs = try p.loadState();
+    defer {
+        for (configs) |cfg| freeConfig(allocator, cfg);
+        allocator.free(configs);
+    }
+    try std.testing.expectEqual(@as(usize, 1), configs.len);
+    try std.testing.expectEqualStrings("worker", configs[0].name);
+    try std.testing.expect(configs[0].autorestart);
+    try std.testing.expect(configs[0].group == null);
+    try std.testing.expect(configs[0].success_exit_codes.isSet(0));
+}